@@ -0,0 +1,19 @@
+-- 0002_instance_connection_history.sql
+-- Description: Append-only audit trail of connection string changes
+
+SET search_path TO toygres_cms, public;
+
+-- Connection strings are stored with passwords redacted
+CREATE TABLE IF NOT EXISTS instance_connection_history (
+    id BIGSERIAL PRIMARY KEY,
+    instance_id UUID NOT NULL REFERENCES instances(id) ON DELETE CASCADE,
+    old_ip_connection_string TEXT,
+    new_ip_connection_string TEXT,
+    old_dns_connection_string TEXT,
+    new_dns_connection_string TEXT,
+    reason TEXT,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_connection_history_instance_id ON instance_connection_history(instance_id);
+CREATE INDEX IF NOT EXISTS idx_connection_history_changed_at ON instance_connection_history(changed_at DESC);
@@ -0,0 +1,4 @@
+-- 0003_health_status_degraded.sql
+-- Description: Add 'degraded' health status for slow-but-responding instances
+
+ALTER TYPE public.health_status ADD VALUE IF NOT EXISTS 'degraded' BEFORE 'unhealthy';
@@ -0,0 +1,7 @@
+-- 0004_instance_password_secret_ref.sql
+-- Description: Name of a pre-existing Kubernetes Secret holding the instance password.
+-- When set, the password itself is never stored in CMS and connection strings omit it.
+
+SET search_path TO toygres_cms, public;
+
+ALTER TABLE instances ADD COLUMN IF NOT EXISTS password_secret_ref VARCHAR(253);
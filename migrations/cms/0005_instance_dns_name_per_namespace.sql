@@ -0,0 +1,13 @@
+-- 0005_instance_dns_name_per_namespace.sql
+-- Description: Scope DNS name uniqueness to a namespace so the same name can exist in different namespaces.
+-- Public Azure DNS labels are still unique per region; that is enforced by Azure, not here.
+
+SET search_path TO toygres_cms, public;
+
+DROP INDEX IF EXISTS idx_instances_dns_name_unique;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_instances_namespace_dns_name_unique
+    ON instances(namespace, dns_name)
+    WHERE dns_name IS NOT NULL
+      AND dns_name NOT LIKE '__deleted_%'
+      AND state IN ('creating', 'running');
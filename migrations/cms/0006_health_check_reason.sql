@@ -0,0 +1,11 @@
+-- 0006_health_check_reason.sql
+-- Description: Classify failed health checks (connection_refused, auth_failed, timeout,
+-- dns_failure, pod_not_running, other) so failures can be grouped without parsing errors
+
+SET search_path TO toygres_cms, public;
+
+ALTER TABLE instance_health_checks ADD COLUMN IF NOT EXISTS health_reason VARCHAR(50);
+
+CREATE INDEX IF NOT EXISTS idx_health_checks_reason
+    ON instance_health_checks(health_reason)
+    WHERE health_reason IS NOT NULL;
@@ -0,0 +1,4 @@
+-- 0007_health_status_provisioning.sql
+-- Description: Add 'provisioning' health status for instances whose connection info is not available yet
+
+ALTER TYPE public.health_status ADD VALUE IF NOT EXISTS 'provisioning' BEFORE 'unknown';
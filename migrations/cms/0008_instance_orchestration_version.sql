@@ -0,0 +1,7 @@
+-- 0008_instance_orchestration_version.sql
+-- Description: Record the version of the create orchestration that provisioned each instance,
+-- so instances from older orchestration code can be found after an upgrade
+
+SET search_path TO toygres_cms, public;
+
+ALTER TABLE instances ADD COLUMN IF NOT EXISTS orchestration_version VARCHAR(50);
@@ -0,0 +1,7 @@
+-- 0009_instance_deletion_protection.sql
+-- Description: Let instances be marked deletion-protected; the API refuses to delete them
+-- unless the request explicitly overrides the protection
+
+SET search_path TO toygres_cms, public;
+
+ALTER TABLE instances ADD COLUMN IF NOT EXISTS deletion_protected BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,7 @@
+-- 0010_health_check_replication_lag.sql
+-- Description: Record how far a replica's WAL replay trails the primary; NULL for
+-- primaries, which have nothing to replay
+
+SET search_path TO toygres_cms, public;
+
+ALTER TABLE instance_health_checks ADD COLUMN IF NOT EXISTS replication_lag_ms BIGINT;
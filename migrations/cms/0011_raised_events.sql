@@ -0,0 +1,12 @@
+-- 0011_raised_events.sql
+-- Description: Idempotency keys of events raised by the raise-event activity, so a
+-- retried activity does not deliver the same event twice
+
+SET search_path TO toygres_cms, public;
+
+CREATE TABLE IF NOT EXISTS raised_events (
+    idempotency_key TEXT PRIMARY KEY,
+    instance_id TEXT NOT NULL,
+    event_name TEXT NOT NULL,
+    raised_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,7 @@
+-- 0012_instance_actor_stopped.sql
+-- Description: Let operators stop an instance's actor without deleting the instance;
+-- supervision sweeps do not restart stopped actors
+
+SET search_path TO toygres_cms, public;
+
+ALTER TABLE instances ADD COLUMN IF NOT EXISTS actor_stopped BOOLEAN NOT NULL DEFAULT FALSE;
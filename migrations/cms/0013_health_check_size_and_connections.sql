@@ -0,0 +1,8 @@
+-- 0013_health_check_size_and_connections.sql
+-- Description: Record the database size and client connection count seen by each
+-- health check, so per-instance metrics can be derived from the latest row
+
+SET search_path TO toygres_cms, public;
+
+ALTER TABLE instance_health_checks ADD COLUMN IF NOT EXISTS database_size_bytes BIGINT;
+ALTER TABLE instance_health_checks ADD COLUMN IF NOT EXISTS connection_count INTEGER;
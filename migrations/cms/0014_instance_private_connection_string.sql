@@ -0,0 +1,7 @@
+-- 0014_instance_private_connection_string.sql
+-- Description: Connection string through the Azure Private DNS zone, set for
+-- instances behind an internal LoadBalancer when a zone is configured
+
+SET search_path TO toygres_cms, public;
+
+ALTER TABLE instances ADD COLUMN IF NOT EXISTS private_connection_string TEXT;
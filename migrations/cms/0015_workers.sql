@@ -0,0 +1,12 @@
+-- 0015_workers.sql
+-- Description: One row per worker process, refreshed by its heartbeat, so the
+-- live workers of a multi-worker deployment can be listed and dead ones spotted
+
+SET search_path TO toygres_cms, public;
+
+CREATE TABLE IF NOT EXISTS workers (
+    worker_id TEXT PRIMARY KEY,
+    hostname TEXT NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL,
+    last_heartbeat TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,14 @@
+-- 0016_maintenance_mode.sql
+-- Description: Fleet-wide maintenance-mode switch; while enabled, instance actors skip
+-- health checks and record 'unknown' with reason 'maintenance' instead of alerting
+
+SET search_path TO toygres_cms, public;
+
+CREATE TABLE IF NOT EXISTS maintenance_mode (
+    -- Single-row table
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO maintenance_mode (id) VALUES (TRUE) ON CONFLICT (id) DO NOTHING;
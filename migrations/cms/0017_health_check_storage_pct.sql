@@ -0,0 +1,7 @@
+-- 0017_health_check_storage_pct.sql
+-- Description: Record how full the instance's volume is (all databases versus the
+-- provisioned storage_size_gb) with each health check
+
+SET search_path TO toygres_cms, public;
+
+ALTER TABLE instance_health_checks ADD COLUMN IF NOT EXISTS storage_pct DOUBLE PRECISION;
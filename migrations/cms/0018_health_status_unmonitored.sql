@@ -0,0 +1,4 @@
+-- 0018_health_status_unmonitored.sql
+-- Description: Add 'unmonitored' health status for running instances whose actor could not be recorded
+
+ALTER TYPE public.health_status ADD VALUE IF NOT EXISTS 'unmonitored' BEFORE 'unknown';
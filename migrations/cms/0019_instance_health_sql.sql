@@ -0,0 +1,7 @@
+-- 0019_instance_health_sql.sql
+-- Description: Custom health query set through the instance's actor config, so an
+-- actor started by the supervisor keeps running it
+
+SET search_path TO toygres_cms, public;
+
+ALTER TABLE instances ADD COLUMN IF NOT EXISTS health_sql TEXT;
@@ -0,0 +1,9 @@
+-- 0020_instance_deploy_options.sql
+-- Description: Create options the deploy renders that have no column of their own
+-- (pooler, internal LoadBalancer, standbys, update strategy, probes, ...), so drift
+-- checks and rendered manifests use what the instance was created with. NULL for
+-- instances created before this column.
+
+SET search_path TO toygres_cms, public;
+
+ALTER TABLE instances ADD COLUMN IF NOT EXISTS deploy_options JSONB;
@@ -0,0 +1,91 @@
+use duroxide::ActivityContext;
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::activity_types::{ClaimInstanceDeletionInput, ClaimInstanceDeletionOutput};
+
+use super::get_pool;
+
+/// Activity name for registration and scheduling
+pub const NAME: &str = "toygres-orchestrations::activity::cms-claim-instance-deletion";
+
+/// Record the orchestration in `delete_orchestration_id` unless another delete holds
+/// the instance. The check and the write happen under a row lock, so of two deletes
+/// racing on one instance exactly one claims it.
+pub async fn activity(
+    ctx: ActivityContext,
+    input: ClaimInstanceDeletionInput,
+) -> Result<ClaimInstanceDeletionOutput, String> {
+    let pool = get_pool().await?;
+    let mut tx = pool.begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    let record = sqlx::query(
+        r#"
+        SELECT id, state::text as state, delete_orchestration_id
+        FROM toygres_cms.instances
+        WHERE k8s_name = $1
+        FOR UPDATE
+        "#
+    )
+    .bind(&input.k8s_name)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to fetch CMS record: {}", e))?;
+
+    // No record: nothing to hold, the orchestration's best-effort cleanup applies
+    let Some(row) = record else {
+        return Ok(ClaimInstanceDeletionOutput { claimed: true, held_by: None });
+    };
+    let instance_id: Uuid = row.try_get("id")
+        .map_err(|e| format!("Failed to read instance id: {}", e))?;
+    let state: String = row.try_get("state")
+        .map_err(|e| format!("Failed to read state: {}", e))?;
+    let holder: Option<String> = row.try_get("delete_orchestration_id")
+        .map_err(|e| format!("Failed to read delete orchestration id: {}", e))?;
+
+    if let Some(other) = conflicting_holder(&state, holder.as_deref(), &input.orchestration_id) {
+        ctx.trace_warn(format!("{} is already being deleted by {}", input.k8s_name, other));
+        return Ok(ClaimInstanceDeletionOutput { claimed: false, held_by: Some(other.to_string()) });
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE toygres_cms.instances
+        SET delete_orchestration_id = $2,
+            updated_at = NOW()
+        WHERE id = $1
+        "#
+    )
+    .bind(instance_id)
+    .bind(&input.orchestration_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to claim instance for deletion: {}", e))?;
+
+    tx.commit()
+        .await
+        .map_err(|e| format!("Failed to commit deletion claim: {}", e))?;
+
+    Ok(ClaimInstanceDeletionOutput { claimed: true, held_by: None })
+}
+
+/// The other delete orchestration holding an instance, if any. A record already in
+/// `deleted` only awaits removal, so finishing that is left to whichever delete runs.
+pub fn conflicting_holder<'a>(state: &str, holder: Option<&'a str>, orchestration_id: &str) -> Option<&'a str> {
+    holder.filter(|holder| *holder != orchestration_id && state != "deleted")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_another_live_delete_conflicts() {
+        assert_eq!(conflicting_holder("deleting", Some("delete-bulk-1"), "delete-mydb"), Some("delete-bulk-1"));
+        assert_eq!(conflicting_holder("deleting", Some("delete-mydb"), "delete-mydb"), None);
+        assert_eq!(conflicting_holder("running", None, "delete-mydb"), None);
+        assert_eq!(conflicting_holder("deleted", Some("delete-bulk-1"), "delete-mydb"), None);
+    }
+}
@@ -1,5 +1,5 @@
 use duroxide::ActivityContext;
-use sqlx::{Error as SqlxError, Row};
+use sqlx::{Error as SqlxError, PgPool, Row};
 use uuid::Uuid;
 
 use crate::activity_types::{CreateInstanceRecordInput, CreateInstanceRecordOutput};
@@ -11,24 +11,128 @@ pub const NAME: &str = "toygres-orchestrations::activity::cms-create-instance-re
 
 pub async fn activity(
     ctx: ActivityContext,
-    input: CreateInstanceRecordInput,
+    mut input: CreateInstanceRecordInput,
 ) -> Result<CreateInstanceRecordOutput, String> {
+    input.orchestration_version.get_or_insert_with(|| ctx.orchestration_version().to_string());
     ctx.trace_info(format!(
         "Creating CMS record for user '{}' (k8s: {})",
         input.user_name, input.k8s_name
     ));
 
     let pool = get_pool().await?;
+
+    match reserve_instance_record(&pool, &input).await? {
+        Reservation::Created(instance_id) => {
+            ctx.trace_info(format!("CMS record stored: {}", instance_id));
+            Ok(CreateInstanceRecordOutput { instance_id })
+        }
+        Reservation::Replayed { instance_id, k8s_name } => {
+            ctx.trace_info(format!(
+                "Reusing CMS record {} (k8s: {}) for orchestration replay",
+                instance_id, k8s_name
+            ));
+            Ok(CreateInstanceRecordOutput { instance_id })
+        }
+    }
+}
+
+/// Result of reserving the CMS record (and its DNS name) for a create orchestration
+#[derive(Debug)]
+enum Reservation {
+    /// A new record was inserted (or this orchestration's record was refreshed, e.g.
+    /// back to 'creating' when a failed create is resumed)
+    Created(Uuid),
+    /// The DNS name is already held by a record owned by this same orchestration
+    Replayed { instance_id: Uuid, k8s_name: String },
+}
+
+/// Advisory lock key for a DNS name reservation in a namespace.
+///
+/// FNV-1a over a fixed prefix plus the namespace and name, so the key is stable across
+/// processes and releases and does not collide with locks taken for other purposes.
+fn dns_lock_key(namespace: &str, dns_name: &str) -> i64 {
+    const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    // '/' is not valid in a namespace, so "a/b-c" and "a-b/c" hash different input
+    let key = format!("toygres-cms:dns:{}/{}", namespace, dns_name);
+    let mut hash = FNV_OFFSET;
+    for byte in key.as_bytes() {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash as i64
+}
+
+/// Reserve the instance record, serializing concurrent reservations of the same
+/// DNS name in the same namespace with a transaction-scoped advisory lock.
+///
+/// Whoever takes the lock second sees the winner's committed row and gets the
+/// permanent "already reserved" error. The partial unique index on
+/// `(namespace, dns_name)` remains as a backstop.
+async fn reserve_instance_record(
+    pool: &PgPool,
+    input: &CreateInstanceRecordInput,
+) -> Result<Reservation, String> {
     let mut tx = pool.begin()
         .await
         .map_err(|e| format!("Failed to start transaction: {}", e))?;
 
+    if let Some(dns_name) = &input.dns_name {
+        // Released automatically on commit/rollback
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(dns_lock_key(&input.namespace, dns_name))
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| format!("Failed to acquire DNS reservation lock: {}", e))?;
+
+        let holder = sqlx::query(
+            r#"
+            SELECT id, k8s_name, user_name, create_orchestration_id
+            FROM toygres_cms.instances
+            WHERE namespace = $1
+              AND dns_name = $2
+              AND dns_name NOT LIKE '__deleted_%'
+              AND state IN ('creating', 'running')
+            "#
+        )
+        .bind(&input.namespace)
+        .bind(dns_name)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to check DNS reservation: {}", e))?;
+
+        if let Some(row) = holder {
+            let owner_id: String = row.try_get("create_orchestration_id")
+                .map_err(|e| format!("Failed to read orchestration id: {}", e))?;
+            let k8s_name: String = row.try_get("k8s_name")
+                .map_err(|e| format!("Failed to read k8s_name: {}", e))?;
+            let user_name: String = row.try_get("user_name")
+                .map_err(|e| format!("Failed to read user_name: {}", e))?;
+            let instance_id: Uuid = row.try_get("id")
+                .map_err(|e| format!("Failed to read instance id: {}", e))?;
+
+            if owner_id == input.orchestration_id {
+                // Replay from same orchestration – treat as success
+                tx.commit().await.map_err(|e| format!("Failed to commit CMS record: {}", e))?;
+                return Ok(Reservation::Replayed { instance_id, k8s_name });
+            }
+
+            tx.rollback().await.map_err(|e| format!("Failed to rollback after DNS conflict: {}", e))?;
+            return Err(format!(
+                "DNS name '{}' is already reserved in namespace '{}' by instance '{}' (user: {})",
+                dns_name, input.namespace, k8s_name, user_name
+            ));
+        }
+    }
+
     let insert_result = sqlx::query(
         r#"
         INSERT INTO toygres_cms.instances
         (user_name, k8s_name, namespace, postgres_version, storage_size_gb,
-         use_load_balancer, dns_name, state, create_orchestration_id)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, 'creating', $8)
+         use_load_balancer, dns_name, state, create_orchestration_id, password_secret_ref, tags,
+         orchestration_version, deploy_options)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, 'creating', $8, $9, COALESCE($10, '{}'::jsonb), $11, $12)
         ON CONFLICT (k8s_name) DO UPDATE
         SET user_name = EXCLUDED.user_name,
             namespace = EXCLUDED.namespace,
@@ -36,6 +140,11 @@ pub async fn activity(
             storage_size_gb = EXCLUDED.storage_size_gb,
             use_load_balancer = EXCLUDED.use_load_balancer,
             dns_name = EXCLUDED.dns_name,
+            password_secret_ref = EXCLUDED.password_secret_ref,
+            tags = EXCLUDED.tags,
+            orchestration_version = EXCLUDED.orchestration_version,
+            deploy_options = EXCLUDED.deploy_options,
+            state = 'creating',
             updated_at = NOW()
         WHERE toygres_cms.instances.create_orchestration_id = EXCLUDED.create_orchestration_id
         RETURNING id
@@ -49,6 +158,10 @@ pub async fn activity(
     .bind(input.use_load_balancer)
     .bind(&input.dns_name)
     .bind(&input.orchestration_id)
+    .bind(&input.password_secret_ref)
+    .bind(input.tags.as_ref().map(sqlx::types::Json))
+    .bind(&input.orchestration_version)
+    .bind(input.deploy_options.as_ref().map(sqlx::types::Json))
     .fetch_optional(&mut *tx)
     .await;
 
@@ -57,61 +170,19 @@ pub async fn activity(
             tx.commit().await.map_err(|e| format!("Failed to commit CMS record: {}", e))?;
             let id: Uuid = row.try_get("id")
                 .map_err(|e| format!("Failed to read CMS record id: {}", e))?;
-            ctx.trace_info(format!("CMS record stored: {}", id));
-            Ok(CreateInstanceRecordOutput { instance_id: id })
+            Ok(Reservation::Created(id))
         }
         Err(SqlxError::Database(db_err))
             if db_err.code().as_deref() == Some("23505")
-                && db_err.constraint() == Some("idx_instances_dns_name_unique") =>
+                && db_err.constraint() == Some("idx_instances_namespace_dns_name_unique") =>
         {
-            let dns_name = input.dns_name.clone().ok_or_else(|| {
-                "DNS conflict detected but DNS name missing from input".to_string()
-            })?;
-
-            let conflict = sqlx::query(
-                r#"
-                SELECT id, k8s_name, user_name, create_orchestration_id
-                FROM toygres_cms.instances
-                WHERE dns_name = $1
-                  AND dns_name NOT LIKE '__deleted_%'
-                  AND state IN ('creating', 'running')
-                FOR UPDATE
-                "#
-            )
-            .bind(&dns_name)
-            .fetch_optional(&mut *tx)
-            .await
-            .map_err(|e| format!("Failed to inspect DNS conflict: {}", e))?;
-
-            if let Some(row) = conflict {
-                let owner_id: String = row.try_get("create_orchestration_id")
-                    .map_err(|e| format!("Failed to read orchestration id: {}", e))?;
-                let k8s_name: String = row.try_get("k8s_name")
-                    .map_err(|e| format!("Failed to read k8s_name: {}", e))?;
-                let user_name: String = row.try_get("user_name")
-                    .map_err(|e| format!("Failed to read user_name: {}", e))?;
-                let instance_id: Uuid = row.try_get("id")
-                    .map_err(|e| format!("Failed to read instance id: {}", e))?;
-
-                if owner_id == input.orchestration_id {
-                    // Replay from same orchestration – treat as success
-                    tx.commit().await.map_err(|e| format!("Failed to commit CMS record: {}", e))?;
-                    ctx.trace_info(format!(
-                        "Reusing CMS record {} (k8s: {}) for orchestration replay",
-                        instance_id, k8s_name
-                    ));
-                    Ok(CreateInstanceRecordOutput { instance_id })
-                } else {
-                    tx.rollback().await.map_err(|e| format!("Failed to rollback after DNS conflict: {}", e))?;
-                    Err(format!(
-                        "DNS name '{}' is already reserved by instance '{}' (user: {})",
-                        dns_name, k8s_name, user_name
-                    ))
-                }
-            } else {
-                tx.rollback().await.map_err(|e| format!("Failed to rollback after DNS conflict inspection: {}", e))?;
-                Err("DNS name conflict detected but record was not found. Please retry.".to_string())
-            }
+            // Backstop: only reachable if a writer bypassed the advisory lock
+            tx.rollback().await.map_err(|e| format!("Failed to rollback after DNS conflict: {}", e))?;
+            Err(format!(
+                "DNS name '{}' is already reserved in namespace '{}'",
+                input.dns_name.as_deref().unwrap_or_default(),
+                input.namespace
+            ))
         }
         Err(e) => {
             tx.rollback().await.map_err(|err| format!("Failed to rollback after error: {}", err))?;
@@ -121,3 +192,154 @@ pub async fn activity(
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activities::cms::test_pool;
+
+    fn input(k8s_name: &str, dns_name: &str) -> CreateInstanceRecordInput {
+        CreateInstanceRecordInput {
+            user_name: dns_name.to_string(),
+            k8s_name: k8s_name.to_string(),
+            namespace: "toygres".to_string(),
+            postgres_version: "18".to_string(),
+            storage_size_gb: 10,
+            use_load_balancer: true,
+            dns_name: Some(dns_name.to_string()),
+            orchestration_id: format!("create-{}", k8s_name),
+            password_secret_ref: None,
+            tags: None,
+            orchestration_version: None,
+            deploy_options: None,
+        }
+    }
+
+    #[test]
+    fn test_dns_lock_key_is_stable_and_distinct() {
+        assert_eq!(dns_lock_key("toygres", "mydb"), dns_lock_key("toygres", "mydb"));
+        assert_ne!(dns_lock_key("toygres", "mydb"), dns_lock_key("toygres", "mydb2"));
+        assert_ne!(dns_lock_key("toygres", "mydb"), dns_lock_key("staging", "mydb"));
+        assert_ne!(dns_lock_key("a", "b-c"), dns_lock_key("a-b", "c"));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL with the toygres_cms schema"]
+    async fn test_same_dns_name_in_two_namespaces() {
+        let pool = test_pool().await;
+        let dns_name = format!("ns-test-{}", &Uuid::new_v4().to_string()[..8]);
+        let in_namespace = |suffix: &str, namespace: &str| CreateInstanceRecordInput {
+            namespace: namespace.to_string(),
+            ..input(&format!("{}-{}", dns_name, suffix), &dns_name)
+        };
+
+        let (first, second) = (in_namespace("aaaaaaaa", "toygres"), in_namespace("bbbbbbbb", "staging"));
+        let (a, b) = tokio::join!(
+            reserve_instance_record(&pool, &first),
+            reserve_instance_record(&pool, &second),
+        );
+        let taken = reserve_instance_record(&pool, &in_namespace("cccccccc", "staging")).await;
+
+        sqlx::query("DELETE FROM toygres_cms.instances WHERE dns_name = $1")
+            .bind(&dns_name)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(matches!(a, Ok(Reservation::Created(_))), "{:?}", a);
+        assert!(matches!(b, Ok(Reservation::Created(_))), "{:?}", b);
+        let err = taken.unwrap_err();
+        assert!(err.contains("already reserved in namespace 'staging'"), "{}", err);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL with the toygres_cms schema"]
+    async fn test_concurrent_reservations_exactly_one_wins() {
+        let pool = test_pool().await;
+        let dns_name = format!("lock-test-{}", &Uuid::new_v4().to_string()[..8]);
+        let first = input(&format!("{}-aaaaaaaa", dns_name), &dns_name);
+        let second = input(&format!("{}-bbbbbbbb", dns_name), &dns_name);
+
+        let (a, b) = tokio::join!(
+            reserve_instance_record(&pool, &first),
+            reserve_instance_record(&pool, &second),
+        );
+
+        sqlx::query("DELETE FROM toygres_cms.instances WHERE dns_name = $1")
+            .bind(&dns_name)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let winners = [&a, &b].iter().filter(|r| r.is_ok()).count();
+        assert_eq!(winners, 1, "results: {:?} / {:?}", a, b);
+
+        let loser = if a.is_err() { a } else { b };
+        assert!(loser.unwrap_err().contains("already reserved"));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL with the toygres_cms schema"]
+    async fn test_orchestration_version_is_stored() {
+        let pool = test_pool().await;
+        let dns_name = format!("version-test-{}", &Uuid::new_v4().to_string()[..8]);
+        let record = CreateInstanceRecordInput {
+            orchestration_version: Some("1.0.2".to_string()),
+            ..input(&format!("{}-aaaaaaaa", dns_name), &dns_name)
+        };
+
+        let reservation = reserve_instance_record(&pool, &record).await;
+        let stored: Option<String> = sqlx::query_scalar(
+            "SELECT orchestration_version FROM toygres_cms.instances WHERE k8s_name = $1"
+        )
+        .bind(&record.k8s_name)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        sqlx::query("DELETE FROM toygres_cms.instances WHERE dns_name = $1")
+            .bind(&dns_name)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(matches!(reservation, Ok(Reservation::Created(_))), "{:?}", reservation);
+        assert_eq!(stored.as_deref(), Some("1.0.2"));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL with the toygres_cms schema"]
+    async fn test_deploy_options_are_stored() {
+        use crate::activity_types::{InstanceDeployOptions, UpdateStrategy};
+
+        let pool = test_pool().await;
+        let dns_name = format!("options-test-{}", &Uuid::new_v4().to_string()[..8]);
+        let options = InstanceDeployOptions {
+            enable_pooler: true,
+            standby_replicas: Some(2),
+            update_strategy: Some(UpdateStrategy::OnDelete),
+            ..Default::default()
+        };
+        let record = CreateInstanceRecordInput {
+            deploy_options: Some(options.clone()),
+            ..input(&format!("{}-aaaaaaaa", dns_name), &dns_name)
+        };
+
+        let reservation = reserve_instance_record(&pool, &record).await;
+        let stored: Option<sqlx::types::Json<InstanceDeployOptions>> = sqlx::query_scalar(
+            "SELECT deploy_options FROM toygres_cms.instances WHERE k8s_name = $1"
+        )
+        .bind(&record.k8s_name)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        sqlx::query("DELETE FROM toygres_cms.instances WHERE dns_name = $1")
+            .bind(&dns_name)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(matches!(reservation, Ok(Reservation::Created(_))), "{:?}", reservation);
+        assert_eq!(stored.map(|json| json.0), Some(options));
+    }
+}
@@ -22,3 +22,17 @@ pub(crate) async fn get_pool() -> Result<PgPool, String> {
     Ok(pool.clone())
 }
 
+
+/// Dedicated pool for tests.
+///
+/// Each `#[tokio::test]` runs on its own runtime, and a pool cached in `POOL` by one
+/// test hangs when used from another test's runtime after the first one shuts down.
+#[cfg(test)]
+pub(crate) async fn test_pool() -> PgPool {
+    let db_url = std::env::var("DATABASE_URL").expect("DATABASE_URL not set");
+    PgPoolOptions::new()
+        .max_connections(4)
+        .connect(&db_url)
+        .await
+        .expect("Failed to connect to database")
+}
@@ -21,7 +21,8 @@ pub async fn activity(
 
     let record = sqlx::query(
         r#"
-        SELECT id, user_name, k8s_name, namespace, state::text as state, dns_name, instance_actor_orchestration_id
+        SELECT id, user_name, k8s_name, namespace, state::text as state, dns_name, postgres_version, instance_actor_orchestration_id,
+               private_connection_string IS NOT NULL AS private_dns
         FROM toygres_cms.instances
         WHERE k8s_name = $1
         "#
@@ -39,6 +40,8 @@ pub async fn activity(
             namespace: row.try_get("namespace").map_err(|e| format!("Failed to read namespace: {}", e))?,
             state: row.try_get("state").map_err(|e| format!("Failed to read state: {}", e))?,
             dns_name: row.try_get("dns_name").ok(),
+            postgres_version: row.try_get("postgres_version").ok(),
+            private_dns: row.try_get("private_dns").map_err(|e| format!("Failed to read private_dns: {}", e))?,
         };
         let instance_actor_orchestration_id: Option<String> = row.try_get("instance_actor_orchestration_id").ok();
         
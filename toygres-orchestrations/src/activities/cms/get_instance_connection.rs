@@ -18,7 +18,14 @@ pub async fn activity(
         r#"
         SELECT 
             COALESCE(dns_connection_string, ip_connection_string) as connection_string,
-            state::text
+            state::text,
+            password_secret_ref,
+            COALESCE((SELECT enabled FROM toygres_cms.maintenance_mode), FALSE) as maintenance_mode,
+            external_ip,
+            storage_size_gb,
+            CASE WHEN private_connection_string IS NOT NULL
+                THEN COALESCE(dns_name, k8s_name)
+            END as private_dns_record
         FROM toygres_cms.instances
         WHERE k8s_name = $1
         LIMIT 1
@@ -33,17 +40,32 @@ pub async fn activity(
         Some(row) => {
             let connection_string: Option<String> = row.try_get("connection_string").ok();
             let state: Option<String> = row.try_get("state").ok();
+            let password_secret_ref: Option<String> = row.try_get("password_secret_ref").ok().flatten();
+            let maintenance_mode: bool = row.try_get("maintenance_mode").unwrap_or(false);
+            let external_ip: Option<String> = row.try_get("external_ip").ok().flatten();
+            let storage_size_gb: Option<i32> = row.try_get("storage_size_gb").ok();
+            let private_dns_record: Option<String> = row.try_get("private_dns_record").ok().flatten();
             
             Ok(GetInstanceConnectionOutput {
                 found: true,
                 connection_string,
                 state,
+                password_secret_ref,
+                maintenance_mode,
+                external_ip,
+                storage_size_gb,
+                private_dns_record,
             })
         }
         None => Ok(GetInstanceConnectionOutput {
             found: false,
             connection_string: None,
             state: None,
+            password_secret_ref: None,
+            maintenance_mode: false,
+            external_ip: None,
+            storage_size_gb: None,
+            private_dns_record: None,
         }),
     }
 }
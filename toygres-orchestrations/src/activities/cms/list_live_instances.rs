@@ -0,0 +1,34 @@
+use duroxide::ActivityContext;
+use crate::activity_types::{ListLiveInstancesInput, ListLiveInstancesOutput};
+
+use super::get_pool;
+
+/// Activity name for registration and scheduling
+pub const NAME: &str = "toygres-orchestrations::activity::cms-list-live-instances";
+
+/// K8s names of every instance in the namespace whose resources may still be in use.
+///
+/// Only `deleted` and `failed` rows are excluded: `deleting` instances are left to
+/// their delete orchestration.
+pub async fn activity(
+    _ctx: ActivityContext,
+    input: ListLiveInstancesInput,
+) -> Result<ListLiveInstancesOutput, String> {
+    let pool = get_pool().await?;
+
+    let k8s_names: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT k8s_name
+        FROM toygres_cms.instances
+        WHERE namespace = $1
+          AND state NOT IN ('deleted', 'failed')
+        ORDER BY k8s_name
+        "#
+    )
+    .bind(&input.namespace)
+    .fetch_all(&pool)
+    .await
+    .map_err(|e| format!("Failed to list live instances: {}", e))?;
+
+    Ok(ListLiveInstancesOutput { k8s_names })
+}
@@ -1,14 +1,23 @@
 pub mod create_instance_record;
 pub mod update_instance_state;
 pub mod free_dns_name;
+pub mod claim_instance_deletion;
+pub mod release_instance_deletion;
 pub mod get_instance_by_k8s_name;
 pub mod get_instance_connection;
 pub mod record_health_check;
 pub mod update_instance_health;
 pub mod record_instance_actor;
 pub mod delete_instance_record;
+pub mod update_postgres_version;
+pub mod update_external_ip;
+pub mod update_exposure;
+pub mod record_instance_event;
+pub mod list_live_instances;
 
 mod db;
 
 pub(crate) use db::get_pool;
+#[cfg(test)]
+pub(crate) use db::test_pool;
 
@@ -17,8 +17,9 @@ pub async fn activity(
     let result = sqlx::query(
         r#"
         INSERT INTO toygres_cms.instance_health_checks 
-        (instance_id, status, postgres_version, response_time_ms, error_message, checked_at)
-        SELECT i.id, $2, $3, $4, $5, NOW()
+        (instance_id, status, postgres_version, response_time_ms, error_message, health_reason,
+         replication_lag_ms, database_size_bytes, connection_count, storage_pct, checked_at)
+        SELECT i.id, $2, $3, $4, $5, $6, $7, $8, $9, $10, NOW()
         FROM toygres_cms.instances i
         WHERE i.k8s_name = $1
         RETURNING id
@@ -29,6 +30,11 @@ pub async fn activity(
     .bind(&input.postgres_version)
     .bind(input.response_time_ms)
     .bind(&input.error_message)
+    .bind(input.health_reason.map(|reason| reason.as_str()))
+    .bind(input.replication_lag_ms)
+    .bind(input.database_size_bytes)
+    .bind(input.connection_count)
+    .bind(input.storage_pct)
     .fetch_optional(&pool)
     .await
     .map_err(|e| format!("Failed to insert health check: {}", e))?;
@@ -0,0 +1,41 @@
+use duroxide::ActivityContext;
+
+use crate::activity_types::{RecordInstanceEventInput, RecordInstanceEventOutput};
+
+use super::get_pool;
+
+/// Activity name for registration and scheduling
+pub const NAME: &str = "toygres-orchestrations::activity::cms-record-instance-event";
+
+pub async fn activity(
+    ctx: ActivityContext,
+    input: RecordInstanceEventInput,
+) -> Result<RecordInstanceEventOutput, String> {
+    let pool = get_pool().await?;
+
+    let result = sqlx::query(
+        r#"
+        INSERT INTO toygres_cms.instance_events
+        (instance_id, event_type, message, metadata)
+        SELECT i.id, $2, $3, $4
+        FROM toygres_cms.instances i
+        WHERE i.k8s_name = $1
+        "#
+    )
+    .bind(&input.k8s_name)
+    .bind(&input.event_type)
+    .bind(&input.message)
+    .bind(&input.metadata)
+    .execute(&pool)
+    .await
+    .map_err(|e| format!("Failed to insert instance event: {}", e))?;
+
+    let recorded = result.rows_affected() > 0;
+    if recorded {
+        ctx.trace_info(format!("Recorded {} event for {}: {}", input.event_type, input.k8s_name, input.message));
+    } else {
+        ctx.trace_warn(format!("No CMS record for {}, {} event not recorded", input.k8s_name, input.event_type));
+    }
+
+    Ok(RecordInstanceEventOutput { recorded })
+}
@@ -0,0 +1,38 @@
+use duroxide::ActivityContext;
+
+use crate::activity_types::{ReleaseInstanceDeletionInput, ReleaseInstanceDeletionOutput};
+
+use super::get_pool;
+
+/// Activity name for registration and scheduling
+pub const NAME: &str = "toygres-orchestrations::activity::cms-release-instance-deletion";
+
+/// Clear `delete_orchestration_id` if this orchestration still holds the instance, so a
+/// delete that failed does not block every later delete of it
+pub async fn activity(
+    ctx: ActivityContext,
+    input: ReleaseInstanceDeletionInput,
+) -> Result<ReleaseInstanceDeletionOutput, String> {
+    let pool = get_pool().await?;
+
+    let result = sqlx::query(
+        r#"
+        UPDATE toygres_cms.instances
+        SET delete_orchestration_id = NULL,
+            updated_at = NOW()
+        WHERE k8s_name = $1 AND delete_orchestration_id = $2
+        "#
+    )
+    .bind(&input.k8s_name)
+    .bind(&input.orchestration_id)
+    .execute(&pool)
+    .await
+    .map_err(|e| format!("Failed to release deletion claim: {}", e))?;
+
+    let released = result.rows_affected() > 0;
+    if released {
+        ctx.trace_info(format!("Released deletion claim on {}", input.k8s_name));
+    }
+
+    Ok(ReleaseInstanceDeletionOutput { released })
+}
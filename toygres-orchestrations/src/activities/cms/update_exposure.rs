@@ -0,0 +1,124 @@
+//! Record an instance's connection info after its Service type changed
+//!
+//! Unlike `update_instance_state`, every field is written as given, so switching to
+//! `ClusterIP` clears the LoadBalancer IP and the DNS and private connection strings.
+
+use duroxide::ActivityContext;
+use serde_json::json;
+use sqlx::Row;
+use toygres_models::redact_connection_string;
+use uuid::Uuid;
+
+use crate::activity_types::{UpdateExposureInput, UpdateExposureOutput};
+
+use super::get_pool;
+
+/// Activity name for registration and scheduling
+pub const NAME: &str = "toygres-orchestrations::activity::cms-update-exposure";
+
+pub async fn activity(
+    ctx: ActivityContext,
+    input: UpdateExposureInput,
+) -> Result<UpdateExposureOutput, String> {
+    let pool = get_pool().await?;
+    let mut tx = pool.begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    let record = sqlx::query(
+        r#"
+        SELECT id, use_load_balancer, ip_connection_string, dns_connection_string
+        FROM toygres_cms.instances
+        WHERE k8s_name = $1
+        FOR UPDATE
+        "#
+    )
+    .bind(&input.k8s_name)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to load CMS record: {}", e))?;
+
+    let Some(row) = record else {
+        ctx.trace_warn(format!("CMS record not found for {}", input.k8s_name));
+        return Ok(UpdateExposureOutput { updated: false });
+    };
+
+    let instance_id: Uuid = row.try_get("id")
+        .map_err(|e| format!("Failed to read instance id: {}", e))?;
+    let was_load_balancer: bool = row.try_get("use_load_balancer")
+        .map_err(|e| format!("Failed to read use_load_balancer: {}", e))?;
+    let old_ip: Option<String> = row.try_get("ip_connection_string")
+        .map_err(|e| format!("Failed to read IP connection string: {}", e))?;
+    let old_dns: Option<String> = row.try_get("dns_connection_string")
+        .map_err(|e| format!("Failed to read DNS connection string: {}", e))?;
+
+    let service_type = |load_balancer: bool| if load_balancer { "LoadBalancer" } else { "ClusterIP" };
+    let message = format!(
+        "Service changed from {} to {}",
+        service_type(was_load_balancer),
+        service_type(input.use_load_balancer)
+    );
+
+    sqlx::query(
+        r#"
+        UPDATE toygres_cms.instances
+        SET use_load_balancer = $2,
+            external_ip = $3,
+            ip_connection_string = $4,
+            dns_connection_string = $5,
+            private_connection_string = NULL,
+            updated_at = NOW()
+        WHERE id = $1
+        "#
+    )
+    .bind(instance_id)
+    .bind(input.use_load_balancer)
+    .bind(&input.external_ip)
+    .bind(&input.ip_connection_string)
+    .bind(&input.dns_connection_string)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to update CMS record: {}", e))?;
+
+    if old_ip.as_deref() != Some(input.ip_connection_string.as_str()) || old_dns != input.dns_connection_string {
+        sqlx::query(
+            r#"
+            INSERT INTO toygres_cms.instance_connection_history
+            (instance_id, old_ip_connection_string, new_ip_connection_string,
+             old_dns_connection_string, new_dns_connection_string, reason)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#
+        )
+        .bind(instance_id)
+        .bind(old_ip.as_deref().map(redact_connection_string))
+        .bind(redact_connection_string(&input.ip_connection_string))
+        .bind(old_dns.as_deref().map(redact_connection_string))
+        .bind(input.dns_connection_string.as_deref().map(redact_connection_string))
+        .bind(&message)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to record connection history: {}", e))?;
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO toygres_cms.instance_events
+        (instance_id, event_type, message, metadata)
+        VALUES ($1, 'exposure_changed', $2, $3)
+        "#
+    )
+    .bind(instance_id)
+    .bind(&message)
+    .bind(json!({
+        "use_load_balancer": input.use_load_balancer,
+        "external_ip": input.external_ip,
+    }))
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to insert instance event: {}", e))?;
+
+    tx.commit().await.map_err(|e| format!("Failed to commit CMS update: {}", e))?;
+
+    ctx.trace_info(format!("{} for {}", message, input.k8s_name));
+    Ok(UpdateExposureOutput { updated: true })
+}
@@ -1,4 +1,7 @@
 use duroxide::ActivityContext;
+use serde_json::json;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
 
 use crate::activity_types::{UpdateInstanceHealthInput, UpdateInstanceHealthOutput};
 
@@ -8,27 +11,252 @@ use super::get_pool;
 pub const NAME: &str = "toygres-orchestrations::activity::cms-update-instance-health";
 
 pub async fn activity(
-    _ctx: ActivityContext,
+    ctx: ActivityContext,
     input: UpdateInstanceHealthInput,
 ) -> Result<UpdateInstanceHealthOutput, String> {
     let pool = get_pool().await?;
-    
-    let result = sqlx::query(
+
+    let Some(previous_health_status) = apply_update(&pool, &input).await? else {
+        return Ok(UpdateInstanceHealthOutput { updated: false, previous_health_status: None });
+    };
+
+    if previous_health_status != input.health_status {
+        ctx.trace_info(format!(
+            "Instance '{}' health transition: {} → {}",
+            input.k8s_name, previous_health_status, input.health_status
+        ));
+    }
+
+    Ok(UpdateInstanceHealthOutput {
+        updated: true,
+        previous_health_status: Some(previous_health_status),
+    })
+}
+
+/// Apply the update in one transaction, recording a `health_change` event when the
+/// status differs from the stored one, and a `first_healthy` event with the time since
+/// creation the first time the instance becomes healthy. Returns the previous status,
+/// or `None` if no creating or running instance has this name.
+async fn apply_update(
+    pool: &PgPool,
+    input: &UpdateInstanceHealthInput,
+) -> Result<Option<String>, String> {
+    let mut tx = pool.begin()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+
+    let record = sqlx::query(
+        r#"
+        SELECT i.id, i.health_status::text as health_status,
+               EXTRACT(EPOCH FROM NOW() - i.created_at)::float8 as seconds_since_creation,
+               EXISTS (
+                   SELECT 1 FROM toygres_cms.instance_events e
+                   WHERE e.instance_id = i.id
+                     AND (e.event_type = 'first_healthy'
+                          OR (e.event_type = 'health_change' AND e.new_state = 'healthy'))
+               ) as was_healthy
+        FROM toygres_cms.instances i
+        WHERE i.k8s_name = $1
+          AND i.state IN ('creating', 'running')
+        FOR UPDATE OF i
+        "#
+    )
+    .bind(&input.k8s_name)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| format!("Failed to load CMS record: {}", e))?;
+
+    let Some(row) = record else {
+        tx.rollback().await.map_err(|e| format!("Failed to rollback after missing instance: {}", e))?;
+        return Ok(None);
+    };
+
+    let instance_id: Uuid = row.try_get("id")
+        .map_err(|e| format!("Failed to read instance id: {}", e))?;
+    let previous_health_status: String = row.try_get("health_status")
+        .map_err(|e| format!("Failed to read previous health status: {}", e))?;
+    let seconds_since_creation: f64 = row.try_get("seconds_since_creation")
+        .map_err(|e| format!("Failed to read instance age: {}", e))?;
+    let was_healthy: bool = row.try_get("was_healthy")
+        .map_err(|e| format!("Failed to read health history: {}", e))?;
+
+    sqlx::query(
         r#"
         UPDATE toygres_cms.instances
         SET health_status = $2::health_status, updated_at = NOW()
-        WHERE k8s_name = $1
-          AND state = 'running'
+        WHERE id = $1
         "#
     )
-    .bind(&input.k8s_name)
+    .bind(instance_id)
     .bind(&input.health_status)
-    .execute(&pool)
+    .execute(&mut *tx)
     .await
     .map_err(|e| format!("Failed to update instance health: {}", e))?;
-    
-    Ok(UpdateInstanceHealthOutput {
-        updated: result.rows_affected() > 0,
-    })
+
+    if previous_health_status != input.health_status {
+        sqlx::query(
+            r#"
+            INSERT INTO toygres_cms.instance_events
+            (instance_id, event_type, old_state, new_state)
+            VALUES ($1, 'health_change', $2, $3)
+            "#
+        )
+        .bind(instance_id)
+        .bind(&previous_health_status)
+        .bind(&input.health_status)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to insert instance event: {}", e))?;
+    }
+
+    // Time-to-ready: an instance that was healthy before, including one healthy since
+    // before this event existed, does not get another
+    if input.health_status == "healthy" && previous_health_status != "healthy" && !was_healthy {
+        sqlx::query(
+            r#"
+            INSERT INTO toygres_cms.instance_events
+            (instance_id, event_type, message, metadata)
+            VALUES ($1, 'first_healthy', $2, $3)
+            "#
+        )
+        .bind(instance_id)
+        .bind(format!("Instance became healthy {:.0}s after creation", seconds_since_creation))
+        .bind(json!({
+            "seconds_since_creation": seconds_since_creation,
+            "previous_health_status": previous_health_status,
+        }))
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| format!("Failed to insert first healthy event: {}", e))?;
+    }
+
+    tx.commit().await.map_err(|e| format!("Failed to commit health update: {}", e))?;
+
+    Ok(Some(previous_health_status))
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activities::cms::test_pool;
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL with the toygres_cms schema"]
+    async fn test_only_health_transitions_write_events() {
+        let pool = test_pool().await;
+        let k8s_name = format!("health-test-{}", &Uuid::new_v4().to_string()[..8]);
+
+        let instance_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO toygres_cms.instances
+            (user_name, k8s_name, postgres_version, storage_size_gb, state, create_orchestration_id)
+            VALUES ($1, $1, '18', 10, 'running', $2)
+            RETURNING id
+            "#
+        )
+        .bind(&k8s_name)
+        .bind(format!("create-{}", k8s_name))
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        let update = |health_status: &str| UpdateInstanceHealthInput {
+            k8s_name: k8s_name.clone(),
+            health_status: health_status.to_string(),
+        };
+        let events = |pool: PgPool| async move {
+            sqlx::query_as::<_, (Option<String>, Option<String>)>(
+                r#"
+                SELECT old_state, new_state
+                FROM toygres_cms.instance_events
+                WHERE instance_id = $1 AND event_type = 'health_change'
+                ORDER BY id
+                "#
+            )
+            .bind(instance_id)
+            .fetch_all(&pool)
+            .await
+            .unwrap()
+        };
+
+        // New instances start out 'unknown'
+        assert_eq!(apply_update(&pool, &update("healthy")).await.unwrap().as_deref(), Some("unknown"));
+        apply_update(&pool, &update("healthy")).await.unwrap();
+        apply_update(&pool, &update("healthy")).await.unwrap();
+        assert_eq!(events(pool.clone()).await.len(), 1);
+
+        apply_update(&pool, &update("unhealthy")).await.unwrap();
+        apply_update(&pool, &update("unhealthy")).await.unwrap();
+        apply_update(&pool, &update("healthy")).await.unwrap();
+        let recorded = events(pool.clone()).await;
+
+        sqlx::query("DELETE FROM toygres_cms.instances WHERE id = $1")
+            .bind(instance_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let transitions: Vec<(&str, &str)> = recorded
+            .iter()
+            .map(|(old, new)| (old.as_deref().unwrap(), new.as_deref().unwrap()))
+            .collect();
+        assert_eq!(transitions, vec![
+            ("unknown", "healthy"),
+            ("healthy", "unhealthy"),
+            ("unhealthy", "healthy"),
+        ]);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL with the toygres_cms schema"]
+    async fn test_only_first_healthy_transition_is_recorded() {
+        let pool = test_pool().await;
+        let k8s_name = format!("first-healthy-test-{}", &Uuid::new_v4().to_string()[..8]);
+
+        // Created a minute ago, so the recorded time-to-ready is about 60s
+        let instance_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO toygres_cms.instances
+            (user_name, k8s_name, postgres_version, storage_size_gb, state, create_orchestration_id, created_at)
+            VALUES ($1, $1, '18', 10, 'creating', $2, NOW() - INTERVAL '60 seconds')
+            RETURNING id
+            "#
+        )
+        .bind(&k8s_name)
+        .bind(format!("create-{}", k8s_name))
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        let update = |health_status: &str| UpdateInstanceHealthInput {
+            k8s_name: k8s_name.clone(),
+            health_status: health_status.to_string(),
+        };
+        for health_status in ["unhealthy", "healthy", "healthy", "unhealthy", "unmonitored", "healthy"] {
+            apply_update(&pool, &update(health_status)).await.unwrap();
+        }
+
+        let recorded: Vec<serde_json::Value> = sqlx::query_scalar(
+            r#"
+            SELECT metadata
+            FROM toygres_cms.instance_events
+            WHERE instance_id = $1 AND event_type = 'first_healthy'
+            "#
+        )
+        .bind(instance_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+
+        sqlx::query("DELETE FROM toygres_cms.instances WHERE id = $1")
+            .bind(instance_id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(recorded.len(), 1, "{:?}", recorded);
+        assert_eq!(recorded[0]["previous_health_status"], "unhealthy");
+        let seconds = recorded[0]["seconds_since_creation"].as_f64().unwrap();
+        assert!((60.0..120.0).contains(&seconds), "{}", seconds);
+    }
+}
@@ -0,0 +1,37 @@
+use duroxide::ActivityContext;
+use crate::activity_types::{UpdatePostgresVersionInput, UpdatePostgresVersionOutput};
+
+use super::get_pool;
+
+/// Activity name for registration and scheduling
+pub const NAME: &str = "toygres-orchestrations::activity::cms-update-postgres-version";
+
+pub async fn activity(
+    ctx: ActivityContext,
+    input: UpdatePostgresVersionInput,
+) -> Result<UpdatePostgresVersionOutput, String> {
+    let pool = get_pool().await?;
+
+    let result = sqlx::query(
+        r#"
+        UPDATE toygres_cms.instances
+        SET postgres_version = $2,
+            updated_at = NOW()
+        WHERE k8s_name = $1
+        "#
+    )
+    .bind(&input.k8s_name)
+    .bind(&input.postgres_version)
+    .execute(&pool)
+    .await
+    .map_err(|e| format!("Failed to update postgres version: {}", e))?;
+
+    let updated = result.rows_affected() > 0;
+    if updated {
+        ctx.trace_info(format!("Recorded postgres version {} for {}", input.postgres_version, input.k8s_name));
+    } else {
+        ctx.trace_warn(format!("No CMS record for {}, postgres version not recorded", input.k8s_name));
+    }
+
+    Ok(UpdatePostgresVersionOutput { updated })
+}
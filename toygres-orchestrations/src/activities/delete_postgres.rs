@@ -2,6 +2,7 @@
 
 use duroxide::ActivityContext;
 use crate::activity_types::{DeletePostgresInput, DeletePostgresOutput};
+use crate::activities::deploy_postgres::headless_service_name;
 use crate::k8s_client::{get_k8s_client, check_resources_exist};
 use k8s_openapi::api::apps::v1::StatefulSet;
 use k8s_openapi::api::core::v1::{PersistentVolumeClaim, Service};
@@ -58,6 +59,14 @@ async fn delete_k8s_resources(
         Err(e) => return Err(anyhow::anyhow!("Failed to delete Service: {}", e)),
     }
     
+    // Only instances with standby replicas have one
+    let headless_name = headless_service_name(&input.instance_name);
+    match services.delete(&headless_name, &delete_params).await {
+        Ok(_) => ctx.trace_info("Headless Service deleted"),
+        Err(kube::Error::Api(response)) if response.code == 404 => {}
+        Err(e) => return Err(anyhow::anyhow!("Failed to delete headless Service: {}", e)),
+    }
+    
     // Delete StatefulSet
     ctx.trace_info("Deleting StatefulSet");
     let statefulsets: Api<StatefulSet> = Api::namespaced(client.clone(), &input.namespace);
@@ -0,0 +1,62 @@
+//! Delete specific Kubernetes resources activity
+
+use duroxide::ActivityContext;
+use crate::activities::list_toygres_resources::{PERSISTENT_VOLUME_CLAIM, SERVICE, STATEFUL_SET};
+use crate::activity_types::{DeleteResourcesInput, DeleteResourcesOutput, ToygresResource};
+use crate::k8s_client::get_k8s_client;
+use k8s_openapi::api::apps::v1::StatefulSet;
+use k8s_openapi::api::core::v1::{PersistentVolumeClaim, Service};
+use kube::api::{Api, DeleteParams, Preconditions};
+
+/// Activity name for registration and scheduling
+pub const NAME: &str = "toygres-orchestrations::activity::delete-resources";
+
+pub async fn activity(
+    ctx: ActivityContext,
+    input: DeleteResourcesInput,
+) -> Result<DeleteResourcesOutput, String> {
+    ctx.trace_info(format!("Deleting {} resources in namespace {}", input.resources.len(), input.namespace));
+    
+    let client = get_k8s_client().await
+        .map_err(|e| format!("Failed to create K8s client: {}", e))?;
+    
+    let mut deleted = Vec::new();
+    for resource in &input.resources {
+        let params = delete_params(resource);
+        let result = match resource.kind.as_str() {
+            STATEFUL_SET => Api::<StatefulSet>::namespaced(client.clone(), &input.namespace)
+                .delete(&resource.name, &params).await.map(|_| ()),
+            SERVICE => Api::<Service>::namespaced(client.clone(), &input.namespace)
+                .delete(&resource.name, &params).await.map(|_| ()),
+            PERSISTENT_VOLUME_CLAIM => Api::<PersistentVolumeClaim>::namespaced(client.clone(), &input.namespace)
+                .delete(&resource.name, &params).await.map(|_| ()),
+            other => return Err(format!("Unsupported resource kind '{}'", other)),
+        };
+        
+        let label = format!("{}/{}", resource.kind, resource.name);
+        match result {
+            Ok(()) => {
+                ctx.trace_info(format!("Deleted {}", label));
+                deleted.push(label);
+            }
+            // Already gone, or replaced by a new object with the same name (UID mismatch)
+            Err(kube::Error::Api(response)) if response.code == 404 || response.code == 409 => {
+                ctx.trace_info(format!("{} no longer matches, skipping ({})", label, response.reason));
+            }
+            Err(e) => return Err(format!("Failed to delete {}: {}", label, e)),
+        }
+    }
+    
+    Ok(DeleteResourcesOutput { deleted })
+}
+
+/// Delete only the object that was listed, not a newer one with the same name
+fn delete_params(resource: &ToygresResource) -> DeleteParams {
+    DeleteParams {
+        preconditions: resource.uid.clone().map(|uid| Preconditions {
+            uid: Some(uid),
+            resource_version: None,
+        }),
+        ..Default::default()
+    }
+}
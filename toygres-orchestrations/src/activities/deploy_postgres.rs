@@ -1,32 +1,86 @@
 //! Deploy PostgreSQL activity
 
 use duroxide::ActivityContext;
-use crate::activity_types::{DeployPostgresInput, DeployPostgresOutput};
-use crate::k8s_client::{get_k8s_client, check_resources_exist};
+use crate::activity_types::{DeployPostgresInput, DeployPostgresOutput, InitContainerSpec, ProbeTimings, UpdateStrategy, MAX_STANDBY_REPLICAS, POOLER_PORT, POSTGRES_UID};
+use crate::types::{is_dns_label, is_dns_subdomain};
+use crate::k8s_client::{get_k8s_client, check_resources_exist, read_secret_password, PASSWORD_SECRET_KEY};
 use k8s_openapi::api::core::v1::{PersistentVolumeClaim, Service};
 use k8s_openapi::api::apps::v1::StatefulSet;
 use kube::api::{Api, PostParams};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use tera::{Tera, Context as TeraContext};
 
 /// Activity name for registration and scheduling
 pub const NAME: &str = "toygres-orchestrations::activity::deploy-postgres";
 
+/// PVC access mode used when none is requested
+pub const DEFAULT_ACCESS_MODE: &str = "ReadWriteOnce";
+
+/// Service annotation carrying the Azure DNS label
+pub const AZURE_DNS_LABEL_ANNOTATION: &str = "service.beta.kubernetes.io/azure-dns-label-name";
+
+/// Service annotation that makes Azure provision an internal (private IP) LoadBalancer
+pub const AZURE_INTERNAL_LB_ANNOTATION: &str = "service.beta.kubernetes.io/azure-load-balancer-internal";
+
+/// Image of the PgBouncer sidecar deployed with `enable_pooler`
+pub const POOLER_IMAGE: &str = "edoburu/pgbouncer:v1.23.1-p2";
+
+/// Name of the PgBouncer sidecar container
+pub const POOLER_CONTAINER: &str = "pgbouncer";
+
+/// Stands in for the password in manifests rendered for display
+pub const PASSWORD_MASK: &str = "********";
+
+/// Directory of template overrides, read at runtime. A file missing from it (say only
+/// `postgres-statefulset.yaml` is customized) falls back to the embedded template.
+pub const TEMPLATE_DIR_ENV: &str = "TOYGRES_TEMPLATE_DIR";
+
+/// Template name, override file name and embedded source
+const TEMPLATES: [(&str, &str, &str); 4] = [
+    ("pvc", "postgres-pvc.yaml", include_str!("../templates/postgres-pvc.yaml")),
+    ("statefulset", "postgres-statefulset.yaml", include_str!("../templates/postgres-statefulset.yaml")),
+    ("service", "postgres-service.yaml", include_str!("../templates/postgres-service.yaml")),
+    ("headless_service", "postgres-headless-service.yaml", include_str!("../templates/postgres-headless-service.yaml")),
+];
+
+/// PVC access modes accepted for single-replica Postgres volumes.
+/// `ReadWriteOncePod` guards against two pods mounting the volume at once.
+pub const ALLOWED_ACCESS_MODES: &[&str] = &["ReadWriteOnce", "ReadWriteOncePod"];
+
 pub async fn activity(
     ctx: ActivityContext,
     input: DeployPostgresInput,
 ) -> Result<DeployPostgresOutput, String> {
     ctx.trace_info(format!("Deploying PostgreSQL: {}", input.instance_name));
     
+    // 1. Validate input
+    resolve_username(input.username.as_deref())?;
+    resolve_access_mode(input.access_mode.as_deref())?;
+    service_annotations(&input)?;
+    init_containers(&input)?;
+    standby_replicas(input.standby_replicas)?;
+    
     // 2. Get K8s client
     let client = get_k8s_client().await
         .map_err(|e| format!("Failed to create K8s client: {}", e))?;
     
+    // The referenced password Secret must exist before the StatefulSet points at it
+    if let Some(secret) = &input.password_secret_ref {
+        read_secret_password(&client, &input.namespace, secret).await
+            .map_err(|e| format!("Invalid password_secret_ref: {}", e))?;
+        ctx.trace_info(format!("Using password from Secret '{}'", secret));
+    }
+    
     // 3. Check idempotency - do resources already exist?
     let already_exists = check_resources_exist(&client, &input.namespace, &input.instance_name).await
         .map_err(|e| format!("Failed to check if resources exist: {}", e))?;
     
     if already_exists {
         ctx.trace_info("Resources already exist, skipping creation");
+        // A retry after a crash between the StatefulSet and the headless Service
+        ensure_headless_service(&client, &input, &ctx).await
+            .map_err(|e| format!("Failed to create headless Service: {}", e))?;
         return Ok(DeployPostgresOutput {
             instance_name: input.instance_name,
             namespace: input.namespace,
@@ -48,78 +102,537 @@ pub async fn activity(
     })
 }
 
+/// The Kubernetes objects `deploy_postgres` creates for an input
+#[derive(Debug, Clone)]
+pub struct RenderedResources {
+    pub pvc: PersistentVolumeClaim,
+    pub statefulset: StatefulSet,
+    pub service: Service,
+    /// Only with standby replicas
+    pub headless_service: Option<Service>,
+}
+
+/// The YAML the PVC, StatefulSet and Service templates render to
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderedManifests {
+    pub pvc: String,
+    pub headless_service: Option<String>,
+    pub statefulset: String,
+    pub service: String,
+}
+
+impl RenderedManifests {
+    /// The manifests as one multi-document YAML stream, in creation order
+    pub fn to_yaml(&self) -> String {
+        [Some(&self.pvc), self.headless_service.as_ref(), Some(&self.statefulset), Some(&self.service)]
+            .into_iter()
+            .flatten()
+            .map(|doc| format!("---\n{}\n", doc.trim()))
+            .collect()
+    }
+    
+    /// Parse the manifests into the objects `deploy_postgres` creates
+    pub fn parse(&self) -> anyhow::Result<RenderedResources> {
+        Ok(RenderedResources {
+            pvc: serde_yaml::from_str(&self.pvc)?,
+            statefulset: serde_yaml::from_str(&self.statefulset)?,
+            service: serde_yaml::from_str(&self.service)?,
+            headless_service: self.headless_service.as_deref().map(serde_yaml::from_str).transpose()?,
+        })
+    }
+}
+
+/// Name of the headless Service deployed for instances with standby replicas
+pub fn headless_service_name(instance_name: &str) -> String {
+    format!("{}-headless", instance_name)
+}
+
+/// Stable DNS name of the primary (pod 0) behind the headless Service
+pub fn primary_host(instance_name: &str, namespace: &str) -> String {
+    format!("{}-0.{}.{}.svc.cluster.local", instance_name, headless_service_name(instance_name), namespace)
+}
+
+/// Render the PVC, StatefulSet and Service templates for `input`
+pub fn render_manifests(input: &DeployPostgresInput) -> anyhow::Result<RenderedManifests> {
+    render_with(&load_templates()?, input)
+}
+
+fn render_with(tera: &Tera, input: &DeployPostgresInput) -> anyhow::Result<RenderedManifests> {
+    let template_ctx = template_context(input).map_err(|e| anyhow::anyhow!(e))?;
+    
+    let replicated = standby_replicas(input.standby_replicas).map_err(|e| anyhow::anyhow!(e))? > 0;
+    
+    Ok(RenderedManifests {
+        pvc: tera.render("pvc", &template_ctx)?,
+        headless_service: replicated.then(|| tera.render("headless_service", &template_ctx)).transpose()?,
+        statefulset: tera.render("statefulset", &template_ctx)?,
+        service: tera.render("service", &template_ctx)?,
+    })
+}
+
+/// [`render_manifests`] with the password replaced by [`PASSWORD_MASK`], for display
+pub fn render_masked_manifests(input: &DeployPostgresInput) -> anyhow::Result<RenderedManifests> {
+    render_manifests(&DeployPostgresInput {
+        password: PASSWORD_MASK.to_string(),
+        ..input.clone()
+    })
+}
+
+/// Render and parse the PVC, StatefulSet and Service for `input`
+pub fn render_resources(input: &DeployPostgresInput) -> anyhow::Result<RenderedResources> {
+    render_manifests(input)?.parse()
+}
+
 async fn create_k8s_resources(
     client: &kube::Client,
     input: &DeployPostgresInput,
     ctx: &ActivityContext,
 ) -> anyhow::Result<()> {
-    // Initialize template engine
-    let mut tera = Tera::default();
-    
-    // Load templates
-    let pvc_template = include_str!("../templates/postgres-pvc.yaml");
-    let statefulset_template = include_str!("../templates/postgres-statefulset.yaml");
-    let service_template = include_str!("../templates/postgres-service.yaml");
-    
-    tera.add_raw_template("pvc", pvc_template)?;
-    tera.add_raw_template("statefulset", statefulset_template)?;
-    tera.add_raw_template("service", service_template)?;
-    
-    // Prepare template context
-    let mut template_ctx = TeraContext::new();
-    template_ctx.insert("name", &input.instance_name);
-    template_ctx.insert("namespace", &input.namespace);
-    template_ctx.insert("password", &input.password);
-    template_ctx.insert("storage_size", &input.storage_size_gb);
-    template_ctx.insert("postgres_version", &input.postgres_version);
-    template_ctx.insert("service_type", if input.use_load_balancer { "LoadBalancer" } else { "ClusterIP" });
-    template_ctx.insert("dns_label", &input.dns_label.as_deref().unwrap_or(""));
+    let resources = render_resources(input)?;
     
     // 1. Create PersistentVolumeClaim
     ctx.trace_info("Creating PersistentVolumeClaim");
-    let pvc_yaml = tera.render("pvc", &template_ctx)?;
-    let pvc: PersistentVolumeClaim = serde_yaml::from_str(&pvc_yaml)?;
-    
     let pvcs: Api<PersistentVolumeClaim> = Api::namespaced(client.clone(), &input.namespace);
-    pvcs.create(&PostParams::default(), &pvc).await?;
+    pvcs.create(&PostParams::default(), &resources.pvc).await?;
     ctx.trace_info("PersistentVolumeClaim created");
     
+    // The StatefulSet's serviceName must exist for its pods to get DNS names
+    if let Some(headless) = &resources.headless_service {
+        ctx.trace_info("Creating headless Service");
+        let services: Api<Service> = Api::namespaced(client.clone(), &input.namespace);
+        services.create(&PostParams::default(), headless).await?;
+        ctx.trace_info("Headless Service created");
+    }
+    
     // 2. Create StatefulSet
     ctx.trace_info("Creating StatefulSet");
-    let statefulset_yaml = tera.render("statefulset", &template_ctx)?;
-    let statefulset: StatefulSet = serde_yaml::from_str(&statefulset_yaml)?;
-    
     let statefulsets: Api<StatefulSet> = Api::namespaced(client.clone(), &input.namespace);
-    statefulsets.create(&PostParams::default(), &statefulset).await?;
+    statefulsets.create(&PostParams::default(), &resources.statefulset).await?;
     ctx.trace_info("StatefulSet created");
     
     // 3. Create Service
     ctx.trace_info("Creating Service");
-    let service_yaml = tera.render("service", &template_ctx)?;
-    let service: Service = serde_yaml::from_str(&service_yaml)?;
-    
     let services: Api<Service> = Api::namespaced(client.clone(), &input.namespace);
-    services.create(&PostParams::default(), &service).await?;
+    services.create(&PostParams::default(), &resources.service).await?;
     ctx.trace_info("Service created");
 
     Ok(())
 }
 
+/// Create the headless Service if the input asks for one and it is missing
+async fn ensure_headless_service(
+    client: &kube::Client,
+    input: &DeployPostgresInput,
+    ctx: &ActivityContext,
+) -> anyhow::Result<()> {
+    let Some(headless) = render_resources(input)?.headless_service else {
+        return Ok(());
+    };
+    
+    let services: Api<Service> = Api::namespaced(client.clone(), &input.namespace);
+    if services.get_opt(&headless_service_name(&input.instance_name)).await?.is_none() {
+        ctx.trace_info("Headless Service missing, creating it");
+        services.create(&PostParams::default(), &headless).await?;
+    }
+    Ok(())
+}
+
+/// Validate the requested superuser, falling back to [`toygres_models::DEFAULT_USERNAME`]
+fn resolve_username(requested: Option<&str>) -> Result<&str, String> {
+    match requested {
+        None => Ok(toygres_models::DEFAULT_USERNAME),
+        Some(username) if toygres_models::is_valid_username(username) => Ok(username),
+        Some(username) => Err(format!(
+            "Invalid username '{}': use lowercase letters, digits and '_', starting with a letter or '_'",
+            username
+        )),
+    }
+}
+
+/// Validate the requested PVC access mode, falling back to [`DEFAULT_ACCESS_MODE`]
+fn resolve_access_mode(requested: Option<&str>) -> Result<&str, String> {
+    match requested {
+        None => Ok(DEFAULT_ACCESS_MODE),
+        Some(mode) if ALLOWED_ACCESS_MODES.contains(&mode) => Ok(mode),
+        Some(mode) => Err(format!(
+            "Invalid PVC access mode '{}'. Allowed: {}",
+            mode,
+            ALLOWED_ACCESS_MODES.join(", ")
+        )),
+    }
+}
+
+/// Annotations for the Service: the DNS label, then user annotations, then the
+/// internal LB annotation when requested (which wins over a user-supplied value)
+fn service_annotations(input: &DeployPostgresInput) -> Result<BTreeMap<String, String>, String> {
+    if input.internal_load_balancer && !input.use_load_balancer {
+        return Err("internal_load_balancer requires use_load_balancer".to_string());
+    }
+    
+    let mut annotations = BTreeMap::new();
+    annotations.insert(
+        AZURE_DNS_LABEL_ANNOTATION.to_string(),
+        input.dns_label.clone().unwrap_or_default(),
+    );
+    if let Some(extra) = &input.service_annotations {
+        validate_service_annotations(extra)?;
+        annotations.extend(extra.clone());
+    }
+    if input.internal_load_balancer {
+        annotations.insert(AZURE_INTERNAL_LB_ANNOTATION.to_string(), "true".to_string());
+    }
+    
+    Ok(annotations)
+}
+
+/// Check user Service annotations are valid keys (`[prefix/]name`) and do not set the
+/// annotations derived from `dns_label` and `internal_load_balancer`
+pub fn validate_service_annotations(annotations: &BTreeMap<String, String>) -> Result<(), String> {
+    for key in annotations.keys() {
+        if [AZURE_DNS_LABEL_ANNOTATION, AZURE_INTERNAL_LB_ANNOTATION].contains(&key.as_str()) {
+            return Err(format!("'{}' is managed by Toygres; use dns_label or internal_load_balancer", key));
+        }
+        if !is_annotation_key(key) {
+            return Err(format!("'{}' is not a valid annotation key", key));
+        }
+    }
+    Ok(())
+}
+
+/// An optional DNS subdomain prefix and '/', then a name of at most 63 alphanumerics,
+/// '-', '_' and '.', starting and ending alphanumeric
+fn is_annotation_key(key: &str) -> bool {
+    let (prefix, name) = match key.split_once('/') {
+        Some((prefix, name)) => (Some(prefix), name),
+        None => (None, key),
+    };
+    prefix.is_none_or(is_dns_subdomain)
+        && !name.is_empty()
+        && name.len() <= 63
+        && name.bytes().all(|b| b.is_ascii_alphanumeric() || b"-_.".contains(&b))
+        && name.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && name.ends_with(|c: char| c.is_ascii_alphanumeric())
+}
+
+/// Validate the requested init containers; none are rendered when omitted
+fn init_containers(input: &DeployPostgresInput) -> Result<&[InitContainerSpec], String> {
+    let specs = input.init_containers.as_deref().unwrap_or_default();
+    validate_init_containers(specs)?;
+    Ok(specs)
+}
+
+/// Check init container names are unique DNS labels other than "postgres" and their
+/// images are valid references
+pub fn validate_init_containers(specs: &[InitContainerSpec]) -> Result<(), String> {
+    let mut names = std::collections::HashSet::new();
+    for spec in specs {
+        if !is_dns_label(&spec.name) || spec.name == "postgres" {
+            return Err(format!("Invalid init container name '{}'", spec.name));
+        }
+        if !names.insert(spec.name.as_str()) {
+            return Err(format!("Duplicate init container name '{}'", spec.name));
+        }
+        validate_image_reference(&spec.image)
+            .map_err(|e| format!("Invalid image for init container '{}': {}", spec.name, e))?;
+    }
+    
+    Ok(())
+}
+
+/// Check an image reference of the form `[registry[:port]/]repo/path[:tag][@algo:digest]`
+fn validate_image_reference(image: &str) -> Result<(), String> {
+    if image.is_empty() {
+        return Err("image must not be empty".to_string());
+    }
+    if image.len() > 512 || image.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(format!("'{}' is not a valid image reference", image));
+    }
+    
+    let (name_and_tag, digest) = match image.split_once('@') {
+        Some((rest, digest)) => (rest, Some(digest)),
+        None => (image, None),
+    };
+    if let Some(digest) = digest {
+        let valid = digest.split_once(':').is_some_and(|(algo, hex)| {
+            !algo.is_empty()
+                && algo.bytes().all(|b| b.is_ascii_alphanumeric())
+                && hex.len() >= 32
+                && hex.bytes().all(|b| b.is_ascii_hexdigit())
+        });
+        if !valid {
+            return Err(format!("'{}' has an invalid digest", image));
+        }
+    }
+    
+    // A ':' after the last '/' starts the tag; earlier ones belong to a registry port
+    let last_slash = name_and_tag.rfind('/').map(|i| i + 1).unwrap_or(0);
+    let (name, tag) = match name_and_tag[last_slash..].find(':') {
+        Some(i) => (&name_and_tag[..last_slash + i], Some(&name_and_tag[last_slash + i + 1..])),
+        None => (name_and_tag, None),
+    };
+    if let Some(tag) = tag {
+        let valid = !tag.is_empty()
+            && tag.len() <= 128
+            && !tag.starts_with(['.', '-'])
+            && tag.bytes().all(|b| b.is_ascii_alphanumeric() || b"_.-".contains(&b));
+        if !valid {
+            return Err(format!("'{}' has an invalid tag", image));
+        }
+    }
+    
+    let mut components = name.split('/').peekable();
+    let mut first = true;
+    while let Some(component) = components.next() {
+        // The registry host may carry uppercase, dots, and a port
+        let is_registry = first && components.peek().is_some()
+            && (component.contains(['.', ':']) || component == "localhost");
+        let valid = if is_registry {
+            !component.is_empty()
+                && component.bytes().all(|b| b.is_ascii_alphanumeric() || b".-:".contains(&b))
+        } else {
+            !component.is_empty()
+                && component.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b"._-".contains(&b))
+                && component.starts_with(|c: char| c.is_ascii_alphanumeric())
+                && component.ends_with(|c: char| c.is_ascii_alphanumeric())
+        };
+        if !valid {
+            return Err(format!("'{}' is not a valid image reference", image));
+        }
+        first = false;
+    }
+    
+    Ok(())
+}
+
+/// Whether a StatefulSet runs the PgBouncer sidecar. The sidecar lives in the Postgres
+/// pod, so deleting the StatefulSet and Service removes the pooler with the instance.
+pub fn has_pooler(statefulset: &StatefulSet) -> bool {
+    statefulset.spec.as_ref()
+        .and_then(|s| s.template.spec.as_ref())
+        .is_some_and(|pod| pod.containers.iter().any(|c| c.name == POOLER_CONTAINER))
+}
+
+/// Standby replicas a StatefulSet was deployed with, i.e. its pods besides the primary
+pub fn standby_replicas_of(statefulset: &StatefulSet) -> Option<u32> {
+    let replicas = statefulset.spec.as_ref()?.replicas?;
+    u32::try_from(replicas - 1).ok().filter(|count| *count > 0)
+}
+
+/// The update strategy a StatefulSet was deployed with, or `None` for Kubernetes'
+/// default (`RollingUpdate` with partition 0)
+pub fn update_strategy_of(statefulset: &StatefulSet) -> Option<UpdateStrategy> {
+    let strategy = statefulset.spec.as_ref()?.update_strategy.as_ref()?;
+    match strategy.type_.as_deref() {
+        Some("OnDelete") => Some(UpdateStrategy::OnDelete),
+        _ => strategy.rolling_update.as_ref()
+            .and_then(|rolling| rolling.partition)
+            .filter(|partition| *partition > 0)
+            .map(|partition| UpdateStrategy::RollingUpdate { partition }),
+    }
+}
+
+/// A uid/gid for the pod securityContext, defaulting to the postgres user
+fn security_id(field: &str, value: Option<i64>) -> Result<i64, String> {
+    match value {
+        Some(id) if id < 0 => Err(format!("{} must not be negative, got {}", field, id)),
+        Some(id) => Ok(id),
+        None => Ok(POSTGRES_UID),
+    }
+}
+
+/// Validate the requested probe timings, defaulting to `default`
+fn probe_timings(field: &str, requested: Option<ProbeTimings>, default: fn() -> ProbeTimings) -> Result<ProbeTimings, String> {
+    match requested {
+        Some(timings) => timings.validate().map(|()| timings).map_err(|e| format!("{} {}", field, e)),
+        None => Ok(default()),
+    }
+}
+
+/// Validate the requested standby count, defaulting to none
+fn standby_replicas(requested: Option<u32>) -> Result<u32, String> {
+    match requested.unwrap_or(0) {
+        count if count > MAX_STANDBY_REPLICAS => Err(format!(
+            "standby_replicas must be at most {}, got {}", MAX_STANDBY_REPLICAS, count
+        )),
+        count => Ok(count),
+    }
+}
+
+/// Validate the requested update strategy; none is rendered when omitted
+fn update_strategy(requested: Option<UpdateStrategy>) -> Result<Option<UpdateStrategy>, String> {
+    match requested {
+        Some(UpdateStrategy::RollingUpdate { partition }) if partition < 0 => {
+            Err(format!("update_strategy partition must not be negative, got {}", partition))
+        }
+        other => Ok(other),
+    }
+}
+
+/// The override directory from [`TEMPLATE_DIR_ENV`], if set
+pub fn template_dir() -> Option<PathBuf> {
+    std::env::var(TEMPLATE_DIR_ENV).ok()
+        .filter(|dir| !dir.trim().is_empty())
+        .map(PathBuf::from)
+}
+
+fn load_templates() -> anyhow::Result<Tera> {
+    load_templates_from(template_dir().as_deref())
+}
+
+/// Load the templates, taking each from `dir` when it has the file
+pub fn load_templates_from(dir: Option<&Path>) -> anyhow::Result<Tera> {
+    use anyhow::Context;
+    
+    let mut tera = Tera::default();
+    for (name, file, embedded) in TEMPLATES {
+        match dir.map(|dir| dir.join(file)).filter(|path| path.is_file()) {
+            Some(path) => {
+                let source = std::fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read template {}", path.display()))?;
+                tera.add_raw_template(name, &source)
+                    .with_context(|| format!("Invalid template {}", path.display()))?;
+            }
+            None => tera.add_raw_template(name, embedded)?,
+        }
+    }
+    
+    Ok(tera)
+}
+
+/// Check the templates in [`TEMPLATE_DIR_ENV`] at startup by rendering a sample
+/// instance, so a broken override fails there rather than on the next create.
+/// Returns the directory, or `None` when only the embedded templates are used.
+pub fn validate_template_override() -> anyhow::Result<Option<PathBuf>> {
+    let Some(dir) = template_dir() else {
+        return Ok(None);
+    };
+    validate_templates(&dir)?;
+    Ok(Some(dir))
+}
+
+/// Load the templates from `dir` and render and parse them for a sample instance
+pub fn validate_templates(dir: &Path) -> anyhow::Result<()> {
+    anyhow::ensure!(dir.is_dir(), "Template directory {} does not exist", dir.display());
+    
+    let sample = DeployPostgresInput {
+        namespace: "toygres".to_string(),
+        instance_name: "template-check".to_string(),
+        password: PASSWORD_MASK.to_string(),
+        username: None,
+        postgres_version: toygres_models::DEFAULT_PG_VERSION.to_string(),
+        storage_size_gb: 10,
+        use_load_balancer: true,
+        dns_label: Some("template-check".to_string()),
+        access_mode: None,
+        readiness_probe: None,
+        liveness_probe: None,
+        service_annotations: None,
+        internal_load_balancer: false,
+        init_containers: None,
+        password_secret_ref: None,
+        fs_group: None,
+        run_as_user: None,
+        enable_pooler: false,
+        update_strategy: None,
+        standby_replicas: None,
+    };
+    render_with(&load_templates_from(Some(dir))?, &sample)?
+        .parse()
+        .map_err(|e| anyhow::anyhow!("Templates in {} render invalid manifests: {}", dir.display(), e))?;
+    Ok(())
+}
+
+fn template_context(input: &DeployPostgresInput) -> Result<TeraContext, String> {
+    let mut template_ctx = TeraContext::new();
+    template_ctx.insert("name", &input.instance_name);
+    template_ctx.insert("namespace", &input.namespace);
+    template_ctx.insert("password", &input.password);
+    template_ctx.insert("username", resolve_username(input.username.as_deref())?);
+    template_ctx.insert("storage_size", &input.storage_size_gb);
+    template_ctx.insert("postgres_version", &input.postgres_version);
+    template_ctx.insert("service_type", if input.use_load_balancer { "LoadBalancer" } else { "ClusterIP" });
+    template_ctx.insert("service_annotations", &service_annotations(input)?);
+    template_ctx.insert("access_mode", resolve_access_mode(input.access_mode.as_deref())?);
+    template_ctx.insert("readiness_probe", &probe_timings("readiness_probe", input.readiness_probe, ProbeTimings::readiness)?);
+    template_ctx.insert("liveness_probe", &probe_timings("liveness_probe", input.liveness_probe, ProbeTimings::liveness)?);
+    template_ctx.insert("init_containers", init_containers(input)?);
+    template_ctx.insert("password_secret_ref", &input.password_secret_ref);
+    template_ctx.insert("password_secret_key", PASSWORD_SECRET_KEY);
+    template_ctx.insert("fs_group", &security_id("fs_group", input.fs_group)?);
+    template_ctx.insert("run_as_user", &security_id("run_as_user", input.run_as_user)?);
+    template_ctx.insert("update_strategy", &update_strategy(input.update_strategy)?);
+    template_ctx.insert("pooler", &input.enable_pooler.then(|| serde_json::json!({
+        "image": POOLER_IMAGE,
+        "port": POOLER_PORT,
+    })));
+    let username = resolve_username(input.username.as_deref())?;
+    let standbys = standby_replicas(input.standby_replicas)?;
+    template_ctx.insert("replicas", &(1 + standbys));
+    template_ctx.insert("replication", &(standbys > 0).then(|| serde_json::json!({
+        "headless_service": headless_service_name(&input.instance_name),
+        "primary_conninfo": format!(
+            "host={} port=5432 user={}",
+            primary_host(&input.instance_name, &input.namespace), username
+        ),
+    })));
+    
+    Ok(template_ctx)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     
-    #[test]
-    fn test_deploy_postgres_input_serialization() {
-        let input = DeployPostgresInput {
+    fn test_input() -> DeployPostgresInput {
+        DeployPostgresInput {
             namespace: "test".to_string(),
             instance_name: "test-pg".to_string(),
             password: "password123".to_string(),
+            username: None,
             postgres_version: "18".to_string(),
             storage_size_gb: 10,
             use_load_balancer: true,
             dns_label: Some("testlabel".to_string()),
-        };
+            access_mode: None,
+            readiness_probe: None,
+            liveness_probe: None,
+            service_annotations: None,
+            internal_load_balancer: false,
+            init_containers: None,
+            password_secret_ref: None,
+            fs_group: None,
+            run_as_user: None,
+            enable_pooler: false,
+            update_strategy: None,
+            standby_replicas: None,
+        }
+    }
+    
+    fn render_pvc(input: &DeployPostgresInput) -> PersistentVolumeClaim {
+        let tera = load_templates().unwrap();
+        let yaml = tera.render("pvc", &template_context(input).unwrap()).unwrap();
+        serde_yaml::from_str(&yaml).unwrap()
+    }
+    
+    fn render_statefulset(input: &DeployPostgresInput) -> StatefulSet {
+        let tera = load_templates().unwrap();
+        let yaml = tera.render("statefulset", &template_context(input).unwrap()).unwrap();
+        serde_yaml::from_str(&yaml).unwrap()
+    }
+    
+    fn render_service(input: &DeployPostgresInput) -> Service {
+        let tera = load_templates().unwrap();
+        let yaml = tera.render("service", &template_context(input).unwrap()).unwrap();
+        serde_yaml::from_str(&yaml).unwrap()
+    }
+    
+    fn access_modes(pvc: &PersistentVolumeClaim) -> Vec<String> {
+        pvc.spec.as_ref().unwrap().access_modes.clone().unwrap()
+    }
+    
+    #[test]
+    fn test_deploy_postgres_input_serialization() {
+        let input = test_input();
         
         let json = serde_json::to_string(&input).unwrap();
         let parsed: DeployPostgresInput = serde_json::from_str(&json).unwrap();
@@ -138,5 +651,413 @@ mod tests {
         let parsed: DeployPostgresOutput = serde_json::from_str(&json).unwrap();
         assert_eq!(output, parsed);
     }
+    
+    #[test]
+    fn test_pvc_renders_requested_access_mode() {
+        assert_eq!(access_modes(&render_pvc(&test_input())), vec!["ReadWriteOnce"]);
+        
+        let input = DeployPostgresInput {
+            access_mode: Some("ReadWriteOncePod".to_string()),
+            ..test_input()
+        };
+        assert_eq!(access_modes(&render_pvc(&input)), vec!["ReadWriteOncePod"]);
+    }
+    
+    #[test]
+    fn test_statefulset_renders_probes_with_configured_timings() {
+        let input = DeployPostgresInput {
+            readiness_probe: Some(ProbeTimings {
+                initial_delay_seconds: 2,
+                period_seconds: 3,
+                timeout_seconds: 1,
+                failure_threshold: 10,
+            }),
+            ..test_input()
+        };
+        let statefulset = render_statefulset(&input);
+        let pod_spec = statefulset.spec.unwrap().template.spec.unwrap();
+        let container = &pod_spec.containers[0];
+        
+        let readiness = container.readiness_probe.as_ref().unwrap();
+        assert_eq!(readiness.initial_delay_seconds, Some(2));
+        assert_eq!(readiness.period_seconds, Some(3));
+        assert_eq!(readiness.timeout_seconds, Some(1));
+        assert_eq!(readiness.failure_threshold, Some(10));
+        let command = readiness.exec.as_ref().unwrap().command.as_ref().unwrap();
+        assert_eq!(command[0], "pg_isready");
+        
+        // Liveness falls back to defaults when not configured
+        let liveness = container.liveness_probe.as_ref().unwrap();
+        let defaults = ProbeTimings::liveness();
+        assert_eq!(liveness.initial_delay_seconds, Some(defaults.initial_delay_seconds));
+        assert_eq!(liveness.period_seconds, Some(defaults.period_seconds));
+        assert!(liveness.exec.is_some());
+        
+        // Out-of-range timings are rejected rather than rendered
+        let input = DeployPostgresInput {
+            liveness_probe: Some(ProbeTimings { period_seconds: 0, ..defaults }),
+            ..test_input()
+        };
+        let err = template_context(&input).unwrap_err();
+        assert!(err.starts_with("liveness_probe period_seconds"), "{}", err);
+    }
+    
+    #[test]
+    fn test_service_renders_internal_lb_and_custom_annotations() {
+        let default = render_service(&test_input());
+        let annotations = default.metadata.annotations.unwrap();
+        assert_eq!(annotations.get(AZURE_DNS_LABEL_ANNOTATION).map(String::as_str), Some("testlabel"));
+        assert!(!annotations.contains_key(AZURE_INTERNAL_LB_ANNOTATION));
+        
+        let probe_path = "service.beta.kubernetes.io/azure-load-balancer-health-probe-request-path";
+        let input = DeployPostgresInput {
+            internal_load_balancer: true,
+            service_annotations: Some(BTreeMap::from([
+                (probe_path.to_string(), "/healthz".to_string()),
+            ])),
+            ..test_input()
+        };
+        let service = render_service(&input);
+        assert_eq!(service.spec.unwrap().type_.as_deref(), Some("LoadBalancer"));
+        let annotations = service.metadata.annotations.unwrap();
+        assert_eq!(annotations.get(AZURE_INTERNAL_LB_ANNOTATION).map(String::as_str), Some("true"));
+        assert_eq!(annotations.get(probe_path).map(String::as_str), Some("/healthz"));
+        assert_eq!(annotations.get(AZURE_DNS_LABEL_ANNOTATION).map(String::as_str), Some("testlabel"));
+    }
+    
+    #[test]
+    fn test_service_annotation_keys_validated() {
+        let annotations = |keys: &[&str]| keys.iter().map(|key| (key.to_string(), "v".to_string())).collect();
+        
+        let valid = ["team", "example.com/owner", "service.beta.kubernetes.io/azure-pip-name", "a_b.c-D"];
+        validate_service_annotations(&annotations(&valid)).unwrap();
+        for key in ["", "/name", "Example.com/owner", "a/b/c", "-team", "team.", &"x".repeat(64), "has space"] {
+            let err = validate_service_annotations(&annotations(&[key])).unwrap_err();
+            assert!(err.contains("not a valid annotation key"), "{}: {}", key, err);
+        }
+        
+        // The DNS label cannot be overridden behind dns_label's back
+        let input = DeployPostgresInput {
+            service_annotations: Some(annotations(&[AZURE_DNS_LABEL_ANNOTATION])),
+            ..test_input()
+        };
+        assert!(template_context(&input).unwrap_err().contains("managed by Toygres"));
+    }
+    
+    #[test]
+    fn test_internal_lb_requires_load_balancer() {
+        let input = DeployPostgresInput {
+            internal_load_balancer: true,
+            use_load_balancer: false,
+            ..test_input()
+        };
+        assert!(template_context(&input).is_err());
+    }
+    
+    #[test]
+    fn test_invalid_access_mode_rejected() {
+        let input = DeployPostgresInput {
+            access_mode: Some("ReadWriteMany".to_string()),
+            ..test_input()
+        };
+        let err = template_context(&input).unwrap_err();
+        assert!(err.contains("ReadWriteMany"));
+    }
+    
+    #[test]
+    fn test_statefulset_renders_init_containers() {
+        let pod_spec = |input: &DeployPostgresInput| render_statefulset(input).spec.unwrap().template.spec.unwrap();
+        
+        // None are rendered by default
+        assert!(pod_spec(&test_input()).init_containers.is_none());
+        
+        let input = DeployPostgresInput {
+            init_containers: Some(vec![
+                InitContainerSpec {
+                    name: "sysctl".to_string(),
+                    image: "busybox:1.36".to_string(),
+                    command: vec!["sh".to_string(), "-c".to_string(), "mkdir -p /var/lib/postgresql/data/wal \"archive\"".to_string()],
+                },
+                InitContainerSpec {
+                    name: "default-entrypoint".to_string(),
+                    image: "myregistry.azurecr.io:5000/tools/pg-init@sha256:0123456789abcdef0123456789abcdef".to_string(),
+                    command: Vec::new(),
+                },
+            ]),
+            ..test_input()
+        };
+        let spec = pod_spec(&input);
+        let init = spec.init_containers.unwrap();
+        assert_eq!(init.len(), 2);
+        assert_eq!(init[0].name, "sysctl");
+        assert_eq!(init[0].image.as_deref(), Some("busybox:1.36"));
+        assert_eq!(init[0].command.as_ref().unwrap()[2], "mkdir -p /var/lib/postgresql/data/wal \"archive\"");
+        assert_eq!(init[0].volume_mounts.as_ref().unwrap()[0].name, "postgres-storage");
+        assert!(init[1].command.is_none());
+        assert_eq!(spec.containers[0].name, "postgres");
+    }
+    
+    #[test]
+    fn test_init_container_validation() {
+        let with = |name: &str, image: &str| DeployPostgresInput {
+            init_containers: Some(vec![InitContainerSpec {
+                name: name.to_string(),
+                image: image.to_string(),
+                command: Vec::new(),
+            }]),
+            ..test_input()
+        };
+        
+        for image in ["busybox", "library/busybox:latest", "localhost:5000/init:v1.2", "ghcr.io/org/tool_x:1.0-rc"] {
+            assert!(init_containers(&with("init", image)).is_ok(), "{} should be valid", image);
+        }
+        for image in ["", "busy box", "Busybox", "busybox:", "busybox:-bad", "ghcr.io//tool", "busybox@sha256:xyz", "busybox\"; rm"] {
+            assert!(init_containers(&with("init", image)).is_err(), "{} should be rejected", image);
+        }
+        
+        assert!(init_containers(&with("Init_1", "busybox")).is_err());
+        assert!(init_containers(&with("postgres", "busybox")).is_err());
+    }
+    
+    #[test]
+    fn test_statefulset_password_from_secret_ref() {
+        let postgres_password = |input: &DeployPostgresInput| {
+            let pod_spec = render_statefulset(input).spec.unwrap().template.spec.unwrap();
+            pod_spec.containers[0].env.as_ref().unwrap()
+                .iter()
+                .find(|e| e.name == "POSTGRES_PASSWORD")
+                .cloned()
+                .unwrap()
+        };
+        
+        let inline = postgres_password(&test_input());
+        assert_eq!(inline.value.as_deref(), Some("password123"));
+        assert!(inline.value_from.is_none());
+        
+        let input = DeployPostgresInput {
+            password: String::new(),
+            password_secret_ref: Some("mydb-credentials".to_string()),
+            ..test_input()
+        };
+        let from_secret = postgres_password(&input);
+        assert!(from_secret.value.is_none());
+        let key_ref = from_secret.value_from.unwrap().secret_key_ref.unwrap();
+        assert_eq!(key_ref.name, "mydb-credentials");
+        assert_eq!(key_ref.key, PASSWORD_SECRET_KEY);
+    }
+    
+    #[test]
+    fn test_statefulset_creates_configured_superuser() {
+        let container = |input: &DeployPostgresInput| {
+            render_statefulset(input).spec.unwrap().template.spec.unwrap().containers.remove(0)
+        };
+        let env = |container: &k8s_openapi::api::core::v1::Container, name: &str| {
+            container.env.as_ref().unwrap().iter().find(|e| e.name == name).unwrap().value.clone()
+        };
+        let probe_user = |probe: Option<k8s_openapi::api::core::v1::Probe>| {
+            probe.unwrap().exec.unwrap().command.unwrap()[2].clone()
+        };
+        
+        let default = container(&test_input());
+        assert_eq!(env(&default, "POSTGRES_USER").as_deref(), Some("postgres"));
+        
+        let input = DeployPostgresInput {
+            username: Some("app_admin".to_string()),
+            ..test_input()
+        };
+        let custom = container(&input);
+        assert_eq!(env(&custom, "POSTGRES_USER").as_deref(), Some("app_admin"));
+        assert_eq!(probe_user(custom.readiness_probe), "app_admin");
+        assert_eq!(probe_user(custom.liveness_probe), "app_admin");
+        
+        for username in ["Admin", "pg_admin", "app-admin", "x\"y"] {
+            let input = DeployPostgresInput { username: Some(username.to_string()), ..test_input() };
+            assert!(template_context(&input).is_err(), "{} should be rejected", username);
+        }
+    }
+    
+    #[test]
+    fn test_statefulset_renders_security_context() {
+        let security_context = |input: &DeployPostgresInput| {
+            render_statefulset(input).spec.unwrap().template.spec.unwrap().security_context.unwrap()
+        };
+        
+        let defaults = security_context(&test_input());
+        assert_eq!(defaults.fs_group, Some(POSTGRES_UID));
+        assert_eq!(defaults.run_as_user, Some(POSTGRES_UID));
+        
+        let input = DeployPostgresInput {
+            fs_group: Some(2000),
+            run_as_user: Some(1001),
+            ..test_input()
+        };
+        let configured = security_context(&input);
+        assert_eq!(configured.fs_group, Some(2000));
+        assert_eq!(configured.run_as_user, Some(1001));
+        
+        let negative = DeployPostgresInput { fs_group: Some(-1), ..test_input() };
+        assert!(template_context(&negative).unwrap_err().contains("fs_group"));
+    }
+    
+    #[test]
+    fn test_pooler_renders_sidecar_and_service_port() {
+        let ports = |service: Service| -> Vec<i32> {
+            service.spec.unwrap().ports.unwrap().iter().map(|p| p.port).collect()
+        };
+        
+        // Off by default
+        assert!(!has_pooler(&render_statefulset(&test_input())));
+        assert_eq!(ports(render_service(&test_input())), vec![5432]);
+        
+        let input = DeployPostgresInput { enable_pooler: true, ..test_input() };
+        let statefulset = render_statefulset(&input);
+        assert!(has_pooler(&statefulset));
+        let pod_spec = statefulset.spec.unwrap().template.spec.unwrap();
+        let pooler = pod_spec.containers.iter().find(|c| c.name == POOLER_CONTAINER).unwrap();
+        assert_eq!(pooler.image.as_deref(), Some(POOLER_IMAGE));
+        let env = |name: &str| pooler.env.as_ref().unwrap().iter()
+            .find(|e| e.name == name)
+            .and_then(|e| e.value.clone());
+        assert_eq!(env("DB_HOST").as_deref(), Some("127.0.0.1"));
+        assert_eq!(env("DB_PASSWORD").as_deref(), Some("password123"));
+        assert_eq!(env("LISTEN_PORT"), Some(POOLER_PORT.to_string()));
+        assert_eq!(ports(render_service(&input)), vec![5432, POOLER_PORT as i32]);
+    }
+    
+    #[test]
+    fn test_statefulset_renders_update_strategy_partition() {
+        let strategy = |input: &DeployPostgresInput| render_statefulset(input).spec.unwrap().update_strategy;
+        
+        // Kubernetes' default applies when none is configured
+        assert_eq!(strategy(&test_input()), None);
+        assert_eq!(update_strategy_of(&render_statefulset(&test_input())), None);
+        
+        let canary = DeployPostgresInput {
+            update_strategy: Some(UpdateStrategy::RollingUpdate { partition: 1 }),
+            ..test_input()
+        };
+        let rendered = strategy(&canary).unwrap();
+        assert_eq!(rendered.type_.as_deref(), Some("RollingUpdate"));
+        assert_eq!(rendered.rolling_update.unwrap().partition, Some(1));
+        assert_eq!(update_strategy_of(&render_statefulset(&canary)), canary.update_strategy);
+        
+        let on_delete = DeployPostgresInput { update_strategy: Some(UpdateStrategy::OnDelete), ..test_input() };
+        let rendered = strategy(&on_delete).unwrap();
+        assert_eq!(rendered.type_.as_deref(), Some("OnDelete"));
+        assert!(rendered.rolling_update.is_none());
+        assert_eq!(update_strategy_of(&render_statefulset(&on_delete)), Some(UpdateStrategy::OnDelete));
+        
+        let negative = DeployPostgresInput {
+            update_strategy: Some(UpdateStrategy::RollingUpdate { partition: -1 }),
+            ..test_input()
+        };
+        assert!(template_context(&negative).unwrap_err().contains("partition"));
+        
+        let json = serde_json::to_string(&canary.update_strategy).unwrap();
+        assert_eq!(json, r#"{"type":"RollingUpdate","partition":1}"#);
+    }
+    
+    #[test]
+    fn test_replica_mode_renders_client_and_headless_services() {
+        // Single-pod instances keep their layout: no headless Service
+        let single = render_resources(&test_input()).unwrap();
+        assert!(single.headless_service.is_none());
+        assert_eq!(single.statefulset.spec.as_ref().unwrap().replicas, Some(1));
+        assert_eq!(standby_replicas_of(&single.statefulset), None);
+        assert_eq!(single.statefulset.spec.unwrap().service_name, "test-pg");
+        assert!(!render_manifests(&test_input()).unwrap().to_yaml().contains("clusterIP: None"));
+        
+        let input = DeployPostgresInput { standby_replicas: Some(2), ..test_input() };
+        let resources = render_resources(&input).unwrap();
+        
+        let headless = resources.headless_service.unwrap();
+        assert_eq!(headless.metadata.name.as_deref(), Some("test-pg-headless"));
+        let headless_spec = headless.spec.unwrap();
+        assert_eq!(headless_spec.cluster_ip.as_deref(), Some("None"));
+        assert_eq!(headless_spec.publish_not_ready_addresses, Some(true));
+        assert_eq!(headless_spec.selector.unwrap()["instance"], "test-pg");
+        
+        // The client Service keeps its type but only targets the primary
+        assert_eq!(resources.service.metadata.name.as_deref(), Some("test-pg-svc"));
+        let client_spec = resources.service.spec.unwrap();
+        assert_eq!(client_spec.type_.as_deref(), Some("LoadBalancer"));
+        assert_eq!(client_spec.selector.unwrap()["statefulset.kubernetes.io/pod-name"], "test-pg-0");
+        
+        // The primary and both standbys run, named through the headless Service and
+        // knowing the primary's stable name
+        assert_eq!(standby_replicas_of(&resources.statefulset), Some(2));
+        let sts_spec = resources.statefulset.spec.unwrap();
+        assert_eq!(sts_spec.replicas, Some(3));
+        assert_eq!(sts_spec.service_name, "test-pg-headless");
+        let postgres = &sts_spec.template.spec.unwrap().containers[0];
+        let conninfo = postgres.env.as_ref().unwrap().iter()
+            .find(|e| e.name == "PRIMARY_CONNINFO")
+            .and_then(|e| e.value.clone())
+            .unwrap();
+        assert_eq!(conninfo, "host=test-pg-0.test-pg-headless.test.svc.cluster.local port=5432 user=postgres");
+        
+        // Created before the StatefulSet that names it
+        let yaml = render_manifests(&input).unwrap().to_yaml();
+        assert!(yaml.find("test-pg-headless").unwrap() < yaml.find("kind: StatefulSet").unwrap());
+        
+        let too_many = DeployPostgresInput { standby_replicas: Some(MAX_STANDBY_REPLICAS + 1), ..test_input() };
+        assert!(template_context(&too_many).unwrap_err().contains("standby_replicas"));
+    }
+    
+    #[test]
+    fn test_masked_manifests_parse_and_hide_the_password() {
+        use serde::Deserialize;
+        
+        let input = DeployPostgresInput { enable_pooler: true, ..test_input() };
+        let manifests = render_masked_manifests(&input).unwrap();
+        let yaml = manifests.to_yaml();
+        
+        assert!(!yaml.contains("password123"), "{}", yaml);
+        assert!(yaml.contains(PASSWORD_MASK));
+        
+        // Every document in the stream is a Kubernetes object
+        let kinds: Vec<String> = serde_yaml::Deserializer::from_str(&yaml)
+            .map(|doc| serde_json::Value::deserialize(doc).unwrap()["kind"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(kinds, ["PersistentVolumeClaim", "StatefulSet", "Service"]);
+        
+        let resources = manifests.parse().unwrap();
+        assert_eq!(resources.statefulset.metadata.name.as_deref(), Some("test-pg"));
+        assert!(has_pooler(&resources.statefulset));
+        assert_eq!(resources.service.metadata.name.as_deref(), Some("test-pg-svc"));
+        
+        // Rendering for deploy keeps the real password
+        assert!(render_manifests(&input).unwrap().statefulset.contains("password123"));
+    }
+    
+    #[test]
+    fn test_template_dir_overrides_and_broken_template_caught() {
+        let dir = std::env::temp_dir().join(format!("toygres-templates-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        
+        // Override only the PVC; the StatefulSet and Service come from the embedded set
+        let pvc = TEMPLATES[0].2.replace("    app: postgres\n", "    app: postgres\n    team: data\n");
+        std::fs::write(dir.join("postgres-pvc.yaml"), pvc).unwrap();
+        validate_templates(&dir).unwrap();
+        
+        let rendered = render_with(&load_templates_from(Some(&dir)).unwrap(), &test_input()).unwrap();
+        let resources = rendered.parse().unwrap();
+        let labels = resources.pvc.metadata.labels.unwrap();
+        assert_eq!(labels.get("team").map(String::as_str), Some("data"));
+        assert_eq!(rendered.statefulset, render_manifests(&test_input()).unwrap().statefulset);
+        
+        // A syntax error fails at load, naming the file
+        std::fs::write(dir.join("postgres-statefulset.yaml"), "metadata:\n  name: {{ name \n").unwrap();
+        let err = format!("{:#}", validate_templates(&dir).unwrap_err());
+        assert!(err.contains("postgres-statefulset.yaml"), "{}", err);
+        
+        // So does a template that renders something other than a Service
+        std::fs::remove_file(dir.join("postgres-statefulset.yaml")).unwrap();
+        std::fs::write(dir.join("postgres-service.yaml"), "kind: Service\nspec: [{{ name }}\n").unwrap();
+        let err = validate_templates(&dir).unwrap_err().to_string();
+        assert!(err.contains("render invalid manifests"), "{}", err);
+        
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(validate_templates(&dir).is_err());
+    }
 }
-
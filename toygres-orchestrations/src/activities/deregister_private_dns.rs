@@ -0,0 +1,56 @@
+//! Deregister private DNS activity
+//!
+//! Removes the A record [`crate::activities::register_private_dns`] wrote for an
+//! instance, so a deleted instance's private-zone name stops resolving (and cannot
+//! point at whatever gets the internal LoadBalancer IP next).
+
+use duroxide::ActivityContext;
+use crate::activities::register_private_dns::{
+    managed_identity_token, private_dns_name, PrivateDnsZone,
+    RESOURCE_GROUP_ENV, SUBSCRIPTION_ENV, ZONE_ENV,
+};
+use crate::activity_types::{DeregisterPrivateDnsInput, DeregisterPrivateDnsOutput};
+use reqwest::StatusCode;
+use std::time::Duration;
+
+/// Activity name for registration and scheduling
+pub const NAME: &str = "toygres-orchestrations::activity::deregister-private-dns";
+
+/// Bound on each Azure request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub async fn activity(
+    ctx: ActivityContext,
+    input: DeregisterPrivateDnsInput,
+) -> Result<DeregisterPrivateDnsOutput, String> {
+    let zone = PrivateDnsZone::from_env().ok_or_else(|| format!(
+        "Private DNS is not configured; set {}, {} and {}",
+        ZONE_ENV, SUBSCRIPTION_ENV, RESOURCE_GROUP_ENV
+    ))?;
+    let fqdn = private_dns_name(&zone.zone, &input.record_name);
+    ctx.trace_info(format!("Removing private DNS record {}", fqdn));
+
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+
+    let token = managed_identity_token(&client).await?;
+    let response = client
+        .delete(zone.record_url(&input.record_name))
+        .bearer_auth(token)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Failed to delete A record {}: {}", fqdn, e))?;
+
+    // ARM answers 204 when the record set does not exist
+    let deleted = response.status() != StatusCode::NO_CONTENT;
+    if deleted {
+        ctx.trace_info(format!("Private DNS record removed: {}", fqdn));
+    } else {
+        ctx.trace_info(format!("Private DNS record {} did not exist", fqdn));
+    }
+
+    Ok(DeregisterPrivateDnsOutput { fqdn, deleted })
+}
@@ -0,0 +1,128 @@
+//! Expose service activity
+//!
+//! Gives an instance's Service the requested type without touching the StatefulSet
+//! or PVC: a Service of another type is patched, a missing one is recreated from the
+//! Service template. The data and pod are never restarted.
+
+use duroxide::ActivityContext;
+use crate::activities::deploy_postgres::{render_resources, PASSWORD_MASK};
+use crate::activity_types::{DeployPostgresInput, ExposeServiceInput, ExposeServiceOutput};
+use crate::k8s_client::get_k8s_client;
+use k8s_openapi::api::core::v1::Service;
+use kube::api::{Api, Patch, PatchParams, PostParams};
+
+/// Activity name for registration and scheduling
+pub const NAME: &str = "toygres-orchestrations::activity::expose-service";
+
+pub async fn activity(
+    ctx: ActivityContext,
+    input: ExposeServiceInput,
+) -> Result<ExposeServiceOutput, String> {
+    let service_name = format!("{}-svc", input.instance_name);
+    ctx.trace_info(format!("Exposing {} as {}", service_name, input.service_type.as_str()));
+
+    let client = get_k8s_client().await
+        .map_err(|e| format!("Failed to create K8s client: {}", e))?;
+    let services: Api<Service> = Api::namespaced(client, &input.namespace);
+
+    let existing = services.get_opt(&service_name).await
+        .map_err(|e| format!("Failed to get Service {}: {}", service_name, e))?;
+
+    let Some(existing) = existing else {
+        ctx.trace_info("Service is missing, recreating it");
+        services.create(&PostParams::default(), &desired_service(&input)?).await
+            .map_err(|e| format!("Failed to create Service {}: {}", service_name, e))?;
+        return Ok(ExposeServiceOutput { previous_type: None, changed: true });
+    };
+
+    let previous_type = existing.spec.and_then(|spec| spec.type_);
+
+    // Idempotency - a retry after a successful patch is a no-op
+    if previous_type.as_deref() == Some(input.service_type.as_str()) {
+        ctx.trace_info("Service already has the requested type");
+        return Ok(ExposeServiceOutput { previous_type, changed: false });
+    }
+
+    services
+        .patch(
+            &service_name,
+            &PatchParams::default(),
+            &Patch::Merge(type_patch(&input)),
+        )
+        .await
+        .map_err(|e| format!("Failed to patch Service type: {}", e))?;
+
+    ctx.trace_info(format!("Service type patched (was {:?})", previous_type));
+    Ok(ExposeServiceOutput { previous_type, changed: true })
+}
+
+/// Merge patch that changes only `spec.type`; Kubernetes releases the node ports and
+/// LoadBalancer when a Service becomes `ClusterIP`
+fn type_patch(input: &ExposeServiceInput) -> serde_json::Value {
+    serde_json::json!({
+        "spec": { "type": input.service_type.as_str() }
+    })
+}
+
+/// The Service `deploy_postgres` would create for the instance with this type
+fn desired_service(input: &ExposeServiceInput) -> Result<Service, String> {
+    let deploy_input = DeployPostgresInput {
+        namespace: input.namespace.clone(),
+        instance_name: input.instance_name.clone(),
+        // The Service template does not use the password
+        password: PASSWORD_MASK.to_string(),
+        username: None,
+        postgres_version: toygres_models::DEFAULT_PG_VERSION.to_string(),
+        storage_size_gb: 1,
+        use_load_balancer: input.service_type.is_load_balancer(),
+        dns_label: input.dns_label.clone(),
+        access_mode: None,
+        readiness_probe: None,
+        liveness_probe: None,
+        service_annotations: None,
+        internal_load_balancer: false,
+        init_containers: None,
+        password_secret_ref: None,
+        fs_group: None,
+        run_as_user: None,
+        enable_pooler: input.enable_pooler,
+        update_strategy: None,
+        standby_replicas: None,
+    };
+    render_resources(&deploy_input)
+        .map(|resources| resources.service)
+        .map_err(|e| format!("Failed to render Service: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity_types::ServiceType;
+
+    fn input(service_type: ServiceType) -> ExposeServiceInput {
+        ExposeServiceInput {
+            namespace: "toygres".to_string(),
+            instance_name: "mydb-1a2b3c4d".to_string(),
+            service_type,
+            dns_label: Some("mydb".to_string()),
+            enable_pooler: true,
+        }
+    }
+
+    #[test]
+    fn test_recreated_service_matches_requested_type() {
+        let service = desired_service(&input(ServiceType::LoadBalancer)).unwrap();
+        assert_eq!(service.metadata.name.as_deref(), Some("mydb-1a2b3c4d-svc"));
+        let spec = service.spec.unwrap();
+        assert_eq!(spec.type_.as_deref(), Some("LoadBalancer"));
+        assert_eq!(spec.ports.unwrap().len(), 2);
+        assert_eq!(
+            service.metadata.annotations.unwrap()[crate::activities::deploy_postgres::AZURE_DNS_LABEL_ANNOTATION],
+            "mydb"
+        );
+
+        let service = desired_service(&input(ServiceType::ClusterIp)).unwrap();
+        assert_eq!(service.spec.unwrap().type_.as_deref(), Some("ClusterIP"));
+        assert_eq!(type_patch(&input(ServiceType::ClusterIp)), serde_json::json!({ "spec": { "type": "ClusterIP" } }));
+    }
+}
@@ -0,0 +1,67 @@
+//! Check once for an instance's LoadBalancer external IP
+
+use duroxide::ActivityContext;
+use crate::activity_types::{GetServiceExternalIpInput, GetServiceExternalIpOutput};
+use crate::k8s_client::get_k8s_client;
+use k8s_openapi::api::core::v1::Service;
+use kube::api::Api;
+
+/// Activity name for registration and scheduling
+pub const NAME: &str = "toygres-orchestrations::activity::get-service-external-ip";
+
+pub async fn activity(
+    ctx: ActivityContext,
+    input: GetServiceExternalIpInput,
+) -> Result<GetServiceExternalIpOutput, String> {
+    let client = get_k8s_client().await
+        .map_err(|e| format!("Failed to create K8s client: {}", e))?;
+
+    // No polling here; the orchestration waits between checks with durable timers
+    let services: Api<Service> = Api::namespaced(client, &input.namespace);
+    let service_name = format!("{}-svc", input.instance_name);
+    let service = services.get(&service_name).await
+        .map_err(|e| format!("Failed to get Service {}: {}", service_name, e))?;
+
+    let external_ip = service_external_ip(&service);
+    match &external_ip {
+        Some(ip) => ctx.trace_info(format!("External IP: {}", ip)),
+        None => ctx.trace_info("LoadBalancer has no external IP yet"),
+    }
+
+    Ok(GetServiceExternalIpOutput { external_ip })
+}
+
+/// The first ingress IP the LoadBalancer has been assigned, if any
+pub fn service_external_ip(service: &Service) -> Option<String> {
+    service.status.as_ref()?
+        .load_balancer.as_ref()?
+        .ingress.as_ref()?
+        .iter()
+        .find_map(|ingress| ingress.ip.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(status: serde_json::Value) -> Service {
+        serde_json::from_value(serde_json::json!({
+            "metadata": { "name": "mydb-1a2b-svc" },
+            "status": status
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_external_ip_read_from_load_balancer_ingress() {
+        let assigned = service(serde_json::json!({ "loadBalancer": { "ingress": [{ "ip": "20.1.2.3" }] } }));
+        let pending = service(serde_json::json!({ "loadBalancer": {} }));
+
+        assert_eq!(service_external_ip(&assigned).as_deref(), Some("20.1.2.3"));
+        assert_eq!(service_external_ip(&pending), None);
+
+        let output = GetServiceExternalIpOutput { external_ip: service_external_ip(&assigned) };
+        let json = serde_json::to_string(&output).unwrap();
+        assert_eq!(json, r#"{"external_ip":"20.1.2.3"}"#);
+        assert_eq!(serde_json::from_str::<GetServiceExternalIpOutput>(&json).unwrap(), output);
+    }
+}
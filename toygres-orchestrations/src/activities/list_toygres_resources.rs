@@ -0,0 +1,133 @@
+//! List Toygres-managed Kubernetes resources activity
+
+use duroxide::ActivityContext;
+use crate::activity_types::{ListToygresResourcesInput, ListToygresResourcesOutput, ToygresResource};
+use crate::activities::deploy_postgres::headless_service_name;
+use crate::k8s_client::get_k8s_client;
+use k8s_openapi::api::apps::v1::StatefulSet;
+use k8s_openapi::api::core::v1::{PersistentVolumeClaim, Service};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+use kube::api::{Api, ListParams};
+use kube::Resource;
+
+/// Activity name for registration and scheduling
+pub const NAME: &str = "toygres-orchestrations::activity::list-toygres-resources";
+
+/// Labels every Toygres template puts on its resources
+pub const LABEL_SELECTOR: &str = "app=postgres,instance";
+
+pub const STATEFUL_SET: &str = "StatefulSet";
+pub const SERVICE: &str = "Service";
+pub const PERSISTENT_VOLUME_CLAIM: &str = "PersistentVolumeClaim";
+
+pub async fn activity(
+    ctx: ActivityContext,
+    input: ListToygresResourcesInput,
+) -> Result<ListToygresResourcesOutput, String> {
+    ctx.trace_info(format!("Listing Toygres resources in namespace {}", input.namespace));
+    
+    let client = get_k8s_client().await
+        .map_err(|e| format!("Failed to create K8s client: {}", e))?;
+    let resources = list_resources(&client, &input.namespace).await?;
+    
+    ctx.trace_info(format!("Found {} Toygres resources", resources.len()));
+    
+    Ok(ListToygresResourcesOutput { resources })
+}
+
+/// The Toygres StatefulSets, Services and PVCs in `namespace`
+pub async fn list_resources(client: &kube::Client, namespace: &str) -> Result<Vec<ToygresResource>, String> {
+    let params = ListParams::default().labels(LABEL_SELECTOR);
+    let mut resources = Vec::new();
+    
+    let statefulsets: Api<StatefulSet> = Api::namespaced(client.clone(), namespace);
+    let list = statefulsets.list(&params).await
+        .map_err(|e| format!("Failed to list StatefulSets: {}", e))?;
+    resources.extend(list.items.iter().filter_map(|sts| toygres_resource(STATEFUL_SET, sts.meta())));
+    
+    let services: Api<Service> = Api::namespaced(client.clone(), namespace);
+    let list = services.list(&params).await
+        .map_err(|e| format!("Failed to list Services: {}", e))?;
+    resources.extend(list.items.iter().filter_map(|svc| toygres_resource(SERVICE, svc.meta())));
+    
+    let pvcs: Api<PersistentVolumeClaim> = Api::namespaced(client.clone(), namespace);
+    let list = pvcs.list(&params).await
+        .map_err(|e| format!("Failed to list PersistentVolumeClaims: {}", e))?;
+    resources.extend(list.items.iter().filter_map(|pvc| toygres_resource(PERSISTENT_VOLUME_CLAIM, pvc.meta())));
+    
+    Ok(resources)
+}
+
+/// Instances in `namespace` whose StatefulSet has at least one ready replica
+pub async fn ready_instances(client: &kube::Client, namespace: &str) -> Result<Vec<String>, String> {
+    let statefulsets: Api<StatefulSet> = Api::namespaced(client.clone(), namespace);
+    let list = statefulsets.list(&ListParams::default().labels(LABEL_SELECTOR)).await
+        .map_err(|e| format!("Failed to list StatefulSets: {}", e))?;
+    
+    Ok(list.items.iter()
+        .filter(|sts| sts.status.as_ref().and_then(|s| s.ready_replicas).unwrap_or(0) > 0)
+        .filter_map(|sts| toygres_resource(STATEFUL_SET, sts.meta()))
+        .map(|resource| resource.instance)
+        .collect())
+}
+
+/// Name the Toygres templates give a resource of `kind` for `instance`
+pub fn expected_name(kind: &str, instance: &str) -> Option<String> {
+    match kind {
+        STATEFUL_SET => Some(instance.to_string()),
+        SERVICE => Some(format!("{}-svc", instance)),
+        PERSISTENT_VOLUME_CLAIM => Some(format!("{}-pvc", instance)),
+        _ => None,
+    }
+}
+
+/// Project a labeled object, skipping any whose name does not follow the Toygres
+/// naming scheme: `app=postgres` alone is common enough to match unrelated workloads
+fn toygres_resource(kind: &str, meta: &ObjectMeta) -> Option<ToygresResource> {
+    let name = meta.name.clone()?;
+    let instance = meta.labels.as_ref()?.get("instance")?.clone();
+    let headless = kind == SERVICE && name == headless_service_name(&instance);
+    if expected_name(kind, &instance).as_deref() != Some(name.as_str()) && !headless {
+        return None;
+    }
+    
+    Some(ToygresResource {
+        kind: kind.to_string(),
+        name,
+        instance,
+        uid: meta.uid.clone(),
+        created_at_ms: meta.creation_timestamp.as_ref().map(|t| t.0.timestamp_millis()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    
+    fn meta(name: &str, instance: Option<&str>) -> ObjectMeta {
+        ObjectMeta {
+            name: Some(name.to_string()),
+            labels: instance.map(|instance| BTreeMap::from([
+                ("app".to_string(), "postgres".to_string()),
+                ("instance".to_string(), instance.to_string()),
+            ])),
+            uid: Some("uid-1".to_string()),
+            ..Default::default()
+        }
+    }
+    
+    #[test]
+    fn test_only_toygres_named_resources_are_listed() {
+        let svc = toygres_resource(SERVICE, &meta("mydb-1a2b-svc", Some("mydb-1a2b"))).unwrap();
+        assert_eq!(svc.instance, "mydb-1a2b");
+        assert_eq!(svc.uid.as_deref(), Some("uid-1"));
+        assert!(toygres_resource(STATEFUL_SET, &meta("mydb-1a2b", Some("mydb-1a2b"))).is_some());
+        assert!(toygres_resource(PERSISTENT_VOLUME_CLAIM, &meta("mydb-1a2b-pvc", Some("mydb-1a2b"))).is_some());
+        assert!(toygres_resource(SERVICE, &meta("mydb-1a2b-headless", Some("mydb-1a2b"))).is_some());
+        
+        // Someone else's app=postgres workload, or a resource without an instance label
+        assert!(toygres_resource(STATEFUL_SET, &meta("analytics-db", Some("primary"))).is_none());
+        assert!(toygres_resource(SERVICE, &meta("mydb-1a2b-svc", None)).is_none());
+    }
+}
@@ -1,8 +1,20 @@
 pub mod deploy_postgres;
+pub mod preflight_capacity;
 pub mod delete_postgres;
 pub mod wait_for_ready;
+pub mod wait_for_rollout;
+pub mod patch_image;
+pub mod expose_service;
+pub mod list_toygres_resources;
+pub mod delete_resources;
 pub mod get_connection_strings;
+pub mod get_service_external_ip;
+pub mod register_private_dns;
+pub mod deregister_private_dns;
 pub mod test_connection;
+pub mod verify_postgres_version;
+pub mod run_maintenance;
 pub mod raise_event;
+pub mod send_notification;
 pub mod cms;
 
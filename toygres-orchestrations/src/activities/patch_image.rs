@@ -0,0 +1,127 @@
+//! Patch StatefulSet image activity
+//!
+//! Changes only the image of the `postgres` container, so a minor version bump rolls
+//! the pod in place and keeps the PVC, Service, and IP instead of recreating the instance.
+
+use duroxide::ActivityContext;
+use crate::activity_types::{PatchImageInput, PatchImageOutput};
+use crate::k8s_client::get_k8s_client;
+use k8s_openapi::api::apps::v1::StatefulSet;
+use kube::api::{Api, Patch, PatchParams};
+
+/// Activity name for registration and scheduling
+pub const NAME: &str = "toygres-orchestrations::activity::patch-image";
+
+/// Name of the container in the StatefulSet template that runs PostgreSQL
+const POSTGRES_CONTAINER: &str = "postgres";
+
+pub async fn activity(
+    ctx: ActivityContext,
+    input: PatchImageInput,
+) -> Result<PatchImageOutput, String> {
+    ctx.trace_info(format!("Patching image of {} to {}", input.instance_name, input.image));
+    
+    let client = get_k8s_client().await
+        .map_err(|e| format!("Failed to create K8s client: {}", e))?;
+    
+    let statefulsets: Api<StatefulSet> = Api::namespaced(client, &input.namespace);
+    let statefulset = statefulsets.get(&input.instance_name).await
+        .map_err(|e| format!("Failed to get StatefulSet {}: {}", input.instance_name, e))?;
+    
+    let previous_image = current_image(&statefulset);
+    
+    // Idempotency - a retry after a successful patch is a no-op
+    if previous_image.as_deref() == Some(input.image.as_str()) {
+        ctx.trace_info("StatefulSet already uses the requested image");
+        return Ok(PatchImageOutput { previous_image, patched: false });
+    }
+    
+    statefulsets
+        .patch(
+            &input.instance_name,
+            &PatchParams::default(),
+            &Patch::Strategic(image_patch(&input.image)),
+        )
+        .await
+        .map_err(|e| format!("Failed to patch StatefulSet image: {}", e))?;
+    
+    ctx.trace_info(format!("Image patched (was {:?})", previous_image));
+    
+    Ok(PatchImageOutput { previous_image, patched: true })
+}
+
+/// Strategic merge patch that replaces the postgres container image.
+///
+/// Containers merge by name, so the other containers and fields are left untouched.
+fn image_patch(image: &str) -> serde_json::Value {
+    serde_json::json!({
+        "spec": {
+            "template": {
+                "spec": {
+                    "containers": [
+                        { "name": POSTGRES_CONTAINER, "image": image }
+                    ]
+                }
+            }
+        }
+    })
+}
+
+/// Image of the postgres container in the StatefulSet pod template
+fn current_image(statefulset: &StatefulSet) -> Option<String> {
+    statefulset.spec.as_ref()?
+        .template
+        .spec.as_ref()?
+        .containers
+        .iter()
+        .find(|c| c.name == POSTGRES_CONTAINER)?
+        .image
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k8s_openapi::api::apps::v1::StatefulSetSpec;
+    use k8s_openapi::api::core::v1::{Container, PodSpec, PodTemplateSpec};
+    
+    #[test]
+    fn test_patch_targets_postgres_container_image() {
+        let patch = image_patch("postgres:18.2");
+        let containers = patch["spec"]["template"]["spec"]["containers"].as_array().unwrap();
+        
+        assert_eq!(containers.len(), 1);
+        assert_eq!(containers[0]["name"], "postgres");
+        assert_eq!(containers[0]["image"], "postgres:18.2");
+        // Nothing but the image is changed
+        assert_eq!(containers[0].as_object().unwrap().len(), 2);
+    }
+    
+    #[test]
+    fn test_current_image_ignores_other_containers() {
+        let container = |name: &str, image: &str| Container {
+            name: name.to_string(),
+            image: Some(image.to_string()),
+            ..Default::default()
+        };
+        let statefulset = StatefulSet {
+            spec: Some(StatefulSetSpec {
+                template: PodTemplateSpec {
+                    spec: Some(PodSpec {
+                        containers: vec![
+                            container("exporter", "prometheuscommunity/postgres-exporter"),
+                            container("postgres", "postgres:18.1"),
+                        ],
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        
+        assert_eq!(current_image(&statefulset).as_deref(), Some("postgres:18.1"));
+        assert_eq!(current_image(&StatefulSet::default()), None);
+    }
+}
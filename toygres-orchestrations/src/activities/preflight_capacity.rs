@@ -0,0 +1,233 @@
+//! Cluster-capacity preflight activity
+
+use duroxide::ActivityContext;
+use crate::activity_types::{PreflightCapacityInput, PreflightCapacityOutput};
+use crate::k8s_client::get_k8s_client;
+use k8s_openapi::api::core::v1::{Node, ResourceQuota};
+use k8s_openapi::api::storage::v1::StorageClass;
+use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+use kube::api::{Api, ListParams};
+
+/// Activity name for registration and scheduling
+pub const NAME: &str = "toygres-orchestrations::activity::preflight-capacity";
+
+/// When set to "true", every create runs the preflight, not only `?preflight=true` ones
+pub const ALWAYS_ENV: &str = "TOYGRES_CREATE_PREFLIGHT";
+
+/// Annotations marking the cluster's default StorageClass (the PVC template names none)
+const DEFAULT_CLASS_ANNOTATIONS: [&str; 2] = [
+    "storageclass.kubernetes.io/is-default-class",
+    "storageclass.beta.kubernetes.io/is-default-class",
+];
+
+/// Whether [`ALWAYS_ENV`] turns the preflight on for every create
+pub fn enabled_by_env() -> bool {
+    std::env::var(ALWAYS_ENV).map(|v| v.eq_ignore_ascii_case("true")).unwrap_or(false)
+}
+
+pub async fn activity(
+    ctx: ActivityContext,
+    input: PreflightCapacityInput,
+) -> Result<PreflightCapacityOutput, String> {
+    ctx.trace_info(format!(
+        "Checking cluster capacity for {} GB in namespace {}",
+        input.storage_size_gb, input.namespace
+    ));
+
+    let client = get_k8s_client().await
+        .map_err(|e| format!("Failed to create K8s client: {}", e))?;
+
+    let nodes: Api<Node> = Api::all(client.clone());
+    let storage_classes: Api<StorageClass> = Api::all(client.clone());
+    let quotas: Api<ResourceQuota> = Api::namespaced(client, &input.namespace);
+
+    let nodes = nodes.list(&ListParams::default()).await
+        .map_err(|e| format!("Failed to list nodes: {}", e))?;
+    let storage_classes = storage_classes.list(&ListParams::default()).await
+        .map_err(|e| format!("Failed to list storage classes: {}", e))?;
+    let quotas = quotas.list(&ListParams::default()).await
+        .map_err(|e| format!("Failed to list resource quotas: {}", e))?;
+
+    let output = assess_capacity(&nodes.items, &storage_classes.items, &quotas.items, input.storage_size_gb);
+    if output.sufficient {
+        ctx.trace_info(format!("Capacity OK ({} schedulable node(s))", output.schedulable_nodes));
+    } else {
+        ctx.trace_warn(format!("Insufficient capacity: {}", output.problems.join("; ")));
+    }
+
+    Ok(output)
+}
+
+/// Decide whether a `storage_size_gb` instance can be scheduled and bound: a Ready,
+/// schedulable node with room for a pod, a default StorageClass for the PVC, and
+/// headroom in every namespace quota that limits storage or PVC count
+pub fn assess_capacity(
+    nodes: &[Node],
+    storage_classes: &[StorageClass],
+    quotas: &[ResourceQuota],
+    storage_size_gb: i32,
+) -> PreflightCapacityOutput {
+    let mut problems = Vec::new();
+
+    let schedulable_nodes = nodes.iter().filter(|n| node_can_run_pod(n)).count();
+    if schedulable_nodes == 0 {
+        problems.push(format!("No Ready, schedulable node with pod capacity ({} node(s) in cluster)", nodes.len()));
+    }
+
+    if !storage_classes.iter().any(is_default_class) {
+        problems.push("No default StorageClass; the PVC would stay Pending".to_string());
+    }
+
+    let requested_bytes = storage_size_gb.max(0) as f64 * GI;
+    for quota in quotas {
+        let name = quota.metadata.name.as_deref().unwrap_or("<unnamed>");
+        if let Some(free) = quota_headroom(quota, "requests.storage") {
+            if free < requested_bytes {
+                problems.push(format!(
+                    "ResourceQuota '{}' has {:.1} GB of requests.storage left, {} GB requested",
+                    name, free / GI, storage_size_gb
+                ));
+            }
+        }
+        if let Some(free) = quota_headroom(quota, "persistentvolumeclaims") {
+            if free < 1.0 {
+                problems.push(format!("ResourceQuota '{}' allows no more persistentvolumeclaims", name));
+            }
+        }
+    }
+
+    PreflightCapacityOutput {
+        sufficient: problems.is_empty(),
+        schedulable_nodes,
+        problems,
+    }
+}
+
+const GI: f64 = 1024.0 * 1024.0 * 1024.0;
+
+fn node_can_run_pod(node: &Node) -> bool {
+    let unschedulable = node.spec.as_ref().and_then(|s| s.unschedulable).unwrap_or(false);
+    let status = node.status.as_ref();
+    let ready = status
+        .and_then(|s| s.conditions.as_ref())
+        .map(|conditions| conditions.iter().any(|c| c.type_ == "Ready" && c.status == "True"))
+        .unwrap_or(false);
+    // Nodes that don't report allocatable pods are given the benefit of the doubt
+    let pods = status
+        .and_then(|s| s.allocatable.as_ref())
+        .and_then(|a| a.get("pods"))
+        .and_then(parse_quantity)
+        .unwrap_or(1.0);
+
+    ready && !unschedulable && pods >= 1.0
+}
+
+fn is_default_class(class: &StorageClass) -> bool {
+    class.metadata.annotations.as_ref().is_some_and(|annotations| {
+        DEFAULT_CLASS_ANNOTATIONS.iter().any(|key| annotations.get(*key).map(String::as_str) == Some("true"))
+    })
+}
+
+/// `hard - used` for a quota resource, or `None` when the quota does not limit it
+fn quota_headroom(quota: &ResourceQuota, resource: &str) -> Option<f64> {
+    let hard = quota.status.as_ref()
+        .and_then(|s| s.hard.as_ref())
+        .or(quota.spec.as_ref().and_then(|s| s.hard.as_ref()))?
+        .get(resource)
+        .and_then(parse_quantity)?;
+    let used = quota.status.as_ref()
+        .and_then(|s| s.used.as_ref())
+        .and_then(|u| u.get(resource))
+        .and_then(parse_quantity)
+        .unwrap_or(0.0);
+    Some(hard - used)
+}
+
+/// Parse a Kubernetes quantity ("10Gi", "500M", "110", "1.5Ti") into base units
+fn parse_quantity(quantity: &Quantity) -> Option<f64> {
+    const SUFFIXES: [(&str, f64); 13] = [
+        ("Ki", 1024.0), ("Mi", 1024.0 * 1024.0), ("Gi", GI), ("Ti", GI * 1024.0),
+        ("Pi", GI * 1024.0 * 1024.0), ("Ei", GI * 1024.0 * 1024.0 * 1024.0),
+        ("m", 1e-3), ("k", 1e3), ("M", 1e6), ("G", 1e9), ("T", 1e12), ("P", 1e15), ("E", 1e18),
+    ];
+
+    let value = quantity.0.trim();
+    let (number, multiplier) = SUFFIXES.iter()
+        .find_map(|(suffix, multiplier)| value.strip_suffix(suffix).map(|n| (n, *multiplier)))
+        .unwrap_or((value, 1.0));
+    number.parse::<f64>().ok().map(|n| n * multiplier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(ready: bool, unschedulable: bool, pods: &str) -> Node {
+        serde_json::from_value(serde_json::json!({
+            "metadata": { "name": "aks-nodepool1-0" },
+            "spec": { "unschedulable": unschedulable },
+            "status": {
+                "allocatable": { "cpu": "1900m", "memory": "5Gi", "pods": pods },
+                "conditions": [{ "type": "Ready", "status": if ready { "True" } else { "False" } }]
+            }
+        })).unwrap()
+    }
+
+    fn default_class() -> StorageClass {
+        serde_json::from_value(serde_json::json!({
+            "metadata": {
+                "name": "default",
+                "annotations": { "storageclass.kubernetes.io/is-default-class": "true" }
+            },
+            "provisioner": "disk.csi.azure.com"
+        })).unwrap()
+    }
+
+    fn storage_quota(hard: &str, used: &str) -> ResourceQuota {
+        serde_json::from_value(serde_json::json!({
+            "metadata": { "name": "toygres-quota" },
+            "status": {
+                "hard": { "requests.storage": hard, "persistentvolumeclaims": "10" },
+                "used": { "requests.storage": used, "persistentvolumeclaims": "3" }
+            }
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_capacity_sufficient_with_ready_node_and_default_class() {
+        let output = assess_capacity(
+            &[node(false, false, "110"), node(true, false, "110")],
+            &[default_class()],
+            &[storage_quota("100Gi", "50Gi")],
+            10,
+        );
+        assert!(output.sufficient, "{:?}", output.problems);
+        assert_eq!(output.schedulable_nodes, 1);
+    }
+
+    #[test]
+    fn test_capacity_insufficient_reports_every_problem() {
+        let output = assess_capacity(
+            &[node(false, false, "110"), node(true, true, "110"), node(true, false, "0")],
+            &[],
+            &[storage_quota("100Gi", "95Gi")],
+            10,
+        );
+        assert!(!output.sufficient);
+        assert_eq!(output.schedulable_nodes, 0);
+        assert_eq!(output.problems.len(), 3, "{:?}", output.problems);
+        assert!(output.problems[0].contains("3 node(s)"));
+        assert!(output.problems[1].contains("StorageClass"));
+        assert!(output.problems[2].contains("5.0 GB of requests.storage left"));
+    }
+
+    #[test]
+    fn test_quantities_parse_to_base_units() {
+        let q = |s: &str| parse_quantity(&Quantity(s.to_string()));
+        assert_eq!(q("10Gi"), Some(10.0 * GI));
+        assert_eq!(q("500M"), Some(5e8));
+        assert_eq!(q("110"), Some(110.0));
+        assert_eq!(q("250m"), Some(0.25));
+        assert_eq!(q("lots"), None);
+    }
+}
@@ -1,27 +1,77 @@
 //! Raise event to another orchestration activity
 
 use duroxide::ActivityContext;
+use crate::activities::cms::get_pool;
 use crate::activity_types::{RaiseEventInput, RaiseEventOutput};
-use once_cell::sync::OnceCell;
-use std::sync::Arc;
+use sqlx::PgPool;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, RwLock};
 use duroxide::Client;
 
 /// Activity name for registration and scheduling
 pub const NAME: &str = "toygres-orchestrations::activity::raise-event";
 
-static DUROXIDE_CLIENT: OnceCell<Arc<Client>> = OnceCell::new();
+/// Instance looked up by [`check_client`]; any answer, including "not found", means the store is reachable
+const HEALTH_CHECK_INSTANCE: &str = "toygres-raise-event-health-check";
 
-/// Initialize the duroxide client for use in activities
+/// Builds a new client, e.g. over a fresh store connection, when the current one stops working
+pub type Reconnect =
+    Arc<dyn Fn() -> Pin<Box<dyn Future<Output = Result<Arc<Client>, String>> + Send>> + Send + Sync>;
+
+struct ClientSlot {
+    client: Option<Arc<Client>>,
+    reconnect: Option<Reconnect>,
+}
+
+static DUROXIDE_CLIENT: RwLock<ClientSlot> = RwLock::new(ClientSlot { client: None, reconnect: None });
+
+/// Initialize the duroxide client for use in activities, replacing any previous one
 pub fn init_client(client: Arc<Client>) {
-    DUROXIDE_CLIENT.set(client).ok();
+    DUROXIDE_CLIENT.write().unwrap_or_else(|e| e.into_inner()).client = Some(client);
+}
+
+/// Use `reconnect` to replace the client when raising an event fails and the client's
+/// store no longer answers
+pub fn set_reconnect(reconnect: Reconnect) {
+    DUROXIDE_CLIENT.write().unwrap_or_else(|e| e.into_inner()).reconnect = Some(reconnect);
 }
 
 /// Get the duroxide client
-fn get_client() -> Result<Arc<Client>, String> {
+pub fn get_client() -> Result<Arc<Client>, String> {
     DUROXIDE_CLIENT
-        .get()
-        .cloned()
-        .ok_or_else(|| "Duroxide client not initialized".to_string())
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .client
+        .clone()
+        .ok_or_else(|| {
+            "Duroxide client not initialized: the process running this worker must call \
+             toygres_orchestrations::init_duroxide_client after starting the runtime"
+                .to_string()
+        })
+}
+
+/// Check that the client's store answers queries
+pub async fn check_client(client: &Client) -> Result<(), String> {
+    client
+        .get_orchestration_status(HEALTH_CHECK_INSTANCE)
+        .await
+        .map(|_| ())
+        .map_err(|e| format!("Duroxide store unreachable: {}", e))
+}
+
+/// Build a new client with the configured [`Reconnect`] and make it the current one
+pub async fn reconnect_client() -> Result<Arc<Client>, String> {
+    let reconnect = DUROXIDE_CLIENT
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .reconnect
+        .clone()
+        .ok_or_else(|| "No reconnect configured for the Duroxide client".to_string())?;
+
+    let client = reconnect().await?;
+    init_client(client.clone());
+    Ok(client)
 }
 
 pub async fn activity(
@@ -32,20 +82,187 @@ pub async fn activity(
         "Raising event '{}' to orchestration '{}'",
         input.event_name, input.instance_id
     ));
-    
+
+    let raised = match &input.idempotency_key {
+        Some(key) => {
+            let pool = get_pool().await?;
+            raise_once(&pool, key, &input, raise(&ctx, &input)).await?
+        }
+        None => {
+            raise(&ctx, &input).await?;
+            true
+        }
+    };
+
+    if raised {
+        ctx.trace_info(format!(
+            "Event '{}' raised successfully to '{}'",
+            input.event_name, input.instance_id
+        ));
+    } else {
+        ctx.trace_info(format!(
+            "Event '{}' already raised for key '{}', skipping",
+            input.event_name,
+            input.idempotency_key.as_deref().unwrap_or_default()
+        ));
+    }
+
+    Ok(RaiseEventOutput { raised })
+}
+
+/// Claim `key`, then run `raise` only if this call made the claim.
+/// Returns whether `raise` ran.
+///
+/// The claim is one insert, so concurrent calls with the same key raise once. A failed
+/// `raise` gives the claim back so a retry can deliver the event.
+async fn raise_once(
+    pool: &PgPool,
+    key: &str,
+    input: &RaiseEventInput,
+    raise: impl Future<Output = Result<(), String>>,
+) -> Result<bool, String> {
+    let claimed: Option<String> = sqlx::query_scalar(
+        "INSERT INTO toygres_cms.raised_events (idempotency_key, instance_id, event_name)
+         VALUES ($1, $2, $3)
+         ON CONFLICT (idempotency_key) DO NOTHING
+         RETURNING idempotency_key"
+    )
+    .bind(key)
+    .bind(&input.instance_id)
+    .bind(&input.event_name)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| format!("Failed to claim idempotency key: {}", e))?;
+    if claimed.is_none() {
+        return Ok(false);
+    }
+
+    if let Err(e) = raise.await {
+        sqlx::query("DELETE FROM toygres_cms.raised_events WHERE idempotency_key = $1")
+            .bind(key)
+            .execute(pool)
+            .await
+            .map_err(|release_error| format!("{} (failed to release idempotency key: {})", e, release_error))?;
+        return Err(e);
+    }
+
+    Ok(true)
+}
+
+/// Raise the event, reconnecting once when the client's store has gone away
+async fn raise(ctx: &ActivityContext, input: &RaiseEventInput) -> Result<(), String> {
     let client = get_client()?;
-    
-    client
-        .raise_event(&input.instance_id, &input.event_name, &input.event_data)
-        .await
-        .map_err(|e| format!("Failed to raise event: {}", e))?;
-    
-    ctx.trace_info(format!(
-        "Event '{}' raised successfully to '{}'",
-        input.event_name, input.instance_id
-    ));
-    
-    Ok(RaiseEventOutput { raised: true })
+
+    if let Err(e) = client.raise_event(&input.instance_id, &input.event_name, &input.event_data).await {
+        // Only a client whose store is gone is worth replacing; other errors are returned as-is
+        let Err(unhealthy) = check_client(&client).await else {
+            return Err(format!("Failed to raise event: {}", e));
+        };
+        ctx.trace_warn(format!("{}; reconnecting", unhealthy));
+
+        let client = reconnect_client()
+            .await
+            .map_err(|reconnect_error| format!("Failed to raise event: {} ({})", e, reconnect_error))?;
+        client
+            .raise_event(&input.instance_id, &input.event_name, &input.event_data)
+            .await
+            .map_err(|e| format!("Failed to raise event after reconnecting: {}", e))?;
+    }
+
+    Ok(())
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use duroxide::providers::sqlite::SqliteProvider;
+
+    async fn sqlite_client() -> Arc<Client> {
+        Arc::new(Client::new(Arc::new(SqliteProvider::new_in_memory().await.unwrap())))
+    }
+
+    // The client is process-wide, so every step runs in one test
+    #[tokio::test]
+    async fn test_client_lifecycle() {
+        let err = get_client().err().unwrap();
+        assert!(err.contains("not initialized") && err.contains("init_duroxide_client"), "{}", err);
+        assert!(reconnect_client().await.err().unwrap().contains("No reconnect configured"));
+
+        let first = sqlite_client().await;
+        init_client(first.clone());
+        let current = get_client().unwrap();
+        assert!(Arc::ptr_eq(&current, &first));
+        assert_eq!(check_client(&current).await, Ok(()));
 
+        let second = sqlite_client().await;
+        let replacement = second.clone();
+        set_reconnect(Arc::new(move || {
+            let client = replacement.clone();
+            Box::pin(async move { Ok(client) })
+        }));
+        let reconnected = reconnect_client().await.unwrap();
+        assert!(Arc::ptr_eq(&reconnected, &second));
+        assert!(Arc::ptr_eq(&get_client().unwrap(), &second));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL with the toygres_cms schema"]
+    async fn test_repeated_raise_with_same_key_is_noop() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let pool = crate::activities::cms::test_pool().await;
+        let key = format!("test-key-{}", uuid::Uuid::new_v4());
+        let other_key = format!("{}-2", key);
+        let input = RaiseEventInput {
+            instance_id: "actor-mydb-1a2b".to_string(),
+            event_name: "UpdateConfig".to_string(),
+            event_data: r#"{"slow_threshold_ms":250}"#.to_string(),
+            idempotency_key: Some(key.clone()),
+        };
+        let deliveries = AtomicUsize::new(0);
+        let deliver = || async {
+            deliveries.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        };
+
+        let first = raise_once(&pool, &key, &input, deliver()).await;
+        let second = raise_once(&pool, &key, &input, deliver()).await;
+        let third = raise_once(&pool, &other_key, &input, deliver()).await;
+
+        sqlx::query("DELETE FROM toygres_cms.raised_events WHERE idempotency_key LIKE $1")
+            .bind(format!("{}%", key))
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(first, Ok(true));
+        assert_eq!(second, Ok(false));
+        assert_eq!(third, Ok(true));
+        assert_eq!(deliveries.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL with the toygres_cms schema"]
+    async fn test_failed_raise_releases_the_key() {
+        let pool = crate::activities::cms::test_pool().await;
+        let key = format!("test-key-{}", uuid::Uuid::new_v4());
+        let input = RaiseEventInput {
+            instance_id: "actor-mydb-1a2b".to_string(),
+            event_name: "UpdateConfig".to_string(),
+            event_data: "{}".to_string(),
+            idempotency_key: Some(key.clone()),
+        };
+
+        let failed = raise_once(&pool, &key, &input, async { Err("store unreachable".to_string()) }).await;
+        let retried = raise_once(&pool, &key, &input, async { Ok(()) }).await;
+
+        sqlx::query("DELETE FROM toygres_cms.raised_events WHERE idempotency_key = $1")
+            .bind(&key)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(failed, Err("store unreachable".to_string()));
+        assert_eq!(retried, Ok(true));
+    }
+}
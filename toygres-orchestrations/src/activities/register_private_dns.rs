@@ -0,0 +1,160 @@
+//! Register private DNS activity
+//!
+//! Instances behind an internal LoadBalancer have no `cloudapp.azure.com` name. When
+//! an Azure Private DNS zone is configured through the environment, this activity
+//! upserts an A record for the instance pointing at the internal LoadBalancer IP.
+//! It authenticates with the managed identity of the node or workload (IMDS).
+
+use duroxide::ActivityContext;
+use crate::activity_types::{RegisterPrivateDnsInput, RegisterPrivateDnsOutput};
+use std::time::Duration;
+
+/// Activity name for registration and scheduling
+pub const NAME: &str = "toygres-orchestrations::activity::register-private-dns";
+
+/// Private DNS zone name, e.g. `toygres.internal`; unset or empty disables private DNS
+pub const ZONE_ENV: &str = "TOYGRES_PRIVATE_DNS_ZONE";
+
+/// Subscription holding the zone
+pub const SUBSCRIPTION_ENV: &str = "TOYGRES_PRIVATE_DNS_SUBSCRIPTION_ID";
+
+/// Resource group holding the zone
+pub const RESOURCE_GROUP_ENV: &str = "TOYGRES_PRIVATE_DNS_RESOURCE_GROUP";
+
+/// TTL of the A records written to the zone
+const RECORD_TTL_SECONDS: u32 = 300;
+
+/// Bound on each Azure request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+const IMDS_TOKEN_URL: &str = "http://169.254.169.254/metadata/identity/oauth2/token";
+const ARM_RESOURCE: &str = "https://management.azure.com/";
+const PRIVATE_DNS_API_VERSION: &str = "2018-09-01";
+
+/// The Azure Private DNS zone configured through the environment
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrivateDnsZone {
+    pub subscription_id: String,
+    pub resource_group: String,
+    pub zone: String,
+}
+
+impl PrivateDnsZone {
+    /// The zone from [`ZONE_ENV`], [`SUBSCRIPTION_ENV`] and [`RESOURCE_GROUP_ENV`];
+    /// `None` unless all three are set
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+        Some(Self {
+            subscription_id: var(SUBSCRIPTION_ENV)?,
+            resource_group: var(RESOURCE_GROUP_ENV)?,
+            zone: var(ZONE_ENV)?,
+        })
+    }
+
+    /// ARM URL of the A record `label` in the zone
+    pub(crate) fn record_url(&self, label: &str) -> String {
+        format!(
+            "https://management.azure.com/subscriptions/{}/resourceGroups/{}/providers/Microsoft.Network/privateDnsZones/{}/A/{}?api-version={}",
+            self.subscription_id,
+            self.resource_group,
+            zone_name(&self.zone),
+            label,
+            PRIVATE_DNS_API_VERSION
+        )
+    }
+}
+
+/// Fully qualified name of record `label` in `zone` (`mydb` in `toygres.internal`
+/// gives `mydb.toygres.internal`)
+pub fn private_dns_name(zone: &str, label: &str) -> String {
+    format!("{}.{}", label, zone_name(zone))
+}
+
+/// The zone without surrounding whitespace or a trailing root dot
+fn zone_name(zone: &str) -> &str {
+    zone.trim().trim_end_matches('.')
+}
+
+pub async fn activity(
+    ctx: ActivityContext,
+    input: RegisterPrivateDnsInput,
+) -> Result<RegisterPrivateDnsOutput, String> {
+    let zone = PrivateDnsZone::from_env().ok_or_else(|| format!(
+        "Private DNS is not configured; set {}, {} and {}",
+        ZONE_ENV, SUBSCRIPTION_ENV, RESOURCE_GROUP_ENV
+    ))?;
+    let fqdn = private_dns_name(&zone.zone, &input.record_name);
+    ctx.trace_info(format!("Registering {} -> {}", fqdn, input.ip_address));
+
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+
+    let token = managed_identity_token(&client).await?;
+    client
+        .put(zone.record_url(&input.record_name))
+        .bearer_auth(token)
+        .json(&serde_json::json!({
+            "properties": {
+                "ttl": RECORD_TTL_SECONDS,
+                "aRecords": [{ "ipv4Address": input.ip_address }]
+            }
+        }))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Failed to write A record {}: {}", fqdn, e))?;
+
+    ctx.trace_info(format!("Private DNS record registered: {}", fqdn));
+    Ok(RegisterPrivateDnsOutput { fqdn })
+}
+
+/// An ARM access token from the instance metadata service, for the user-assigned
+/// identity in `AZURE_CLIENT_ID` when set
+pub(crate) async fn managed_identity_token(client: &reqwest::Client) -> Result<String, String> {
+    #[derive(serde::Deserialize)]
+    struct TokenResponse {
+        access_token: String,
+    }
+
+    let mut query = vec![("api-version", "2018-02-01".to_string()), ("resource", ARM_RESOURCE.to_string())];
+    if let Ok(client_id) = std::env::var("AZURE_CLIENT_ID") {
+        query.push(("client_id", client_id));
+    }
+
+    let response: TokenResponse = client
+        .get(IMDS_TOKEN_URL)
+        .header("Metadata", "true")
+        .query(&query)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Failed to get managed identity token: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid managed identity token response: {}", e))?;
+
+    Ok(response.access_token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_private_dns_name_from_zone_and_instance() {
+        assert_eq!(private_dns_name("toygres.internal", "mydb"), "mydb.toygres.internal");
+        assert_eq!(private_dns_name(" privatelink.postgres.example.com. ", "mydb"), "mydb.privatelink.postgres.example.com");
+
+        let zone = PrivateDnsZone {
+            subscription_id: "sub-1".to_string(),
+            resource_group: "rg-net".to_string(),
+            zone: "toygres.internal.".to_string(),
+        };
+        assert_eq!(
+            zone.record_url("mydb"),
+            "https://management.azure.com/subscriptions/sub-1/resourceGroups/rg-net/providers/Microsoft.Network/privateDnsZones/toygres.internal/A/mydb?api-version=2018-09-01"
+        );
+    }
+}
@@ -0,0 +1,202 @@
+//! Run VACUUM/ANALYZE maintenance activity
+
+use duroxide::ActivityContext;
+use crate::activity_types::{RunMaintenanceInput, RunMaintenanceOutput};
+use crate::k8s_client::{get_k8s_client, read_secret_password};
+use std::time::{Duration, Instant};
+use tokio_postgres::NoTls;
+
+/// Activity name for registration and scheduling
+pub const NAME: &str = "toygres-orchestrations::activity::run-maintenance";
+
+/// Surfaced whenever a VACUUM FULL is requested
+pub const VACUUM_FULL_WARNING: &str =
+    "VACUUM FULL rewrites each table under an ACCESS EXCLUSIVE lock; reads and writes block until it finishes";
+
+/// Bound on connecting only; the VACUUM itself runs as long as it needs
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub async fn activity(
+    ctx: ActivityContext,
+    input: RunMaintenanceInput,
+) -> Result<RunMaintenanceOutput, String> {
+    validate_tables(input.tables.as_deref()).map_err(|errors| errors.join("; "))?;
+
+    let command = vacuum_command(input.full);
+    ctx.trace_info(format!("Running {}", command));
+    if input.full {
+        ctx.trace_warn(VACUUM_FULL_WARNING);
+    }
+
+    // 1. Resolve the password from the referenced Secret when the connection string omits it
+    let password = match &input.password_secret {
+        Some(secret) => {
+            let client = get_k8s_client().await
+                .map_err(|e| format!("Failed to create K8s client: {}", e))?;
+            let password = read_secret_password(&client, &secret.namespace, &secret.name).await
+                .map_err(|e| format!("Failed to read password Secret: {}", e))?;
+            Some(password)
+        }
+        None => None,
+    };
+
+    // 2. Vacuum the requested tables, or every user table
+    let started = Instant::now();
+    let tables = vacuum_tables(&input, password.as_deref(), command, &ctx)
+        .await
+        .map_err(|e| format!("Maintenance failed: {}", e))?;
+    let duration_ms = started.elapsed().as_millis() as u64;
+
+    ctx.trace_info(format!("{} finished on {} table(s) in {}ms", command, tables.len(), duration_ms));
+
+    Ok(RunMaintenanceOutput {
+        command: command.to_string(),
+        tables_processed: tables.len(),
+        tables,
+        duration_ms,
+    })
+}
+
+/// The VACUUM form to run against each table
+pub fn vacuum_command(full: bool) -> &'static str {
+    if full { "VACUUM (FULL, ANALYZE)" } else { "VACUUM (ANALYZE)" }
+}
+
+/// Check an explicit table list: non-empty, and every entry a plain `table` or
+/// `schema.table` identifier. Returns every problem found.
+pub fn validate_tables(tables: Option<&[String]>) -> Result<(), Vec<String>> {
+    let Some(tables) = tables else {
+        return Ok(());
+    };
+    if tables.is_empty() {
+        return Err(vec!["tables: must not be empty; omit it to process all tables".to_string()]);
+    }
+
+    let errors: Vec<String> = tables
+        .iter()
+        .filter(|table| {
+            let parts: Vec<&str> = table.split('.').collect();
+            parts.len() > 2 || !parts.iter().all(|part| is_identifier(part))
+        })
+        .map(|table| format!("tables: '{}' is not a valid table name", table))
+        .collect();
+
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
+
+/// Unquoted PostgreSQL identifier: a letter or `_`, then letters, digits, `_` or `$`
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+        && name.len() <= 63
+}
+
+/// Double-quote an identifier, escaping embedded quotes
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+async fn vacuum_tables(
+    input: &RunMaintenanceInput,
+    password: Option<&str>,
+    command: &str,
+    ctx: &ActivityContext,
+) -> anyhow::Result<Vec<String>> {
+    let mut config: tokio_postgres::Config = input.connection_string.parse()
+        .map_err(|e| anyhow::anyhow!("Invalid connection string: {}", e))?;
+    if let Some(password) = password {
+        config.password(password);
+    }
+    config.connect_timeout(CONNECT_TIMEOUT);
+    let (client, connection) = config.connect(NoTls)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to connect: {}", e))?;
+
+    // Spawn connection handler
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            eprintln!("PostgreSQL connection error: {}", e);
+        }
+    });
+
+    // (display name, quoted name) per table
+    let targets: Vec<(String, String)> = match &input.tables {
+        Some(tables) => tables
+            .iter()
+            .map(|table| {
+                let quoted: Vec<String> = table.split('.').map(quote_ident).collect();
+                (table.clone(), quoted.join("."))
+            })
+            .collect(),
+        None => client
+            .query("SELECT schemaname::text, relname::text FROM pg_stat_user_tables ORDER BY 1, 2", &[])
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to list tables: {}", e))?
+            .iter()
+            .map(|row| {
+                let (schema, table): (String, String) = (row.get(0), row.get(1));
+                (format!("{}.{}", schema, table), format!("{}.{}", quote_ident(&schema), quote_ident(&table)))
+            })
+            .collect(),
+    };
+
+    // VACUUM cannot run inside a transaction block, so each table is its own statement
+    for (display, quoted) in &targets {
+        ctx.trace_info(format!("{} {}", command, display));
+        client
+            .batch_execute(&format!("{} {}", command, quoted))
+            .await
+            .map_err(|e| anyhow::anyhow!("{} {} failed: {}", command, display, e))?;
+    }
+
+    Ok(targets.into_iter().map(|(display, _)| display).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tables(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_table_list_validation() {
+        // All tables, or a list of plain / schema-qualified names
+        assert!(validate_tables(None).is_ok());
+        assert!(validate_tables(Some(&tables(&["orders", "public.users", "_audit$log"]))).is_ok());
+
+        let empty = validate_tables(Some(&[])).unwrap_err();
+        assert!(empty[0].contains("must not be empty"));
+
+        // Every bad entry is reported, and nothing that needs quoting gets through
+        let errors = validate_tables(Some(&tables(&[
+            "orders",
+            "users; DROP TABLE orders",
+            "a.b.c",
+            "1st",
+            "public.",
+        ]))).unwrap_err();
+        assert_eq!(errors.len(), 4, "errors: {:?}", errors);
+        assert!(errors[0].contains("users; DROP TABLE orders"));
+    }
+
+    #[test]
+    fn test_full_flag_selects_command_and_defaults_off() {
+        assert_eq!(vacuum_command(false), "VACUUM (ANALYZE)");
+        assert_eq!(vacuum_command(true), "VACUUM (FULL, ANALYZE)");
+
+        let input: RunMaintenanceInput = serde_json::from_str(
+            r#"{"connection_string":"postgresql://postgres@host:5432/postgres"}"#
+        ).unwrap();
+        assert!(!input.full);
+        assert_eq!(input.tables, None);
+    }
+
+    #[test]
+    fn test_quote_ident_escapes_quotes() {
+        assert_eq!(quote_ident("orders"), "\"orders\"");
+        assert_eq!(quote_ident("we\"ird"), "\"we\"\"ird\"");
+    }
+}
@@ -0,0 +1,193 @@
+//! Send notification activity
+//!
+//! POSTs a JSON payload describing a create/delete outcome to the webhook in
+//! `TOYGRES_WEBHOOK_URL`. The payload carries a `text` summary, so a Slack incoming
+//! webhook URL works as-is. Without a URL the activity does nothing.
+//!
+//! Since it runs once at the terminal point of a create or delete, the activity also
+//! records the orchestration's duration ([`crate::metrics::observe_orchestration`]).
+
+use duroxide::{ActivityContext, OrchestrationContext};
+use crate::activity_types::{NotificationOutcome, SendNotificationInput, SendNotificationOutput};
+use crate::trace::Tracer;
+use std::time::{Duration, SystemTime};
+
+/// Activity name for registration and scheduling
+pub const NAME: &str = "toygres-orchestrations::activity::send-notification";
+
+/// Environment variable holding the webhook URL; unset or empty disables notifications
+pub const WEBHOOK_URL_ENV: &str = "TOYGRES_WEBHOOK_URL";
+
+/// Bound on the whole webhook request, so a slow receiver cannot hold up the orchestration
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub async fn activity(
+    ctx: ActivityContext,
+    input: SendNotificationInput,
+) -> Result<SendNotificationOutput, String> {
+    crate::metrics::observe_orchestration(ctx.orchestration_name(), Duration::from_secs(input.duration_seconds));
+    
+    let url = std::env::var(WEBHOOK_URL_ENV).ok();
+    let sent = send(url.as_deref(), &input).await?;
+    
+    if sent {
+        ctx.trace_info(format!("Sent '{}' notification for {}", outcome_label(input.outcome), input.instance_name));
+    }
+    Ok(SendNotificationOutput { sent })
+}
+
+/// Notify from an orchestration's terminal point. Best-effort: a failed notification
+/// is traced and never fails the orchestration.
+pub async fn notify(
+    ctx: &OrchestrationContext,
+    trace: &Tracer,
+    started: SystemTime,
+    outcome: NotificationOutcome,
+    instance_name: &str,
+    orchestration_id: &str,
+    error: Option<String>,
+) {
+    let duration_seconds = match ctx.utcnow().await {
+        Ok(now) => now.duration_since(started).map(|d| d.as_secs()).unwrap_or_default(),
+        Err(e) => {
+            trace.warn(format!("Failed to get notification time: {}", e));
+            0
+        }
+    };
+    
+    let input = SendNotificationInput {
+        instance_name: instance_name.to_string(),
+        orchestration_id: orchestration_id.to_string(),
+        outcome,
+        duration_seconds,
+        error,
+    };
+    if let Err(err) = ctx
+        .schedule_activity_typed::<SendNotificationInput, SendNotificationOutput>(NAME, &input)
+        .into_activity_typed::<SendNotificationOutput>()
+        .await
+    {
+        trace.warn(format!("Failed to send notification: {}", err));
+    }
+}
+
+/// POST the payload to `url`; `Ok(false)` without a URL
+async fn send(url: Option<&str>, input: &SendNotificationInput) -> Result<bool, String> {
+    let Some(url) = url.filter(|u| !u.trim().is_empty()) else {
+        return Ok(false);
+    };
+    
+    let client = reqwest::Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to build webhook client: {}", e))?;
+    
+    client
+        .post(url)
+        .json(&payload(input))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Webhook request failed: {}", e))?;
+    
+    Ok(true)
+}
+
+fn outcome_label(outcome: NotificationOutcome) -> &'static str {
+    match outcome {
+        NotificationOutcome::Created => "created",
+        NotificationOutcome::CreateFailed => "create failed",
+        NotificationOutcome::Deleted => "deleted",
+    }
+}
+
+/// The webhook body: the input's fields plus a human-readable `text`
+pub fn payload(input: &SendNotificationInput) -> serde_json::Value {
+    let mut text = format!(
+        "toygres: instance {} {} after {}s",
+        input.instance_name,
+        outcome_label(input.outcome),
+        input.duration_seconds
+    );
+    if let Some(error) = &input.error {
+        text.push_str(&format!(": {}", error));
+    }
+    
+    let mut body = serde_json::json!(input);
+    body["text"] = serde_json::json!(text);
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn failed_create() -> SendNotificationInput {
+        SendNotificationInput {
+            instance_name: "mydb-1a2b3c4d".to_string(),
+            orchestration_id: "create-mydb-1a2b3c4d".to_string(),
+            outcome: NotificationOutcome::CreateFailed,
+            duration_seconds: 95,
+            error: Some("Timeout: Pod still in phase 'Pending'".to_string()),
+        }
+    }
+    
+    #[test]
+    fn test_payload_serialization() {
+        assert_eq!(payload(&failed_create()), serde_json::json!({
+            "instance_name": "mydb-1a2b3c4d",
+            "orchestration_id": "create-mydb-1a2b3c4d",
+            "outcome": "create_failed",
+            "duration_seconds": 95,
+            "error": "Timeout: Pod still in phase 'Pending'",
+            "text": "toygres: instance mydb-1a2b3c4d create failed after 95s: Timeout: Pod still in phase 'Pending'",
+        }));
+        
+        let deleted = SendNotificationInput { outcome: NotificationOutcome::Deleted, error: None, ..failed_create() };
+        let body = payload(&deleted);
+        assert_eq!(body["outcome"], "deleted");
+        assert!(body.get("error").is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_missing_url_is_a_no_op() {
+        assert_eq!(send(None, &failed_create()).await, Ok(false));
+        assert_eq!(send(Some("  "), &failed_create()).await, Ok(false));
+    }
+    
+    #[tokio::test]
+    async fn test_terminal_notification_records_the_duration_once() {
+        use duroxide::providers::sqlite::SqliteProvider;
+        use duroxide::runtime::{self, registry::ActivityRegistry};
+        use duroxide::{Client, OrchestrationRegistry};
+        use std::sync::Arc;
+        
+        const ORCHESTRATION: &str = "toygres-orchestrations::orchestration::notify-metrics-test";
+        
+        let activities = ActivityRegistry::builder()
+            .register_typed(NAME, activity)
+            .build();
+        let orchestrations = OrchestrationRegistry::builder()
+            .register(ORCHESTRATION, |ctx: OrchestrationContext, _input: String| async move {
+                let trace = Tracer::new(&ctx, None);
+                let started = ctx.utcnow().await?;
+                notify(&ctx, &trace, started, NotificationOutcome::Deleted, "mydb-1a2b3c4d", "delete-mydb-1a2b3c4d", None).await;
+                Ok("done".to_string())
+            })
+            .build();
+        
+        let store = Arc::new(SqliteProvider::new_in_memory().await.unwrap());
+        let rt = runtime::Runtime::start_with_store(store.clone(), Arc::new(activities), orchestrations).await;
+        let client = Client::new(store);
+        client.start_orchestration("delete-mydb-1a2b3c4d", ORCHESTRATION, "\"\"").await.unwrap();
+        let status = client.wait_for_orchestration("delete-mydb-1a2b3c4d", Duration::from_secs(10)).await.unwrap();
+        rt.shutdown(None).await;
+        assert!(matches!(status, duroxide::OrchestrationStatus::Completed { .. }), "status: {:?}", status);
+        
+        let out = crate::metrics::render_prometheus();
+        assert!(
+            out.contains("toygres_orchestration_duration_seconds_count{orchestration=\"notify-metrics-test\"} 1\n"),
+            "{}", out
+        );
+    }
+}
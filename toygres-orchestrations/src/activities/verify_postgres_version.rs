@@ -0,0 +1,85 @@
+//! Verify PostgreSQL version activity
+//!
+//! Compares the major version a new instance's server reports in `SELECT version()`
+//! with the one the create asked for, so a stale or wrong image tag is caught before
+//! the instance is marked running.
+
+use duroxide::ActivityContext;
+use crate::activity_types::{VerifyPostgresVersionInput, VerifyPostgresVersionOutput};
+
+/// Activity name for registration and scheduling
+pub const NAME: &str = "toygres-orchestrations::activity::verify-postgres-version";
+
+pub async fn activity(
+    ctx: ActivityContext,
+    input: VerifyPostgresVersionInput,
+) -> Result<VerifyPostgresVersionOutput, String> {
+    let requested_major = requested_major_version(&input.requested_version)
+        .ok_or_else(|| format!("Invalid requested PostgreSQL version: {}", input.requested_version))?;
+    let server_major = server_major_version(&input.server_version);
+
+    let matches = server_major == Some(requested_major);
+    if matches {
+        ctx.trace_info(format!("Server runs the requested PostgreSQL {}", requested_major));
+    } else {
+        ctx.trace_warn(format!(
+            "Requested PostgreSQL {}, server reports: {}",
+            requested_major, input.server_version
+        ));
+    }
+
+    Ok(VerifyPostgresVersionOutput {
+        requested_major,
+        server_major,
+        matches,
+    })
+}
+
+/// Major version of a `postgres_version` such as "18" or "17.6"
+pub fn requested_major_version(version: &str) -> Option<u32> {
+    version.trim().split('.').next()?.parse().ok()
+}
+
+/// Major version in a `SELECT version()` string, e.g. 16 for
+/// "PostgreSQL 16.4 (Debian 16.4-1.pgdg120+2) on x86_64-pc-linux-gnu, ...".
+/// Pre-release builds ("17beta1", "18devel") count as their major; for servers
+/// before 10 only the first component is returned ("9.6.24" is 9).
+pub fn server_major_version(version: &str) -> Option<u32> {
+    let mut words = version.split_whitespace();
+    words.find(|word| *word == "PostgreSQL")?;
+    let number = words.next()?;
+    let digits = number.find(|c: char| !c.is_ascii_digit()).map_or(number, |end| &number[..end]);
+    digits.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_major_version_parsing() {
+        let cases = [
+            ("PostgreSQL 18.0 on x86_64-pc-linux-gnu, compiled by gcc (GCC) 12.2.0, 64-bit", Some(18)),
+            ("PostgreSQL 16.4 (Debian 16.4-1.pgdg120+2) on aarch64-unknown-linux-gnu, compiled by gcc, 64-bit", Some(16)),
+            ("PostgreSQL 17beta1 on x86_64-pc-linux-musl", Some(17)),
+            ("PostgreSQL 18devel", Some(18)),
+            ("PostgreSQL 9.6.24 on x86_64-pc-linux-gnu", Some(9)),
+            ("PostgreSQL", None),
+            ("PostgreSQL unknown", None),
+            ("EnterpriseDB 16.2", None),
+            ("", None),
+        ];
+        for (version, expected) in cases {
+            assert_eq!(server_major_version(version), expected, "{}", version);
+        }
+
+        assert_eq!(requested_major_version("18"), Some(18));
+        assert_eq!(requested_major_version("17.6"), Some(17));
+        assert_eq!(requested_major_version("latest"), None);
+
+        // A stale tag: 18 requested, 16 running
+        let server = server_major_version("PostgreSQL 16.4 (Debian 16.4-1.pgdg120+2) on x86_64-pc-linux-gnu");
+        assert_ne!(server, requested_major_version("18"));
+        assert_eq!(server, requested_major_version("16.4"));
+    }
+}
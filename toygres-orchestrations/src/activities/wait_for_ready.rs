@@ -20,7 +20,7 @@ pub async fn activity(
         .map_err(|e| format!("Failed to create K8s client: {}", e))?;
     
     // 3. Check current pod status (no polling, orchestration handles that)
-    let (phase, is_ready) = check_pod_ready(&client, &input.namespace, &input.instance_name, &ctx).await
+    let (phase, is_ready) = check_pod_ready(&client, &input, &ctx).await
         .map_err(|e| format!("Failed to check pod status: {}", e))?;
     
     ctx.trace_info(format!("Pod phase: {}, ready: {}", phase, is_ready));
@@ -34,22 +34,30 @@ pub async fn activity(
 
 async fn check_pod_ready(
     client: &kube::Client,
-    namespace: &str,
-    instance_name: &str,
-    _ctx: &ActivityContext,
+    input: &WaitForReadyInput,
+    ctx: &ActivityContext,
 ) -> anyhow::Result<(String, bool)> {
-    let pods: Api<Pod> = Api::namespaced(client.clone(), namespace);
-    let label_selector = format!("instance={}", instance_name);
+    let pods: Api<Pod> = Api::namespaced(client.clone(), &input.namespace);
+    let label_selector = format!("instance={}", input.instance_name);
     
     let pod_list = pods
         .list(&ListParams::default().labels(&label_selector))
         .await?;
 
     if let Some(pod) = pod_list.items.first() {
+        // During a rolling update the old pod can still be Ready; it doesn't count
+        if let Some(expected) = &input.expected_image {
+            let image = postgres_container_image(pod);
+            if image != Some(expected.as_str()) {
+                ctx.trace_info(format!("Pod runs {:?}, waiting for {}", image, expected));
+                let phase = pod.status.as_ref().and_then(|s| s.phase.clone()).unwrap_or_else(|| "Unknown".to_string());
+                return Ok((phase, false));
+            }
+        }
+        
         // Check if pod is ready
         if let Some(status) = &pod.status {
-            let phase = status.phase.as_ref()
-                .map(|p| p.as_str())
+            let phase = status.phase.as_deref()
                 .unwrap_or("Unknown")
                 .to_string();
             
@@ -70,6 +78,16 @@ async fn check_pod_ready(
     Ok(("NotFound".to_string(), false))
 }
 
+/// Image of the pod's postgres container
+fn postgres_container_image(pod: &Pod) -> Option<&str> {
+    pod.spec.as_ref()?
+        .containers
+        .iter()
+        .find(|c| c.name == "postgres")?
+        .image
+        .as_deref()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -80,6 +98,7 @@ mod tests {
             namespace: "test".to_string(),
             instance_name: "test-pg".to_string(),
             timeout_seconds: 300,
+            expected_image: None,
         };
         
         let json = serde_json::to_string(&input).unwrap();
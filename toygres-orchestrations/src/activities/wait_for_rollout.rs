@@ -0,0 +1,129 @@
+//! Check once whether a StatefulSet change has rolled out
+//!
+//! After an image or template patch the old pod keeps reporting Ready until the
+//! controller replaces it, so pod readiness alone can report success too early.
+//! The rollout is complete once every replica was created from the update revision,
+//! is ready, and the controller has observed the latest spec.
+
+use duroxide::ActivityContext;
+use crate::activity_types::{WaitForRolloutInput, WaitForRolloutOutput};
+use crate::k8s_client::get_k8s_client;
+use k8s_openapi::api::apps::v1::StatefulSet;
+use kube::api::Api;
+
+/// Activity name for registration and scheduling
+pub const NAME: &str = "toygres-orchestrations::activity::wait-for-rollout";
+
+pub async fn activity(
+    ctx: ActivityContext,
+    input: WaitForRolloutInput,
+) -> Result<WaitForRolloutOutput, String> {
+    let client = get_k8s_client().await
+        .map_err(|e| format!("Failed to create K8s client: {}", e))?;
+
+    // No polling here; the orchestration waits between checks with durable timers
+    let statefulsets: Api<StatefulSet> = Api::namespaced(client, &input.namespace);
+    let statefulset = statefulsets.get(&input.instance_name).await
+        .map_err(|e| format!("Failed to get StatefulSet {}: {}", input.instance_name, e))?;
+
+    let output = rollout_status(&statefulset);
+    ctx.trace_info(format!(
+        "Rollout of {}: {}/{} updated, {}/{} ready, revision {:?} -> {:?}",
+        input.instance_name,
+        output.updated_replicas, output.replicas,
+        output.ready_replicas, output.replicas,
+        output.current_revision, output.update_revision,
+    ));
+
+    Ok(output)
+}
+
+/// Rollout progress from a StatefulSet's spec and status
+pub fn rollout_status(statefulset: &StatefulSet) -> WaitForRolloutOutput {
+    let replicas = statefulset.spec.as_ref().and_then(|s| s.replicas).unwrap_or(1);
+    let status = statefulset.status.as_ref();
+    let updated_replicas = status.and_then(|s| s.updated_replicas).unwrap_or(0);
+    let ready_replicas = status.and_then(|s| s.ready_replicas).unwrap_or(0);
+    let current_revision = status.and_then(|s| s.current_revision.clone());
+    let update_revision = status.and_then(|s| s.update_revision.clone());
+
+    // A status the controller wrote before seeing the patch describes the old spec
+    let observed = match (statefulset.metadata.generation, status.and_then(|s| s.observed_generation)) {
+        (Some(generation), Some(observed)) => observed >= generation,
+        (Some(_), None) => false,
+        (None, _) => true,
+    };
+
+    let complete = observed
+        && update_revision.is_some()
+        && current_revision == update_revision
+        && updated_replicas == replicas
+        && ready_replicas == replicas;
+
+    WaitForRolloutOutput {
+        complete,
+        replicas,
+        updated_replicas,
+        ready_replicas,
+        current_revision,
+        update_revision,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn statefulset(generation: i64, status: serde_json::Value) -> StatefulSet {
+        serde_json::from_value(serde_json::json!({
+            "metadata": { "name": "mydb-1a2b", "generation": generation },
+            "spec": {
+                "replicas": 1,
+                "serviceName": "mydb-1a2b-svc",
+                "selector": { "matchLabels": { "instance": "mydb-1a2b" } },
+                "template": { "metadata": { "labels": { "instance": "mydb-1a2b" } } }
+            },
+            "status": status
+        })).unwrap()
+    }
+
+    #[test]
+    fn test_rollout_complete_only_on_update_revision() {
+        // Old pod still Ready, new revision not rolled out yet
+        let rolling = statefulset(2, serde_json::json!({
+            "replicas": 1, "readyReplicas": 1, "updatedReplicas": 0, "observedGeneration": 2,
+            "currentRevision": "mydb-1a2b-7d4f", "updateRevision": "mydb-1a2b-9c1e"
+        }));
+        // New pod created but not ready
+        let starting = statefulset(2, serde_json::json!({
+            "replicas": 1, "readyReplicas": 0, "updatedReplicas": 1, "observedGeneration": 2,
+            "currentRevision": "mydb-1a2b-9c1e", "updateRevision": "mydb-1a2b-9c1e"
+        }));
+        // Status from before the patch was observed
+        let stale = statefulset(3, serde_json::json!({
+            "replicas": 1, "readyReplicas": 1, "updatedReplicas": 1, "observedGeneration": 2,
+            "currentRevision": "mydb-1a2b-9c1e", "updateRevision": "mydb-1a2b-9c1e"
+        }));
+        let done = statefulset(2, serde_json::json!({
+            "replicas": 1, "readyReplicas": 1, "updatedReplicas": 1, "observedGeneration": 2,
+            "currentRevision": "mydb-1a2b-9c1e", "updateRevision": "mydb-1a2b-9c1e"
+        }));
+
+        assert!(!rollout_status(&rolling).complete);
+        assert!(!rollout_status(&starting).complete);
+        assert!(!rollout_status(&stale).complete);
+        assert!(!rollout_status(&statefulset(1, serde_json::json!({ "replicas": 0 }))).complete);
+
+        let output = rollout_status(&done);
+        assert_eq!(output, WaitForRolloutOutput {
+            complete: true,
+            replicas: 1,
+            updated_replicas: 1,
+            ready_replicas: 1,
+            current_revision: Some("mydb-1a2b-9c1e".to_string()),
+            update_revision: Some("mydb-1a2b-9c1e".to_string()),
+        });
+        let json = serde_json::to_string(&output).unwrap();
+        assert_eq!(serde_json::from_str::<WaitForRolloutOutput>(&json).unwrap(), output);
+    }
+}
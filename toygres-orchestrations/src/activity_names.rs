@@ -15,6 +15,17 @@ pub mod activities {
     /// - Creates Service (LoadBalancer or ClusterIP)
     pub const DEPLOY_POSTGRES: &str = "toygres-orchestrations::activity::deploy-postgres";
     
+    /// Check the cluster can run a new instance before deploying it
+    /// 
+    /// **Input:** [`crate::types::PreflightCapacityInput`]  
+    /// **Output:** [`crate::types::PreflightCapacityOutput`]  
+    /// **Idempotent:** Yes (read-only)
+    /// **Operations:**
+    /// - Lists nodes for a Ready, schedulable one with pod capacity
+    /// - Lists StorageClasses for a default class
+    /// - Checks namespace ResourceQuotas for storage and PVC headroom
+    pub const PREFLIGHT_CAPACITY: &str = "toygres-orchestrations::activity::preflight-capacity";
+    
     /// Delete PostgreSQL deployment from Kubernetes
     /// 
     /// **Input:** [`crate::types::DeletePostgresInput`]  
@@ -36,17 +47,57 @@ pub mod activities {
     /// - Timeout after configured duration
     pub const WAIT_FOR_READY: &str = "toygres-orchestrations::activity::wait-for-ready";
     
+    /// Check once whether a StatefulSet change has rolled out
+    /// 
+    /// **Input:** [`crate::types::WaitForRolloutInput`]  
+    /// **Output:** [`crate::types::WaitForRolloutOutput`]  
+    /// **Idempotent:** Yes (read-only)
+    /// **Operations:**
+    /// - Compares the StatefulSet's current and update revisions and replica counts (no polling)
+    pub const WAIT_FOR_ROLLOUT: &str = "toygres-orchestrations::activity::wait-for-rollout";
+    
+    /// Check once whether the instance's LoadBalancer has an external IP
+    /// 
+    /// **Input:** [`crate::types::GetServiceExternalIpInput`]  
+    /// **Output:** [`crate::types::GetServiceExternalIpOutput`]  
+    /// **Idempotent:** Yes (read-only)
+    /// **Operations:**
+    /// - Reads the Service's LoadBalancer ingress IP (no polling)
+    pub const GET_SERVICE_EXTERNAL_IP: &str = "toygres-orchestrations::activity::get-service-external-ip";
+    
     /// Get connection strings for PostgreSQL instance
     /// 
     /// **Input:** [`crate::types::GetConnectionStringsInput`]  
     /// **Output:** [`crate::types::GetConnectionStringsOutput`]  
     /// **Idempotent:** Yes
     /// **Operations:**
-    /// - Gets LoadBalancer external IP
+    /// - Uses the given LoadBalancer external IP (or checks for it once)
     /// - Constructs IP-based connection string
     /// - Constructs DNS-based connection string (if DNS label provided)
+    /// - For internal LoadBalancers, constructs the private-zone connection string
+    ///   instead (if a private DNS zone is configured)
     pub const GET_CONNECTION_STRINGS: &str = "toygres-orchestrations::activity::get-connection-strings";
     
+    /// Register an instance in the Azure Private DNS zone
+    /// 
+    /// **Input:** [`crate::types::RegisterPrivateDnsInput`]  
+    /// **Output:** [`crate::types::RegisterPrivateDnsOutput`]  
+    /// **Idempotent:** Yes (PUT of the A record)
+    /// **Operations:**
+    /// - Gets an ARM token from the managed identity
+    /// - Upserts an A record pointing at the internal LoadBalancer IP
+    pub const REGISTER_PRIVATE_DNS: &str = "toygres-orchestrations::activity::register-private-dns";
+    
+    /// Remove an instance's record from the Azure Private DNS zone
+    /// 
+    /// **Input:** [`crate::types::DeregisterPrivateDnsInput`]  
+    /// **Output:** [`crate::types::DeregisterPrivateDnsOutput`]  
+    /// **Idempotent:** Yes (reports `deleted: false` when the record is gone)
+    /// **Operations:**
+    /// - Gets an ARM token from the managed identity
+    /// - Deletes the instance's A record
+    pub const DEREGISTER_PRIVATE_DNS: &str = "toygres-orchestrations::activity::deregister-private-dns";
+    
     /// Test PostgreSQL connection
     /// 
     /// **Input:** [`crate::types::TestConnectionInput`]  
@@ -66,6 +117,15 @@ pub mod activities {
     /// **Operations:**
     /// - Raises external event to target orchestration
     pub const RAISE_EVENT: &str = "toygres-orchestrations::activity::raise-event";
+    
+    /// Notify the `TOYGRES_WEBHOOK_URL` webhook of a create/delete outcome
+    /// 
+    /// **Input:** [`crate::types::SendNotificationInput`]  
+    /// **Output:** [`crate::types::SendNotificationOutput`]  
+    /// **Idempotent:** No (a retry may notify twice)
+    /// **Operations:**
+    /// - POSTs a JSON payload to the webhook; no-op when none is configured
+    pub const SEND_NOTIFICATION: &str = "toygres-orchestrations::activity::send-notification";
 
     /// CMS-related activities
     pub mod cms {
@@ -78,6 +138,12 @@ pub mod activities {
         /// Free DNS name by prefixing with __deleted_
         pub const FREE_DNS_NAME: &str = "toygres-orchestrations::activity::cms-free-dns-name";
 
+        /// Record the delete orchestration on the instance unless another delete holds it
+        pub const CLAIM_INSTANCE_DELETION: &str = "toygres-orchestrations::activity::cms-claim-instance-deletion";
+
+        /// Clear a failed delete's claim on the instance
+        pub const RELEASE_INSTANCE_DELETION: &str = "toygres-orchestrations::activity::cms-release-instance-deletion";
+
         /// Fetch CMS instance by Kubernetes name
         pub const GET_INSTANCE_BY_K8S_NAME: &str = "toygres-orchestrations::activity::cms-get-instance-by-k8s-name";
 
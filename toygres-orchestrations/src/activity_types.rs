@@ -1,13 +1,15 @@
 //! Input and output types for Toygres activities
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
 // ============================================================================
 // Deploy PostgreSQL Activity
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct DeployPostgresInput {
     /// Kubernetes namespace
     pub namespace: String,
@@ -15,6 +17,9 @@ pub struct DeployPostgresInput {
     pub instance_name: String,
     /// PostgreSQL password
     pub password: String,
+    /// Superuser created by the image (`POSTGRES_USER`; default: "postgres")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
     /// PostgreSQL version (e.g., "16", "18")
     pub postgres_version: String,
     /// Storage size in GB
@@ -23,9 +28,171 @@ pub struct DeployPostgresInput {
     pub use_load_balancer: bool,
     /// Optional DNS label for Azure DNS
     pub dns_label: Option<String>,
+    /// PVC access mode: "ReadWriteOnce" (default) or "ReadWriteOncePod"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_mode: Option<String>,
+    /// Readiness probe timings (default: [`ProbeTimings::readiness`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub readiness_probe: Option<ProbeTimings>,
+    /// Liveness probe timings (default: [`ProbeTimings::liveness`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub liveness_probe: Option<ProbeTimings>,
+    /// Extra Service annotations, e.g. Azure LB health-probe settings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service_annotations: Option<BTreeMap<String, String>>,
+    /// Use an Azure internal LoadBalancer (private IP); requires `use_load_balancer`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub internal_load_balancer: bool,
+    /// Init containers run before Postgres starts, e.g. to set kernel params or pre-create directories
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub init_containers: Option<Vec<InitContainerSpec>>,
+    /// Existing Secret holding the password; when set, `password` is ignored and must be empty
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_secret_ref: Option<String>,
+    /// Pod `securityContext.fsGroup`, so the data volume is group-writable by Postgres
+    /// (default: [`POSTGRES_UID`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fs_group: Option<i64>,
+    /// Pod `securityContext.runAsUser`; init containers inherit it (default: [`POSTGRES_UID`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_as_user: Option<i64>,
+    /// Run a PgBouncer sidecar and expose it on the Service at [`POOLER_PORT`]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub enable_pooler: bool,
+    /// StatefulSet `updateStrategy` (default: none rendered, i.e. Kubernetes'
+    /// `RollingUpdate` with partition 0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub update_strategy: Option<UpdateStrategy>,
+    /// Standby replicas to run next to the primary (default: 0). The StatefulSet runs
+    /// `1 + standby_replicas` pods; above 0 a headless Service gives each pod a stable
+    /// DNS name, the client Service targets only the primary (pod 0), and pods get
+    /// `PRIMARY_CONNINFO` for the primary's headless name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub standby_replicas: Option<u32>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Create options of a [`DeployPostgresInput`] that have no CMS column of their own.
+/// The create stores them with the instance record, so the desired spec of an
+/// existing instance can be rendered as it was created.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct InstanceDeployOptions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_mode: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub readiness_probe: Option<ProbeTimings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub liveness_probe: Option<ProbeTimings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service_annotations: Option<BTreeMap<String, String>>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub internal_load_balancer: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub init_containers: Option<Vec<InitContainerSpec>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fs_group: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_as_user: Option<i64>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub enable_pooler: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub update_strategy: Option<UpdateStrategy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub standby_replicas: Option<u32>,
+}
+
+/// Most standby replicas an instance can be laid out for
+pub const MAX_STANDBY_REPLICAS: u32 = 5;
+
+/// How the StatefulSet rolls out a changed pod template, e.g. a new image
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[serde(tag = "type")]
+pub enum UpdateStrategy {
+    /// Replace pods with an ordinal at or above `partition`, highest first. Pods below
+    /// it keep the old revision until the partition is lowered, so a partition of
+    /// `replicas - 1` canaries the change on one pod.
+    RollingUpdate {
+        #[serde(default)]
+        partition: i32,
+    },
+    /// Replace a pod only when it is deleted
+    OnDelete,
+}
+
+/// uid/gid of the `postgres` user in the official image
+pub const POSTGRES_UID: i64 = 999;
+
+/// Port the PgBouncer sidecar listens on, and the advertised port when it is enabled
+pub const POOLER_PORT: u16 = 6432;
+
+/// An init container added to the Postgres pod; it mounts the data volume at the same path
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct InitContainerSpec {
+    /// Container name (DNS-1123 label)
+    pub name: String,
+    /// Image reference, e.g. "busybox:1.36" or "myregistry.azurecr.io/tools/init@sha256:..."
+    pub image: String,
+    /// Entrypoint override; the image's default entrypoint runs when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub command: Vec<String>,
+}
+
+/// Timings for a `pg_isready` container probe
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct ProbeTimings {
+    pub initial_delay_seconds: i32,
+    pub period_seconds: i32,
+    pub timeout_seconds: i32,
+    pub failure_threshold: i32,
+}
+
+impl ProbeTimings {
+    /// Default readiness probe: check early and often so Ready tracks pg_isready
+    pub fn readiness() -> Self {
+        Self {
+            initial_delay_seconds: 5,
+            period_seconds: 5,
+            timeout_seconds: 3,
+            failure_threshold: 6,
+        }
+    }
+
+    /// Default liveness probe: generous delay so initdb is not killed mid-run
+    pub fn liveness() -> Self {
+        Self {
+            initial_delay_seconds: 30,
+            period_seconds: 10,
+            timeout_seconds: 5,
+            failure_threshold: 6,
+        }
+    }
+    
+    /// Check every timing is in range, and that a check times out before the next
+    /// one is due
+    pub fn validate(&self) -> Result<(), String> {
+        let ranges = [
+            ("initial_delay_seconds", self.initial_delay_seconds, 0, 3600),
+            ("period_seconds", self.period_seconds, 1, 300),
+            ("timeout_seconds", self.timeout_seconds, 1, 300),
+            ("failure_threshold", self.failure_threshold, 1, 100),
+        ];
+        for (field, value, min, max) in ranges {
+            if !(min..=max).contains(&value) {
+                return Err(format!("{} must be between {} and {}, got {}", field, min, max, value));
+            }
+        }
+        if self.timeout_seconds > self.period_seconds {
+            return Err(format!(
+                "timeout_seconds ({}) must not exceed period_seconds ({})",
+                self.timeout_seconds, self.period_seconds
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct DeployPostgresOutput {
     /// Instance name
     pub instance_name: String,
@@ -35,11 +202,33 @@ pub struct DeployPostgresOutput {
     pub created: bool,
 }
 
+// ============================================================================
+// Preflight Capacity Activity
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct PreflightCapacityInput {
+    /// Kubernetes namespace the instance will be created in
+    pub namespace: String,
+    /// Requested PVC size in GB
+    pub storage_size_gb: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct PreflightCapacityOutput {
+    /// Whether the cluster can schedule and bind the instance
+    pub sufficient: bool,
+    /// Ready, schedulable nodes with room for another pod
+    pub schedulable_nodes: usize,
+    /// Why capacity is insufficient (empty when sufficient)
+    pub problems: Vec<String>,
+}
+
 // ============================================================================
 // Delete PostgreSQL Activity
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct DeletePostgresInput {
     /// Kubernetes namespace
     pub namespace: String,
@@ -47,17 +236,131 @@ pub struct DeletePostgresInput {
     pub instance_name: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct DeletePostgresOutput {
     /// Whether resources were deleted (false if didn't exist)
     pub deleted: bool,
 }
 
+// ============================================================================
+// List / Delete Toygres Resources Activities
+// ============================================================================
+
+/// A Kubernetes resource created for a Toygres instance
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct ToygresResource {
+    /// "StatefulSet", "Service" or "PersistentVolumeClaim"
+    pub kind: String,
+    pub name: String,
+    /// Value of the `instance` label (the instance's K8s name)
+    pub instance: String,
+    /// Object UID, so deletion only ever hits the object that was listed
+    pub uid: Option<String>,
+    /// Creation time (Unix millis)
+    pub created_at_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct ListToygresResourcesInput {
+    pub namespace: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct ListToygresResourcesOutput {
+    pub resources: Vec<ToygresResource>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct DeleteResourcesInput {
+    pub namespace: String,
+    pub resources: Vec<ToygresResource>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct DeleteResourcesOutput {
+    /// `Kind/name` of each resource deleted (already-gone ones are skipped)
+    pub deleted: Vec<String>,
+}
+
+// ============================================================================
+// Patch Image Activity
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct PatchImageInput {
+    /// Kubernetes namespace
+    pub namespace: String,
+    /// Instance name (StatefulSet name)
+    pub instance_name: String,
+    /// New image for the postgres container, e.g. "postgres:18.2"
+    pub image: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct PatchImageOutput {
+    /// Image the postgres container had before the patch
+    pub previous_image: Option<String>,
+    /// Whether the StatefulSet was changed (false if it already had the image)
+    pub patched: bool,
+}
+
+// ============================================================================
+// Expose Service Activity
+// ============================================================================
+
+/// Kubernetes Service type an instance is reached through
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+pub enum ServiceType {
+    /// Reachable only inside the cluster
+    #[serde(rename = "ClusterIP")]
+    ClusterIp,
+    /// Public LoadBalancer IP, plus an Azure DNS name when the instance has a DNS label
+    LoadBalancer,
+}
+
+impl ServiceType {
+    /// The Service `spec.type` value
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ServiceType::ClusterIp => "ClusterIP",
+            ServiceType::LoadBalancer => "LoadBalancer",
+        }
+    }
+
+    pub fn is_load_balancer(self) -> bool {
+        self == ServiceType::LoadBalancer
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct ExposeServiceInput {
+    /// Kubernetes namespace
+    pub namespace: String,
+    /// Instance name (the Service is `<instance_name>-svc`)
+    pub instance_name: String,
+    /// Type the Service should have
+    pub service_type: ServiceType,
+    /// DNS label annotation for a Service that has to be recreated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dns_label: Option<String>,
+    /// Expose the PgBouncer port on a Service that has to be recreated
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub enable_pooler: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct ExposeServiceOutput {
+    /// Type the Service had; `None` when it was missing and has been recreated
+    pub previous_type: Option<String>,
+    /// Whether the Service was created or patched (false if it already had the type)
+    pub changed: bool,
+}
+
 // ============================================================================
 // Wait For Ready Activity
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct WaitForReadyInput {
     /// Kubernetes namespace
     pub namespace: String,
@@ -65,9 +368,12 @@ pub struct WaitForReadyInput {
     pub instance_name: String,
     /// Timeout in seconds (0 = no timeout, just check current status)
     pub timeout_seconds: u64,
+    /// Only report ready once the postgres container runs this image (after an image patch)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_image: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct WaitForReadyOutput {
     /// Pod phase (e.g., "Running", "Pending")
     pub pod_phase: String,
@@ -75,11 +381,57 @@ pub struct WaitForReadyOutput {
     pub is_ready: bool,
 }
 
+// ============================================================================
+// Wait For Rollout Activity
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct WaitForRolloutInput {
+    /// Kubernetes namespace
+    pub namespace: String,
+    /// Instance name (StatefulSet name)
+    pub instance_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct WaitForRolloutOutput {
+    /// Every replica runs the update revision and is ready
+    pub complete: bool,
+    /// `spec.replicas`
+    pub replicas: i32,
+    /// `status.updatedReplicas`: pods created from the update revision
+    pub updated_replicas: i32,
+    /// `status.readyReplicas`
+    pub ready_replicas: i32,
+    /// `status.currentRevision`
+    pub current_revision: Option<String>,
+    /// `status.updateRevision`
+    pub update_revision: Option<String>,
+}
+
+// ============================================================================
+// Get Service External IP Activity
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct GetServiceExternalIpInput {
+    /// Kubernetes namespace
+    pub namespace: String,
+    /// Instance name
+    pub instance_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct GetServiceExternalIpOutput {
+    /// LoadBalancer external IP, or `None` while it is still being assigned
+    pub external_ip: Option<String>,
+}
+
 // ============================================================================
 // Get Connection Strings Activity
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct GetConnectionStringsInput {
     /// Kubernetes namespace
     pub namespace: String,
@@ -87,13 +439,22 @@ pub struct GetConnectionStringsInput {
     pub instance_name: String,
     /// PostgreSQL password
     pub password: String,
+    /// User in the connection strings (default: "postgres")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
     /// Whether LoadBalancer was used
     pub use_load_balancer: bool,
     /// DNS label (if used)
     pub dns_label: Option<String>,
+    /// LoadBalancer IP already waited for by the orchestration (checked once when absent)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_ip: Option<String>,
+    /// Whether a PgBouncer sidecar was deployed; connection strings then use [`POOLER_PORT`]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub enable_pooler: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct GetConnectionStringsOutput {
     /// IP-based connection string
     pub ip_connection_string: String,
@@ -103,31 +464,147 @@ pub struct GetConnectionStringsOutput {
     pub external_ip: Option<String>,
     /// Azure DNS name (if DNS label provided)
     pub dns_name: Option<String>,
+    /// Name in the Azure Private DNS zone, for internal LoadBalancers when a zone is
+    /// configured (see [`crate::activities::register_private_dns`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_dns_name: Option<String>,
+    /// Connection string using [`Self::private_dns_name`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_connection_string: Option<String>,
+}
+
+// ============================================================================
+// Register Private DNS Activity
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct RegisterPrivateDnsInput {
+    /// Record name within the zone (the instance's DNS label)
+    pub record_name: String,
+    /// Private IP of the internal LoadBalancer
+    pub ip_address: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct RegisterPrivateDnsOutput {
+    /// Fully qualified name of the A record
+    pub fqdn: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct DeregisterPrivateDnsInput {
+    /// Record name within the zone (the instance's DNS label)
+    pub record_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct DeregisterPrivateDnsOutput {
+    /// Fully qualified name of the A record
+    pub fqdn: String,
+    /// Whether the record existed
+    pub deleted: bool,
 }
 
 // ============================================================================
 // Test Connection Activity
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct TestConnectionInput {
     /// Connection string to test
     pub connection_string: String,
+    /// Secret to read the password from when the connection string omits it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_secret: Option<PasswordSecretRef>,
+    /// Custom read-only health query, run after the version query
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health_sql: Option<String>,
+}
+
+/// A Kubernetes Secret holding an instance password under [`crate::k8s_client::PASSWORD_SECRET_KEY`]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct PasswordSecretRef {
+    pub namespace: String,
+    pub name: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct TestConnectionOutput {
     /// PostgreSQL version string
     pub version: String,
     /// Whether connection succeeded
     pub connected: bool,
+    /// How far WAL replay trails the primary; `None` when the server is not a replica
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replication_lag_ms: Option<i64>,
+    /// Size of the connected database (`pg_database_size`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub database_size_bytes: Option<i64>,
+    /// Client backends connected to the server, including the check itself
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connection_count: Option<i32>,
+    /// Size of all databases on the server, i.e. what the data volume holds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage_used_bytes: Option<i64>,
+}
+
+// ============================================================================
+// Verify PostgreSQL Version Activity
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct VerifyPostgresVersionInput {
+    /// `postgres_version` the instance was created with, e.g. "18" or "17.6"
+    pub requested_version: String,
+    /// What the server reported from `SELECT version()`
+    pub server_version: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct VerifyPostgresVersionOutput {
+    pub requested_major: u32,
+    /// `None` when the reported version could not be parsed
+    pub server_major: Option<u32>,
+    /// Whether the server runs the requested major version
+    pub matches: bool,
+}
+
+// ============================================================================
+// Run Maintenance Activity
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct RunMaintenanceInput {
+    /// Connection string of the instance
+    pub connection_string: String,
+    /// Secret to read the password from when the connection string omits it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_secret: Option<PasswordSecretRef>,
+    /// Run VACUUM FULL, which rewrites each table under an exclusive lock (default: false)
+    #[serde(default)]
+    pub full: bool,
+    /// Tables to process as `table` or `schema.table` (default: all user tables)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tables: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct RunMaintenanceOutput {
+    /// Command run against each table, e.g. "VACUUM (ANALYZE)"
+    pub command: String,
+    /// Number of tables processed
+    pub tables_processed: usize,
+    /// Qualified names of the processed tables
+    pub tables: Vec<String>,
+    /// Wall-clock time for the whole run
+    pub duration_ms: u64,
 }
 
 // ============================================================================
 // CMS Activities
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct CreateInstanceRecordInput {
     pub user_name: String,
     pub k8s_name: String,
@@ -137,14 +614,25 @@ pub struct CreateInstanceRecordInput {
     pub use_load_balancer: bool,
     pub dns_name: Option<String>,
     pub orchestration_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_secret_ref: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<BTreeMap<String, serde_json::Value>>,
+    /// Version of the create orchestration writing the record; defaults to the version
+    /// of the orchestration that scheduled the activity
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub orchestration_version: Option<String>,
+    /// Deploy options without a column of their own, stored as `deploy_options`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deploy_options: Option<InstanceDeployOptions>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct CreateInstanceRecordOutput {
     pub instance_id: Uuid,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct UpdateInstanceStateInput {
     pub k8s_name: String,
     pub state: String,
@@ -153,30 +641,64 @@ pub struct UpdateInstanceStateInput {
     pub external_ip: Option<String>,
     pub delete_orchestration_id: Option<String>,
     pub message: Option<String>,
+    /// Connection string via the Azure Private DNS zone (internal LoadBalancers)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_connection_string: Option<String>,
+    /// Stored in `instance_events.metadata` when the state changes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct UpdateInstanceStateOutput {
     pub updated: bool,
     pub previous_state: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct FreeDnsNameInput {
     pub k8s_name: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct FreeDnsNameOutput {
     pub freed: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct ClaimInstanceDeletionInput {
+    pub k8s_name: String,
+    /// The delete orchestration claiming the instance
+    pub orchestration_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct ClaimInstanceDeletionOutput {
+    /// The instance is now (or was already) claimed by this orchestration
+    pub claimed: bool,
+    /// Another delete orchestration that holds the instance, when not claimed
+    pub held_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct ReleaseInstanceDeletionInput {
+    pub k8s_name: String,
+    /// The delete orchestration giving up its claim
+    pub orchestration_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct ReleaseInstanceDeletionOutput {
+    /// The claim was held by this orchestration and is now cleared
+    pub released: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct GetInstanceByK8sNameInput {
     pub k8s_name: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct CmsInstanceRecord {
     pub id: Uuid,
     pub user_name: String,
@@ -184,9 +706,15 @@ pub struct CmsInstanceRecord {
     pub namespace: String,
     pub state: String,
     pub dns_name: Option<String>,
+    #[serde(default)]
+    pub postgres_version: Option<String>,
+    /// The instance has a record in the private DNS zone (its private connection
+    /// string is set)
+    #[serde(default)]
+    pub private_dns: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct GetInstanceByK8sNameOutput {
     pub found: bool,
     pub record: Option<CmsInstanceRecord>,
@@ -197,32 +725,108 @@ pub struct GetInstanceByK8sNameOutput {
 // Get Instance Connection Activity (CMS)
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct GetInstanceConnectionInput {
     pub k8s_name: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct GetInstanceConnectionOutput {
     pub found: bool,
     pub connection_string: Option<String>,
     pub state: Option<String>,
+    /// Secret holding the password when the connection string omits it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_secret_ref: Option<String>,
+    /// Fleet-wide maintenance mode is on; health checks are paused
+    #[serde(default)]
+    pub maintenance_mode: bool,
+    /// LoadBalancer IP recorded at creation; `None` without a LoadBalancer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_ip: Option<String>,
+    /// Provisioned volume size
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage_size_gb: Option<i32>,
+    /// Name of the instance's record in the private DNS zone; `None` unless it was
+    /// registered there
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_dns_record: Option<String>,
 }
 
 // ============================================================================
 // Record Health Check Activity (CMS)
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Why a health check failed or was skipped, so failures can be grouped without
+/// parsing error text
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthReason {
+    /// Nothing listens on the Postgres port
+    ConnectionRefused,
+    /// Postgres rejected the credentials, e.g. after a password rotation
+    AuthFailed,
+    /// Connecting or querying did not finish in time
+    Timeout,
+    /// The host name in the connection string does not resolve
+    DnsFailure,
+    /// The instance pod is missing or not ready
+    PodNotRunning,
+    /// Any other failure; see the error message
+    Other,
+    /// Skipped: the instance has no connection string yet (`provisioning`)
+    AwaitingConnectionInfo,
+    /// A replica answered, but trails the primary by more than the allowed lag
+    ReplicationLag,
+    /// Skipped: maintenance mode is on (`unknown`)
+    Maintenance,
+    /// Connected, but the instance's custom health query failed
+    HealthQueryFailed,
+}
+
+impl HealthReason {
+    /// Value stored in `instance_health_checks.health_reason`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HealthReason::ConnectionRefused => "connection_refused",
+            HealthReason::AuthFailed => "auth_failed",
+            HealthReason::Timeout => "timeout",
+            HealthReason::DnsFailure => "dns_failure",
+            HealthReason::PodNotRunning => "pod_not_running",
+            HealthReason::Other => "other",
+            HealthReason::AwaitingConnectionInfo => "awaiting_connection_info",
+            HealthReason::ReplicationLag => "replication_lag",
+            HealthReason::Maintenance => "maintenance",
+            HealthReason::HealthQueryFailed => "health_query_failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct RecordHealthCheckInput {
     pub k8s_name: String,
-    pub status: String,  // "healthy", "unhealthy", "unknown"
+    pub status: String,  // "healthy", "degraded", "unhealthy", "unknown"
     pub postgres_version: Option<String>,
     pub response_time_ms: Option<i32>,
     pub error_message: Option<String>,
+    /// Classification of `error_message` for failed checks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health_reason: Option<HealthReason>,
+    /// Replay lag reported by a replica (`None` for primaries)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replication_lag_ms: Option<i64>,
+    /// Database size seen by a successful check
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub database_size_bytes: Option<i64>,
+    /// Client connection count seen by a successful check
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connection_count: Option<i32>,
+    /// Share of the provisioned volume in use (0-100), seen by a successful check
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage_pct: Option<f64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct RecordHealthCheckOutput {
     pub recorded: bool,
     pub check_id: i64,
@@ -232,28 +836,31 @@ pub struct RecordHealthCheckOutput {
 // Update Instance Health Activity (CMS)
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct UpdateInstanceHealthInput {
     pub k8s_name: String,
-    pub health_status: String,  // "healthy", "unhealthy", "unknown"
+    pub health_status: String,  // "healthy", "degraded", "unhealthy", "provisioning", "unmonitored", "unknown"
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct UpdateInstanceHealthOutput {
     pub updated: bool,
+    /// Health status before this update (when a running instance was found)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_health_status: Option<String>,
 }
 
 // ============================================================================
 // Record Instance Actor Activity (CMS)
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct RecordInstanceActorInput {
     pub k8s_name: String,
     pub instance_actor_orchestration_id: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct RecordInstanceActorOutput {
     pub recorded: bool,
 }
@@ -262,21 +869,108 @@ pub struct RecordInstanceActorOutput {
 // Delete Instance Record Activity (CMS)
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct DeleteInstanceRecordInput {
     pub k8s_name: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct DeleteInstanceRecordOutput {
     pub deleted: bool,
 }
 
+// ============================================================================
+// Update Postgres Version Activity (CMS)
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct UpdatePostgresVersionInput {
+    pub k8s_name: String,
+    pub postgres_version: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct UpdatePostgresVersionOutput {
+    pub updated: bool,
+}
+
+// ============================================================================
+// Record Instance Event Activity (CMS)
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct RecordInstanceEventInput {
+    pub k8s_name: String,
+    /// `instance_events.event_type`, e.g. "storage_near_full"
+    pub event_type: String,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct RecordInstanceEventOutput {
+    pub recorded: bool,
+}
+
+// ============================================================================
+// Update Exposure Activity (CMS)
+// ============================================================================
+
+/// Connection info after an instance's Service type changed; unlike
+/// [`UpdateInstanceStateInput`], `None` clears the stored value
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct UpdateExposureInput {
+    pub k8s_name: String,
+    pub use_load_balancer: bool,
+    pub external_ip: Option<String>,
+    pub ip_connection_string: String,
+    pub dns_connection_string: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct UpdateExposureOutput {
+    pub updated: bool,
+}
+
+// ============================================================================
+// Update External IP Activity (CMS)
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct UpdateExternalIpInput {
+    pub k8s_name: String,
+    /// IP the CMS is expected to hold; the update is skipped if it holds another
+    pub old_ip: String,
+    /// IP the Service now has
+    pub new_ip: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct UpdateExternalIpOutput {
+    pub updated: bool,
+}
+
+// ============================================================================
+// List Live Instances Activity (CMS)
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct ListLiveInstancesInput {
+    pub namespace: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct ListLiveInstancesOutput {
+    /// K8s names of instances that are not `deleted` or `failed`
+    pub k8s_names: Vec<String>,
+}
+
 // ============================================================================
 // Raise Event Activity
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct RaiseEventInput {
     /// Target orchestration instance ID
     pub instance_id: String,
@@ -284,11 +978,47 @@ pub struct RaiseEventInput {
     pub event_name: String,
     /// Event data (JSON string)
     pub event_data: String,
+    /// Raise the event at most once per key: a retried activity whose key was already
+    /// raised skips it (recorded in `toygres_cms.raised_events`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct RaiseEventOutput {
-    /// Whether the event was raised successfully
+    /// Whether the event was raised; false when skipped as a duplicate of its key
     pub raised: bool,
 }
 
+
+// ============================================================================
+// Send Notification Activity
+// ============================================================================
+
+/// Terminal outcome of a create or delete orchestration
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationOutcome {
+    Created,
+    CreateFailed,
+    Deleted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct SendNotificationInput {
+    /// Instance (k8s) name
+    pub instance_name: String,
+    pub orchestration_id: String,
+    pub outcome: NotificationOutcome,
+    /// Time from the orchestration's start to the outcome
+    pub duration_seconds: u64,
+    /// The error, for failures
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct SendNotificationOutput {
+    /// False when no webhook is configured
+    pub sent: bool,
+}
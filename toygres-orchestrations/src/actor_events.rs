@@ -0,0 +1,168 @@
+//! Typed events for the instance actor
+//!
+//! Duroxide external events are a name plus a string payload. Senders and the actor
+//! used to agree on those by convention (`"InstanceDeleted"` with `"{}"`); [`ActorEvent`]
+//! is the single definition of both sides, so a typo no longer compiles into an event
+//! the actor silently never receives.
+
+use duroxide::{DurableOutput, OrchestrationContext};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::activities;
+use crate::activity_types::{RaiseEventInput, RaiseEventOutput};
+use crate::names::events;
+use crate::trace::TraceLevel;
+
+/// Actor settings that can be changed while it runs; unset fields keep their value
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InstanceActorConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slow_threshold_ms: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_level: Option<TraceLevel>,
+    /// Custom health query; see [`crate::types::InstanceActorInput::health_sql`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health_sql: Option<String>,
+}
+
+/// An event the instance actor understands
+#[derive(Debug, Clone, PartialEq)]
+pub enum ActorEvent {
+    /// The instance is being deleted; the actor stops
+    InstanceDeleted,
+    /// Apply new settings from the next iteration on
+    UpdateConfig(InstanceActorConfig),
+    /// Take a backup now instead of waiting for the schedule
+    TriggerBackup,
+    /// Run the next health check now
+    CheckNow,
+    /// Stop the actor without deleting the instance
+    Cancel,
+}
+
+impl ActorEvent {
+    /// Every event name the actor subscribes to
+    pub const NAMES: [&'static str; 5] = [
+        events::INSTANCE_DELETED,
+        events::UPDATE_CONFIG,
+        events::TRIGGER_BACKUP,
+        events::CHECK_NOW,
+        events::CANCEL,
+    ];
+
+    /// Duroxide event name
+    pub fn name(&self) -> &'static str {
+        match self {
+            ActorEvent::InstanceDeleted => events::INSTANCE_DELETED,
+            ActorEvent::UpdateConfig(_) => events::UPDATE_CONFIG,
+            ActorEvent::TriggerBackup => events::TRIGGER_BACKUP,
+            ActorEvent::CheckNow => events::CHECK_NOW,
+            ActorEvent::Cancel => events::CANCEL,
+        }
+    }
+
+    /// Event payload (JSON); `"{}"` for events without one
+    pub fn data(&self) -> String {
+        match self {
+            ActorEvent::UpdateConfig(config) => {
+                serde_json::to_string(config).unwrap_or_else(|_| "{}".to_string())
+            }
+            _ => "{}".to_string(),
+        }
+    }
+
+    /// Rebuild an event from its name and payload
+    pub fn decode(name: &str, data: &str) -> Result<Self, String> {
+        match name {
+            events::INSTANCE_DELETED => Ok(ActorEvent::InstanceDeleted),
+            events::UPDATE_CONFIG => serde_json::from_str(data)
+                .map(ActorEvent::UpdateConfig)
+                .map_err(|e| format!("Invalid {} payload: {}", name, e)),
+            events::TRIGGER_BACKUP => Ok(ActorEvent::TriggerBackup),
+            events::CHECK_NOW => Ok(ActorEvent::CheckNow),
+            events::CANCEL => Ok(ActorEvent::Cancel),
+            other => Err(format!("Unknown actor event '{}'", other)),
+        }
+    }
+
+    /// Input for the raise-event activity delivering this event to `actor_id`
+    pub fn to_raise_input(&self, actor_id: &str) -> RaiseEventInput {
+        RaiseEventInput {
+            instance_id: actor_id.to_string(),
+            event_name: self.name().to_string(),
+            event_data: self.data(),
+            idempotency_key: None,
+        }
+    }
+}
+
+/// Send `event` to the actor `actor_id` from an orchestration
+pub async fn raise_actor_event(
+    ctx: &OrchestrationContext,
+    actor_id: &str,
+    event: ActorEvent,
+) -> Result<RaiseEventOutput, String> {
+    ctx.schedule_activity_typed::<RaiseEventInput, RaiseEventOutput>(
+        activities::raise_event::NAME,
+        &event.to_raise_input(actor_id),
+    )
+    .into_activity_typed::<RaiseEventOutput>()
+    .await
+}
+
+/// Wait up to `timeout` for the next actor event.
+///
+/// Returns `None` when the timer wins, or `Some(Err(..))` for an event whose payload
+/// could not be decoded.
+pub async fn wait_for_actor_event(
+    ctx: &OrchestrationContext,
+    timeout: Duration,
+) -> Option<Result<ActorEvent, String>> {
+    let mut futures = vec![ctx.schedule_timer(timeout)];
+    futures.extend(ActorEvent::NAMES.iter().map(|name| ctx.schedule_wait(*name)));
+
+    let (winner_index, output) = ctx.select(futures).await;
+    match (winner_index, output) {
+        (0, _) => None,
+        (i, DurableOutput::External(data)) => Some(ActorEvent::decode(ActorEvent::NAMES[i - 1], &data)),
+        (i, other) => Some(Err(format!("Unexpected output for {}: {:?}", ActorEvent::NAMES[i - 1], other))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_round_trip_through_name_and_data() {
+        let events = vec![
+            ActorEvent::InstanceDeleted,
+            ActorEvent::UpdateConfig(InstanceActorConfig {
+                slow_threshold_ms: Some(250),
+                trace_level: Some(TraceLevel::Warn),
+                health_sql: Some("SELECT 1 FROM orders LIMIT 1".to_string()),
+            }),
+            ActorEvent::UpdateConfig(InstanceActorConfig::default()),
+            ActorEvent::TriggerBackup,
+            ActorEvent::CheckNow,
+            ActorEvent::Cancel,
+        ];
+
+        for event in events {
+            let raised = event.to_raise_input("actor-db-1");
+            assert_eq!(raised.instance_id, "actor-db-1");
+            assert!(ActorEvent::NAMES.contains(&raised.event_name.as_str()));
+            assert_eq!(ActorEvent::decode(&raised.event_name, &raised.event_data), Ok(event));
+        }
+
+        // The wire format the delete orchestration has always sent still decodes
+        assert_eq!(ActorEvent::decode("InstanceDeleted", "{}"), Ok(ActorEvent::InstanceDeleted));
+    }
+
+    #[test]
+    fn test_decode_rejects_unknown_names_and_bad_payloads() {
+        assert!(ActorEvent::decode("InstanceDeletd", "{}").unwrap_err().contains("Unknown"));
+        assert!(ActorEvent::decode(events::UPDATE_CONFIG, "not json").unwrap_err().contains("Invalid"));
+    }
+}
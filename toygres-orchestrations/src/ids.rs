@@ -0,0 +1,175 @@
+//! Orchestration instance ids
+//!
+//! An instance's orchestrations run under `<kind>-<k8s_name>`. Kinds that run more
+//! than once per instance (maintenance, expose, restarted actors, recreated creates)
+//! append `.<run>` with a random run suffix. K8s names are DNS labels and never contain
+//! a '.', so [`parse`] gets the kind and k8s name back even when the name is hyphenated.
+//!
+//! Ids written before this scheme joined the run suffix with a '-'
+//! (`<kind>-<k8s_name>-<run>`). [`parse`] strips it from maintenance and expose ids,
+//! which always had one, and from actor ids that end in two suffixes of one length
+//! (the k8s name's and the run's), so older runs still belong to their instance.
+
+/// What an orchestration id was made for; the id starts with `<prefix>-`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdKind {
+    Create,
+    Delete,
+    Actor,
+    /// Deletion of a failed create's partial resources
+    Cleanup,
+    Maintenance,
+    Expose,
+}
+
+impl IdKind {
+    pub const ALL: [IdKind; 6] = [
+        IdKind::Create,
+        IdKind::Delete,
+        IdKind::Actor,
+        IdKind::Cleanup,
+        IdKind::Maintenance,
+        IdKind::Expose,
+    ];
+
+    pub fn prefix(self) -> &'static str {
+        match self {
+            IdKind::Create => "create",
+            IdKind::Delete => "delete",
+            IdKind::Actor => "actor",
+            IdKind::Cleanup => "cleanup",
+            IdKind::Maintenance => "maintenance",
+            IdKind::Expose => "expose",
+        }
+    }
+}
+
+/// Separates the k8s name from a run suffix; not valid in a DNS label
+const RUN_SEPARATOR: char = '.';
+
+/// The id of the instance's create orchestration
+pub fn create(k8s_name: &str) -> String {
+    format!("{}-{}", IdKind::Create.prefix(), k8s_name)
+}
+
+/// The id of the instance's delete orchestration
+pub fn delete(k8s_name: &str) -> String {
+    format!("{}-{}", IdKind::Delete.prefix(), k8s_name)
+}
+
+/// The id the create orchestration starts the instance actor under
+pub fn actor(k8s_name: &str) -> String {
+    format!("{}-{}", IdKind::Actor.prefix(), k8s_name)
+}
+
+/// The id of the delete started to clean up after a failed create
+pub fn cleanup(k8s_name: &str) -> String {
+    format!("{}-{}", IdKind::Cleanup.prefix(), k8s_name)
+}
+
+/// A fresh id for another run of `kind` on the instance
+pub fn run(kind: IdKind, k8s_name: &str) -> String {
+    format!("{}-{}{}{}", kind.prefix(), k8s_name, RUN_SEPARATOR, toygres_models::generate_instance_suffix())
+}
+
+/// The kind and k8s name of an id made by this module, or `None` for other ids
+/// (e.g. fleet-wide sweeps)
+pub fn parse(id: &str) -> Option<(IdKind, &str)> {
+    IdKind::ALL.into_iter().find_map(|kind| {
+        let rest = id.strip_prefix(kind.prefix())?.strip_prefix('-')?;
+        let k8s_name = match rest.split_once(RUN_SEPARATOR) {
+            Some((name, _)) => name,
+            None => legacy_k8s_name(kind, rest),
+        };
+        (!k8s_name.is_empty()).then_some((kind, k8s_name))
+    })
+}
+
+/// The k8s name in `rest` of a `<kind>-<rest>` id without a '.' run suffix
+fn legacy_k8s_name(kind: IdKind, rest: &str) -> &str {
+    let Some((name, run)) = rest.rsplit_once('-') else {
+        return rest;
+    };
+    let suffixed = match kind {
+        IdKind::Maintenance | IdKind::Expose => true,
+        // Create-time actors had no run suffix, and their k8s name ends in a suffix too
+        IdKind::Actor => {
+            is_suffix(run)
+                && name.rsplit_once('-').is_some_and(|(_, suffix)| is_suffix(suffix) && suffix.len() == run.len())
+        }
+        _ => false,
+    };
+    if suffixed { name } else { rest }
+}
+
+/// Whether `value` could be a generated suffix (lowercase hex)
+fn is_suffix(value: &str) -> bool {
+    !value.is_empty() && value.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+/// Whether `id` belongs to the instance `name`, given either as its k8s name or as
+/// the user name the k8s name was generated from (`<user_name>-<suffix>`)
+pub fn belongs_to(id: &str, name: &str) -> bool {
+    parse(id).is_some_and(|(_, k8s_name)| {
+        k8s_name == name || k8s_name.rsplit_once('-').is_some_and(|(user_name, _)| user_name == name)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hyphenated_names_round_trip() {
+        for k8s_name in ["mydb-1a2b3c4d", "my-app-db-1a2b3c4d", "db"] {
+            assert_eq!(parse(&create(k8s_name)), Some((IdKind::Create, k8s_name)));
+            assert_eq!(parse(&delete(k8s_name)), Some((IdKind::Delete, k8s_name)));
+            assert_eq!(parse(&actor(k8s_name)), Some((IdKind::Actor, k8s_name)));
+            assert_eq!(parse(&cleanup(k8s_name)), Some((IdKind::Cleanup, k8s_name)));
+            for kind in IdKind::ALL {
+                let id = run(kind, k8s_name);
+                assert_ne!(id, run(kind, k8s_name));
+                assert_eq!(parse(&id), Some((kind, k8s_name)), "{}", id);
+            }
+        }
+
+        // Prefixes must be followed by the separator; unrelated ids do not parse
+        assert_eq!(parse("created-mydb"), None);
+        assert_eq!(parse("gc-orphans-1a2b3c4d"), None);
+        assert_eq!(parse("create-"), None);
+    }
+
+    #[test]
+    fn test_ids_match_their_instance_only() {
+        let id = run(IdKind::Maintenance, "my-db-1a2b3c4d");
+        assert!(belongs_to(&id, "my-db"));
+        assert!(belongs_to(&id, "my-db-1a2b3c4d"));
+        assert!(belongs_to(&create("my-db-1a2b3c4d"), "my-db"));
+
+        // Substrings of the name, or a name that merely contains it, do not match
+        assert!(!belongs_to(&id, "my"));
+        assert!(!belongs_to(&id, "db"));
+        assert!(!belongs_to(&create("mydb2-1a2b3c4d"), "mydb"));
+        assert!(!belongs_to("gc-orphans-1a2b3c4d", "orphans"));
+    }
+
+    #[test]
+    fn test_legacy_run_ids_parse_to_their_instance() {
+        let cases = [
+            ("maintenance-my-db-1a2b3c4d-5e6f7a8b", IdKind::Maintenance, "my-db-1a2b3c4d"),
+            ("expose-mydb-1a2b3c4d-5e6f7a8b", IdKind::Expose, "mydb-1a2b3c4d"),
+            ("actor-my-db-1a2b3c4d-5e6f7a8b", IdKind::Actor, "my-db-1a2b3c4d"),
+            // Create-time actors never had a run suffix, even when the name ends in hex
+            ("actor-mydb-1a2b3c4d", IdKind::Actor, "mydb-1a2b3c4d"),
+            ("actor-app-db-1a2b3c4d", IdKind::Actor, "app-db-1a2b3c4d"),
+            ("create-mydb-1a2b3c4d", IdKind::Create, "mydb-1a2b3c4d"),
+        ];
+        for (id, kind, k8s_name) in cases {
+            assert_eq!(parse(id), Some((kind, k8s_name)), "{}", id);
+        }
+
+        assert!(belongs_to("maintenance-my-db-1a2b3c4d-5e6f7a8b", "my-db"));
+        assert!(belongs_to("actor-mydb-1a2b3c4d-5e6f7a8b", "mydb"));
+        assert!(!belongs_to("expose-mydb-1a2b3c4d-5e6f7a8b", "mydb-1a2b3c4d-5e6f7a8b"));
+    }
+}
@@ -2,8 +2,15 @@
 
 use anyhow::{Context, Result};
 use k8s_openapi::api::apps::v1::StatefulSet;
-use k8s_openapi::api::core::v1::{Node, PersistentVolumeClaim, Service};
-use kube::{api::Api, Client};
+use k8s_openapi::api::core::v1::{Node, PersistentVolumeClaim, Pod, Secret, Service};
+use kube::config::{KubeConfigOptions, Kubeconfig};
+use kube::{api::Api, Client, Config};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+
+/// Key within a referenced password Secret that holds the Postgres password
+pub const PASSWORD_SECRET_KEY: &str = "password";
 
 /// Get a Kubernetes client
 pub async fn get_k8s_client() -> Result<Client> {
@@ -12,6 +19,44 @@ pub async fn get_k8s_client() -> Result<Client> {
         .context("Failed to create Kubernetes client")
 }
 
+/// Clients for named kubeconfig contexts, built on first use
+static CONTEXT_CLIENTS: LazyLock<Mutex<HashMap<String, Client>>> = LazyLock::new(Mutex::default);
+
+/// Get a client for the cluster of kubeconfig context `context`, or the default
+/// client ([`get_k8s_client`]) for `None`. One Toygres can manage instances across
+/// clusters this way; each context's client is cached.
+pub async fn get_k8s_client_for(context: Option<&str>) -> Result<Client> {
+    let Some(context) = context else {
+        return get_k8s_client().await;
+    };
+    if let Some(client) = CONTEXT_CLIENTS.lock().unwrap().get(context) {
+        return Ok(client.clone());
+    }
+    
+    let kubeconfig = Kubeconfig::read().context("Failed to read kubeconfig")?;
+    let config = config_for_context(kubeconfig, context).await?;
+    let client = Client::try_from(config)
+        .with_context(|| format!("Failed to create Kubernetes client for context '{}'", context))?;
+    
+    CONTEXT_CLIENTS.lock().unwrap().insert(context.to_string(), client.clone());
+    Ok(client)
+}
+
+/// The client configuration of context `context` in `kubeconfig`
+pub async fn config_for_context(kubeconfig: Kubeconfig, context: &str) -> Result<Config> {
+    if !kubeconfig.contexts.iter().any(|c| c.name == context) {
+        anyhow::bail!("Kubeconfig has no context '{}'", context);
+    }
+    
+    let options = KubeConfigOptions {
+        context: Some(context.to_string()),
+        ..Default::default()
+    };
+    Config::from_custom_kubeconfig(kubeconfig, &options)
+        .await
+        .with_context(|| format!("Invalid kubeconfig context '{}'", context))
+}
+
 /// Check if PostgreSQL resources exist for an instance
 pub async fn check_resources_exist(
     client: &Client,
@@ -27,6 +72,32 @@ pub async fn check_resources_exist(
     }
 }
 
+/// Read the Postgres password from a pre-existing Secret's [`PASSWORD_SECRET_KEY`]
+pub async fn read_secret_password(
+    client: &Client,
+    namespace: &str,
+    secret_name: &str,
+) -> Result<String> {
+    let secrets: Api<Secret> = Api::namespaced(client.clone(), namespace);
+    
+    let secret = match secrets.get(secret_name).await {
+        Ok(secret) => secret,
+        Err(kube::Error::Api(response)) if response.code == 404 => {
+            anyhow::bail!("Secret '{}' not found in namespace '{}'", secret_name, namespace)
+        }
+        Err(e) => return Err(anyhow::anyhow!("Failed to read Secret '{}': {}", secret_name, e)),
+    };
+    
+    let bytes = secret
+        .data
+        .as_ref()
+        .and_then(|data| data.get(PASSWORD_SECRET_KEY))
+        .ok_or_else(|| anyhow::anyhow!("Secret '{}' has no '{}' key", secret_name, PASSWORD_SECRET_KEY))?;
+    
+    String::from_utf8(bytes.0.clone())
+        .with_context(|| format!("Secret '{}' key '{}' is not valid UTF-8", secret_name, PASSWORD_SECRET_KEY))
+}
+
 /// Get Azure region from node labels
 pub async fn get_azure_region(client: &Client) -> Result<String> {
     let nodes: Api<Node> = Api::all(client.clone());
@@ -78,3 +149,161 @@ pub async fn pvc_exists(
     }
 }
 
+/// Point-in-time status of an instance's pod and StatefulSet, as seen by Kubernetes
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LiveStatus {
+    /// Pod phase ("Running", "Pending", ...), or `None` when the pod does not exist
+    pub pod_phase: Option<String>,
+    /// Whether the pod's Ready condition is true
+    pub pod_ready: bool,
+    /// Restart count of the postgres container
+    pub restart_count: i32,
+    /// Reason the postgres container is waiting, e.g. "CrashLoopBackOff"
+    pub waiting_reason: Option<String>,
+    /// StatefulSet ready replicas / desired replicas
+    pub ready_replicas: i32,
+    pub replicas: i32,
+}
+
+impl LiveStatus {
+    /// Summarize the instance pod (`<name>-0`) and its StatefulSet; either may be missing
+    pub fn from_resources(pod: Option<&Pod>, statefulset: Option<&StatefulSet>) -> Self {
+        let mut live = LiveStatus::default();
+        
+        if let Some(status) = pod.and_then(|p| p.status.as_ref()) {
+            live.pod_phase = Some(status.phase.clone().unwrap_or_else(|| "Unknown".to_string()));
+            live.pod_ready = status.conditions.iter().flatten()
+                .any(|c| c.type_ == "Ready" && c.status == "True");
+            if let Some(container) = status.container_statuses.iter().flatten().find(|c| c.name == "postgres") {
+                live.restart_count = container.restart_count;
+                live.waiting_reason = container.state.as_ref()
+                    .and_then(|s| s.waiting.as_ref())
+                    .and_then(|w| w.reason.clone());
+            }
+        }
+        
+        if let Some(sts) = statefulset {
+            live.replicas = sts.spec.as_ref().and_then(|s| s.replicas).unwrap_or(1);
+            live.ready_replicas = sts.status.as_ref().and_then(|s| s.ready_replicas).unwrap_or(0);
+        }
+        
+        live
+    }
+}
+
+/// Fetch the live pod and StatefulSet status for an instance
+pub async fn live_status(
+    client: &Client,
+    namespace: &str,
+    instance_name: &str,
+) -> Result<LiveStatus> {
+    let pods: Api<Pod> = Api::namespaced(client.clone(), namespace);
+    let statefulsets: Api<StatefulSet> = Api::namespaced(client.clone(), namespace);
+    
+    // Pod name is <k8s_name>-0 for StatefulSet
+    let pod = pods.get_opt(&format!("{}-0", instance_name)).await
+        .context("Failed to get pod")?;
+    let statefulset = statefulsets.get_opt(instance_name).await
+        .context("Failed to get StatefulSet")?;
+    
+    Ok(LiveStatus::from_resources(pod.as_ref(), statefulset.as_ref()))
+}
+
+/// An instance's PVC, StatefulSet and Service as they exist in the cluster
+#[derive(Debug, Clone, Default)]
+pub struct LiveResources {
+    pub pvc: Option<PersistentVolumeClaim>,
+    pub statefulset: Option<StatefulSet>,
+    pub service: Option<Service>,
+}
+
+/// Fetch the resources `deploy_postgres` creates for an instance; missing ones are `None`
+pub async fn live_resources(
+    client: &Client,
+    namespace: &str,
+    instance_name: &str,
+) -> Result<LiveResources> {
+    let pvcs: Api<PersistentVolumeClaim> = Api::namespaced(client.clone(), namespace);
+    let statefulsets: Api<StatefulSet> = Api::namespaced(client.clone(), namespace);
+    let services: Api<Service> = Api::namespaced(client.clone(), namespace);
+    
+    Ok(LiveResources {
+        pvc: pvcs.get_opt(&format!("{}-pvc", instance_name)).await
+            .context("Failed to get PVC")?,
+        statefulset: statefulsets.get_opt(instance_name).await
+            .context("Failed to get StatefulSet")?,
+        service: services.get_opt(&format!("{}-svc", instance_name)).await
+            .context("Failed to get Service")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_live_status_reports_crash_loop() {
+        let pod: Pod = serde_json::from_value(serde_json::json!({
+            "metadata": { "name": "mydb-1a2b-0" },
+            "status": {
+                "phase": "Running",
+                "conditions": [{ "type": "Ready", "status": "False" }],
+                "containerStatuses": [{
+                    "name": "postgres",
+                    "image": "postgres:18",
+                    "imageID": "",
+                    "ready": false,
+                    "restartCount": 7,
+                    "state": { "waiting": { "reason": "CrashLoopBackOff" } }
+                }]
+            }
+        })).unwrap();
+        let statefulset: StatefulSet = serde_json::from_value(serde_json::json!({
+            "metadata": { "name": "mydb-1a2b" },
+            "spec": { "replicas": 1, "selector": {}, "serviceName": "mydb-1a2b", "template": {} },
+            "status": { "replicas": 1, "readyReplicas": 0 }
+        })).unwrap();
+        
+        let live = LiveStatus::from_resources(Some(&pod), Some(&statefulset));
+        assert_eq!(live.pod_phase.as_deref(), Some("Running"));
+        assert!(!live.pod_ready);
+        assert_eq!(live.restart_count, 7);
+        assert_eq!(live.waiting_reason.as_deref(), Some("CrashLoopBackOff"));
+        assert_eq!((live.ready_replicas, live.replicas), (0, 1));
+        
+        // Missing resources produce an empty status rather than an error
+        assert_eq!(LiveStatus::from_resources(None, None), LiveStatus::default());
+    }
+    
+    #[tokio::test]
+    async fn test_context_name_selects_its_cluster() {
+        let kubeconfig = Kubeconfig::from_yaml(r#"
+apiVersion: v1
+kind: Config
+current-context: aks-westus3
+clusters:
+  - name: westus3
+    cluster: { server: "https://westus3.example.com:443" }
+  - name: eastus
+    cluster: { server: "https://eastus.example.com:443" }
+users:
+  - name: toygres
+    user: { token: "t0ken" }
+contexts:
+  - name: aks-westus3
+    context: { cluster: westus3, user: toygres, namespace: toygres }
+  - name: aks-eastus
+    context: { cluster: eastus, user: toygres, namespace: team-east }
+"#).unwrap();
+        
+        let east = config_for_context(kubeconfig.clone(), "aks-eastus").await.unwrap();
+        assert_eq!(east.cluster_url.to_string(), "https://eastus.example.com:443/");
+        assert_eq!(east.default_namespace, "team-east");
+        
+        let west = config_for_context(kubeconfig.clone(), "aks-westus3").await.unwrap();
+        assert_eq!(west.cluster_url.to_string(), "https://westus3.example.com:443/");
+        
+        let missing = config_for_context(kubeconfig, "aks-northeurope").await.unwrap_err();
+        assert!(missing.to_string().contains("no context 'aks-northeurope'"), "{}", missing);
+    }
+}
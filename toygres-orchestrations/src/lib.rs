@@ -27,6 +27,12 @@
 pub mod names;
 pub mod types;
 pub mod registry;
+pub mod trace;
+pub mod retry;
+pub mod actor_events;
+pub mod metrics;
+pub mod spec_diff;
+pub mod ids;
 
 // Activity exports - activities module is public for IDE navigation (F12 to jump to implementation)
 pub mod activities;
@@ -35,6 +41,9 @@ pub mod k8s_client;
 
 mod orchestrations;
 
+#[cfg(test)]
+mod test_support;
+
 // Re-export orchestration flows for UI visualization
 pub mod flows {
     pub use crate::orchestrations::flows::*;
@@ -53,3 +62,8 @@ pub fn init_duroxide_client(client: std::sync::Arc<duroxide::Client>) {
     activities::raise_event::init_client(client);
 }
 
+/// How those activities get a new client when the current one's store is unreachable
+pub fn set_duroxide_reconnect(reconnect: activities::raise_event::Reconnect) {
+    activities::raise_event::set_reconnect(reconnect);
+}
+
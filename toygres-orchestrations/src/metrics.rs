@@ -0,0 +1,209 @@
+//! Latency histograms for activities and orchestrations
+//!
+//! Durations are kept in process and rendered in the Prometheus text format by the
+//! server's `/metrics` endpoint. Activities are timed by [`RegisterTimed::register_timed`], which
+//! the activity registry uses in place of `register_typed`. Orchestration code replays
+//! and must not record anything itself, so create and delete durations are recorded by
+//! the notification activity they run at their terminal point.
+
+use duroxide::runtime::registry::ActivityRegistryBuilder;
+use duroxide::ActivityContext;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::future::Future;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tracing::Instrument;
+
+/// Histogram of activity run times, labeled by `activity`
+pub const ACTIVITY_DURATION: &str = "toygres_activity_duration_seconds";
+
+/// Histogram of end-to-end orchestration times, labeled by `orchestration`
+pub const ORCHESTRATION_DURATION: &str = "toygres_orchestration_duration_seconds";
+
+/// Bucket upper bounds (seconds): sub-second CMS calls up to multi-minute deploys
+pub const BUCKETS: [f64; 12] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0];
+
+#[derive(Debug, Clone, Default)]
+struct Histogram {
+    /// Non-cumulative count per bucket in [`BUCKETS`]; slower observations only reach `count`
+    buckets: [u64; BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, seconds: f64) {
+        if let Some(i) = BUCKETS.iter().position(|bound| seconds <= *bound) {
+            self.buckets[i] += 1;
+        }
+        self.sum += seconds;
+        self.count += 1;
+    }
+}
+
+/// (metric, label value) -> histogram
+type Histograms = BTreeMap<(&'static str, String), Histogram>;
+
+fn histograms() -> &'static Mutex<Histograms> {
+    static HISTOGRAMS: OnceLock<Mutex<Histograms>> = OnceLock::new();
+    HISTOGRAMS.get_or_init(Mutex::default)
+}
+
+fn observe(metric: &'static str, label: &str, duration: Duration) {
+    let mut histograms = histograms().lock().unwrap_or_else(|e| e.into_inner());
+    histograms
+        .entry((metric, label.to_string()))
+        .or_default()
+        .observe(duration.as_secs_f64());
+}
+
+/// Record one run of `activity`
+pub fn observe_activity(activity: &str, duration: Duration) {
+    observe(ACTIVITY_DURATION, short_name(activity), duration);
+}
+
+/// Record one completed run of `orchestration`
+pub fn observe_orchestration(orchestration: &str, duration: Duration) {
+    observe(ORCHESTRATION_DURATION, short_name(orchestration), duration);
+}
+
+/// `deploy-postgres` from `toygres-orchestrations::activity::deploy-postgres`
+fn short_name(name: &str) -> &str {
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+/// Records the time from its creation to its drop as one run of an activity, so
+/// early returns and panics are counted too
+pub struct ActivityTimer {
+    activity: &'static str,
+    started: Instant,
+}
+
+impl ActivityTimer {
+    pub fn start(activity: &'static str) -> Self {
+        Self { activity, started: Instant::now() }
+    }
+}
+
+impl Drop for ActivityTimer {
+    fn drop(&mut self) {
+        observe_activity(self.activity, self.started.elapsed());
+    }
+}
+
+/// Activity registration that times every run into [`ACTIVITY_DURATION`]
+pub trait RegisterTimed: Sized {
+    /// `register_typed`, wrapped in an [`ActivityTimer`] and run inside
+    /// [`activity_span`](crate::trace::activity_span)
+    fn register_timed<In, Out, F, Fut>(self, name: &'static str, f: F) -> Self
+    where
+        In: serde::de::DeserializeOwned + Send + 'static,
+        Out: serde::Serialize + Send + 'static,
+        F: Fn(ActivityContext, In) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Out, String>> + Send + 'static;
+}
+
+impl RegisterTimed for ActivityRegistryBuilder {
+    fn register_timed<In, Out, F, Fut>(self, name: &'static str, f: F) -> Self
+    where
+        In: serde::de::DeserializeOwned + Send + 'static,
+        Out: serde::Serialize + Send + 'static,
+        F: Fn(ActivityContext, In) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Out, String>> + Send + 'static,
+    {
+        self.register_typed(name, move |ctx: ActivityContext, input: In| {
+            let timer = ActivityTimer::start(name);
+            let span = crate::trace::activity_span(&ctx);
+            let run = f(ctx, input);
+            async move {
+                let result = run.await;
+                drop(timer);
+                result
+            }
+            .instrument(span)
+        })
+    }
+}
+
+/// All histograms in the Prometheus text exposition format
+pub fn render_prometheus() -> String {
+    let histograms = histograms().lock().unwrap_or_else(|e| e.into_inner());
+    let mut out = String::new();
+
+    for (metric, label, help) in [
+        (ACTIVITY_DURATION, "activity", "Activity run time in seconds"),
+        (ORCHESTRATION_DURATION, "orchestration", "End-to-end orchestration time in seconds"),
+    ] {
+        let _ = writeln!(out, "# HELP {} {}", metric, help);
+        let _ = writeln!(out, "# TYPE {} histogram", metric);
+
+        for ((_, name), histogram) in histograms.range((metric, String::new())..).take_while(|((m, _), _)| *m == metric) {
+            let name = name.replace('\\', "\\\\").replace('"', "\\\"");
+            let mut cumulative = 0;
+            for (bound, count) in BUCKETS.iter().zip(histogram.buckets) {
+                cumulative += count;
+                let _ = writeln!(out, "{}_bucket{{{}=\"{}\",le=\"{}\"}} {}", metric, label, name, bound, cumulative);
+            }
+            let _ = writeln!(out, "{}_bucket{{{}=\"{}\",le=\"+Inf\"}} {}", metric, label, name, histogram.count);
+            let _ = writeln!(out, "{}_sum{{{}=\"{}\"}} {}", metric, label, name, histogram.sum);
+            let _ = writeln!(out, "{}_count{{{}=\"{}\"}} {}", metric, label, name, histogram.count);
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use duroxide::providers::sqlite::SqliteProvider;
+    use duroxide::runtime::{self, registry::ActivityRegistry};
+    use duroxide::{Client, OrchestrationContext, OrchestrationRegistry};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_recorded_durations_appear_in_histogram_output() {
+        observe_orchestration("toygres-orchestrations::orchestration::metrics-test", Duration::from_millis(800));
+        observe_orchestration("toygres-orchestrations::orchestration::metrics-test", Duration::from_secs(400));
+
+        let out = render_prometheus();
+        assert!(out.contains("# TYPE toygres_orchestration_duration_seconds histogram"));
+        // 0.8s falls in the 1s bucket; 400s is only in +Inf
+        assert!(out.contains("toygres_orchestration_duration_seconds_bucket{orchestration=\"metrics-test\",le=\"0.5\"} 0\n"), "{}", out);
+        assert!(out.contains("toygres_orchestration_duration_seconds_bucket{orchestration=\"metrics-test\",le=\"1\"} 1\n"), "{}", out);
+        assert!(out.contains("toygres_orchestration_duration_seconds_bucket{orchestration=\"metrics-test\",le=\"300\"} 1\n"), "{}", out);
+        assert!(out.contains("toygres_orchestration_duration_seconds_bucket{orchestration=\"metrics-test\",le=\"+Inf\"} 2\n"), "{}", out);
+        assert!(out.contains("toygres_orchestration_duration_seconds_sum{orchestration=\"metrics-test\"} 400.8\n"), "{}", out);
+        assert!(out.contains("toygres_orchestration_duration_seconds_count{orchestration=\"metrics-test\"} 2\n"), "{}", out);
+    }
+
+    #[tokio::test]
+    async fn test_registered_activities_are_timed() {
+        const NAME: &str = "toygres-orchestrations::activity::metrics-timed-test";
+
+        let activities = ActivityRegistry::builder()
+            .register_timed(NAME, |_ctx: ActivityContext, input: String| async move {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                Ok::<_, String>(input)
+            })
+            .build();
+        let orchestrations = OrchestrationRegistry::builder()
+            .register("metrics-orchestration", |ctx: OrchestrationContext, input: String| async move {
+                ctx.schedule_activity(NAME, input).into_activity().await
+            })
+            .build();
+
+        let store = Arc::new(SqliteProvider::new_in_memory().await.unwrap());
+        let rt = runtime::Runtime::start_with_store(store.clone(), Arc::new(activities), orchestrations).await;
+        let client = Client::new(store);
+        client.start_orchestration("metrics-1", "metrics-orchestration", "\"hi\"").await.unwrap();
+        let status = client.wait_for_orchestration("metrics-1", Duration::from_secs(10)).await.unwrap();
+        rt.shutdown(None).await;
+        assert!(matches!(status, duroxide::OrchestrationStatus::Completed { .. }), "status: {:?}", status);
+
+        let out = render_prometheus();
+        assert!(out.contains("toygres_activity_duration_seconds_count{activity=\"metrics-timed-test\"} 1\n"), "{}", out);
+        assert!(out.contains("toygres_activity_duration_seconds_bucket{activity=\"metrics-timed-test\",le=\"0.05\"}"), "{}", out);
+    }
+}
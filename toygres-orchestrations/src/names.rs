@@ -11,8 +11,10 @@ pub mod orchestrations {
     /// **Activities used:**
     /// - [`toygres_activities::names::activities::DEPLOY_POSTGRES`]
     /// - [`toygres_activities::names::activities::WAIT_FOR_READY`]
+    /// - [`toygres_activities::names::activities::GET_SERVICE_EXTERNAL_IP`]
     /// - [`toygres_activities::names::activities::GET_CONNECTION_STRINGS`]
     /// - [`toygres_activities::names::activities::TEST_CONNECTION`]
+    ///
     /// **Duration:** ~30-60 seconds
     pub const CREATE_INSTANCE: &str = "toygres-orchestrations::orchestration::create-instance";
     
@@ -22,8 +24,9 @@ pub mod orchestrations {
     /// **Output:** [`crate::types::DeleteInstanceOutput`]  
     /// **Activities used:**
     /// - [`toygres_activities::names::activities::DELETE_POSTGRES`]
-    /// **Duration:** ~10 seconds
-    /// **Note:** Cancels instance actor orchestration before deletion
+    ///
+    /// **Duration:** ~10 seconds  
+    /// **Note:** Signals the instance actor before deleting resources
     pub const DELETE_INSTANCE: &str = "toygres-orchestrations::orchestration::delete-instance";
     
     /// Instance Actor - Continuous per-instance operations
@@ -31,10 +34,100 @@ pub mod orchestrations {
     /// **Input:** [`crate::types::InstanceActorInput`]  
     /// **Output:** Never completes (continues-as-new forever)  
     /// **Activities used:**
-    /// - Health monitoring every 30 seconds
+    /// - Health monitoring every 30 seconds; failed checks are classified by
+    ///   [`crate::activity_types::HealthReason`], using the pod status
+    /// - LoadBalancer IP re-resolved each cycle; a changed IP is recorded with
+    ///   [`crate::activities::cms::update_external_ip::NAME`]
     /// - Future: Auto-scaling, backups, maintenance
-    /// **Duration:** Runs until instance deleted
+    ///
+    /// **Duration:** Runs until instance deleted  
     /// **Pattern:** Detached orchestration with continue-as-new
     pub const INSTANCE_ACTOR: &str = "toygres-orchestrations::orchestration::instance-actor";
+    
+    /// Bump an instance to a new minor PostgreSQL version in place
+    /// 
+    /// **Input:** [`crate::types::BumpMinorVersionInput`]  
+    /// **Output:** [`crate::types::BumpMinorVersionOutput`]  
+    /// **Activities used:**
+    /// - [`crate::activities::patch_image::NAME`]
+    /// - [`crate::activities::wait_for_rollout::NAME`]
+    /// - [`crate::activities::test_connection::NAME`]
+    /// - [`crate::activities::cms::update_postgres_version::NAME`]
+    ///
+    /// **Duration:** ~30-60 seconds (one pod restart)  
+    /// **Note:** Rolls the image back if the new pod never becomes ready or fails validation
+    pub const BUMP_MINOR_VERSION: &str = "toygres-orchestrations::orchestration::bump-minor-version";
+    
+    /// Run VACUUM ANALYZE (or VACUUM FULL) on an instance
+    /// 
+    /// **Input:** [`crate::types::RunMaintenanceOrchestrationInput`]  
+    /// **Output:** [`crate::types::RunMaintenanceOrchestrationOutput`]  
+    /// **Activities used:**
+    /// - [`crate::activities::cms::get_instance_connection::NAME`]
+    /// - [`crate::activities::run_maintenance::NAME`]
+    ///
+    /// **Duration:** Seconds to hours, depending on table sizes  
+    /// **Note:** VACUUM FULL blocks reads and writes on each table while it runs
+    pub const RUN_MAINTENANCE: &str = "toygres-orchestrations::orchestration::run-maintenance";
+    
+    /// Switch an instance's Service between `ClusterIP` and `LoadBalancer`, or recreate
+    /// a deleted one, keeping the data
+    /// 
+    /// **Input:** [`crate::types::ExposeInstanceInput`]  
+    /// **Output:** [`crate::types::ExposeInstanceOutput`]  
+    /// **Activities used:**
+    /// - [`crate::activities::cms::get_instance_by_k8s_name::NAME`]
+    /// - [`crate::activities::cms::get_instance_connection::NAME`]
+    /// - [`crate::activities::expose_service::NAME`]
+    /// - [`crate::activities::get_service_external_ip::NAME`]
+    /// - [`crate::activities::get_connection_strings::NAME`]
+    /// - [`crate::activities::cms::update_exposure::NAME`]
+    ///
+    /// **Duration:** Seconds, up to ~2 minutes while a LoadBalancer gets its IP  
+    /// **Note:** Clients using the old address lose their connection
+    pub const EXPOSE_INSTANCE: &str = "toygres-orchestrations::orchestration::expose-instance";
+    
+    /// Delete Kubernetes resources left behind by instances that no longer exist
+    /// 
+    /// **Input:** [`crate::types::GcOrphansInput`]  
+    /// **Output:** [`crate::types::GcOrphansOutput`]  
+    /// **Activities used:**
+    /// - [`crate::activities::list_toygres_resources::NAME`]
+    /// - [`crate::activities::cms::list_live_instances::NAME`]
+    /// - [`crate::activities::delete_resources::NAME`]
+    ///
+    /// **Duration:** Seconds  
+    /// **Note:** Runs once, or every `interval_secs` via continue-as-new
+    pub const GC_ORPHANS: &str = "toygres-orchestrations::orchestration::gc-orphans";
 }
 
+/// External event names
+pub mod events {
+    /// Raised by the delete orchestration to stop an instance actor
+    ///
+    /// **Target:** [`super::orchestrations::INSTANCE_ACTOR`]  
+    /// **Note:** Best-effort; the actor also stops once its CMS record is gone
+    pub const INSTANCE_DELETED: &str = "InstanceDeleted";
+
+    /// Changes instance actor settings from its next iteration on
+    ///
+    /// **Target:** [`super::orchestrations::INSTANCE_ACTOR`]  
+    /// **Payload:** [`crate::actor_events::InstanceActorConfig`]
+    pub const UPDATE_CONFIG: &str = "UpdateConfig";
+
+    /// Asks an instance actor for an immediate backup
+    ///
+    /// **Target:** [`super::orchestrations::INSTANCE_ACTOR`]
+    pub const TRIGGER_BACKUP: &str = "TriggerBackup";
+
+    /// Asks an instance actor to run its next health check now instead of after the
+    /// 30-second wait
+    ///
+    /// **Target:** [`super::orchestrations::INSTANCE_ACTOR`]
+    pub const CHECK_NOW: &str = "CheckNow";
+
+    /// Stops an instance actor without deleting its instance
+    ///
+    /// **Target:** [`super::orchestrations::INSTANCE_ACTOR`]
+    pub const CANCEL: &str = "Cancel";
+}
@@ -0,0 +1,389 @@
+//! Bump minor version orchestration
+//!
+//! Minor PostgreSQL releases share the on-disk format, so moving between them only
+//! needs a new image, not a new instance. Steps run in this order:
+//! 1. Load the CMS record and check the target is a minor bump of the current version
+//! 2. Patch the StatefulSet's postgres container image
+//! 3. Wait for the StatefulSet to roll out the new revision and its pod to become ready
+//! 4. Connect and check the server reports the target version
+//! 5. Record the new `postgres_version` in the CMS
+//!
+//! If step 3 or 4 fails the image is patched back to the previous one, so a bad
+//! release leaves the instance on the version it started with. That happens even when
+//! step 2 found the target image already in place (a bump retried after a failed
+//! rollback, say): the instance is then put back on the recorded version's image.
+//! An instance whose current version is not recorded is not bumped, as neither the
+//! major version check nor a rollback would have a version to go by.
+
+use duroxide::{OrchestrationContext, RetryPolicy, BackoffStrategy};
+use std::time::Duration;
+use crate::types::{BumpMinorVersionInput, BumpMinorVersionOutput};
+use crate::trace::Tracer;
+use crate::activities::{self, cms};
+use crate::activity_types::{
+    GetInstanceByK8sNameInput, GetInstanceByK8sNameOutput,
+    PatchImageInput, PatchImageOutput,
+    WaitForRolloutInput, WaitForRolloutOutput,
+    GetInstanceConnectionInput, GetInstanceConnectionOutput,
+    TestConnectionInput, TestConnectionOutput, PasswordSecretRef,
+    UpdatePostgresVersionInput, UpdatePostgresVersionOutput,
+};
+
+pub async fn bump_minor_version_orchestration(
+    ctx: OrchestrationContext,
+    input: BumpMinorVersionInput,
+) -> Result<BumpMinorVersionOutput, String> {
+    let trace = Tracer::new(&ctx, input.trace_level);
+    trace.info(format!(
+        "Bumping {} to PostgreSQL {} (orchestration: {})",
+        input.name, input.target_version, input.orchestration_id
+    ));
+
+    let namespace = input.namespace.clone();
+
+    // Step 1: Load the CMS record and validate the bump
+    let cms_record = ctx
+        .schedule_activity_with_retry_typed::<GetInstanceByK8sNameInput, GetInstanceByK8sNameOutput>(
+            cms::get_instance_by_k8s_name::NAME,
+            &GetInstanceByK8sNameInput {
+                k8s_name: input.name.clone(),
+            },
+            RetryPolicy::new(3)
+                .with_backoff(BackoffStrategy::Fixed {
+                    delay: Duration::from_secs(2),
+                })
+                .with_timeout(Duration::from_secs(10)),
+        )
+        .await
+        .map_err(|e| format!("Failed to query CMS record after retries: {}", e))?;
+
+    let record = cms_record.record
+        .ok_or_else(|| format!("Instance {} not found in CMS", input.name))?;
+    if record.state != "running" {
+        return Err(format!("Instance {} is '{}', only running instances can be upgraded", input.name, record.state));
+    }
+    let current_version = record.postgres_version
+        .ok_or_else(|| format!("Current PostgreSQL version of {} is not recorded; cannot check the bump", input.name))?;
+    check_minor_bump(&current_version, &input.target_version)?;
+
+    // Step 2: Patch the image
+    trace.info("Step 2: Patching StatefulSet image");
+    let image = format!("postgres:{}", input.target_version);
+    let patch_output = patch_image(&ctx, &namespace, &input.name, &image).await?;
+
+    // Steps 3-4: Wait for the new pod and validate it, rolling back on failure
+    if let Err(err) = roll_out(&ctx, &trace, &namespace, &input, &image).await {
+        trace.error(format!("Upgrade failed: {}", err));
+        let previous_image = patch_output.previous_image.clone()
+            .filter(|_| patch_output.patched)
+            .unwrap_or_else(|| format!("postgres:{}", current_version));
+        trace.warn(format!("Rolling back to {}", previous_image));
+        patch_image(&ctx, &namespace, &input.name, &previous_image).await
+            .map_err(|e| format!("{}; rollback to {} also failed: {}", err, previous_image, e))?;
+        return Err(format!("{}; rolled back to {}", err, previous_image));
+    }
+
+    // Step 5: Record the new version
+    trace.info("Step 5: Recording new version in CMS");
+    ctx.schedule_activity_with_retry_typed::<UpdatePostgresVersionInput, UpdatePostgresVersionOutput>(
+        cms::update_postgres_version::NAME,
+        &UpdatePostgresVersionInput {
+            k8s_name: input.name.clone(),
+            postgres_version: input.target_version.clone(),
+        },
+        RetryPolicy::new(3)
+            .with_backoff(BackoffStrategy::Fixed {
+                delay: Duration::from_secs(2),
+            })
+            .with_timeout(Duration::from_secs(10)),
+    )
+    .await
+    .map_err(|e| format!("Failed to record postgres version: {}", e))?;
+
+    trace.info(format!("Instance {} now on PostgreSQL {}", input.name, input.target_version));
+
+    Ok(BumpMinorVersionOutput {
+        instance_name: input.name,
+        previous_version: Some(current_version),
+        postgres_version: input.target_version,
+        patched: patch_output.patched,
+    })
+}
+
+async fn patch_image(
+    ctx: &OrchestrationContext,
+    namespace: &str,
+    instance_name: &str,
+    image: &str,
+) -> Result<PatchImageOutput, String> {
+    ctx.schedule_activity_with_retry_typed::<PatchImageInput, PatchImageOutput>(
+        activities::patch_image::NAME,
+        &PatchImageInput {
+            namespace: namespace.to_string(),
+            instance_name: instance_name.to_string(),
+            image: image.to_string(),
+        },
+        RetryPolicy::new(3)
+            .with_backoff(BackoffStrategy::Exponential {
+                base: Duration::from_secs(1),
+                multiplier: 2.0,
+                max: Duration::from_secs(10),
+            })
+            .with_timeout(Duration::from_secs(30)),
+    )
+    .await
+    .map_err(|e| format!("Failed to patch image to {}: {}", image, e))
+}
+
+/// Wait for the rollout to `image` to complete, then check it serves the target version
+async fn roll_out(
+    ctx: &OrchestrationContext,
+    trace: &Tracer,
+    namespace: &str,
+    input: &BumpMinorVersionInput,
+    image: &str,
+) -> Result<(), String> {
+    // Step 3: Poll the rollout (using Duroxide timers for determinism). The old pod
+    // stays Ready until it is replaced, so pod readiness alone would pass too early.
+    trace.info("Step 3: Waiting for the rollout to the new image");
+    let max_attempts = 60; // 5 minutes (60 attempts * 5 seconds)
+
+    for attempt in 1..=max_attempts {
+        let wait_input = WaitForRolloutInput {
+            namespace: namespace.to_string(),
+            instance_name: input.name.clone(),
+        };
+
+        let rollout = ctx
+            .schedule_activity_typed::<WaitForRolloutInput, WaitForRolloutOutput>(activities::wait_for_rollout::NAME, &wait_input)
+            .into_activity_typed::<WaitForRolloutOutput>()
+            .await
+            .map_err(|e| format!("Failed to check rollout status: {}", e))?;
+
+        if rollout.complete {
+            trace.info(format!("Rolled out {} (attempt {})", image, attempt));
+            break;
+        }
+
+        if attempt >= max_attempts {
+            return Err(format!(
+                "Timeout: rollout still at {}/{} updated, {}/{} ready after {} attempts",
+                rollout.updated_replicas, rollout.replicas, rollout.ready_replicas, rollout.replicas, max_attempts
+            ));
+        }
+
+        trace.info(format!("Rollout {}/{} updated, {}/{} ready (attempt {}/{}), waiting 5 seconds...",
+                           rollout.updated_replicas, rollout.replicas, rollout.ready_replicas, rollout.replicas,
+                           attempt, max_attempts));
+        ctx.schedule_timer(Duration::from_secs(5)).into_timer().await;
+    }
+
+    // Step 4: Validate the server version
+    trace.info("Step 4: Validating server version");
+    let conn_info = ctx
+        .schedule_activity_typed::<GetInstanceConnectionInput, GetInstanceConnectionOutput>(
+            cms::get_instance_connection::NAME,
+            &GetInstanceConnectionInput { k8s_name: input.name.clone() },
+        )
+        .into_activity_typed::<GetInstanceConnectionOutput>()
+        .await
+        .map_err(|e| format!("Failed to get connection info: {}", e))?;
+
+    let connection_string = conn_info.connection_string
+        .ok_or_else(|| "No connection string recorded for instance".to_string())?;
+
+    let test_input = TestConnectionInput {
+        connection_string,
+        password_secret: conn_info.password_secret_ref.map(|name| PasswordSecretRef {
+            namespace: namespace.to_string(),
+            name,
+        }),
+        health_sql: None,
+    };
+
+    // The server may still be finishing startup after the pod reports ready
+    let test_output = ctx
+        .schedule_activity_with_retry_typed::<TestConnectionInput, TestConnectionOutput>(
+            activities::test_connection::NAME,
+            &test_input,
+            RetryPolicy::new(5)
+                .with_backoff(BackoffStrategy::Exponential {
+                    base: Duration::from_secs(2),
+                    multiplier: 2.0,
+                    max: Duration::from_secs(30),
+                })
+                .with_timeout(Duration::from_secs(60)),
+        )
+        .await
+        .map_err(|e| format!("Connection test failed: {}", e))?;
+
+    if !version_matches(&test_output.version, &input.target_version) {
+        return Err(format!("Server reports '{}', expected PostgreSQL {}", test_output.version, input.target_version));
+    }
+
+    trace.info(format!("PostgreSQL version: {}", test_output.version));
+    Ok(())
+}
+
+/// Check that `target` is a different version with the same major as `current`
+fn check_minor_bump(current: &str, target: &str) -> Result<(), String> {
+    let major = |v: &str| v.split('.').next().unwrap_or(v).to_string();
+    if target.is_empty() || !target.split('.').all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit())) {
+        return Err(format!("Invalid target version '{}'", target));
+    }
+    if major(current) != major(target) {
+        return Err(format!(
+            "Cannot change major version from {} to {} in place; create a new instance instead",
+            current, target
+        ));
+    }
+    if current == target {
+        return Err(format!("Instance is already on PostgreSQL {}", target));
+    }
+    Ok(())
+}
+
+/// Whether a `SELECT version()` string is for `target` (e.g. "18.2" or just "18")
+fn version_matches(version: &str, target: &str) -> bool {
+    version
+        .strip_prefix("PostgreSQL ")
+        .and_then(|rest| rest.split_whitespace().next())
+        .map(|reported| reported == target || reported.starts_with(&format!("{}.", target)))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::names;
+    use crate::test_support::{mock, run_orchestration, CallLog};
+    use duroxide::runtime::registry::ActivityRegistry;
+    use duroxide::OrchestrationRegistry;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_check_minor_bump() {
+        assert!(check_minor_bump("18", "18.2").is_ok());
+        assert!(check_minor_bump("18.1", "18.2").is_ok());
+        assert!(check_minor_bump("17.6", "18.1").unwrap_err().contains("major"));
+        assert!(check_minor_bump("18.2", "18.2").unwrap_err().contains("already"));
+        assert!(check_minor_bump("18", "18.2-alpine").unwrap_err().contains("Invalid"));
+    }
+
+    #[test]
+    fn test_version_matches() {
+        let reported = "PostgreSQL 18.2 (Debian 18.2-1.pgdg120+1) on x86_64-pc-linux-gnu";
+        assert!(version_matches(reported, "18.2"));
+        assert!(version_matches(reported, "18"));
+        assert!(!version_matches(reported, "18.1"));
+        assert!(!version_matches(reported, "1"));
+        assert!(!version_matches("not a version", "18.2"));
+    }
+
+    /// Bump an instance recorded at `postgres_version` to 18.2 with a new pod that
+    /// still reports 18.1. `patch_image` answers the first patch with `patch_output`.
+    async fn bump_to_wrong_version(postgres_version: Option<&str>, patch_output: serde_json::Value) -> (String, Vec<(String, serde_json::Value)>) {
+        let calls: CallLog = Arc::default();
+        let activities = ActivityRegistry::builder()
+            .register(cms::get_instance_by_k8s_name::NAME, mock(&calls, cms::get_instance_by_k8s_name::NAME, serde_json::json!({
+                "found": true,
+                "record": {
+                    "id": "00000000-0000-0000-0000-000000000000",
+                    "user_name": "mydb",
+                    "k8s_name": "mydb-1a2b3c4d",
+                    "namespace": "toygres",
+                    "state": "running",
+                    "dns_name": null,
+                    "postgres_version": postgres_version,
+                },
+                "instance_actor_orchestration_id": null,
+            })))
+            .register(activities::patch_image::NAME, mock(&calls, activities::patch_image::NAME, patch_output))
+            .register(activities::wait_for_rollout::NAME, mock(&calls, activities::wait_for_rollout::NAME, serde_json::json!({
+                "complete": true,
+                "replicas": 1,
+                "updated_replicas": 1,
+                "ready_replicas": 1,
+                "current_revision": "mydb-1a2b3c4d-9c1e",
+                "update_revision": "mydb-1a2b3c4d-9c1e",
+            })))
+            .register(cms::get_instance_connection::NAME, mock(&calls, cms::get_instance_connection::NAME, serde_json::json!({
+                "found": true,
+                "connection_string": "postgresql://postgres:pw@10.0.0.1:5432/postgres",
+                "state": "running",
+            })))
+            // The new pod still reports the old version
+            .register(activities::test_connection::NAME, mock(&calls, activities::test_connection::NAME, serde_json::json!({
+                "version": "PostgreSQL 18.1 on x86_64-pc-linux-gnu",
+                "connected": true,
+            })))
+            .register(cms::update_postgres_version::NAME, mock(&calls, cms::update_postgres_version::NAME, serde_json::json!({ "updated": true })))
+            .build();
+        let orchestrations = OrchestrationRegistry::builder()
+            .register_typed(names::orchestrations::BUMP_MINOR_VERSION, bump_minor_version_orchestration)
+            .build();
+
+        let input = BumpMinorVersionInput {
+            name: "mydb-1a2b3c4d".to_string(),
+            namespace: "toygres".to_string(),
+            target_version: "18.2".to_string(),
+            orchestration_id: "bump-mydb".to_string(),
+            trace_level: None,
+        };
+        let status = run_orchestration(
+            activities,
+            orchestrations,
+            "bump-mydb",
+            names::orchestrations::BUMP_MINOR_VERSION,
+            &input,
+            Duration::from_secs(10),
+        ).await;
+        let duroxide::OrchestrationStatus::Failed { details, .. } = status else {
+            panic!("status: {:?}", status);
+        };
+
+        let calls = calls.lock().unwrap().clone();
+        (details.display_message(), calls)
+    }
+
+    #[tokio::test]
+    async fn test_wrong_version_rolls_back_image() {
+        let (error, calls) = bump_to_wrong_version(Some("18.1"), serde_json::json!({
+            "previous_image": "registry.example.com/postgres:18.1",
+            "patched": true,
+        })).await;
+        let order: Vec<&str> = calls.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(order, vec![
+            "cms-get-instance-by-k8s-name",
+            "patch-image",
+            "wait-for-rollout",
+            "cms-get-instance-connection",
+            "test-connection",
+            "patch-image",
+        ]);
+        assert_eq!(calls[1].1["image"], "postgres:18.2");
+        assert_eq!(calls[2].1["instance_name"], "mydb-1a2b3c4d");
+        assert_eq!(calls[5].1["image"], "registry.example.com/postgres:18.1");
+        assert!(error.contains("rolled back to registry.example.com/postgres:18.1"), "{}", error);
+
+        // The target image was already in place: roll back to the recorded version
+        let (_, calls) = bump_to_wrong_version(Some("18.1"), serde_json::json!({
+            "previous_image": "postgres:18.2",
+            "patched": false,
+        })).await;
+        let (name, rollback) = calls.last().unwrap();
+        assert_eq!(name, "patch-image");
+        assert_eq!(rollback["image"], "postgres:18.1");
+    }
+
+    #[tokio::test]
+    async fn test_unknown_current_version_is_not_bumped() {
+        let (error, calls) = bump_to_wrong_version(None, serde_json::json!({
+            "previous_image": "postgres:17.6",
+            "patched": true,
+        })).await;
+        assert!(error.contains("not recorded"), "{}", error);
+        let order: Vec<&str> = calls.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(order, vec!["cms-get-instance-by-k8s-name"]);
+    }
+}
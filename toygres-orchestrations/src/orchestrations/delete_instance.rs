@@ -1,27 +1,63 @@
 //! Delete PostgreSQL instance orchestration
+//!
+//! Steps run in this order:
+//! 1. Load the CMS record, claim it for this orchestration and mark it `deleting`
+//! 2. Signal the instance actor (`InstanceDeleted`) so it stops before resources vanish
+//! 3. Delete the Kubernetes resources
+//! 4. Mark the record `deleted`, free its DNS name and remove its private DNS record
+//! 5. Delete the CMS record (always last)
+//!
+//! The signal is best-effort: an actor that misses it stops on its next iteration when
+//! it finds no CMS record, which is the authoritative stop condition. Removing the
+//! private DNS record is best-effort too: a record left behind is reported in the
+//! trace and does not fail the delete.
+//!
+//! Deleting an instance that was already deleted is safe. With no CMS record there is
+//! nothing to mark, free, or remove, so only the Kubernetes cleanup runs again (it
+//! reports `deleted: false` when the resources are already gone) and the CMS steps
+//! are skipped rather than logging misleading "not found" warnings.
+//!
+//! Two deletes of one instance with different orchestration IDs (a bulk delete and a
+//! manual one, say) do not both run. The claim records the orchestration in
+//! `delete_orchestration_id` under a row lock; a delete that finds another one's ID
+//! there completes without touching anything and reports the conflict. A delete that
+//! fails to remove the Kubernetes resources clears its claim again, so the instance
+//! can still be deleted by a later orchestration.
 
 use duroxide::{OrchestrationContext, RetryPolicy, BackoffStrategy};
 use std::time::Duration;
 use crate::types::{DeleteInstanceInput, DeleteInstanceOutput};
-use crate::activities::{self, cms};
+use crate::trace::Tracer;
+use crate::retry::{cms_retry_policy, schedule_activity_with_retry_counted};
+use crate::orchestrations::create_instance::update_cms_state;
+use crate::activities::{self, cms, send_notification};
 use crate::activity_types::{
     DeletePostgresInput, DeletePostgresOutput,
-    UpdateInstanceStateInput, UpdateInstanceStateOutput,
+    UpdateInstanceStateInput,
     FreeDnsNameInput, FreeDnsNameOutput,
+    DeregisterPrivateDnsInput, DeregisterPrivateDnsOutput,
+    ClaimInstanceDeletionInput, ClaimInstanceDeletionOutput,
+    ReleaseInstanceDeletionInput, ReleaseInstanceDeletionOutput,
     GetInstanceByK8sNameInput, GetInstanceByK8sNameOutput,
     DeleteInstanceRecordInput, DeleteInstanceRecordOutput,
+    RecordInstanceEventInput, RecordInstanceEventOutput,
+    NotificationOutcome,
 };
+use crate::actor_events::{raise_actor_event, ActorEvent};
 
 pub async fn delete_instance_orchestration(
     ctx: OrchestrationContext,
     input: DeleteInstanceInput,
 ) -> Result<DeleteInstanceOutput, String> {
-    ctx.trace_info(format!(
+    let trace = Tracer::new(&ctx, input.trace_level);
+    trace.info(format!(
         "Deleting PostgreSQL instance: {} (orchestration: {})",
         input.name, input.orchestration_id
     ));
     
-    let namespace = input.namespace.clone().unwrap_or_else(|| "toygres".to_string());
+    let started = ctx.utcnow().await
+        .map_err(|e| format!("Failed to get start time: {}", e))?;
+    let namespace = input.namespace.clone();
     
     // Get CMS record with retry for resilience
     let cms_record = ctx
@@ -43,6 +79,35 @@ pub async fn delete_instance_orchestration(
     let instance_actor_id = cms_record.instance_actor_orchestration_id.clone();
     
     if cms_record.found {
+        let claim = ctx
+            .schedule_activity_with_retry_typed::<ClaimInstanceDeletionInput, ClaimInstanceDeletionOutput>(
+                cms::claim_instance_deletion::NAME,
+                &ClaimInstanceDeletionInput {
+                    k8s_name: input.name.clone(),
+                    orchestration_id: input.orchestration_id.clone(),
+                },
+                RetryPolicy::new(3)
+                    .with_backoff(BackoffStrategy::Fixed {
+                        delay: Duration::from_secs(2),
+                    })
+                    .with_timeout(Duration::from_secs(10)),
+            )
+            .await
+            .map_err(|e| format!("Failed to claim CMS record after retries: {}", e))?;
+        
+        if !claim.claimed {
+            let conflict = format!(
+                "Instance is already being deleted by orchestration '{}'",
+                claim.held_by.as_deref().unwrap_or("unknown")
+            );
+            trace.warn(format!("{}; nothing to do", conflict));
+            return Ok(DeleteInstanceOutput {
+                instance_name: input.name,
+                deleted: false,
+                conflict: Some(conflict),
+            });
+        }
+        
         let update_input = UpdateInstanceStateInput {
             k8s_name: input.name.clone(),
             state: "deleting".to_string(),
@@ -51,29 +116,28 @@ pub async fn delete_instance_orchestration(
             external_ip: None,
             delete_orchestration_id: Some(input.orchestration_id.clone()),
             message: Some("Deletion requested".to_string()),
+            private_connection_string: None,
+            metadata: None,
         };
-        update_cms_state(&ctx, update_input).await;
+        update_cms_state(&ctx, &trace, update_input).await;
     } else {
-        ctx.trace_info("CMS record not found, proceeding with best-effort cleanup");
+        trace.info("CMS record not found, proceeding with best-effort cleanup");
     }
     
-    // Step 0.5: Note that instance actor will be signaled after deletion
+    // Step 2: Signal the instance actor before its resources disappear
     if let Some(ref actor_id) = instance_actor_id {
-        ctx.trace_info(format!(
-            "Instance actor '{}' will receive deletion signal after cleanup",
-            actor_id
-        ));
+        signal_instance_actor(&ctx, &trace, actor_id).await;
     }
     
-    // Step 1: Delete PostgreSQL resources
-    ctx.trace_info("Step 1: Deleting PostgreSQL from Kubernetes");
+    // Step 3: Delete PostgreSQL resources
+    trace.info("Step 3: Deleting PostgreSQL from Kubernetes");
     let delete_input = DeletePostgresInput {
         namespace: namespace.clone(),
         instance_name: input.name.clone(),
     };
     
     // Delete K8s resources with retry - API calls can be flaky
-    let delete_output = ctx
+    let delete_output = match ctx
         .schedule_activity_with_retry_typed::<DeletePostgresInput, DeletePostgresOutput>(
             activities::delete_postgres::NAME,
             &delete_input,
@@ -85,11 +149,20 @@ pub async fn delete_instance_orchestration(
                 })
                 .with_timeout(Duration::from_secs(60)),
         )
-        .await?;
+        .await
+    {
+        Ok(output) => output,
+        Err(err) => {
+            if cms_record.found {
+                release_deletion_claim(&ctx, &trace, &input.name, &input.orchestration_id).await;
+            }
+            return Err(err);
+        }
+    };
     
-    ctx.trace_info(format!("Instance deletion complete (deleted: {})", delete_output.deleted));
+    trace.info(format!("Instance deletion complete (deleted: {})", delete_output.deleted));
     
-    // Mark as deleted state (instance actor will detect this and exit gracefully)
+    // Step 4: Mark as deleted and release the DNS name
     let update_input = UpdateInstanceStateInput {
         k8s_name: input.name.clone(),
         state: "deleted".to_string(),
@@ -98,40 +171,108 @@ pub async fn delete_instance_orchestration(
         external_ip: None,
         delete_orchestration_id: Some(input.orchestration_id.clone()),
         message: Some(format!("Deleted (resources deleted: {})", delete_output.deleted)),
+        private_connection_string: None,
+        metadata: None,
     };
-    update_cms_state(&ctx, update_input).await;
-    
-    // Step 3: Delete the CMS record
-    ctx.trace_info("Removing CMS record");
-    delete_cms_record(&ctx, &input.name).await;
-    
-    free_dns_name(&ctx, &input.name).await;
+    if cms_record.found {
+        update_cms_state(&ctx, &trace, update_input).await;
+        free_dns_name(&ctx, &trace, &input.name).await;
+        if let Some(record) = cms_record.record.as_ref().filter(|record| record.private_dns) {
+            deregister_private_dns(&ctx, &trace, record.dns_name.as_deref().unwrap_or(&record.k8s_name)).await;
+        }
+        
+        // Step 5: Delete the CMS record last; this is what stops any actor that missed the signal
+        trace.info("Removing CMS record");
+        delete_cms_record(&ctx, &trace, &input.name).await;
+        
+        // Only the run that removed the record notifies, so repeated deletes stay quiet
+        send_notification::notify(
+            &ctx, &trace, started, NotificationOutcome::Deleted,
+            &input.name, &input.orchestration_id, None,
+        ).await;
+    } else {
+        trace.info("CMS record already removed, skipping CMS cleanup");
+    }
     
     // Return output
     Ok(DeleteInstanceOutput {
         instance_name: input.name,
         deleted: delete_output.deleted,
+        conflict: None,
     })
 }
 
-async fn update_cms_state(
+async fn release_deletion_claim(
     ctx: &OrchestrationContext,
-    update_input: UpdateInstanceStateInput,
+    trace: &Tracer,
+    k8s_name: &str,
+    orchestration_id: &str,
 ) {
+    trace.info("Releasing deletion claim so a later delete can retry");
+    
     if let Err(err) = ctx
-        .schedule_activity_typed::<UpdateInstanceStateInput, UpdateInstanceStateOutput>(
-            cms::update_instance_state::NAME,
-            &update_input,
+        .schedule_activity_with_retry_typed::<ReleaseInstanceDeletionInput, ReleaseInstanceDeletionOutput>(
+            cms::release_instance_deletion::NAME,
+            &ReleaseInstanceDeletionInput {
+                k8s_name: k8s_name.to_string(),
+                orchestration_id: orchestration_id.to_string(),
+            },
+            cms_retry_policy(),
         )
-        .into_activity_typed::<UpdateInstanceStateOutput>()
         .await
     {
-        ctx.trace_warn(format!("Failed to update CMS state: {}", err));
+        trace.error(format!("Failed to release deletion claim after retries: {}", err));
+    }
+}
+
+async fn deregister_private_dns(
+    ctx: &OrchestrationContext,
+    trace: &Tracer,
+    record_name: &str,
+) {
+    trace.info(format!("Removing private DNS record '{}'", record_name));
+    
+    match ctx
+        .schedule_activity_with_retry_typed::<DeregisterPrivateDnsInput, DeregisterPrivateDnsOutput>(
+            activities::deregister_private_dns::NAME,
+            &DeregisterPrivateDnsInput {
+                record_name: record_name.to_string(),
+            },
+            RetryPolicy::new(3)
+                .with_backoff(BackoffStrategy::Linear {
+                    base: Duration::from_secs(5),
+                    max: Duration::from_secs(15),
+                })
+                .with_timeout(Duration::from_secs(60)),
+        )
+        .await
+    {
+        Ok(output) => trace.info(format!("Private DNS record {} removed (existed: {})", output.fqdn, output.deleted)),
+        Err(err) => trace.warn(format!(
+            "Failed to remove private DNS record '{}' after retries; remove it from the zone by hand: {}",
+            record_name, err
+        )),
+    }
+}
+
+async fn signal_instance_actor(
+    ctx: &OrchestrationContext,
+    trace: &Tracer,
+    actor_id: &str,
+) {
+    trace.info(format!("Signaling instance actor '{}' to stop", actor_id));
+    
+    if let Err(err) = raise_actor_event(ctx, actor_id, ActorEvent::InstanceDeleted).await {
+        trace.warn(format!(
+            "Failed to signal instance actor (it will stop once the CMS record is removed): {}",
+            err
+        ));
     }
 }
 
 async fn free_dns_name(
     ctx: &OrchestrationContext,
+    trace: &Tracer,
     k8s_name: &str,
 ) {
     if let Err(err) = ctx
@@ -144,42 +285,68 @@ async fn free_dns_name(
         .into_activity_typed::<FreeDnsNameOutput>()
         .await
     {
-        ctx.trace_warn(format!("Failed to free DNS name: {}", err));
+        trace.warn(format!("Failed to free DNS name: {}", err));
     }
 }
 
 async fn delete_cms_record(
     ctx: &OrchestrationContext,
+    trace: &Tracer,
     k8s_name: &str,
 ) {
-    ctx.trace_info("Deleting CMS record (triggers instance actor completion)");
+    trace.info("Deleting CMS record (triggers instance actor completion)");
     
+    let Err(exhausted) = schedule_activity_with_retry_counted::<DeleteInstanceRecordInput, DeleteInstanceRecordOutput>(
+        ctx,
+        cms::delete_instance_record::NAME,
+        &DeleteInstanceRecordInput {
+            k8s_name: k8s_name.to_string(),
+        },
+        cms_retry_policy(),
+    )
+    .await
+    else {
+        trace.info("CMS record deleted, instance actor will complete on next iteration");
+        return;
+    };
+    
+    // The record outlives the instance, so say so where the instance's history is read
+    trace.error(format!("Failed to delete CMS record: {}", exhausted));
     if let Err(err) = ctx
-        .schedule_activity_typed::<DeleteInstanceRecordInput, DeleteInstanceRecordOutput>(
-            cms::delete_instance_record::NAME,
-            &DeleteInstanceRecordInput {
+        .schedule_activity_typed::<RecordInstanceEventInput, RecordInstanceEventOutput>(
+            cms::record_instance_event::NAME,
+            &RecordInstanceEventInput {
                 k8s_name: k8s_name.to_string(),
+                event_type: "record_delete_failed".to_string(),
+                message: "CMS record not deleted; the instance's resources are gone".to_string(),
+                metadata: Some(exhausted.metadata()),
             },
         )
-        .into_activity_typed::<DeleteInstanceRecordOutput>()
+        .into_activity_typed::<RecordInstanceEventOutput>()
         .await
     {
-        ctx.trace_warn(format!("Failed to delete CMS record: {}", err));
-    } else {
-        ctx.trace_info("CMS record deleted, instance actor will complete on next iteration");
+        trace.warn(format!("Failed to record the record deletion failure: {}", err));
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::names;
+    use crate::activity_types::{RaiseEventInput, RaiseEventOutput, SendNotificationInput, SendNotificationOutput, UpdateInstanceStateOutput};
+    use crate::retry::CMS_RETRY_ATTEMPTS;
+    use crate::test_support::{call_names, mock, run_orchestration, short_name, CallLog, TestRuntime};
+    use duroxide::runtime::registry::ActivityRegistry;
+    use duroxide::{ActivityContext, OrchestrationRegistry};
+    use std::sync::{Arc, Mutex};
     
     #[test]
     fn test_delete_instance_input_serialization() {
         let input = DeleteInstanceInput {
             name: "test-pg".to_string(),
-            namespace: Some("toygres".to_string()),
+            namespace: "toygres".to_string(),
             orchestration_id: "delete-test".to_string(),
+            trace_level: None,
         };
         
         let json = serde_json::to_string(&input).unwrap();
@@ -192,11 +359,361 @@ mod tests {
         let output = DeleteInstanceOutput {
             instance_name: "test-pg".to_string(),
             deleted: true,
+            conflict: None,
         };
         
         let json = serde_json::to_string(&output).unwrap();
         let parsed: DeleteInstanceOutput = serde_json::from_str(&json).unwrap();
         assert_eq!(output, parsed);
     }
+    
+    #[tokio::test]
+    async fn test_actor_signaled_before_delete_and_record_removed_last() {
+        let calls: CallLog = Arc::default();
+        let activities = ActivityRegistry::builder()
+            .register(cms::get_instance_by_k8s_name::NAME, mock(&calls, cms::get_instance_by_k8s_name::NAME, serde_json::json!({
+                "found": true,
+                "record": {
+                    "id": "00000000-0000-0000-0000-000000000001",
+                    "user_name": "test",
+                    "k8s_name": "test-pg",
+                    "namespace": "toygres",
+                    "state": "running",
+                    "dns_name": "testdb",
+                    "private_dns": true,
+                },
+                "instance_actor_orchestration_id": "actor-test-pg",
+            })))
+            .register(cms::claim_instance_deletion::NAME, mock(&calls, cms::claim_instance_deletion::NAME, serde_json::json!({
+                "claimed": true,
+                "held_by": null,
+            })))
+            .register(cms::update_instance_state::NAME, mock(&calls, cms::update_instance_state::NAME, serde_json::json!({
+                "updated": true,
+                "previous_state": "running",
+            })))
+            .register(activities::raise_event::NAME, mock(&calls, activities::raise_event::NAME, serde_json::json!({ "raised": true })))
+            .register(activities::delete_postgres::NAME, mock(&calls, activities::delete_postgres::NAME, serde_json::json!({ "deleted": true })))
+            .register(cms::free_dns_name::NAME, mock(&calls, cms::free_dns_name::NAME, serde_json::json!({ "freed": true })))
+            .register(activities::deregister_private_dns::NAME, mock(&calls, activities::deregister_private_dns::NAME, serde_json::json!({
+                "fqdn": "testdb.toygres.internal",
+                "deleted": true,
+            })))
+            .register(cms::delete_instance_record::NAME, mock(&calls, cms::delete_instance_record::NAME, serde_json::json!({ "deleted": true })))
+            .register(send_notification::NAME, mock(&calls, send_notification::NAME, serde_json::json!({ "sent": false })))
+            .build();
+        let orchestrations = OrchestrationRegistry::builder()
+            .register_typed(names::orchestrations::DELETE_INSTANCE, delete_instance_orchestration)
+            .build();
+        
+        let input = DeleteInstanceInput {
+            name: "test-pg".to_string(),
+            namespace: "toygres".to_string(),
+            orchestration_id: "delete-test-pg".to_string(),
+            trace_level: None,
+        };
+        let status = run_orchestration(
+            activities,
+            orchestrations,
+            "delete-test-pg",
+            names::orchestrations::DELETE_INSTANCE,
+            &input,
+            Duration::from_secs(10),
+        ).await;
+        assert!(matches!(status, duroxide::OrchestrationStatus::Completed { .. }), "status: {:?}", status);
+        
+        assert_eq!(call_names(&calls), vec![
+            "cms-get-instance-by-k8s-name",
+            "cms-claim-instance-deletion",
+            "cms-update-instance-state",
+            "raise-event",
+            "delete-postgres",
+            "cms-update-instance-state",
+            "cms-free-dns-name",
+            "deregister-private-dns",
+            "cms-delete-instance-record",
+            "send-notification",
+        ]);
+        
+        let calls = calls.lock().unwrap().clone();
+        let signal = calls[3].1.to_string();
+        assert!(signal.contains("actor-test-pg"), "signal: {}", signal);
+        assert!(signal.contains(names::events::INSTANCE_DELETED), "signal: {}", signal);
+        assert_eq!(calls[7].1["record_name"], "testdb");
+    }
+    
+    /// In-memory stand-in for the CMS row and the Kubernetes resources
+    #[derive(Debug, Default)]
+    struct FakeCluster {
+        record: Option<FakeRecord>,
+        resources: bool,
+        calls: Vec<&'static str>,
+        /// Activity that fails every attempt, to simulate an outage
+        failing: Option<&'static str>,
+    }
+    
+    #[derive(Debug, Clone, PartialEq)]
+    struct FakeRecord {
+        state: String,
+        dns_name: String,
+        delete_orchestration_id: Option<String>,
+    }
+    
+    type SharedCluster = Arc<Mutex<FakeCluster>>;
+    
+    /// Register activities that read and mutate `cluster` the way the real ones mutate CMS/K8s
+    fn stateful_activities(cluster: &SharedCluster) -> ActivityRegistry {
+        fn handler<I, O>(
+            cluster: &SharedCluster,
+            name: &'static str,
+            f: fn(&mut FakeCluster, I) -> O,
+        ) -> impl Fn(ActivityContext, String) -> std::future::Ready<Result<String, String>> + Send + Sync + 'static
+        where
+            I: serde::de::DeserializeOwned + 'static,
+            O: serde::Serialize + 'static,
+        {
+            let cluster = cluster.clone();
+            move |_ctx, input| {
+                let input: I = serde_json::from_str(&input).unwrap();
+                let mut cluster = cluster.lock().unwrap();
+                cluster.calls.push(short_name(name));
+                if cluster.failing == Some(name) {
+                    return std::future::ready(Err(format!("{} unavailable", name)));
+                }
+                let output = f(&mut cluster, input);
+                std::future::ready(Ok(serde_json::to_string(&output).unwrap()))
+            }
+        }
+        
+        ActivityRegistry::builder()
+            .register(cms::get_instance_by_k8s_name::NAME, handler(cluster, cms::get_instance_by_k8s_name::NAME, |c, _: GetInstanceByK8sNameInput| {
+                GetInstanceByK8sNameOutput {
+                    found: c.record.is_some(),
+                    record: None,
+                    instance_actor_orchestration_id: c.record.as_ref().map(|_| "actor-test-pg".to_string()),
+                }
+            }))
+            .register(cms::claim_instance_deletion::NAME, handler(cluster, cms::claim_instance_deletion::NAME, |c, input: ClaimInstanceDeletionInput| {
+                let Some(record) = c.record.as_mut() else {
+                    return ClaimInstanceDeletionOutput { claimed: true, held_by: None };
+                };
+                let holder = record.delete_orchestration_id.as_deref();
+                match cms::claim_instance_deletion::conflicting_holder(&record.state, holder, &input.orchestration_id) {
+                    Some(other) => ClaimInstanceDeletionOutput { claimed: false, held_by: Some(other.to_string()) },
+                    None => {
+                        record.delete_orchestration_id = Some(input.orchestration_id);
+                        ClaimInstanceDeletionOutput { claimed: true, held_by: None }
+                    }
+                }
+            }))
+            .register(cms::release_instance_deletion::NAME, handler(cluster, cms::release_instance_deletion::NAME, |c, input: ReleaseInstanceDeletionInput| {
+                let released = c.record.as_mut()
+                    .filter(|r| r.delete_orchestration_id.as_deref() == Some(input.orchestration_id.as_str()))
+                    .map(|r| r.delete_orchestration_id = None)
+                    .is_some();
+                ReleaseInstanceDeletionOutput { released }
+            }))
+            .register(cms::update_instance_state::NAME, handler(cluster, cms::update_instance_state::NAME, |c, input: UpdateInstanceStateInput| {
+                let previous_state = c.record.as_mut().map(|r| std::mem::replace(&mut r.state, input.state));
+                UpdateInstanceStateOutput { updated: previous_state.is_some(), previous_state }
+            }))
+            .register(activities::raise_event::NAME, handler(cluster, activities::raise_event::NAME, |_, _: RaiseEventInput| {
+                RaiseEventOutput { raised: true }
+            }))
+            .register(activities::delete_postgres::NAME, handler(cluster, activities::delete_postgres::NAME, |c, _: DeletePostgresInput| {
+                DeletePostgresOutput { deleted: std::mem::take(&mut c.resources) }
+            }))
+            .register(cms::free_dns_name::NAME, handler(cluster, cms::free_dns_name::NAME, |c, _: FreeDnsNameInput| {
+                let freed = match c.record.as_mut() {
+                    Some(r) if !r.dns_name.starts_with("__deleted_") => {
+                        r.dns_name = format!("__deleted_{}", r.dns_name);
+                        true
+                    }
+                    _ => false,
+                };
+                FreeDnsNameOutput { freed }
+            }))
+            .register(cms::delete_instance_record::NAME, handler(cluster, cms::delete_instance_record::NAME, |c, _: DeleteInstanceRecordInput| {
+                DeleteInstanceRecordOutput { deleted: c.record.take().is_some() }
+            }))
+            .register(cms::record_instance_event::NAME, handler(cluster, cms::record_instance_event::NAME, |_, _: RecordInstanceEventInput| {
+                RecordInstanceEventOutput { recorded: true }
+            }))
+            .register(send_notification::NAME, handler(cluster, send_notification::NAME, |_, _: SendNotificationInput| {
+                SendNotificationOutput { sent: false }
+            }))
+            .build()
+    }
+    
+    #[tokio::test]
+    async fn test_delete_twice_is_idempotent() {
+        let cluster: SharedCluster = Arc::new(Mutex::new(FakeCluster {
+            record: Some(FakeRecord { state: "running".to_string(), dns_name: "mydb".to_string(), delete_orchestration_id: None }),
+            resources: true,
+            calls: Vec::new(),
+            failing: None,
+        }));
+        let orchestrations = OrchestrationRegistry::builder()
+            .register_typed(names::orchestrations::DELETE_INSTANCE, delete_instance_orchestration)
+            .build();
+        
+        let rt = TestRuntime::start(stateful_activities(&cluster), orchestrations).await;
+        
+        let mut outputs = Vec::new();
+        for attempt in ["delete-test-pg-1", "delete-test-pg-2"] {
+            let input = DeleteInstanceInput {
+                name: "test-pg".to_string(),
+                namespace: "toygres".to_string(),
+                orchestration_id: attempt.to_string(),
+                trace_level: None,
+            };
+            let status = rt.run(attempt, names::orchestrations::DELETE_INSTANCE, &input, Duration::from_secs(10)).await;
+            let duroxide::OrchestrationStatus::Completed { output } = status else {
+                panic!("{} did not complete: {:?}", attempt, status);
+            };
+            outputs.push(serde_json::from_str::<DeleteInstanceOutput>(&output).unwrap());
+            
+            let mut cluster = cluster.lock().unwrap();
+            assert!(cluster.record.is_none(), "record left behind after {}", attempt);
+            assert!(!cluster.resources, "resources left behind after {}", attempt);
+            if attempt.ends_with("-2") {
+                // The second pass only re-checks state and re-runs the K8s cleanup
+                assert_eq!(cluster.calls, vec!["cms-get-instance-by-k8s-name", "delete-postgres"]);
+            }
+            cluster.calls.clear();
+        }
+        rt.shutdown().await;
+        
+        assert!(outputs[0].deleted);
+        assert!(!outputs[1].deleted);
+        assert_eq!(outputs[0].instance_name, outputs[1].instance_name);
+    }
+    
+    #[tokio::test]
+    async fn test_delete_already_claimed_by_another_orchestration_is_a_no_op() {
+        let claimed = FakeRecord {
+            state: "deleting".to_string(),
+            dns_name: "mydb".to_string(),
+            delete_orchestration_id: Some("delete-bulk-test-pg".to_string()),
+        };
+        let cluster: SharedCluster = Arc::new(Mutex::new(FakeCluster {
+            record: Some(claimed.clone()),
+            resources: true,
+            calls: Vec::new(),
+            failing: None,
+        }));
+        let orchestrations = OrchestrationRegistry::builder()
+            .register_typed(names::orchestrations::DELETE_INSTANCE, delete_instance_orchestration)
+            .build();
+        
+        let rt = TestRuntime::start(stateful_activities(&cluster), orchestrations).await;
+        
+        let input = DeleteInstanceInput {
+            name: "test-pg".to_string(),
+            namespace: "toygres".to_string(),
+            orchestration_id: "delete-test-pg".to_string(),
+            trace_level: None,
+        };
+        let status = rt.run("delete-test-pg", names::orchestrations::DELETE_INSTANCE, &input, Duration::from_secs(10)).await;
+        rt.shutdown().await;
+        
+        let duroxide::OrchestrationStatus::Completed { output } = status else {
+            panic!("delete did not complete: {:?}", status);
+        };
+        let output: DeleteInstanceOutput = serde_json::from_str(&output).unwrap();
+        assert!(!output.deleted);
+        assert_eq!(
+            output.conflict.as_deref(),
+            Some("Instance is already being deleted by orchestration 'delete-bulk-test-pg'")
+        );
+        
+        // No signal, no K8s delete and no CMS writes: the other orchestration owns those
+        let cluster = cluster.lock().unwrap();
+        assert_eq!(cluster.calls, vec!["cms-get-instance-by-k8s-name", "cms-claim-instance-deletion"]);
+        assert_eq!(cluster.record.as_ref(), Some(&claimed));
+        assert!(cluster.resources);
+    }
+    
+    #[tokio::test]
+    async fn test_failed_delete_does_not_block_the_next_one() {
+        let cluster: SharedCluster = Arc::new(Mutex::new(FakeCluster {
+            record: Some(FakeRecord { state: "running".to_string(), dns_name: "mydb".to_string(), delete_orchestration_id: None }),
+            resources: true,
+            calls: Vec::new(),
+            failing: Some(activities::delete_postgres::NAME),
+        }));
+        let orchestrations = OrchestrationRegistry::builder()
+            .register_typed(names::orchestrations::DELETE_INSTANCE, delete_instance_orchestration)
+            .build();
+        
+        let rt = TestRuntime::start(stateful_activities(&cluster), orchestrations).await;
+        
+        let mut statuses = Vec::new();
+        for attempt in ["delete-test-pg-1", "delete-test-pg-2"] {
+            let input = DeleteInstanceInput {
+                name: "test-pg".to_string(),
+                namespace: "toygres".to_string(),
+                orchestration_id: attempt.to_string(),
+                trace_level: None,
+            };
+            statuses.push(rt.run(attempt, names::orchestrations::DELETE_INSTANCE, &input, Duration::from_secs(20)).await);
+            
+            // The outage is over by the time the instance is deleted again
+            let mut cluster = cluster.lock().unwrap();
+            if cluster.failing.take().is_some() {
+                let record = cluster.record.as_ref().unwrap();
+                assert_eq!(record.state, "deleting");
+                assert_eq!(record.delete_orchestration_id, None, "stale claim left by {}", attempt);
+                assert_eq!(cluster.calls.last(), Some(&"cms-release-instance-deletion"));
+            }
+        }
+        rt.shutdown().await;
+        
+        assert!(matches!(statuses[0], duroxide::OrchestrationStatus::Failed { .. }), "status: {:?}", statuses[0]);
+        let duroxide::OrchestrationStatus::Completed { output } = &statuses[1] else {
+            panic!("second delete did not complete: {:?}", statuses[1]);
+        };
+        let output: DeleteInstanceOutput = serde_json::from_str(output).unwrap();
+        assert!(output.deleted);
+        assert_eq!(output.conflict, None);
+        
+        let cluster = cluster.lock().unwrap();
+        assert!(cluster.record.is_none());
+        assert!(!cluster.resources);
+    }
+    
+    #[tokio::test]
+    async fn test_record_delete_failure_is_recorded_as_an_event() {
+        let cluster: SharedCluster = Arc::new(Mutex::new(FakeCluster {
+            record: Some(FakeRecord { state: "running".to_string(), dns_name: "mydb".to_string(), delete_orchestration_id: None }),
+            resources: true,
+            calls: Vec::new(),
+            failing: Some(cms::delete_instance_record::NAME),
+        }));
+        let orchestrations = OrchestrationRegistry::builder()
+            .register_typed(names::orchestrations::DELETE_INSTANCE, delete_instance_orchestration)
+            .build();
+        
+        let rt = TestRuntime::start(stateful_activities(&cluster), orchestrations).await;
+        
+        let input = DeleteInstanceInput {
+            name: "test-pg".to_string(),
+            namespace: "toygres".to_string(),
+            orchestration_id: "delete-test-pg".to_string(),
+            trace_level: None,
+        };
+        let status = rt.run("delete-test-pg", names::orchestrations::DELETE_INSTANCE, &input, Duration::from_secs(30)).await;
+        rt.shutdown().await;
+        
+        // The resources are gone, so the delete still completes
+        assert!(matches!(status, duroxide::OrchestrationStatus::Completed { .. }), "status: {:?}", status);
+        let cluster = cluster.lock().unwrap();
+        assert!(!cluster.resources);
+        assert!(cluster.record.is_some());
+        
+        let attempts = cluster.calls.iter().filter(|c| **c == "cms-delete-instance-record").count();
+        assert_eq!(attempts, CMS_RETRY_ATTEMPTS as usize);
+        let last = cluster.calls.iter().rposition(|c| *c == "cms-delete-instance-record").unwrap();
+        assert_eq!(cluster.calls[last + 1], "cms-record-instance-event");
+    }
 }
-
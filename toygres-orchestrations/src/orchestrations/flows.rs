@@ -3,6 +3,11 @@
 //! These Mermaid diagrams represent the expected flow of each orchestration.
 //! They can be used by the UI to show execution progress against the expected flow.
 
+use duroxide::{Event, EventKind};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
 /// Node IDs map to activity names for matching against execution history
 pub struct FlowDiagram {
     /// The orchestration name this flow belongs to
@@ -20,6 +25,7 @@ pub const CREATE_INSTANCE_FLOW: FlowDiagram = FlowDiagram {
     subgraph init["Initialization"]
         start(["▶ Start"])
         cms_record["📋 Create CMS Record<br/><small>Reserve DNS name</small>"]
+        preflight["📋 Preflight Capacity<br/><small>if requested</small>"]
     end
 
     subgraph deploy["Deploy to Kubernetes"]
@@ -31,8 +37,12 @@ pub const CREATE_INSTANCE_FLOW: FlowDiagram = FlowDiagram {
     end
 
     subgraph connect["Connection Setup"]
+        wait_ip{"⏳ External IP?<br/><small>LoadBalancer only</small>"}
+        ip_timer["⏱ Wait 5s"]
+        ip_timeout(["💥 Timeout"])
         get_conn["📋 Get Connection Strings<br/><small>with retry (5x)</small>"]
         test_conn["📋 Test Connection<br/><small>with retry (5x)</small>"]
+        verify_version["📋 Verify Version<br/><small>if strict</small>"]
     end
 
     subgraph finalize["Finalize"]
@@ -50,17 +60,26 @@ pub const CREATE_INSTANCE_FLOW: FlowDiagram = FlowDiagram {
     end
 
     start --> cms_record
-    cms_record --> deploy_k8s
+    cms_record --> preflight
+    preflight -->|OK| deploy_k8s
+    preflight -->|Insufficient| mark_failed
     deploy_k8s --> wait_ready
     wait_ready -->|No| timeout_check
     timeout_check -->|Yes| timer_wait
     timer_wait --> wait_ready
     timeout_check -->|No| timeout_fail
     timeout_fail --> mark_failed
-    wait_ready -->|Yes| get_conn
+    wait_ready -->|Yes| wait_ip
+    wait_ip -->|Yes / ClusterIP| get_conn
+    wait_ip -->|No, attempt < 24| ip_timer
+    ip_timer --> wait_ip
+    wait_ip -->|No, attempt 24| ip_timeout
+    ip_timeout --> mark_failed
     get_conn --> test_conn
-    test_conn -->|Success| update_running
+    test_conn -->|Success| verify_version
     test_conn -->|Fail| mark_failed
+    verify_version -->|Match / not strict| update_running
+    verify_version -->|Mismatch| mark_failed
     update_running --> start_actor
     start_actor --> record_actor
     record_actor --> success
@@ -77,18 +96,21 @@ pub const CREATE_INSTANCE_FLOW: FlowDiagram = FlowDiagram {
     classDef start fill:#a855f7,color:#fff,stroke:#9333ea
 
     class start start
-    class cms_record,deploy_k8s,get_conn,test_conn,update_running,record_actor,mark_failed,free_dns activity
-    class timer_wait timer
-    class wait_ready,timeout_check decision
+    class cms_record,preflight,deploy_k8s,get_conn,test_conn,verify_version,update_running,record_actor,mark_failed,free_dns activity
+    class timer_wait,ip_timer timer
+    class wait_ready,timeout_check,wait_ip decision
     class success success
-    class timeout_fail,failed failure
+    class timeout_fail,ip_timeout,failed failure
     class cleanup,start_actor suborg"#,
     node_mappings: &[
         ("cms_record", "cms-create-instance-record"),
+        ("preflight", "preflight-capacity"),
         ("deploy_k8s", "deploy-postgres"),
         ("wait_ready", "wait-for-ready"),
+        ("wait_ip", "get-service-external-ip"),
         ("get_conn", "get-connection-strings"),
         ("test_conn", "test-connection"),
+        ("verify_version", "verify-postgres-version"),
         ("update_running", "cms-update-instance-state"),
         ("start_actor", "instance-actor"),
         ("record_actor", "cms-record-instance-actor"),
@@ -106,46 +128,72 @@ pub const DELETE_INSTANCE_FLOW: FlowDiagram = FlowDiagram {
         start(["▶ Start"])
         get_cms["📋 Get CMS Record<br/><small>with retry (3x)</small>"]
         check_found{"Record Found?"}
+        claim["📋 Claim for Deletion<br/><small>with retry (3x)</small>"]
+        check_claimed{"Claimed?"}
+        conflict(["🏁 Held by Another Delete"])
         mark_deleting["📋 Mark State: Deleting"]
+        has_actor{"Has Instance Actor?"}
+        signal_actor["📋 Raise InstanceDeleted"]
     end
 
     subgraph delete["Delete Resources"]
         delete_k8s["📋 Delete K8s Resources<br/><small>with retry (3x)</small>"]
         mark_deleted["📋 Mark State: Deleted"]
+        release_claim["📋 Release Deletion Claim"]
+        failed(["💥 Failed"])
     end
 
     subgraph cleanup["Cleanup"]
-        delete_record["📋 Delete CMS Record"]
         free_dns["📋 Free DNS Name"]
+        has_private_dns{"Private DNS Record?"}
+        deregister_dns["📋 Deregister Private DNS<br/><small>with retry (3x)</small>"]
+        delete_record["📋 Delete CMS Record"]
         success(["🏁 Success"])
     end
 
     start --> get_cms
     get_cms --> check_found
-    check_found -->|Yes| mark_deleting
-    check_found -->|No| delete_k8s
-    mark_deleting --> delete_k8s
+    check_found -->|Yes| claim
+    check_found -->|No| has_actor
+    claim --> check_claimed
+    check_claimed -->|Yes| mark_deleting
+    check_claimed -->|No| conflict
+    mark_deleting --> has_actor
+    has_actor -->|Yes| signal_actor
+    has_actor -->|No| delete_k8s
+    signal_actor --> delete_k8s
     delete_k8s --> mark_deleted
-    mark_deleted --> delete_record
-    delete_record --> free_dns
-    free_dns --> success
+    delete_k8s -->|Failed| release_claim
+    release_claim --> failed
+    mark_deleted --> free_dns
+    free_dns --> has_private_dns
+    has_private_dns -->|Yes| deregister_dns
+    has_private_dns -->|No| delete_record
+    deregister_dns --> delete_record
+    delete_record --> success
 
     classDef activity fill:#3b82f6,color:#fff,stroke:#1d4ed8
     classDef decision fill:#f59e0b,color:#000,stroke:#d97706
     classDef success fill:#22c55e,color:#fff,stroke:#16a34a
+    classDef failure fill:#ef4444,color:#fff,stroke:#dc2626
     classDef start fill:#a855f7,color:#fff,stroke:#9333ea
 
     class start start
-    class get_cms,mark_deleting,delete_k8s,mark_deleted,delete_record,free_dns activity
-    class check_found decision
-    class success success"#,
+    class get_cms,claim,mark_deleting,signal_actor,delete_k8s,mark_deleted,release_claim,delete_record,free_dns,deregister_dns activity
+    class check_found,check_claimed,has_actor,has_private_dns decision
+    class success,conflict success
+    class failed failure"#,
     node_mappings: &[
         ("get_cms", "cms-get-instance-by-k8s-name"),
+        ("claim", "cms-claim-instance-deletion"),
         ("mark_deleting", "cms-update-instance-state"),
+        ("signal_actor", "raise-event"),
         ("delete_k8s", "delete-postgres"),
         ("mark_deleted", "cms-update-instance-state"),
-        ("delete_record", "cms-delete-instance-record"),
+        ("release_claim", "cms-release-instance-deletion"),
         ("free_dns", "cms-free-dns-name"),
+        ("deregister_dns", "deregister-private-dns"),
+        ("delete_record", "cms-delete-instance-record"),
     ],
 };
 
@@ -157,6 +205,7 @@ pub const INSTANCE_ACTOR_FLOW: FlowDiagram = FlowDiagram {
         start(["▶ Start Iteration"])
         get_conn["📋 Get Instance Connection<br/><small>with retry (3x)</small>"]
         check_exists{"Instance Exists?"}
+        check_deleting{"Deleting?"}
         check_conn{"Has Connection<br/>String?"}
         test_conn["📋 Test Connection<br/><small>with retry (3x)</small>"]
         record_health["📋 Record Health Check"]
@@ -166,7 +215,8 @@ pub const INSTANCE_ACTOR_FLOW: FlowDiagram = FlowDiagram {
     subgraph wait["Wait for Next Cycle"]
         race{{"⚡ Race"}}
         timer["⏱ Wait 30s"]
-        deletion_signal["⏳ Wait: InstanceDeleted"]
+        deletion_signal["⏳ Wait: InstanceDeleted / Cancel"]
+        config_signal["⏳ Wait: UpdateConfig / TriggerBackup / CheckNow"]
     end
 
     subgraph exit["Exit Conditions"]
@@ -179,7 +229,9 @@ pub const INSTANCE_ACTOR_FLOW: FlowDiagram = FlowDiagram {
     start --> get_conn
     get_conn --> check_exists
     check_exists -->|No| not_found
-    check_exists -->|Yes| check_conn
+    check_exists -->|Yes| check_deleting
+    check_deleting -->|Yes| race
+    check_deleting -->|No| check_conn
     check_conn -->|No| no_conn_continue
     check_conn -->|Yes| test_conn
     test_conn --> record_health
@@ -187,8 +239,10 @@ pub const INSTANCE_ACTOR_FLOW: FlowDiagram = FlowDiagram {
     update_health --> race
     race --> timer
     race --> deletion_signal
+    race --> config_signal
     timer -->|Winner| continue_new
     deletion_signal -->|Winner| deleted
+    config_signal -->|Winner| continue_new
 
     classDef activity fill:#3b82f6,color:#fff,stroke:#1d4ed8
     classDef timer fill:#06b6d4,color:#fff,stroke:#0891b2
@@ -202,7 +256,7 @@ pub const INSTANCE_ACTOR_FLOW: FlowDiagram = FlowDiagram {
     class start start
     class get_conn,test_conn,record_health,update_health activity
     class timer timer
-    class check_exists,check_conn decision
+    class check_exists,check_deleting,check_conn decision
     class not_found,deleted success
     class continue_new,no_conn_continue continue
     class race race
@@ -215,36 +269,160 @@ pub const INSTANCE_ACTOR_FLOW: FlowDiagram = FlowDiagram {
     ],
 };
 
+/// Every flow diagram
+static ALL_FLOWS: [&FlowDiagram; 3] = [
+    &CREATE_INSTANCE_FLOW,
+    &DELETE_INSTANCE_FLOW,
+    &INSTANCE_ACTOR_FLOW,
+];
+
+/// Flows keyed by both full (`toygres-orchestrations::orchestration::create-instance`)
+/// and short (`create-instance`) orchestration name, built on first lookup
+static FLOWS_BY_NAME: LazyLock<HashMap<&'static str, &'static FlowDiagram>> = LazyLock::new(|| {
+    ALL_FLOWS
+        .iter()
+        .flat_map(|flow| [(flow.orchestration_name, *flow), (short_name(flow.orchestration_name), *flow)])
+        .collect()
+});
+
 /// Get all flow diagrams
 pub fn get_all_flows() -> Vec<&'static FlowDiagram> {
-    vec![
-        &CREATE_INSTANCE_FLOW,
-        &DELETE_INSTANCE_FLOW,
-        &INSTANCE_ACTOR_FLOW,
-    ]
+    ALL_FLOWS.to_vec()
 }
 
-/// Get flow diagram by orchestration name
+/// Get flow diagram by full or short orchestration name
 pub fn get_flow_by_name(name: &str) -> Option<&'static FlowDiagram> {
-    // Match by full name or short name
-    let short_name = name.split("::").last().unwrap_or(name);
+    FLOWS_BY_NAME.get(name).or_else(|| FLOWS_BY_NAME.get(short_name(name))).copied()
+}
+
+/// `create-instance` from `toygres-orchestrations::orchestration::create-instance`
+fn short_name(name: &str) -> &str {
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+
+/// Execution state of a flow node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NodeStatus {
+    Done,
+    Running,
+    Failed,
+    Pending,
+}
+
+/// Status of each mapped node of `flow` given an orchestration's history.
+///
+/// A node follows the latest activity or sub-orchestration matching its pattern, so a
+/// polled or retried step reports its most recent attempt. Detached orchestrations
+/// count as done once started; nodes with nothing scheduled yet are pending.
+pub fn compute_flow_progress(flow: &FlowDiagram, history: &[Event]) -> Vec<(&'static str, NodeStatus)> {
+    let mut scheduled: Vec<(u64, &str)> = Vec::new();
+    let mut outcomes: HashMap<u64, NodeStatus> = HashMap::new();
     
-    match short_name {
-        "create-instance" => Some(&CREATE_INSTANCE_FLOW),
-        "delete-instance" => Some(&DELETE_INSTANCE_FLOW),
-        "instance-actor" => Some(&INSTANCE_ACTOR_FLOW),
-        _ => {
-            // Try full name match
-            if name.contains("create-instance") {
-                Some(&CREATE_INSTANCE_FLOW)
-            } else if name.contains("delete-instance") {
-                Some(&DELETE_INSTANCE_FLOW)
-            } else if name.contains("instance-actor") {
-                Some(&INSTANCE_ACTOR_FLOW)
-            } else {
-                None
+    for event in history {
+        match &event.kind {
+            EventKind::ActivityScheduled { name, .. } | EventKind::SubOrchestrationScheduled { name, .. } => {
+                scheduled.push((event.event_id, name));
+            }
+            EventKind::OrchestrationChained { name, .. } => {
+                scheduled.push((event.event_id, name));
+                outcomes.insert(event.event_id, NodeStatus::Done);
+            }
+            EventKind::ActivityCompleted { .. } | EventKind::SubOrchestrationCompleted { .. } => {
+                if let Some(source) = event.source_event_id {
+                    outcomes.insert(source, NodeStatus::Done);
+                }
             }
+            EventKind::ActivityFailed { .. } | EventKind::SubOrchestrationFailed { .. } => {
+                if let Some(source) = event.source_event_id {
+                    outcomes.insert(source, NodeStatus::Failed);
+                }
+            }
+            _ => {}
         }
     }
+    
+    flow.node_mappings.iter()
+        .map(|(node_id, pattern)| {
+            let status = scheduled.iter().rev()
+                .find(|(_, name)| name.contains(pattern))
+                .map(|(id, _)| outcomes.get(id).copied().unwrap_or(NodeStatus::Running))
+                .unwrap_or(NodeStatus::Pending);
+            (*node_id, status)
+        })
+        .collect()
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activities;
+    
+    #[test]
+    fn test_registered_orchestrations_resolve_to_their_flow() {
+        use crate::names::orchestrations;
+        
+        let registered = crate::registry::create_orchestration_registry().list_names();
+        for flow in get_all_flows() {
+            assert!(registered.iter().any(|name| name == flow.orchestration_name), "{} is not registered", flow.orchestration_name);
+            for name in [flow.orchestration_name, short_name(flow.orchestration_name)] {
+                let found = get_flow_by_name(name).map(|f| f.orchestration_name);
+                assert_eq!(found, Some(flow.orchestration_name), "{}", name);
+            }
+        }
+        assert_eq!(FLOWS_BY_NAME.len(), 2 * ALL_FLOWS.len());
+        
+        assert_eq!(get_flow_by_name(orchestrations::CREATE_INSTANCE).map(|f| f.mermaid), Some(CREATE_INSTANCE_FLOW.mermaid));
+        // A renamed crate prefix still resolves through the short name
+        assert!(get_flow_by_name("other-crate::orchestration::instance-actor").is_some());
+        assert!(get_flow_by_name(orchestrations::GC_ORPHANS).is_none());
+        assert!(get_flow_by_name("recreate-instance-later").is_none());
+    }
+    
+    #[test]
+    fn test_flow_progress_follows_latest_attempt() {
+        let event = |id: u64, source: Option<u64>, kind: EventKind| Event::with_event_id(id, "create-mydb", 1, source, kind);
+        let scheduled = |id: u64, name: &str| event(id, None, EventKind::ActivityScheduled { name: name.to_string(), input: "{}".to_string() });
+        let completed = |id: u64, source: u64| event(id, Some(source), EventKind::ActivityCompleted { result: "{}".to_string() });
+        
+        let history = vec![
+            scheduled(1, activities::cms::create_instance_record::NAME),
+            completed(2, 1),
+            scheduled(3, activities::deploy_postgres::NAME),
+            completed(4, 3),
+            scheduled(5, activities::wait_for_ready::NAME),
+            completed(6, 5),
+            scheduled(7, activities::wait_for_ready::NAME),
+            event(8, Some(7), EventKind::ActivityFailed {
+                details: duroxide::ErrorDetails::Application {
+                    kind: duroxide::AppErrorKind::ActivityFailed,
+                    message: "boom".to_string(),
+                    retryable: false,
+                },
+            }),
+            scheduled(9, activities::wait_for_ready::NAME),
+        ];
+        
+        // Every mapped node, in mapping order; the failed wait is superseded by its retry
+        assert_eq!(
+            compute_flow_progress(&CREATE_INSTANCE_FLOW, &history),
+            vec![
+                ("cms_record", NodeStatus::Done),
+                ("preflight", NodeStatus::Pending),
+                ("deploy_k8s", NodeStatus::Done),
+                ("wait_ready", NodeStatus::Running),
+                ("wait_ip", NodeStatus::Pending),
+                ("get_conn", NodeStatus::Pending),
+                ("test_conn", NodeStatus::Pending),
+                ("verify_version", NodeStatus::Pending),
+                ("update_running", NodeStatus::Pending),
+                ("start_actor", NodeStatus::Pending),
+                ("record_actor", NodeStatus::Pending),
+                ("mark_failed", NodeStatus::Pending),
+                ("free_dns", NodeStatus::Pending),
+                ("cleanup", NodeStatus::Pending),
+            ]
+        );
+    }
+}
@@ -0,0 +1,301 @@
+//! Orphan resource garbage collection orchestration
+//!
+//! Failed creates whose cleanup also failed, or cleanups interrupted by a crash, can
+//! leave StatefulSets, Services and PVCs behind with no CMS record using them. A sweep:
+//! 1. Lists Toygres-labeled resources in the namespace
+//! 2. Lists instances the CMS still considers live (anything not `deleted`/`failed`)
+//! 3. Picks instances with no live record whose resources are all older than the
+//!    grace period
+//! 4. Re-reads the live instances, drops any orphan that came back to life, and
+//!    deletes the rest by UID
+//!
+//! Resources are listed before the CMS because a create records its CMS row before it
+//! deploys anything, so a resource seen in step 1 already has its row by step 2.
+
+use duroxide::{OrchestrationContext, RetryPolicy, BackoffStrategy};
+use std::collections::{BTreeMap, HashSet};
+use std::time::{Duration, UNIX_EPOCH};
+use crate::types::{GcOrphansInput, GcOrphansOutput, OrphanedInstance, DEFAULT_GC_GRACE_PERIOD_SECS, MIN_GC_GRACE_PERIOD_SECS};
+use crate::trace::Tracer;
+use crate::activities::{self, cms};
+use crate::activity_types::{
+    ListToygresResourcesInput, ListToygresResourcesOutput, ToygresResource,
+    ListLiveInstancesInput, ListLiveInstancesOutput,
+    DeleteResourcesInput, DeleteResourcesOutput,
+};
+
+pub async fn gc_orphans_orchestration(
+    ctx: OrchestrationContext,
+    input: GcOrphansInput,
+) -> Result<GcOrphansOutput, String> {
+    let trace = Tracer::new(&ctx, input.trace_level);
+    let namespace = input.namespace.clone();
+    let grace_period_secs = input.grace_period_secs.unwrap_or(DEFAULT_GC_GRACE_PERIOD_SECS);
+    if grace_period_secs < MIN_GC_GRACE_PERIOD_SECS {
+        return Err(format!(
+            "grace_period_secs must be at least {} (got {})",
+            MIN_GC_GRACE_PERIOD_SECS, grace_period_secs
+        ));
+    }
+    trace.info(format!(
+        "Collecting orphaned resources in {} older than {}s{} (orchestration: {})",
+        namespace, grace_period_secs, if input.dry_run { " (dry run)" } else { "" }, input.orchestration_id
+    ));
+
+    // Step 1: List labeled resources
+    let listed = ctx
+        .schedule_activity_with_retry_typed::<ListToygresResourcesInput, ListToygresResourcesOutput>(
+            activities::list_toygres_resources::NAME,
+            &ListToygresResourcesInput { namespace: namespace.clone() },
+            RetryPolicy::new(3)
+                .with_backoff(BackoffStrategy::Fixed {
+                    delay: Duration::from_secs(2),
+                })
+                .with_timeout(Duration::from_secs(30)),
+        )
+        .await
+        .map_err(|e| format!("Failed to list resources: {}", e))?;
+
+    // Step 2: List live instances
+    let live = list_live_instances(&ctx, &namespace).await?;
+
+    // Step 3: Pick orphans past the grace period
+    let now_ms = ctx.utcnow().await
+        .map_err(|e| format!("Failed to get current time: {}", e))?
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| format!("Invalid current time: {}", e))?
+        .as_millis() as i64;
+    let mut orphans = find_orphans(&listed.resources, &live, now_ms, grace_period_secs);
+    trace.info(format!("{} resources checked, {} orphaned instances", listed.resources.len(), orphans.len()));
+
+    // Step 4: Delete them, after checking none has become live meanwhile
+    let mut deleted = Vec::new();
+    if !input.dry_run && !orphans.is_empty() {
+        let live = list_live_instances(&ctx, &namespace).await?;
+        orphans.retain(|orphan| {
+            let revived = live.contains(&orphan.instance);
+            if revived {
+                trace.warn(format!("{} has a live CMS record now, leaving its resources alone", orphan.instance));
+            }
+            !revived
+        });
+
+        let resources: Vec<ToygresResource> = orphans.iter().flat_map(|o| o.resources.clone()).collect();
+        if !resources.is_empty() {
+            trace.warn(format!(
+                "Deleting {} orphaned resources of: {}",
+                resources.len(),
+                orphans.iter().map(|o| o.instance.as_str()).collect::<Vec<_>>().join(", ")
+            ));
+            deleted = ctx
+                .schedule_activity_typed::<DeleteResourcesInput, DeleteResourcesOutput>(
+                    activities::delete_resources::NAME,
+                    &DeleteResourcesInput { namespace: namespace.clone(), resources },
+                )
+                .into_activity_typed::<DeleteResourcesOutput>()
+                .await
+                .map_err(|e| format!("Failed to delete orphaned resources: {}", e))?
+                .deleted;
+        }
+    }
+
+    let output = GcOrphansOutput {
+        checked_resources: listed.resources.len(),
+        orphans,
+        deleted,
+        dry_run: input.dry_run,
+    };
+
+    // Periodic mode: wait, then sweep again with fresh history
+    if let Some(interval_secs) = input.interval_secs {
+        ctx.schedule_timer(Duration::from_secs(interval_secs)).into_timer().await;
+        let input_json = serde_json::to_string(&input)
+            .map_err(|e| format!("Failed to serialize input: {}", e))?;
+        // The continue-as-new future never resolves; the runtime restarts this orchestration
+        ctx.continue_as_new(input_json).await?;
+    }
+
+    Ok(output)
+}
+
+async fn list_live_instances(ctx: &OrchestrationContext, namespace: &str) -> Result<HashSet<String>, String> {
+    ctx.schedule_activity_with_retry_typed::<ListLiveInstancesInput, ListLiveInstancesOutput>(
+        cms::list_live_instances::NAME,
+        &ListLiveInstancesInput { namespace: namespace.to_string() },
+        RetryPolicy::new(3)
+            .with_backoff(BackoffStrategy::Fixed {
+                delay: Duration::from_secs(2),
+            })
+            .with_timeout(Duration::from_secs(10)),
+    )
+    .await
+    .map(|output| output.k8s_names.into_iter().collect())
+    .map_err(|e| format!("Failed to list live instances: {}", e))
+}
+
+/// Group resources by instance and keep the instances that have no live CMS record
+/// and whose resources are all older than the grace period.
+///
+/// A resource without a creation time is treated as new, so its instance is kept.
+/// Resources are ordered Service, StatefulSet, PVC, the order the delete path uses.
+fn find_orphans(
+    resources: &[ToygresResource],
+    live: &HashSet<String>,
+    now_ms: i64,
+    grace_period_secs: u64,
+) -> Vec<OrphanedInstance> {
+    let cutoff_ms = now_ms.saturating_sub((grace_period_secs as i64).saturating_mul(1000));
+
+    let mut by_instance: BTreeMap<&str, Vec<ToygresResource>> = BTreeMap::new();
+    for resource in resources {
+        by_instance.entry(resource.instance.as_str()).or_default().push(resource.clone());
+    }
+
+    by_instance
+        .into_iter()
+        .filter(|(instance, _)| !live.contains(*instance))
+        .filter(|(_, resources)| resources.iter().all(|r| r.created_at_ms.is_some_and(|t| t <= cutoff_ms)))
+        .map(|(instance, mut resources)| {
+            resources.sort_by_key(|r| match r.kind.as_str() {
+                activities::list_toygres_resources::SERVICE => 0,
+                activities::list_toygres_resources::STATEFUL_SET => 1,
+                _ => 2,
+            });
+            OrphanedInstance { instance: instance.to_string(), resources }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activities::list_toygres_resources::{PERSISTENT_VOLUME_CLAIM, SERVICE, STATEFUL_SET};
+    use crate::names;
+    use crate::test_support::{call_names, mock, run_orchestration, CallLog};
+    use duroxide::runtime::registry::ActivityRegistry;
+    use duroxide::OrchestrationRegistry;
+    use std::sync::Arc;
+
+    const HOUR_MS: i64 = 3_600_000;
+
+    fn resource(kind: &str, instance: &str, created_at_ms: Option<i64>) -> ToygresResource {
+        let name = activities::list_toygres_resources::expected_name(kind, instance).unwrap();
+        ToygresResource {
+            kind: kind.to_string(),
+            uid: Some(format!("uid-{}", name)),
+            name,
+            instance: instance.to_string(),
+            created_at_ms,
+        }
+    }
+
+    #[test]
+    fn test_orphans_exclude_live_and_recent_instances() {
+        let now = 100 * HOUR_MS;
+        let old = Some(now - 2 * HOUR_MS);
+        let resources = vec![
+            // Live instance: never collected, however old
+            resource(STATEFUL_SET, "live-1", old),
+            resource(SERVICE, "live-1", old),
+            // Orphan past the grace period
+            resource(PERSISTENT_VOLUME_CLAIM, "gone-1", old),
+            resource(STATEFUL_SET, "gone-1", old),
+            resource(SERVICE, "gone-1", old),
+            // Orphan with one resource still inside the grace period
+            resource(STATEFUL_SET, "gone-2", old),
+            resource(SERVICE, "gone-2", Some(now - 10 * 60_000)),
+            // Orphan without a creation time
+            resource(PERSISTENT_VOLUME_CLAIM, "gone-3", None),
+        ];
+        let live = HashSet::from(["live-1".to_string()]);
+
+        let orphans = find_orphans(&resources, &live, now, 3600);
+        assert_eq!(orphans.len(), 1, "orphans: {:?}", orphans);
+        assert_eq!(orphans[0].instance, "gone-1");
+        let kinds: Vec<&str> = orphans[0].resources.iter().map(|r| r.kind.as_str()).collect();
+        assert_eq!(kinds, vec![SERVICE, STATEFUL_SET, PERSISTENT_VOLUME_CLAIM]);
+
+        // A longer grace period spares it too
+        assert!(find_orphans(&resources, &live, now, 3 * 3600).is_empty());
+        // A shorter one catches gone-2 as well
+        let instances: Vec<String> = find_orphans(&resources, &live, now, 300).into_iter().map(|o| o.instance).collect();
+        assert_eq!(instances, vec!["gone-1".to_string(), "gone-2".to_string()]);
+    }
+
+    async fn run(dry_run: bool, calls: &CallLog) -> GcOrphansOutput {
+        let resources = vec![
+            resource(STATEFUL_SET, "live-1", Some(0)),
+            resource(SERVICE, "gone-1", Some(0)),
+            resource(PERSISTENT_VOLUME_CLAIM, "gone-1", Some(0)),
+        ];
+        let activities = ActivityRegistry::builder()
+            .register(activities::list_toygres_resources::NAME, mock(calls, activities::list_toygres_resources::NAME, serde_json::json!({
+                "resources": resources,
+            })))
+            .register(cms::list_live_instances::NAME, mock(calls, cms::list_live_instances::NAME, serde_json::json!({
+                "k8s_names": ["live-1"],
+            })))
+            .register(activities::delete_resources::NAME, mock(calls, activities::delete_resources::NAME, serde_json::json!({
+                "deleted": ["Service/gone-1-svc", "PersistentVolumeClaim/gone-1-pvc"],
+            })))
+            .build();
+        let orchestrations = OrchestrationRegistry::builder()
+            .register_typed(names::orchestrations::GC_ORPHANS, gc_orphans_orchestration)
+            .build();
+
+        let input = GcOrphansInput {
+            namespace: "toygres".to_string(),
+            grace_period_secs: None,
+            dry_run,
+            interval_secs: None,
+            orchestration_id: "gc-test".to_string(),
+            trace_level: None,
+        };
+        let status = run_orchestration(
+            activities,
+            orchestrations,
+            "gc-test",
+            names::orchestrations::GC_ORPHANS,
+            &input,
+            Duration::from_secs(10),
+        ).await;
+
+        match status {
+            duroxide::OrchestrationStatus::Completed { output } => serde_json::from_str(&output).unwrap(),
+            other => panic!("status: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sweep_deletes_only_orphaned_resources() {
+        let calls: CallLog = Arc::default();
+        let output = run(false, &calls).await;
+
+        assert_eq!(output.checked_resources, 3);
+        assert_eq!(output.orphans.len(), 1);
+        assert_eq!(output.deleted.len(), 2);
+
+        assert_eq!(call_names(&calls), vec![
+            "list-toygres-resources",
+            "cms-list-live-instances",
+            "cms-list-live-instances",
+            "delete-resources",
+        ]);
+        let calls = calls.lock().unwrap().clone();
+        let names: Vec<&str> = calls[3].1["resources"].as_array().unwrap().iter()
+            .map(|r| r["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["gone-1-svc", "gone-1-pvc"]);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_deletes_nothing() {
+        let calls: CallLog = Arc::default();
+        let output = run(true, &calls).await;
+
+        assert!(output.dry_run);
+        assert_eq!(output.orphans[0].instance, "gone-1");
+        assert!(output.deleted.is_empty());
+        assert!(!call_names(&calls).contains(&"delete-resources".to_string()));
+    }
+}
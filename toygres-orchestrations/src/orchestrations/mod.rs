@@ -1,5 +1,9 @@
 pub mod create_instance;
 pub mod delete_instance;
 pub mod instance_actor;
+pub mod bump_minor_version;
+pub mod run_maintenance;
+pub mod expose_instance;
+pub mod gc_orphans;
 pub mod flows;
 
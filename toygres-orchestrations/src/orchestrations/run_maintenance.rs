@@ -0,0 +1,177 @@
+//! Run maintenance orchestration
+//!
+//! Runs `VACUUM (ANALYZE)` (or `VACUUM (FULL, ANALYZE)` when `full` is set) against
+//! an instance:
+//! 1. Validate the table list
+//! 2. Load the instance's connection info from the CMS; it must be `running`
+//! 3. Run the maintenance activity and return its stats
+//!
+//! The maintenance activity is not retried: a failed VACUUM FULL may have held its
+//! locks for a long time already, so rerunning it is left to the caller.
+
+use duroxide::{OrchestrationContext, RetryPolicy, BackoffStrategy};
+use std::time::Duration;
+use crate::types::{RunMaintenanceOrchestrationInput, RunMaintenanceOrchestrationOutput};
+use crate::trace::Tracer;
+use crate::activities::{self, cms};
+use crate::activity_types::{
+    GetInstanceConnectionInput, GetInstanceConnectionOutput,
+    RunMaintenanceInput, RunMaintenanceOutput, PasswordSecretRef,
+};
+
+pub async fn run_maintenance_orchestration(
+    ctx: OrchestrationContext,
+    input: RunMaintenanceOrchestrationInput,
+) -> Result<RunMaintenanceOrchestrationOutput, String> {
+    let trace = Tracer::new(&ctx, input.trace_level);
+    trace.info(format!(
+        "Running {} on {} (orchestration: {})",
+        activities::run_maintenance::vacuum_command(input.full), input.name, input.orchestration_id
+    ));
+
+    // Step 1: Validate the table list
+    activities::run_maintenance::validate_tables(input.tables.as_deref())
+        .map_err(|errors| errors.join("; "))?;
+    if input.full {
+        trace.warn(activities::run_maintenance::VACUUM_FULL_WARNING);
+    }
+
+    let namespace = input.namespace.clone();
+
+    // Step 2: Load connection info
+    let conn_info = ctx
+        .schedule_activity_with_retry_typed::<GetInstanceConnectionInput, GetInstanceConnectionOutput>(
+            cms::get_instance_connection::NAME,
+            &GetInstanceConnectionInput {
+                k8s_name: input.name.clone(),
+            },
+            RetryPolicy::new(3)
+                .with_backoff(BackoffStrategy::Fixed {
+                    delay: Duration::from_secs(2),
+                })
+                .with_timeout(Duration::from_secs(10)),
+        )
+        .await
+        .map_err(|e| format!("Failed to get connection info after retries: {}", e))?;
+
+    if !conn_info.found {
+        return Err(format!("Instance {} not found in CMS", input.name));
+    }
+    if conn_info.state.as_deref() != Some("running") {
+        return Err(format!(
+            "Instance {} is '{}', maintenance only runs on running instances",
+            input.name,
+            conn_info.state.as_deref().unwrap_or("unknown")
+        ));
+    }
+    let connection_string = conn_info.connection_string
+        .ok_or_else(|| "No connection string recorded for instance".to_string())?;
+
+    // Step 3: Run maintenance
+    trace.info("Step 3: Running maintenance");
+    let stats = ctx
+        .schedule_activity_typed::<RunMaintenanceInput, RunMaintenanceOutput>(
+            activities::run_maintenance::NAME,
+            &RunMaintenanceInput {
+                connection_string,
+                password_secret: conn_info.password_secret_ref.map(|name| PasswordSecretRef {
+                    namespace: namespace.clone(),
+                    name,
+                }),
+                full: input.full,
+                tables: input.tables.clone(),
+            },
+        )
+        .into_activity_typed::<RunMaintenanceOutput>()
+        .await?;
+
+    trace.info(format!(
+        "Maintenance complete: {} table(s) in {}ms",
+        stats.tables_processed, stats.duration_ms
+    ));
+
+    Ok(RunMaintenanceOrchestrationOutput {
+        instance_name: input.name,
+        stats,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::names;
+    use crate::test_support::{mock, run_orchestration, CallLog};
+    use duroxide::runtime::registry::ActivityRegistry;
+    use duroxide::OrchestrationRegistry;
+    use std::sync::Arc;
+
+    async fn run(input: RunMaintenanceOrchestrationInput, calls: &CallLog) -> duroxide::OrchestrationStatus {
+        let activities = ActivityRegistry::builder()
+            .register(cms::get_instance_connection::NAME, mock(calls, cms::get_instance_connection::NAME, serde_json::json!({
+                "found": true,
+                "connection_string": "postgresql://postgres@10.0.0.1:5432/postgres",
+                "state": "running",
+                "password_secret_ref": "mydb-1a2b3c4d-password",
+            })))
+            .register(activities::run_maintenance::NAME, mock(calls, activities::run_maintenance::NAME, serde_json::json!({
+                "command": "VACUUM (FULL, ANALYZE)",
+                "tables_processed": 1,
+                "tables": ["public.orders"],
+                "duration_ms": 42,
+            })))
+            .build();
+        let orchestrations = OrchestrationRegistry::builder()
+            .register_typed(names::orchestrations::RUN_MAINTENANCE, run_maintenance_orchestration)
+            .build();
+
+        run_orchestration(
+            activities,
+            orchestrations,
+            &input.orchestration_id,
+            names::orchestrations::RUN_MAINTENANCE,
+            &input,
+            Duration::from_secs(10),
+        ).await
+    }
+
+    fn input(tables: Option<Vec<String>>) -> RunMaintenanceOrchestrationInput {
+        RunMaintenanceOrchestrationInput {
+            name: "mydb-1a2b3c4d".to_string(),
+            namespace: "toygres".to_string(),
+            full: true,
+            tables,
+            orchestration_id: "maintenance-mydb".to_string(),
+            trace_level: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_passes_options_and_returns_stats() {
+        let calls: CallLog = Arc::default();
+        let status = run(input(Some(vec!["public.orders".to_string()])), &calls).await;
+
+        let output: RunMaintenanceOrchestrationOutput = match status {
+            duroxide::OrchestrationStatus::Completed { output } => serde_json::from_str(&output).unwrap(),
+            other => panic!("status: {:?}", other),
+        };
+        assert_eq!(output.instance_name, "mydb-1a2b3c4d");
+        assert_eq!(output.stats.tables_processed, 1);
+        assert_eq!(output.stats.duration_ms, 42);
+
+        let calls = calls.lock().unwrap().clone();
+        assert_eq!(calls.len(), 2);
+        let maintenance = &calls[1].1;
+        assert_eq!(maintenance["full"], true);
+        assert_eq!(maintenance["tables"], serde_json::json!(["public.orders"]));
+        assert_eq!(maintenance["password_secret"]["namespace"], "toygres");
+    }
+
+    #[tokio::test]
+    async fn test_invalid_tables_fail_before_connecting() {
+        let calls: CallLog = Arc::default();
+        let status = run(input(Some(vec!["orders; DROP TABLE orders".to_string()])), &calls).await;
+
+        assert!(matches!(status, duroxide::OrchestrationStatus::Failed { .. }), "status: {:?}", status);
+        assert!(calls.lock().unwrap().is_empty());
+    }
+}
@@ -1,9 +1,35 @@
 //! Registry builders for Toygres orchestrations and activities
 
-use duroxide::runtime::registry::ActivityRegistry;
-use duroxide::OrchestrationRegistry;
+use duroxide::runtime::registry::{ActivityRegistry, ActivityRegistryBuilder};
+use duroxide::{OrchestrationRegistry, OrchestrationRegistryBuilder};
+use schemars::schema::RootSchema;
+use schemars::{schema_for, JsonSchema};
+use serde::Serialize;
 use crate::names::orchestrations;
 use crate::activities;
+use crate::activity_types::*;
+use crate::metrics::RegisterTimed;
+use crate::trace::orchestration_span;
+use crate::types::{CreateInstanceInput, DeleteInstanceInput};
+use tracing::Instrument;
+
+/// A registered activity with the JSON schemas of its typed input and output
+#[derive(Debug, Clone, Serialize)]
+pub struct ActivityDescriptor {
+    pub name: &'static str,
+    pub input_schema: RootSchema,
+    pub output_schema: RootSchema,
+}
+
+impl ActivityDescriptor {
+    fn new<In: JsonSchema, Out: JsonSchema>(name: &'static str) -> Self {
+        Self {
+            name,
+            input_schema: schema_for!(In),
+            output_schema: schema_for!(Out),
+        }
+    }
+}
 
 /// Create an OrchestrationRegistry with all Toygres orchestrations
 ///
@@ -15,20 +41,60 @@ use crate::activities;
 /// let orchestrations = create_orchestration_registry();
 /// ```
 pub fn create_orchestration_registry() -> OrchestrationRegistry {
+    orchestration_registry_builder().build()
+}
+
+/// An OrchestrationRegistry builder with all Toygres orchestrations registered, for
+/// embedding Toygres alongside your own orchestrations
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use duroxide::OrchestrationContext;
+/// use toygres_orchestrations::registry::orchestration_registry_builder;
+/// 
+/// let orchestrations = orchestration_registry_builder()
+///     .register("my-app::orchestration::nightly-report", |_ctx: OrchestrationContext, input: String| async move {
+///         Ok(input)
+///     })
+///     .build();
+/// ```
+pub fn orchestration_registry_builder() -> OrchestrationRegistryBuilder {
     OrchestrationRegistry::builder()
         .register_typed(
             orchestrations::CREATE_INSTANCE,
-            crate::orchestrations::create_instance::create_instance_orchestration,
+            |ctx, input: CreateInstanceInput| {
+                let span = orchestration_span(&input.orchestration_id, &input.name);
+                crate::orchestrations::create_instance::create_instance_orchestration(ctx, input).instrument(span)
+            },
         )
         .register_typed(
             orchestrations::DELETE_INSTANCE,
-            crate::orchestrations::delete_instance::delete_instance_orchestration,
+            |ctx, input: DeleteInstanceInput| {
+                let span = orchestration_span(&input.orchestration_id, &input.name);
+                crate::orchestrations::delete_instance::delete_instance_orchestration(ctx, input).instrument(span)
+            },
         )
         .register_typed(
             orchestrations::INSTANCE_ACTOR,
             crate::orchestrations::instance_actor::instance_actor_orchestration,
         )
-        .build()
+        .register_typed(
+            orchestrations::BUMP_MINOR_VERSION,
+            crate::orchestrations::bump_minor_version::bump_minor_version_orchestration,
+        )
+        .register_typed(
+            orchestrations::RUN_MAINTENANCE,
+            crate::orchestrations::run_maintenance::run_maintenance_orchestration,
+        )
+        .register_typed(
+            orchestrations::EXPOSE_INSTANCE,
+            crate::orchestrations::expose_instance::expose_instance_orchestration,
+        )
+        .register_typed(
+            orchestrations::GC_ORPHANS,
+            crate::orchestrations::gc_orphans::gc_orphans_orchestration,
+        )
 }
 
 /// Create an ActivityRegistry with all Toygres activities
@@ -41,70 +107,208 @@ pub fn create_orchestration_registry() -> OrchestrationRegistry {
 /// let activities = create_activity_registry();
 /// ```
 pub fn create_activity_registry() -> ActivityRegistry {
+    activity_registry_builder().build()
+}
+
+/// An ActivityRegistry builder with all Toygres activities registered, for adding
+/// your own activities (e.g. a notification) before `.build()`
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use duroxide::ActivityContext;
+/// use toygres_orchestrations::registry::activity_registry_builder;
+/// 
+/// let activities = activity_registry_builder()
+///     .register_typed("my-app::activity::notify", |_ctx: ActivityContext, message: String| async move {
+///         Ok::<_, String>(message)
+///     })
+///     .build();
+/// ```
+pub fn activity_registry_builder() -> ActivityRegistryBuilder {
     ActivityRegistry::builder()
         // K8s activities
-        .register_typed(
+        .register_timed(
             activities::deploy_postgres::NAME,
             activities::deploy_postgres::activity,
         )
-        .register_typed(
+        .register_timed(
+            activities::preflight_capacity::NAME,
+            activities::preflight_capacity::activity,
+        )
+        .register_timed(
             activities::delete_postgres::NAME,
             activities::delete_postgres::activity,
         )
-        .register_typed(
+        .register_timed(
             activities::wait_for_ready::NAME,
             activities::wait_for_ready::activity,
         )
-        .register_typed(
+        .register_timed(
+            activities::wait_for_rollout::NAME,
+            activities::wait_for_rollout::activity,
+        )
+        .register_timed(
+            activities::patch_image::NAME,
+            activities::patch_image::activity,
+        )
+        .register_timed(
+            activities::expose_service::NAME,
+            activities::expose_service::activity,
+        )
+        .register_timed(
+            activities::list_toygres_resources::NAME,
+            activities::list_toygres_resources::activity,
+        )
+        .register_timed(
+            activities::delete_resources::NAME,
+            activities::delete_resources::activity,
+        )
+        .register_timed(
+            activities::get_service_external_ip::NAME,
+            activities::get_service_external_ip::activity,
+        )
+        .register_timed(
             activities::get_connection_strings::NAME,
             activities::get_connection_strings::activity,
         )
-        .register_typed(
+        .register_timed(
+            activities::register_private_dns::NAME,
+            activities::register_private_dns::activity,
+        )
+        .register_timed(
+            activities::deregister_private_dns::NAME,
+            activities::deregister_private_dns::activity,
+        )
+        .register_timed(
             activities::test_connection::NAME,
             activities::test_connection::activity,
         )
-        .register_typed(
+        .register_timed(
+            activities::verify_postgres_version::NAME,
+            activities::verify_postgres_version::activity,
+        )
+        .register_timed(
+            activities::run_maintenance::NAME,
+            activities::run_maintenance::activity,
+        )
+        .register_timed(
             activities::raise_event::NAME,
             activities::raise_event::activity,
         )
+        .register_timed(
+            activities::send_notification::NAME,
+            activities::send_notification::activity,
+        )
         // CMS activities
-        .register_typed(
+        .register_timed(
             activities::cms::create_instance_record::NAME,
             activities::cms::create_instance_record::activity,
         )
-        .register_typed(
+        .register_timed(
             activities::cms::update_instance_state::NAME,
             activities::cms::update_instance_state::activity,
         )
-        .register_typed(
+        .register_timed(
             activities::cms::free_dns_name::NAME,
             activities::cms::free_dns_name::activity,
         )
-        .register_typed(
+        .register_timed(
+            activities::cms::claim_instance_deletion::NAME,
+            activities::cms::claim_instance_deletion::activity,
+        )
+        .register_timed(
+            activities::cms::release_instance_deletion::NAME,
+            activities::cms::release_instance_deletion::activity,
+        )
+        .register_timed(
             activities::cms::get_instance_by_k8s_name::NAME,
             activities::cms::get_instance_by_k8s_name::activity,
         )
-        .register_typed(
+        .register_timed(
             activities::cms::get_instance_connection::NAME,
             activities::cms::get_instance_connection::activity,
         )
-        .register_typed(
+        .register_timed(
             activities::cms::record_health_check::NAME,
             activities::cms::record_health_check::activity,
         )
-        .register_typed(
+        .register_timed(
             activities::cms::update_instance_health::NAME,
             activities::cms::update_instance_health::activity,
         )
-        .register_typed(
+        .register_timed(
             activities::cms::record_instance_actor::NAME,
             activities::cms::record_instance_actor::activity,
         )
-        .register_typed(
+        .register_timed(
             activities::cms::delete_instance_record::NAME,
             activities::cms::delete_instance_record::activity,
         )
-        .build()
+        .register_timed(
+            activities::cms::update_postgres_version::NAME,
+            activities::cms::update_postgres_version::activity,
+        )
+        .register_timed(
+            activities::cms::update_external_ip::NAME,
+            activities::cms::update_external_ip::activity,
+        )
+        .register_timed(
+            activities::cms::update_exposure::NAME,
+            activities::cms::update_exposure::activity,
+        )
+        .register_timed(
+            activities::cms::record_instance_event::NAME,
+            activities::cms::record_instance_event::activity,
+        )
+        .register_timed(
+            activities::cms::list_live_instances::NAME,
+            activities::cms::list_live_instances::activity,
+        )
+}
+
+/// Describe every activity registered by [`create_activity_registry`]
+///
+/// Keep in sync with the registry; a test checks that every registered name has a descriptor.
+pub fn activity_descriptors() -> Vec<ActivityDescriptor> {
+    vec![
+        // K8s activities
+        ActivityDescriptor::new::<DeployPostgresInput, DeployPostgresOutput>(activities::deploy_postgres::NAME),
+        ActivityDescriptor::new::<PreflightCapacityInput, PreflightCapacityOutput>(activities::preflight_capacity::NAME),
+        ActivityDescriptor::new::<DeletePostgresInput, DeletePostgresOutput>(activities::delete_postgres::NAME),
+        ActivityDescriptor::new::<WaitForReadyInput, WaitForReadyOutput>(activities::wait_for_ready::NAME),
+        ActivityDescriptor::new::<WaitForRolloutInput, WaitForRolloutOutput>(activities::wait_for_rollout::NAME),
+        ActivityDescriptor::new::<PatchImageInput, PatchImageOutput>(activities::patch_image::NAME),
+        ActivityDescriptor::new::<ExposeServiceInput, ExposeServiceOutput>(activities::expose_service::NAME),
+        ActivityDescriptor::new::<ListToygresResourcesInput, ListToygresResourcesOutput>(activities::list_toygres_resources::NAME),
+        ActivityDescriptor::new::<DeleteResourcesInput, DeleteResourcesOutput>(activities::delete_resources::NAME),
+        ActivityDescriptor::new::<GetServiceExternalIpInput, GetServiceExternalIpOutput>(activities::get_service_external_ip::NAME),
+        ActivityDescriptor::new::<GetConnectionStringsInput, GetConnectionStringsOutput>(activities::get_connection_strings::NAME),
+        ActivityDescriptor::new::<RegisterPrivateDnsInput, RegisterPrivateDnsOutput>(activities::register_private_dns::NAME),
+        ActivityDescriptor::new::<DeregisterPrivateDnsInput, DeregisterPrivateDnsOutput>(activities::deregister_private_dns::NAME),
+        ActivityDescriptor::new::<TestConnectionInput, TestConnectionOutput>(activities::test_connection::NAME),
+        ActivityDescriptor::new::<VerifyPostgresVersionInput, VerifyPostgresVersionOutput>(activities::verify_postgres_version::NAME),
+        ActivityDescriptor::new::<RunMaintenanceInput, RunMaintenanceOutput>(activities::run_maintenance::NAME),
+        ActivityDescriptor::new::<RaiseEventInput, RaiseEventOutput>(activities::raise_event::NAME),
+        ActivityDescriptor::new::<SendNotificationInput, SendNotificationOutput>(activities::send_notification::NAME),
+        // CMS activities
+        ActivityDescriptor::new::<CreateInstanceRecordInput, CreateInstanceRecordOutput>(activities::cms::create_instance_record::NAME),
+        ActivityDescriptor::new::<UpdateInstanceStateInput, UpdateInstanceStateOutput>(activities::cms::update_instance_state::NAME),
+        ActivityDescriptor::new::<FreeDnsNameInput, FreeDnsNameOutput>(activities::cms::free_dns_name::NAME),
+        ActivityDescriptor::new::<ClaimInstanceDeletionInput, ClaimInstanceDeletionOutput>(activities::cms::claim_instance_deletion::NAME),
+        ActivityDescriptor::new::<ReleaseInstanceDeletionInput, ReleaseInstanceDeletionOutput>(activities::cms::release_instance_deletion::NAME),
+        ActivityDescriptor::new::<GetInstanceByK8sNameInput, GetInstanceByK8sNameOutput>(activities::cms::get_instance_by_k8s_name::NAME),
+        ActivityDescriptor::new::<GetInstanceConnectionInput, GetInstanceConnectionOutput>(activities::cms::get_instance_connection::NAME),
+        ActivityDescriptor::new::<RecordHealthCheckInput, RecordHealthCheckOutput>(activities::cms::record_health_check::NAME),
+        ActivityDescriptor::new::<UpdateInstanceHealthInput, UpdateInstanceHealthOutput>(activities::cms::update_instance_health::NAME),
+        ActivityDescriptor::new::<RecordInstanceActorInput, RecordInstanceActorOutput>(activities::cms::record_instance_actor::NAME),
+        ActivityDescriptor::new::<DeleteInstanceRecordInput, DeleteInstanceRecordOutput>(activities::cms::delete_instance_record::NAME),
+        ActivityDescriptor::new::<UpdatePostgresVersionInput, UpdatePostgresVersionOutput>(activities::cms::update_postgres_version::NAME),
+        ActivityDescriptor::new::<UpdateExternalIpInput, UpdateExternalIpOutput>(activities::cms::update_external_ip::NAME),
+        ActivityDescriptor::new::<UpdateExposureInput, UpdateExposureOutput>(activities::cms::update_exposure::NAME),
+        ActivityDescriptor::new::<RecordInstanceEventInput, RecordInstanceEventOutput>(activities::cms::record_instance_event::NAME),
+        ActivityDescriptor::new::<ListLiveInstancesInput, ListLiveInstancesOutput>(activities::cms::list_live_instances::NAME),
+    ]
 }
 
 #[cfg(test)]
@@ -122,5 +326,48 @@ mod tests {
         let _registry = create_activity_registry();
         // Registry creation should not panic
     }
+    
+    #[test]
+    fn test_builders_accept_custom_registrations() {
+        use duroxide::{ActivityContext, OrchestrationContext};
+        
+        const NOTIFY: &str = "my-app::activity::notify";
+        const REPORT: &str = "my-app::orchestration::nightly-report";
+        
+        let activity_registry = activity_registry_builder()
+            .register_typed(NOTIFY, |_ctx: ActivityContext, message: String| async move {
+                Ok::<_, String>(message)
+            })
+            .build_result()
+            .unwrap();
+        let names = activity_registry.list_names();
+        assert!(names.iter().any(|n| n == NOTIFY));
+        assert!(names.iter().any(|n| n == activities::deploy_postgres::NAME));
+        assert_eq!(names.len(), create_activity_registry().list_names().len() + 1);
+        
+        let orchestration_registry = orchestration_registry_builder()
+            .register(REPORT, |_ctx: OrchestrationContext, input: String| async move { Ok(input) })
+            .build_result()
+            .unwrap();
+        let names = orchestration_registry.list_names();
+        assert!(names.iter().any(|n| n == REPORT));
+        assert!(names.iter().any(|n| n == orchestrations::CREATE_INSTANCE));
+    }
+    
+    #[test]
+    fn test_every_registered_activity_has_a_descriptor() {
+        let mut registered = create_activity_registry().list_names();
+        registered.sort();
+        
+        let descriptors = activity_descriptors();
+        let mut described: Vec<String> = descriptors.iter().map(|d| d.name.to_string()).collect();
+        described.sort();
+        
+        assert_eq!(registered, described);
+        
+        let deploy = descriptors.iter().find(|d| d.name == activities::deploy_postgres::NAME).unwrap();
+        let properties = &deploy.input_schema.schema.object.as_ref().unwrap().properties;
+        assert!(properties.contains_key("instance_name"));
+    }
 }
 
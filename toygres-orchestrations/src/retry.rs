@@ -0,0 +1,223 @@
+//! Activity retries that report how many attempts they made
+//!
+//! Duroxide's `schedule_activity_with_retry_typed` returns only the last error, so a
+//! failure after five attempts over two minutes looks the same as one that failed
+//! immediately. [`schedule_activity_with_retry_counted`] follows the same policy
+//! (backoff between attempts, no retry after a per-attempt timeout) and returns a
+//! [`RetryExhausted`] that says how far it got.
+
+use duroxide::{BackoffStrategy, DurableOutput, OrchestrationContext, RetryPolicy};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+/// Attempts for CMS writes under [`cms_retry_policy`]
+pub const CMS_RETRY_ATTEMPTS: u32 = 4;
+
+/// Per-attempt bound for CMS writes, so a hung database connection cannot hold the
+/// orchestration
+pub const CMS_RETRY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Policy for CMS writes whose loss leaves the CMS stale, such as a create's final
+/// state update. Backs off 1s, 2s, 4s, which rides out a database failover.
+pub fn cms_retry_policy() -> RetryPolicy {
+    RetryPolicy::new(CMS_RETRY_ATTEMPTS)
+        .with_backoff(BackoffStrategy::Exponential {
+            base: Duration::from_secs(1),
+            multiplier: 2.0,
+            max: Duration::from_secs(10),
+        })
+        .with_timeout(CMS_RETRY_TIMEOUT)
+}
+
+/// A retried activity that gave up
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetryExhausted {
+    /// Short activity name, e.g. `test-connection`
+    pub activity: String,
+    /// Attempts made, including the first
+    pub attempts: u32,
+    pub max_attempts: u32,
+    /// Orchestration time from the first attempt to giving up, backoff included
+    pub elapsed_seconds: u64,
+    /// Whether the last attempt hit the per-attempt timeout (which is not retried)
+    pub timed_out: bool,
+    pub last_error: String,
+}
+
+impl RetryExhausted {
+    /// `instance_events.metadata` for the failure
+    pub fn metadata(&self) -> serde_json::Value {
+        serde_json::json!({ "retry": self })
+    }
+}
+
+impl fmt::Display for RetryExhausted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cause = if self.timed_out { "timed out" } else { "failed" };
+        write!(
+            f,
+            "{} ({} {} on attempt {}/{} after {}s)",
+            self.last_error, self.activity, cause, self.attempts, self.max_attempts, self.elapsed_seconds
+        )
+    }
+}
+
+/// `schedule_activity_with_retry_typed` that returns a [`RetryExhausted`] on failure
+pub async fn schedule_activity_with_retry_counted<In, Out>(
+    ctx: &OrchestrationContext,
+    name: &str,
+    input: &In,
+    policy: RetryPolicy,
+) -> Result<Out, RetryExhausted>
+where
+    In: Serialize,
+    Out: serde::de::DeserializeOwned,
+{
+    let failure = |attempts: u32, elapsed_seconds: u64, timed_out: bool, last_error: String| RetryExhausted {
+        activity: short_name(name).to_string(),
+        attempts,
+        max_attempts: policy.max_attempts,
+        elapsed_seconds,
+        timed_out,
+        last_error,
+    };
+
+    let payload = serde_json::to_string(input)
+        .map_err(|e| failure(0, 0, false, format!("Failed to encode input: {}", e)))?;
+    let started = ctx.utcnow().await.ok();
+
+    let mut attempt = 0;
+    let (last_error, timed_out) = loop {
+        attempt += 1;
+        match run_attempt(ctx, name, &payload, &policy).await {
+            Ok(output) => {
+                return serde_json::from_str(&output)
+                    .map_err(|e| failure(attempt, 0, false, format!("Failed to decode output: {}", e)));
+            }
+            Err((error, timed_out)) if timed_out || attempt >= policy.max_attempts => {
+                break (error, timed_out);
+            }
+            Err((error, _)) => {
+                ctx.trace_warn(format!(
+                    "Activity '{}' attempt {}/{} failed: {}. Retrying...",
+                    name, attempt, policy.max_attempts, error
+                ));
+                let delay = policy.delay_for_attempt(attempt);
+                if !delay.is_zero() {
+                    ctx.schedule_timer(delay).into_timer().await;
+                }
+            }
+        }
+    };
+
+    let finished = ctx.utcnow().await.ok();
+    let elapsed_seconds = match (started, finished) {
+        (Some(started), Some(finished)) => finished.duration_since(started).map(|d| d.as_secs()).unwrap_or(0),
+        _ => 0,
+    };
+
+    Err(failure(attempt, elapsed_seconds, timed_out, last_error))
+}
+
+/// One attempt, raced against the policy's per-attempt timeout. Errors carry whether
+/// the attempt timed out.
+async fn run_attempt(
+    ctx: &OrchestrationContext,
+    name: &str,
+    payload: &str,
+    policy: &RetryPolicy,
+) -> Result<String, (String, bool)> {
+    let Some(timeout) = policy.timeout else {
+        return ctx.schedule_activity(name, payload).into_activity().await.map_err(|e| (e, false));
+    };
+
+    let activity = ctx.schedule_activity(name, payload);
+    let deadline = ctx.schedule_timer(timeout);
+    match ctx.select2(activity, deadline).await {
+        (0, DurableOutput::Activity(result)) => result.map_err(|e| (e, false)),
+        _ => Err((format!("timeout: activity timed out after {:?}", timeout), true)),
+    }
+}
+
+/// `test-connection` from `toygres-orchestrations::activity::test-connection`
+fn short_name(name: &str) -> &str {
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use duroxide::providers::sqlite::SqliteProvider;
+    use duroxide::runtime::{self, registry::ActivityRegistry};
+    use duroxide::{ActivityContext, Client, OrchestrationRegistry, OrchestrationStatus};
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    const FLAKY: &str = "toygres-orchestrations::activity::retry-test-flaky";
+
+    /// Run an orchestration that retries [`FLAKY`] (failing its first `failures` calls)
+    /// with `max_attempts`, returning its output: the activity result or the
+    /// serialized [`RetryExhausted`]
+    async fn run(instance: &str, failures: u32, max_attempts: u32) -> (String, u32) {
+        let calls = Arc::new(AtomicU32::new(0));
+        let counter = calls.clone();
+        let activities = ActivityRegistry::builder()
+            .register(FLAKY, move |_ctx: ActivityContext, input: String| {
+                let call = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                async move {
+                    if call <= failures {
+                        Err(format!("connection refused (call {})", call))
+                    } else {
+                        Ok(input)
+                    }
+                }
+            })
+            .build();
+        let orchestrations = OrchestrationRegistry::builder()
+            .register("retry-test", move |ctx: OrchestrationContext, input: String| async move {
+                let policy = RetryPolicy::new(max_attempts).with_backoff(BackoffStrategy::None);
+                match schedule_activity_with_retry_counted::<String, String>(&ctx, FLAKY, &input, policy).await {
+                    Ok(output) => Ok(output),
+                    Err(exhausted) => Ok(serde_json::to_string(&exhausted).unwrap()),
+                }
+            })
+            .build();
+
+        let store = Arc::new(SqliteProvider::new_in_memory().await.unwrap());
+        let rt = runtime::Runtime::start_with_store(store.clone(), Arc::new(activities), orchestrations).await;
+        let client = Client::new(store);
+        client.start_orchestration(instance, "retry-test", "\"ok\"").await.unwrap();
+        let status = client.wait_for_orchestration(instance, Duration::from_secs(10)).await.unwrap();
+        rt.shutdown(None).await;
+
+        match status {
+            OrchestrationStatus::Completed { output } => (output, calls.load(Ordering::SeqCst)),
+            other => panic!("unexpected status: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_attempt_count_recorded_on_exhaustion() {
+        let (output, calls) = run("retry-exhausted", u32::MAX, 3).await;
+        let exhausted: RetryExhausted = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(calls, 3);
+        assert_eq!(exhausted.activity, "retry-test-flaky");
+        assert_eq!((exhausted.attempts, exhausted.max_attempts), (3, 3));
+        assert!(!exhausted.timed_out);
+        assert_eq!(exhausted.last_error, "connection refused (call 3)");
+        assert_eq!(exhausted.metadata()["retry"]["attempts"], 3);
+        assert!(
+            exhausted.to_string().starts_with("connection refused (call 3) (retry-test-flaky failed on attempt 3/3 after "),
+            "{}", exhausted
+        );
+    }
+
+    #[tokio::test]
+    async fn test_success_after_retries_returns_output() {
+        let (output, calls) = run("retry-recovers", 2, 5).await;
+        assert_eq!(output, "\"ok\"");
+        assert_eq!(calls, 3);
+    }
+}
@@ -0,0 +1,251 @@
+//! Drift between an instance's desired and actual Kubernetes spec
+//!
+//! The desired spec is what `deploy_postgres` would render from the instance's CMS
+//! config ([`render_resources`](crate::activities::deploy_postgres::render_resources));
+//! the actual spec is what [`live_resources`](crate::k8s_client::live_resources) finds in
+//! the cluster. Only fields Toygres sets and Kubernetes does not default are compared,
+//! so a freshly deployed instance reports no drift.
+
+use crate::activities::deploy_postgres::{has_pooler, update_strategy_of, RenderedResources, POOLER_CONTAINER};
+use crate::activity_types::UpdateStrategy;
+use crate::k8s_client::LiveResources;
+use k8s_openapi::api::apps::v1::StatefulSet;
+use k8s_openapi::api::core::v1::{PersistentVolumeClaim, Service};
+use serde::{Deserialize, Serialize};
+
+/// One field whose live value differs from the rendered one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpecDrift {
+    /// Kind and name, e.g. "StatefulSet/mydb-1a2b3c4d"
+    pub resource: String,
+    /// Path of the field, e.g. "spec.resources.requests.storage"; "exists" when the
+    /// whole resource is missing
+    pub field: String,
+    pub desired: Option<String>,
+    pub actual: Option<String>,
+}
+
+/// (field, value) pairs compared for one resource
+type Fields = Vec<(String, Option<String>)>;
+
+fn pvc_fields(pvc: &PersistentVolumeClaim) -> Fields {
+    let spec = pvc.spec.as_ref();
+    vec![
+        (
+            "spec.accessModes".to_string(),
+            spec.and_then(|s| s.access_modes.as_ref()).map(|modes| modes.join(",")),
+        ),
+        (
+            "spec.resources.requests.storage".to_string(),
+            spec.and_then(|s| s.resources.as_ref())
+                .and_then(|r| r.requests.as_ref())
+                .and_then(|r| r.get("storage"))
+                .map(|q| q.0.clone()),
+        ),
+    ]
+}
+
+fn statefulset_fields(statefulset: &StatefulSet) -> Fields {
+    let spec = statefulset.spec.as_ref();
+    let pod_spec = spec.and_then(|s| s.template.spec.as_ref());
+    let postgres = pod_spec.and_then(|p| p.containers.iter().find(|c| c.name == "postgres"));
+    // Kubernetes defaults an unset strategy to RollingUpdate with partition 0
+    let update_strategy = match update_strategy_of(statefulset) {
+        None => "RollingUpdate".to_string(),
+        Some(UpdateStrategy::RollingUpdate { partition }) => format!("RollingUpdate(partition={})", partition),
+        Some(UpdateStrategy::OnDelete) => "OnDelete".to_string(),
+    };
+    vec![
+        ("spec.replicas".to_string(), spec.and_then(|s| s.replicas).map(|r| r.to_string())),
+        ("spec.updateStrategy".to_string(), Some(update_strategy)),
+        ("containers[postgres].image".to_string(), postgres.and_then(|c| c.image.clone())),
+        (format!("containers[{}].exists", POOLER_CONTAINER), Some(has_pooler(statefulset).to_string())),
+        (
+            "securityContext.fsGroup".to_string(),
+            pod_spec.and_then(|p| p.security_context.as_ref())
+                .and_then(|s| s.fs_group)
+                .map(|g| g.to_string()),
+        ),
+    ]
+}
+
+/// `annotation_keys` are the annotations Toygres sets; others (added by the cloud
+/// provider, kubectl, ...) are not drift
+fn service_fields(service: &Service, annotation_keys: &[String]) -> Fields {
+    let spec = service.spec.as_ref();
+    let mut fields = vec![
+        ("spec.type".to_string(), spec.and_then(|s| s.type_.clone())),
+        (
+            "spec.ports".to_string(),
+            spec.and_then(|s| s.ports.as_ref()).map(|ports| {
+                ports.iter().map(|p| p.port.to_string()).collect::<Vec<_>>().join(",")
+            }),
+        ),
+    ];
+    for key in annotation_keys {
+        let value = service.metadata.annotations.as_ref().and_then(|a| a.get(key)).cloned();
+        fields.push((format!("metadata.annotations[{}]", key), value));
+    }
+    fields
+}
+
+fn compare(resource: String, desired: Fields, actual: Option<Fields>, drift: &mut Vec<SpecDrift>) {
+    let Some(actual) = actual else {
+        drift.push(SpecDrift {
+            resource,
+            field: "exists".to_string(),
+            desired: Some("true".to_string()),
+            actual: Some("false".to_string()),
+        });
+        return;
+    };
+    
+    for ((field, desired), (_, actual)) in desired.into_iter().zip(actual) {
+        if desired != actual {
+            drift.push(SpecDrift { resource: resource.clone(), field, desired, actual });
+        }
+    }
+}
+
+/// Every compared field where `live` differs from `desired`; empty when in sync
+pub fn diff_specs(desired: &RenderedResources, live: &LiveResources) -> Vec<SpecDrift> {
+    let name = |meta: &k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta| {
+        meta.name.clone().unwrap_or_default()
+    };
+    let annotation_keys: Vec<String> = desired.service.metadata.annotations
+        .iter()
+        .flat_map(|a| a.keys().cloned())
+        .collect();
+    
+    let mut drift = Vec::new();
+    compare(
+        format!("PersistentVolumeClaim/{}", name(&desired.pvc.metadata)),
+        pvc_fields(&desired.pvc),
+        live.pvc.as_ref().map(pvc_fields),
+        &mut drift,
+    );
+    compare(
+        format!("StatefulSet/{}", name(&desired.statefulset.metadata)),
+        statefulset_fields(&desired.statefulset),
+        live.statefulset.as_ref().map(statefulset_fields),
+        &mut drift,
+    );
+    compare(
+        format!("Service/{}", name(&desired.service.metadata)),
+        service_fields(&desired.service, &annotation_keys),
+        live.service.as_ref().map(|s| service_fields(s, &annotation_keys)),
+        &mut drift,
+    );
+    drift
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activities::deploy_postgres::render_resources;
+    use crate::activity_types::DeployPostgresInput;
+    use k8s_openapi::apimachinery::pkg::api::resource::Quantity;
+    
+    fn rendered() -> RenderedResources {
+        render_resources(&DeployPostgresInput {
+            namespace: "toygres".to_string(),
+            instance_name: "mydb-1a2b3c4d".to_string(),
+            password: String::new(),
+            username: None,
+            postgres_version: "18".to_string(),
+            storage_size_gb: 10,
+            use_load_balancer: true,
+            dns_label: Some("mydb".to_string()),
+            access_mode: None,
+            readiness_probe: None,
+            liveness_probe: None,
+            service_annotations: None,
+            internal_load_balancer: false,
+            init_containers: None,
+            password_secret_ref: None,
+            fs_group: None,
+            run_as_user: None,
+            enable_pooler: false,
+            update_strategy: None,
+            standby_replicas: None,
+        }).unwrap()
+    }
+    
+    fn live_copy(desired: &RenderedResources) -> LiveResources {
+        LiveResources {
+            pvc: Some(desired.pvc.clone()),
+            statefulset: Some(desired.statefulset.clone()),
+            service: Some(desired.service.clone()),
+        }
+    }
+    
+    #[test]
+    fn test_out_of_band_changes_are_reported() {
+        let desired = rendered();
+        assert_eq!(diff_specs(&desired, &live_copy(&desired)), vec![]);
+        
+        // Storage resized out of band; an annotation added by the cloud provider is not drift
+        let mut live = live_copy(&desired);
+        let pvc = live.pvc.as_mut().unwrap();
+        pvc.spec.as_mut().unwrap().resources.as_mut().unwrap().requests.as_mut().unwrap()
+            .insert("storage".to_string(), Quantity("20Gi".to_string()));
+        live.service.as_mut().unwrap().metadata.annotations.as_mut().unwrap()
+            .insert("kubectl.kubernetes.io/last-applied-configuration".to_string(), "{}".to_string());
+        
+        assert_eq!(diff_specs(&desired, &live), vec![SpecDrift {
+            resource: "PersistentVolumeClaim/mydb-1a2b3c4d-pvc".to_string(),
+            field: "spec.resources.requests.storage".to_string(),
+            desired: Some("10Gi".to_string()),
+            actual: Some("20Gi".to_string()),
+        }]);
+    }
+    
+    #[test]
+    fn test_image_change_and_missing_service_are_reported() {
+        let desired = rendered();
+        let mut live = live_copy(&desired);
+        let pod_spec = live.statefulset.as_mut().unwrap().spec.as_mut().unwrap().template.spec.as_mut().unwrap();
+        pod_spec.containers.iter_mut().find(|c| c.name == "postgres").unwrap().image = Some("postgres:17".to_string());
+        live.service = None;
+        
+        let drift = diff_specs(&desired, &live);
+        let fields: Vec<(&str, &str)> = drift.iter().map(|d| (d.resource.as_str(), d.field.as_str())).collect();
+        assert_eq!(fields, vec![
+            ("StatefulSet/mydb-1a2b3c4d", "containers[postgres].image"),
+            ("Service/mydb-1a2b3c4d-svc", "exists"),
+        ]);
+        assert_eq!(drift[0].desired.as_deref(), Some("postgres:18"));
+        assert_eq!(drift[0].actual.as_deref(), Some("postgres:17"));
+    }
+    
+    #[test]
+    fn test_pooler_and_update_strategy_changes_are_reported() {
+        let desired = rendered();
+        let mut live = live_copy(&desired);
+        let spec = live.statefulset.as_mut().unwrap().spec.as_mut().unwrap();
+        spec.update_strategy = Some(k8s_openapi::api::apps::v1::StatefulSetUpdateStrategy {
+            type_: Some("OnDelete".to_string()),
+            rolling_update: None,
+        });
+        let pod_spec = spec.template.spec.as_mut().unwrap();
+        let mut pooler = pod_spec.containers[0].clone();
+        pooler.name = POOLER_CONTAINER.to_string();
+        pod_spec.containers.push(pooler);
+        
+        let drift = diff_specs(&desired, &live);
+        assert_eq!(drift, vec![
+            SpecDrift {
+                resource: "StatefulSet/mydb-1a2b3c4d".to_string(),
+                field: "spec.updateStrategy".to_string(),
+                desired: Some("RollingUpdate".to_string()),
+                actual: Some("OnDelete".to_string()),
+            },
+            SpecDrift {
+                resource: "StatefulSet/mydb-1a2b3c4d".to_string(),
+                field: "containers[pgbouncer].exists".to_string(),
+                desired: Some("false".to_string()),
+                actual: Some("true".to_string()),
+            },
+        ]);
+    }
+}
@@ -0,0 +1,100 @@
+//! Shared harness for orchestration tests
+//!
+//! Activities are replaced by mocks that log their short name and JSON input to a
+//! [`CallLog`], and orchestrations run on an in-memory SQLite store.
+
+use duroxide::providers::sqlite::SqliteProvider;
+use duroxide::runtime::{self, registry::ActivityRegistry};
+use duroxide::{ActivityContext, Client, OrchestrationRegistry, OrchestrationStatus};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Short name and input of every mocked activity call, in call order
+pub type CallLog = Arc<Mutex<Vec<(String, serde_json::Value)>>>;
+
+/// The activity name without its crate prefix, e.g. "cms-update-instance-state"
+pub fn short_name(name: &str) -> &str {
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+/// Log a call to `name` with `input`; returns the number of calls logged so far
+pub fn record(calls: &CallLog, name: &str, input: &str) -> usize {
+    let mut calls = calls.lock().unwrap();
+    calls.push((short_name(name).to_string(), serde_json::from_str(input).unwrap()));
+    calls.len()
+}
+
+/// Short names of the logged calls
+pub fn call_names(calls: &CallLog) -> Vec<String> {
+    calls.lock().unwrap().iter().map(|(name, _)| name.clone()).collect()
+}
+
+/// Mock activity that logs its call, then returns `output`
+pub fn mock(
+    calls: &CallLog,
+    name: &'static str,
+    output: serde_json::Value,
+) -> impl Fn(ActivityContext, String) -> std::future::Ready<Result<String, String>> + Send + Sync + 'static {
+    mock_result(calls, name, Ok(output))
+}
+
+/// Mock activity that logs its call, then returns `output` or fails with its error
+pub fn mock_result(
+    calls: &CallLog,
+    name: &'static str,
+    output: Result<serde_json::Value, &'static str>,
+) -> impl Fn(ActivityContext, String) -> std::future::Ready<Result<String, String>> + Send + Sync + 'static {
+    let calls = calls.clone();
+    move |_ctx, input| {
+        record(&calls, name, &input);
+        std::future::ready(output.clone().map(|output| output.to_string()).map_err(str::to_string))
+    }
+}
+
+/// A runtime on an in-memory store, with a client for it
+pub struct TestRuntime {
+    pub client: Client,
+    runtime: Arc<runtime::Runtime>,
+}
+
+impl TestRuntime {
+    pub async fn start(activities: ActivityRegistry, orchestrations: OrchestrationRegistry) -> Self {
+        let store = Arc::new(SqliteProvider::new_in_memory().await.unwrap());
+        let runtime = runtime::Runtime::start_with_store(store.clone(), Arc::new(activities), orchestrations).await;
+        Self { client: Client::new(store), runtime }
+    }
+
+    /// Start `orchestration` as `instance` and wait up to `timeout` for it to finish
+    pub async fn run(
+        &self,
+        instance: &str,
+        orchestration: &str,
+        input: &impl serde::Serialize,
+        timeout: Duration,
+    ) -> OrchestrationStatus {
+        self.client
+            .start_orchestration(instance, orchestration, serde_json::to_string(input).unwrap())
+            .await
+            .unwrap();
+        self.client.wait_for_orchestration(instance, timeout).await.unwrap()
+    }
+
+    pub async fn shutdown(self) {
+        self.runtime.shutdown(None).await;
+    }
+}
+
+/// Run one orchestration on a fresh [`TestRuntime`] and return how it finished
+pub async fn run_orchestration(
+    activities: ActivityRegistry,
+    orchestrations: OrchestrationRegistry,
+    instance: &str,
+    orchestration: &str,
+    input: &impl serde::Serialize,
+    timeout: Duration,
+) -> OrchestrationStatus {
+    let rt = TestRuntime::start(activities, orchestrations).await;
+    let status = rt.run(instance, orchestration, input, timeout).await;
+    rt.shutdown().await;
+    status
+}
@@ -0,0 +1,229 @@
+//! Trace verbosity control for orchestrations
+//!
+//! Orchestration traces are recorded in history as system calls, so every
+//! `ctx.trace_info` both logs a line and grows the history. During bulk operations
+//! that floods the logs. [`Tracer`] wraps the context and drops traces below the
+//! configured [`TraceLevel`].
+//!
+//! The level travels on the orchestration input (not read from the environment inside
+//! the orchestration) so replays make the same decisions and stay deterministic.
+//! Callers that start orchestrations use [`TraceLevel::from_env`] to fill it in.
+//!
+//! Create/delete orchestrations and every timed activity also run inside a tracing
+//! span ([`orchestration_span`], [`activity_span`]) carrying the orchestration id, so
+//! one flow can be followed end to end in `server.log`.
+
+use duroxide::{ActivityContext, OrchestrationContext};
+use serde::{Deserialize, Serialize};
+use tracing::Span;
+
+/// Environment variable read by callers to pick the level for new orchestrations
+pub const TRACE_LEVEL_ENV: &str = "TOYGRES_ORCH_TRACE_LEVEL";
+
+/// Minimum severity of orchestration traces that are emitted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TraceLevel {
+    /// Only errors
+    Error,
+    /// Warnings and errors
+    Warn,
+    /// Everything, including routine step traces (default)
+    #[default]
+    Info,
+}
+
+impl TraceLevel {
+    /// Parse a level name ("error", "warn"/"warning", "info"), case-insensitively
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "error" => Some(TraceLevel::Error),
+            "warn" | "warning" => Some(TraceLevel::Warn),
+            "info" => Some(TraceLevel::Info),
+            _ => None,
+        }
+    }
+
+    /// Read `TOYGRES_ORCH_TRACE_LEVEL`, defaulting to `Info` when unset or invalid
+    pub fn from_env() -> Self {
+        std::env::var(TRACE_LEVEL_ENV)
+            .ok()
+            .and_then(|v| Self::parse(&v))
+            .unwrap_or_default()
+    }
+
+    /// Whether a trace of severity `severity` is emitted at this level
+    pub fn allows(self, severity: TraceLevel) -> bool {
+        severity <= self
+    }
+}
+
+/// Orchestration tracer that respects a [`TraceLevel`]
+#[derive(Clone)]
+pub struct Tracer {
+    ctx: OrchestrationContext,
+    level: TraceLevel,
+}
+
+impl Tracer {
+    pub fn new(ctx: &OrchestrationContext, level: Option<TraceLevel>) -> Self {
+        Self {
+            ctx: ctx.clone(),
+            level: level.unwrap_or_default(),
+        }
+    }
+
+    pub fn level(&self) -> TraceLevel {
+        self.level
+    }
+
+    pub fn info(&self, message: impl Into<String>) {
+        if self.level.allows(TraceLevel::Info) {
+            self.ctx.trace_info(message);
+        }
+    }
+
+    pub fn warn(&self, message: impl Into<String>) {
+        if self.level.allows(TraceLevel::Warn) {
+            self.ctx.trace_warn(message);
+        }
+    }
+
+    pub fn error(&self, message: impl Into<String>) {
+        if self.level.allows(TraceLevel::Error) {
+            self.ctx.trace_error(message);
+        }
+    }
+}
+
+/// Span entered on every poll of a create/delete orchestration, so its traces carry
+/// `orchestration_id` and `k8s_name`
+pub fn orchestration_span(orchestration_id: &str, k8s_name: &str) -> Span {
+    tracing::info_span!("orchestration", orchestration_id = %orchestration_id, k8s_name = %k8s_name)
+}
+
+/// Span for one activity run, carrying the id of the orchestration that scheduled it
+pub fn activity_span(ctx: &ActivityContext) -> Span {
+    let activity = ctx.activity_name();
+    tracing::info_span!(
+        "activity",
+        orchestration_id = %ctx.instance_id(),
+        activity = %activity.rsplit("::").next().unwrap_or(activity),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_gating() {
+        assert!(TraceLevel::Info.allows(TraceLevel::Info));
+        assert!(TraceLevel::Info.allows(TraceLevel::Warn));
+        assert!(TraceLevel::Info.allows(TraceLevel::Error));
+
+        assert!(!TraceLevel::Warn.allows(TraceLevel::Info));
+        assert!(TraceLevel::Warn.allows(TraceLevel::Warn));
+        assert!(TraceLevel::Warn.allows(TraceLevel::Error));
+
+        assert!(!TraceLevel::Error.allows(TraceLevel::Info));
+        assert!(!TraceLevel::Error.allows(TraceLevel::Warn));
+        assert!(TraceLevel::Error.allows(TraceLevel::Error));
+    }
+
+    #[test]
+    fn test_parse_and_default() {
+        assert_eq!(TraceLevel::parse("WARN"), Some(TraceLevel::Warn));
+        assert_eq!(TraceLevel::parse("warning"), Some(TraceLevel::Warn));
+        assert_eq!(TraceLevel::parse(" error "), Some(TraceLevel::Error));
+        assert_eq!(TraceLevel::parse("verbose"), None);
+        assert_eq!(TraceLevel::default(), TraceLevel::Info);
+    }
+
+    #[tokio::test]
+    async fn test_orchestration_log_lines_include_its_id() {
+        use crate::metrics::RegisterTimed;
+        use duroxide::providers::sqlite::SqliteProvider;
+        use duroxide::runtime::{self, registry::ActivityRegistry};
+        use duroxide::{Client, OrchestrationRegistry};
+        use std::sync::{Arc, Mutex};
+        use tracing::Instrument;
+
+        /// Collects everything the subscriber writes
+        #[derive(Clone, Default)]
+        struct Captured(Arc<Mutex<Vec<u8>>>);
+
+        impl std::io::Write for Captured {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        const ACTIVITY: &str = "toygres-orchestrations::activity::span-test";
+
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .json()
+            .with_current_span(false)
+            .with_span_list(true)
+            .with_writer(move || writer.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let activities = ActivityRegistry::builder()
+            .register_timed(ACTIVITY, |ctx: ActivityContext, input: String| async move {
+                ctx.trace_info("span-test activity ran");
+                tracing::info!("span-test plain log line");
+                Ok::<_, String>(input)
+            })
+            .build();
+        let orchestrations = OrchestrationRegistry::builder()
+            .register("span-test", |ctx: OrchestrationContext, input: String| {
+                let span = orchestration_span("create-spandb-1a2b", "spandb-1a2b");
+                async move {
+                    ctx.trace_info("span-test orchestration started");
+                    ctx.schedule_activity(ACTIVITY, input).into_activity().await
+                }
+                .instrument(span)
+            })
+            .build();
+
+        let store = Arc::new(SqliteProvider::new_in_memory().await.unwrap());
+        let rt = runtime::Runtime::start_with_store(store.clone(), Arc::new(activities), orchestrations).await;
+        let client = Client::new(store);
+        client.start_orchestration("create-spandb-1a2b", "span-test", "\"hi\"").await.unwrap();
+        client.wait_for_orchestration("create-spandb-1a2b", std::time::Duration::from_secs(10)).await.unwrap();
+        rt.shutdown(None).await;
+
+        let output = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<serde_json::Value> = output.lines()
+            .filter(|line| line.contains("span-test "))
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3, "{}", output);
+
+        for line in &lines {
+            let spans = line["spans"].as_array().unwrap_or_else(|| panic!("no spans: {}", line));
+            assert!(
+                spans.iter().any(|span| span["orchestration_id"] == "create-spandb-1a2b"),
+                "missing orchestration_id: {}", line
+            );
+        }
+        let orchestration_line = lines.iter().find(|l| l["fields"]["message"] == "span-test orchestration started").unwrap();
+        assert_eq!(orchestration_line["spans"][0]["k8s_name"], "spandb-1a2b");
+        let activity_line = lines.iter().find(|l| l["fields"]["message"] == "span-test plain log line").unwrap();
+        assert_eq!(activity_line["spans"][0]["activity"], "span-test");
+    }
+
+    #[test]
+    fn test_serde_lowercase() {
+        assert_eq!(serde_json::to_string(&TraceLevel::Warn).unwrap(), "\"warn\"");
+        let level: TraceLevel = serde_json::from_str("\"error\"").unwrap();
+        assert_eq!(level, TraceLevel::Error);
+    }
+}
@@ -1,20 +1,36 @@
 //! Input and output types for Toygres orchestrations
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::activities::deploy_postgres::{validate_init_containers, validate_service_annotations, ALLOWED_ACCESS_MODES};
+use crate::activity_types::{InitContainerSpec, InstanceDeployOptions, ProbeTimings, UpdateStrategy, MAX_STANDBY_REPLICAS};
+use crate::trace::TraceLevel;
 
 // ============================================================================
 // Create Instance Orchestration
 // ============================================================================
 
+/// Largest serialized [`CreateInstanceInput`]. The input is stored in the history
+/// and read back on every replay, so oversized ones slow the orchestration down.
+pub const MAX_CREATE_INPUT_BYTES: usize = 64 * 1024;
+
+/// Most CMS tags a create may carry
+pub const MAX_CREATE_TAGS: usize = 64;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct CreateInstanceInput {
     /// User-friendly instance name (without GUID suffix)
     pub user_name: String,
     /// Instance name
     pub name: String,
-    /// PostgreSQL password
+    /// PostgreSQL password (empty when `password_secret_ref` is set)
+    #[serde(default)]
     pub password: String,
-    /// PostgreSQL version (default: "18")
+    /// Superuser to create and connect as (default: "postgres")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// PostgreSQL version (default: [`toygres_models::DEFAULT_PG_VERSION`])
     pub postgres_version: Option<String>,
     /// Storage size in GB (default: 10)
     pub storage_size_gb: Option<i32>,
@@ -22,10 +38,209 @@ pub struct CreateInstanceInput {
     pub use_load_balancer: Option<bool>,
     /// DNS label for Azure DNS (optional)
     pub dns_label: Option<String>,
-    /// Kubernetes namespace (default: "toygres")
-    pub namespace: Option<String>,
+    /// Kubernetes namespace. Callers resolve the default ([`toygres_models::default_namespace`])
+    /// so a replay never reads the environment.
+    pub namespace: String,
     /// Unique orchestration/request identifier
     pub orchestration_id: String,
+    /// Trace verbosity (default: info)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_level: Option<TraceLevel>,
+    /// PVC access mode (default: "ReadWriteOnce")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_mode: Option<String>,
+    /// Existing Kubernetes Secret (in the instance namespace) whose `password` key holds
+    /// the Postgres password; mutually exclusive with `password`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_secret_ref: Option<String>,
+    /// CMS tags to store on the instance record
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<BTreeMap<String, serde_json::Value>>,
+    /// Check cluster capacity before deploying (default: false)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preflight: Option<bool>,
+    /// Put a PgBouncer sidecar in front of Postgres and advertise it (default: false)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enable_pooler: Option<bool>,
+    /// Give the LoadBalancer a private VNet IP instead of a public one (default: false).
+    /// Such instances get a record in the Azure Private DNS zone when one is configured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub internal_load_balancer: Option<bool>,
+    /// Fail the create when the server's major version is not the requested one, e.g.
+    /// because an image tag points at an older release (default: false)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strict_version: Option<bool>,
+    /// Standby replicas to run next to the primary, at most [`MAX_STANDBY_REPLICAS`]
+    /// (default: 0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub standby_replicas: Option<u32>,
+    /// How the StatefulSet rolls out later pod template changes, e.g. a partition to
+    /// canary a version bump on one pod (default: Kubernetes' `RollingUpdate`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub update_strategy: Option<UpdateStrategy>,
+    /// Readiness probe timings (default: [`ProbeTimings::readiness`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub readiness_probe: Option<ProbeTimings>,
+    /// Liveness probe timings (default: [`ProbeTimings::liveness`]); slow volumes may
+    /// need a longer initial delay so initdb is not killed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub liveness_probe: Option<ProbeTimings>,
+    /// Extra Service annotations, e.g. Azure LB health-probe settings. The DNS label
+    /// and internal LB annotations come from `dns_label` and `internal_load_balancer`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service_annotations: Option<BTreeMap<String, String>>,
+    /// Init containers run before Postgres starts, e.g. to set kernel params or
+    /// pre-create directories on the data volume
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub init_containers: Option<Vec<InitContainerSpec>>,
+    /// Pod `securityContext.fsGroup`, e.g. for an image whose postgres user has
+    /// another gid (default: [`crate::activity_types::POSTGRES_UID`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fs_group: Option<i64>,
+    /// Pod `securityContext.runAsUser` (default: [`crate::activity_types::POSTGRES_UID`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_as_user: Option<i64>,
+}
+
+impl CreateInstanceInput {
+    /// The deploy options to store with the instance record, with defaults applied as
+    /// the deploy applies them
+    pub fn deploy_options(&self) -> InstanceDeployOptions {
+        InstanceDeployOptions {
+            username: self.username.clone(),
+            access_mode: self.access_mode.clone(),
+            readiness_probe: self.readiness_probe,
+            liveness_probe: self.liveness_probe,
+            service_annotations: self.service_annotations.clone(),
+            internal_load_balancer: self.internal_load_balancer.unwrap_or(false),
+            init_containers: self.init_containers.clone(),
+            fs_group: self.fs_group,
+            run_as_user: self.run_as_user,
+            enable_pooler: self.enable_pooler.unwrap_or(false),
+            update_strategy: self.update_strategy,
+            standby_replicas: self.standby_replicas,
+        }
+    }
+    
+    /// Require exactly one of `password` or `password_secret_ref`
+    pub fn validate_password(&self) -> Result<(), String> {
+        match (&self.password_secret_ref, self.password.is_empty()) {
+            (Some(_), false) => Err("Specify either password or password_secret_ref, not both".to_string()),
+            (None, true) => Err("A password or password_secret_ref is required".to_string()),
+            (Some(secret), true) if !is_dns_subdomain(secret) => Err(format!(
+                "Invalid password_secret_ref '{}': must be a valid Kubernetes Secret name",
+                secret
+            )),
+            _ => Ok(()),
+        }
+    }
+    
+    /// Reject inputs over [`MAX_CREATE_TAGS`] tags or [`MAX_CREATE_INPUT_BYTES`] serialized
+    pub fn validate_size(&self) -> Result<(), String> {
+        let tags = self.tags.as_ref().map_or(0, BTreeMap::len);
+        if tags > MAX_CREATE_TAGS {
+            return Err(format!("Too many tags: {} (at most {})", tags, MAX_CREATE_TAGS));
+        }
+        
+        let bytes = serde_json::to_vec(self).map_err(|e| format!("Failed to encode input: {}", e))?.len();
+        if bytes > MAX_CREATE_INPUT_BYTES {
+            return Err(format!(
+                "Create input is {} bytes, over the {} byte limit; shrink tags",
+                bytes, MAX_CREATE_INPUT_BYTES
+            ));
+        }
+        Ok(())
+    }
+    
+    /// Check the input as the orchestration will see it, with every default applied,
+    /// so a bad request fails before the orchestration starts rather than partway
+    /// through it. Returns every problem as `<field>: <message>`.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let config = toygres_models::DeploymentConfig {
+            name: self.user_name.clone(),
+            username: self.username.clone().unwrap_or_else(|| toygres_models::DEFAULT_USERNAME.to_string()),
+            password: self.password.clone(),
+            postgres_version: self.postgres_version.clone().unwrap_or_else(|| toygres_models::DEFAULT_PG_VERSION.to_string()),
+            storage_size_gb: self.storage_size_gb.unwrap_or(10),
+        };
+        let mut errors = config.validate().err().unwrap_or_default();
+        // Length rules only apply to an inline password, and not when it is missing altogether
+        let password_rule = self.validate_password();
+        errors.retain(|e| !e.starts_with("password:") || (self.password_secret_ref.is_none() && password_rule.is_ok()));
+        if let Err(e) = password_rule {
+            errors.push(format!("password: {}", e));
+        }
+        
+        if !is_dns_label(&self.name) {
+            errors.push(format!("name: '{}' is not a valid Kubernetes name (lowercase, at most 63 characters)", self.name));
+        }
+        if !is_dns_label(&self.namespace) {
+            errors.push(format!("namespace: '{}' is not a valid Kubernetes namespace", self.namespace));
+        }
+        if let Some(label) = self.dns_label.as_deref().filter(|label| !is_dns_label(label)) {
+            errors.push(format!("dns_label: '{}' is not a valid DNS label", label));
+        }
+        if self.orchestration_id.trim().is_empty() {
+            errors.push("orchestration_id: is required".to_string());
+        }
+        if let Some(mode) = self.access_mode.as_deref().filter(|mode| !ALLOWED_ACCESS_MODES.contains(mode)) {
+            errors.push(format!("access_mode: '{}' is not one of {}", mode, ALLOWED_ACCESS_MODES.join(", ")));
+        }
+        if self.internal_load_balancer.unwrap_or(false) && !self.use_load_balancer.unwrap_or(true) {
+            errors.push("internal_load_balancer: requires use_load_balancer".to_string());
+        }
+        if let Some(count) = self.standby_replicas.filter(|count| *count > MAX_STANDBY_REPLICAS) {
+            errors.push(format!("standby_replicas: must be at most {}, got {}", MAX_STANDBY_REPLICAS, count));
+        }
+        if let Some(UpdateStrategy::RollingUpdate { partition }) = self.update_strategy {
+            if partition < 0 {
+                errors.push(format!("update_strategy: partition must not be negative, got {}", partition));
+            }
+        }
+        if let Some(Err(e)) = self.service_annotations.as_ref().map(validate_service_annotations) {
+            errors.push(format!("service_annotations: {}", e));
+        }
+        if let Some(Err(e)) = self.init_containers.as_deref().map(validate_init_containers) {
+            errors.push(format!("init_containers: {}", e));
+        }
+        for (field, id) in [("fs_group", self.fs_group), ("run_as_user", self.run_as_user)] {
+            if let Some(id) = id.filter(|id| *id < 0) {
+                errors.push(format!("{}: must not be negative, got {}", field, id));
+            }
+        }
+        for (field, probe) in [("readiness_probe", self.readiness_probe), ("liveness_probe", self.liveness_probe)] {
+            if let Some(Err(e)) = probe.map(|probe| probe.validate()) {
+                errors.push(format!("{}: {}", field, e));
+            }
+        }
+        if let Err(e) = self.validate_size() {
+            errors.push(e);
+        }
+        
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Lowercase alphanumerics and '-', starting and ending alphanumeric, at most 63 chars
+pub(crate) fn is_dns_label(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= 63
+        && value.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+        && !value.starts_with('-')
+        && !value.ends_with('-')
+}
+
+/// Kubernetes object name rules: lowercase alphanumerics, '-' and '.', at most 253 chars
+pub(crate) fn is_dns_subdomain(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= 253
+        && value.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-' || b == b'.')
+        && value.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && value.ends_with(|c: char| c.is_ascii_alphanumeric())
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -42,6 +257,12 @@ pub struct CreateInstanceOutput {
     pub external_ip: Option<String>,
     /// Azure DNS name
     pub dns_name: Option<String>,
+    /// Name in the Azure Private DNS zone (internal LoadBalancers)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_dns_name: Option<String>,
+    /// Connection string using [`Self::private_dns_name`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub private_connection_string: Option<String>,
     /// PostgreSQL version
     pub postgres_version: String,
     /// Time taken to deploy (seconds)
@@ -56,10 +277,37 @@ pub struct CreateInstanceOutput {
 pub struct DeleteInstanceInput {
     /// Instance name
     pub name: String,
-    /// Kubernetes namespace (default: "toygres")
-    pub namespace: Option<String>,
+    /// Kubernetes namespace. Callers resolve the default ([`toygres_models::default_namespace`])
+    /// so a replay never reads the environment.
+    pub namespace: String,
     /// Orchestration/request identifier
     pub orchestration_id: String,
+    /// Trace verbosity (default: info)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_level: Option<TraceLevel>,
+}
+
+impl DeleteInstanceInput {
+    /// Check the names before the orchestration starts. Returns every problem as
+    /// `<field>: <message>`.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        if !is_dns_label(&self.name) {
+            errors.push(format!("name: '{}' is not a valid Kubernetes name", self.name));
+        }
+        if !is_dns_label(&self.namespace) {
+            errors.push(format!("namespace: '{}' is not a valid Kubernetes namespace", self.namespace));
+        }
+        if self.orchestration_id.trim().is_empty() {
+            errors.push("orchestration_id: is required".to_string());
+        }
+        
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -68,6 +316,156 @@ pub struct DeleteInstanceOutput {
     pub instance_name: String,
     /// Whether instance was deleted (false if didn't exist)
     pub deleted: bool,
+    /// Set when another delete orchestration already holds the instance; nothing was done
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conflict: Option<String>,
+}
+
+// ============================================================================
+// Bump Minor Version Orchestration
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BumpMinorVersionInput {
+    /// K8s instance name (with GUID)
+    pub name: String,
+    /// Kubernetes namespace. Callers resolve the default ([`toygres_models::default_namespace`])
+    /// so a replay never reads the environment.
+    pub namespace: String,
+    /// Target version within the same major, e.g. "18.2"
+    pub target_version: String,
+    /// Orchestration/request identifier
+    pub orchestration_id: String,
+    /// Trace verbosity (default: info)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_level: Option<TraceLevel>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BumpMinorVersionOutput {
+    /// Instance name
+    pub instance_name: String,
+    /// Version recorded in the CMS before the bump
+    pub previous_version: Option<String>,
+    /// Version now recorded in the CMS
+    pub postgres_version: String,
+    /// Whether the StatefulSet image was changed (false if already on the target)
+    pub patched: bool,
+}
+
+// ============================================================================
+// Expose Instance Orchestration
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExposeInstanceInput {
+    /// K8s instance name (with GUID)
+    pub name: String,
+    /// Kubernetes namespace. Callers resolve the default ([`toygres_models::default_namespace`])
+    /// so a replay never reads the environment.
+    pub namespace: String,
+    /// Service type to switch to
+    pub service_type: crate::activity_types::ServiceType,
+    /// Orchestration/request identifier
+    pub orchestration_id: String,
+    /// Trace verbosity (default: info)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_level: Option<TraceLevel>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExposeInstanceOutput {
+    /// Instance name
+    pub instance_name: String,
+    pub service_type: crate::activity_types::ServiceType,
+    /// Type the Service had; `None` when it was missing and has been recreated
+    pub previous_type: Option<String>,
+    /// LoadBalancer IP (LoadBalancer only)
+    pub external_ip: Option<String>,
+    /// Azure DNS name (LoadBalancer with a DNS label only)
+    pub dns_name: Option<String>,
+}
+
+// ============================================================================
+// Run Maintenance Orchestration
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RunMaintenanceOrchestrationInput {
+    /// K8s instance name (with GUID)
+    pub name: String,
+    /// Kubernetes namespace. Callers resolve the default ([`toygres_models::default_namespace`])
+    /// so a replay never reads the environment.
+    pub namespace: String,
+    /// Run VACUUM FULL instead of VACUUM (locks each table while it is rewritten)
+    #[serde(default)]
+    pub full: bool,
+    /// Tables to process (default: all user tables)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tables: Option<Vec<String>>,
+    /// Orchestration/request identifier
+    pub orchestration_id: String,
+    /// Trace verbosity (default: info)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_level: Option<TraceLevel>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RunMaintenanceOrchestrationOutput {
+    /// Instance name
+    pub instance_name: String,
+    /// Stats from the maintenance run
+    pub stats: crate::activity_types::RunMaintenanceOutput,
+}
+
+// ============================================================================
+// GC Orphans Orchestration
+// ============================================================================
+
+/// Default age a resource must reach before it can be collected
+pub const DEFAULT_GC_GRACE_PERIOD_SECS: u64 = 3600;
+
+/// Shortest grace period accepted, so a create in flight is never collected
+pub const MIN_GC_GRACE_PERIOD_SECS: u64 = 300;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GcOrphansInput {
+    /// Kubernetes namespace. Callers resolve the default ([`toygres_models::default_namespace`])
+    /// so a replay never reads the environment.
+    pub namespace: String,
+    /// Only collect resources older than this (default: 3600, minimum: 300)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grace_period_secs: Option<u64>,
+    /// Report orphans without deleting them
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Sweep again every this many seconds (default: run once)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval_secs: Option<u64>,
+    /// Orchestration/request identifier
+    pub orchestration_id: String,
+    /// Trace verbosity (default: info)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_level: Option<TraceLevel>,
+}
+
+/// Resources of one instance that has no live CMS record
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OrphanedInstance {
+    /// K8s instance name from the `instance` label
+    pub instance: String,
+    pub resources: Vec<crate::activity_types::ToygresResource>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GcOrphansOutput {
+    /// Toygres resources listed in the namespace
+    pub checked_resources: usize,
+    /// Orphans older than the grace period
+    pub orphans: Vec<OrphanedInstance>,
+    /// `Kind/name` of each resource deleted (empty on a dry run)
+    pub deleted: Vec<String>,
+    pub dry_run: bool,
 }
 
 // ============================================================================
@@ -82,8 +480,34 @@ pub struct InstanceActorInput {
     pub namespace: String,
     /// Orchestration ID
     pub orchestration_id: String,
+    /// Trace verbosity (default: info)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_level: Option<TraceLevel>,
+    /// Report `degraded` when a successful check takes longer than this (default: disabled)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slow_threshold_ms: Option<u32>,
+    /// Report a replica `unhealthy` when its replay lag exceeds this
+    /// (default: [`DEFAULT_MAX_REPLICATION_LAG_MS`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_replication_lag_ms: Option<u32>,
+    /// Read-only query that must also succeed for the instance to be `healthy`, e.g. to
+    /// assert a table the application needs exists (default: connecting and querying
+    /// the version is enough, as with `SELECT 1`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health_sql: Option<String>,
+    /// Storage use seen by the last check that measured it, carried across
+    /// continue-as-new so [`STORAGE_ALERT_PCT`] is reported once per crossing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_storage_pct: Option<f64>,
 }
 
+/// Replay lag above which a replica is no longer trusted for reads
+pub const DEFAULT_MAX_REPLICATION_LAG_MS: u32 = 30_000;
+
+/// Share of the provisioned volume in use at which a `storage_near_full` event is
+/// recorded, leaving time to resize before Postgres runs out of disk
+pub const STORAGE_ALERT_PCT: f64 = 85.0;
+
 // Output: Unit type, continues forever or exits with error
 // This orchestration uses continue-as-new and never completes normally
 
@@ -281,7 +281,13 @@ pub async fn auth_middleware(
     let path = req.uri().path();
     
     // Public routes that don't require auth
-    if path == "/login" || path == "/health" || path.starts_with("/static/") {
+    if path == "/login"
+        || path == "/health"
+        || path == "/livez"
+        || path == "/readyz"
+        || path == "/metrics"
+        || path.starts_with("/static/")
+    {
         return next.run(req).await;
     }
     
@@ -1,4 +1,7 @@
 use clap::{Parser, Subcommand};
+use serde::de::DeserializeOwned;
+use std::path::PathBuf;
+use toygres_orchestrations::activity_types::{InitContainerSpec, ProbeTimings};
 
 /// Toygres - PostgreSQL as a Service on AKS
 #[derive(Parser, Debug)]
@@ -37,15 +40,30 @@ pub enum Mode {
     
     /// Create a new PostgreSQL instance
     Create {
-        /// DNS name for the instance (e.g., "mydb" creates mydb.<region>.cloudapp.azure.com)
-        name: String,
+        /// DNS name for the instance (e.g., "mydb" creates mydb.<region>.cloudapp.azure.com).
+        /// Optional with --from-manifest, where it overrides the manifest name.
+        #[arg(required_unless_present = "from_manifest")]
+        name: Option<String>,
         
-        /// PostgreSQL password
-        #[arg(short, long)]
-        password: String,
+        /// Public DNS label when it should differ from the name (e.g. "pay" for
+        /// pay.<region>.cloudapp.azure.com); commands that take a DNS name then use it
+        #[arg(long)]
+        dns_label: Option<String>,
+        
+        /// PostgreSQL password (visible in shell history; prefer --password-stdin or --password-file)
+        #[arg(short, long, conflicts_with_all = ["password_stdin", "password_file"])]
+        password: Option<String>,
+        
+        /// Read the PostgreSQL password from the first line of stdin
+        #[arg(long, conflicts_with = "password_file")]
+        password_stdin: bool,
+        
+        /// Read the PostgreSQL password from the first line of a file
+        #[arg(long, value_name = "FILE")]
+        password_file: Option<PathBuf>,
         
-        /// PostgreSQL version (default: "18")
-        #[arg(long, default_value = "18")]
+        /// PostgreSQL version
+        #[arg(long, default_value = toygres_models::DEFAULT_PG_VERSION)]
         version: Option<String>,
         
         /// Storage size in GB (default: 10)
@@ -56,9 +74,62 @@ pub enum Mode {
         #[arg(long)]
         internal: bool,
         
-        /// Kubernetes namespace (default: "toygres")
-        #[arg(long, default_value = "toygres")]
+        /// Kubernetes namespace (default: $AKS_NAMESPACE or "toygres")
+        #[arg(long)]
         namespace: Option<String>,
+        
+        /// Create from a manifest produced by `toygres export` (JSON or YAML)
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["version", "storage", "internal"])]
+        from_manifest: Option<PathBuf>,
+        
+        /// Wait until the instance is running and print its connection string
+        #[arg(long)]
+        wait: bool,
+        
+        /// Seconds to wait with --wait before giving up
+        #[arg(long, value_name = "SECONDS", default_value = "900", requires = "wait")]
+        wait_timeout: u64,
+        
+        #[command(flatten)]
+        deploy: Box<DeployOptions>,
+    },
+    
+    /// Export an instance's shape as a portable manifest
+    Export {
+        /// DNS name of the instance
+        name: String,
+        
+        /// Output format (yaml or json)
+        #[arg(short, long, default_value = "yaml")]
+        output: String,
+    },
+    
+    /// Compare an instance's desired K8s spec (from its CMS config) with the cluster
+    Diff {
+        /// DNS name of the instance
+        name: String,
+        
+        /// Namespace of the instance, needed when the name exists in several namespaces
+        #[arg(long)]
+        namespace: Option<String>,
+        
+        /// Output format (table or json)
+        #[arg(short, long, default_value = "table")]
+        output: String,
+    },
+    
+    /// Print the Kubernetes manifests Toygres deploys for an instance (password masked)
+    Render {
+        /// DNS name of the instance
+        name: String,
+        
+        /// Namespace of the instance, needed when the name exists in several namespaces
+        #[arg(long)]
+        namespace: Option<String>,
+        
+        /// Output format (yaml or json)
+        #[arg(short, long, default_value = "yaml")]
+        output: String,
     },
     
     /// Delete a PostgreSQL instance
@@ -66,8 +137,8 @@ pub enum Mode {
         /// DNS name of the instance to delete (e.g., "adardb5")
         name: String,
         
-        /// Kubernetes namespace (default: "toygres")
-        #[arg(long, default_value = "toygres")]
+        /// Namespace of the instance, needed when the name exists in several namespaces
+        #[arg(long)]
         namespace: Option<String>,
     },
     
@@ -88,6 +159,26 @@ pub enum Mode {
         output: String,
     },
     
+    /// Show PostgreSQL pod logs for an instance
+    Logs {
+        /// DNS name of the instance
+        name: String,
+        
+        /// Number of lines to show
+        #[arg(short = 'n', long, default_value = "100")]
+        tail: i64,
+        
+        /// Follow log output (stream new lines as they are written)
+        #[arg(short, long)]
+        follow: bool,
+    },
+    
+    /// Show an orchestration's flow diagram with the progress of each step
+    Flow {
+        /// Orchestration ID (e.g., create-mydb-1a2b3c4d5e6f)
+        id: String,
+    },
+    
     /// Manage local development server
     Server {
         #[command(subcommand)]
@@ -142,6 +233,10 @@ pub enum ServerCommand {
         /// Limit number of results
         #[arg(short, long, default_value = "20")]
         limit: usize,
+        
+        /// Skip this many matching orchestrations (for paging)
+        #[arg(long, default_value = "0")]
+        offset: usize,
     },
     
     /// Get orchestration details (advanced diagnostics)
@@ -164,6 +259,20 @@ pub enum ServerCommand {
         force: bool,
     },
     
+    /// Raise an external event on a running orchestration
+    RaiseEvent {
+        /// Orchestration ID (e.g. actor-mydb-1a2b3c4d)
+        id: String,
+        
+        /// Event name (e.g. UpdateConfig, Cancel)
+        #[arg(long)]
+        name: String,
+        
+        /// Event payload as JSON
+        #[arg(long, default_value = "{}", value_parser = parse_json)]
+        data: String,
+    },
+    
     /// Show system statistics and metrics
     Stats {
         /// Watch mode (refresh every 2s)
@@ -189,3 +298,182 @@ pub enum ServerCommand {
     },
 }
 
+/// Pod and Service settings for `create`; each defaults to what the deploy renders
+/// when it is omitted
+#[derive(clap::Args, Debug, Clone, Default, PartialEq)]
+pub struct DeployOptions {
+    /// Readiness probe timings as JSON, e.g. '{"initial_delay_seconds":5,"period_seconds":5,
+    /// "timeout_seconds":3,"failure_threshold":6}'
+    #[arg(long, value_name = "JSON", value_parser = parse_json_as::<ProbeTimings>)]
+    pub readiness_probe: Option<ProbeTimings>,
+    
+    /// Liveness probe timings as JSON, in the same form as --readiness-probe
+    #[arg(long, value_name = "JSON", value_parser = parse_json_as::<ProbeTimings>)]
+    pub liveness_probe: Option<ProbeTimings>,
+    
+    /// Extra Service annotation; repeat for several
+    #[arg(long = "service-annotation", value_name = "KEY=VALUE", value_parser = parse_key_value)]
+    pub service_annotations: Vec<(String, String)>,
+    
+    /// Init container as JSON, e.g. '{"name":"sysctl","image":"busybox:1.36",
+    /// "command":["sysctl","-w","vm.swappiness=1"]}'; repeat for several, run in order
+    #[arg(long = "init-container", value_name = "JSON", value_parser = parse_json_as::<InitContainerSpec>)]
+    pub init_containers: Vec<InitContainerSpec>,
+    
+    /// Pod securityContext fsGroup (default: 999, the postgres gid of the official image)
+    #[arg(long, value_name = "GID")]
+    pub fs_group: Option<i64>,
+    
+    /// Pod securityContext runAsUser (default: 999, the postgres uid of the official image)
+    #[arg(long, value_name = "UID")]
+    pub run_as_user: Option<i64>,
+}
+
+/// Split a `KEY=VALUE` pair at the first '='
+fn parse_key_value(value: &str) -> Result<(String, String), String> {
+    value.split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected KEY=VALUE, got '{}'", value))
+}
+
+/// Parse `value` as JSON for `T`
+fn parse_json_as<T: DeserializeOwned>(value: &str) -> Result<T, String> {
+    serde_json::from_str(value).map_err(|e| format!("invalid JSON: {}", e))
+}
+
+/// Accept `value` only if it is valid JSON, normalized to its compact form
+fn parse_json(value: &str) -> Result<String, String> {
+    serde_json::from_str::<serde_json::Value>(value)
+        .map(|json| json.to_string())
+        .map_err(|e| format!("invalid JSON: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_logs_defaults() {
+        let args = Args::try_parse_from(["toygres", "logs", "mydb"]).unwrap();
+        match args.mode {
+            Mode::Logs { name, tail, follow } => {
+                assert_eq!(name, "mydb");
+                assert_eq!(tail, 100);
+                assert!(!follow);
+            }
+            other => panic!("unexpected mode: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_logs_tail_and_follow() {
+        let args = Args::try_parse_from(["toygres", "logs", "mydb", "-n", "25", "-f"]).unwrap();
+        match args.mode {
+            Mode::Logs { tail, follow, .. } => {
+                assert_eq!(tail, 25);
+                assert!(follow);
+            }
+            other => panic!("unexpected mode: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_flow_takes_orchestration_id() {
+        let args = Args::try_parse_from(["toygres", "flow", "create-mydb-1a2b"]).unwrap();
+        assert!(matches!(args.mode, Mode::Flow { id } if id == "create-mydb-1a2b"));
+        assert!(Args::try_parse_from(["toygres", "flow"]).is_err());
+    }
+
+    #[test]
+    fn test_raise_event_validates_json_data() {
+        let args = Args::try_parse_from([
+            "toygres", "server", "raise-event", "actor-mydb-1a2b",
+            "--name", "UpdateConfig", "--data", r#"{ "slow_threshold_ms": 250 }"#,
+        ]).unwrap();
+        match args.mode {
+            Mode::Server { command: ServerCommand::RaiseEvent { id, name, data } } => {
+                assert_eq!(id, "actor-mydb-1a2b");
+                assert_eq!(name, "UpdateConfig");
+                assert_eq!(data, r#"{"slow_threshold_ms":250}"#);
+            }
+            other => panic!("unexpected mode: {:?}", other),
+        }
+        
+        let args = Args::try_parse_from(["toygres", "server", "raise-event", "actor-mydb-1a2b", "--name", "Cancel"]).unwrap();
+        assert!(matches!(args.mode, Mode::Server { command: ServerCommand::RaiseEvent { data, .. } } if data == "{}"));
+        
+        let invalid = Args::try_parse_from([
+            "toygres", "server", "raise-event", "actor-mydb-1a2b", "--name", "UpdateConfig", "--data", "{slow: 250}",
+        ]).unwrap_err();
+        assert!(invalid.to_string().contains("invalid JSON"), "{}", invalid);
+        assert!(Args::try_parse_from(["toygres", "server", "raise-event", "actor-mydb-1a2b"]).is_err());
+    }
+    
+    #[test]
+    fn test_logs_requires_instance_name() {
+        assert!(Args::try_parse_from(["toygres", "logs"]).is_err());
+    }
+
+    #[test]
+    fn test_create_from_manifest_makes_name_optional() {
+        let args = Args::try_parse_from([
+            "toygres", "create", "--from-manifest", "mydb.yaml", "-p", "password123",
+        ]).unwrap();
+        match args.mode {
+            Mode::Create { name, from_manifest, .. } => {
+                assert!(name.is_none());
+                assert_eq!(from_manifest, Some(PathBuf::from("mydb.yaml")));
+            }
+            other => panic!("unexpected mode: {:?}", other),
+        }
+
+        assert!(Args::try_parse_from(["toygres", "create", "-p", "password123"]).is_err());
+        assert!(Args::try_parse_from([
+            "toygres", "create", "mydb", "-p", "password123", "--password-stdin",
+        ]).is_err());
+        assert!(Args::try_parse_from([
+            "toygres", "create", "mydb", "--password-stdin", "--password-file", "pw.txt",
+        ]).is_err());
+        assert!(Args::try_parse_from([
+            "toygres", "create", "mydb", "--password-stdin", "--wait-timeout", "60",
+        ]).is_err());
+        assert!(Args::try_parse_from([
+            "toygres", "create", "--from-manifest", "mydb.yaml", "-p", "password123", "--storage", "20",
+        ]).is_err());
+    }
+    
+    #[test]
+    fn test_create_parses_deploy_options() {
+        let args = Args::try_parse_from([
+            "toygres", "create", "mydb", "-p", "password123",
+            "--liveness-probe", r#"{"initial_delay_seconds":120,"period_seconds":10,"timeout_seconds":5,"failure_threshold":6}"#,
+            "--service-annotation", "team=data",
+            "--service-annotation", "example.com/query=a=b",
+            "--init-container", r#"{"name":"setup","image":"busybox:1.36"}"#,
+            "--fs-group", "70",
+        ]).unwrap();
+        match args.mode {
+            Mode::Create { deploy, .. } => {
+                assert_eq!(deploy.readiness_probe, None);
+                assert_eq!(deploy.liveness_probe.map(|probe| probe.initial_delay_seconds), Some(120));
+                assert_eq!(deploy.service_annotations, vec![
+                    ("team".to_string(), "data".to_string()),
+                    ("example.com/query".to_string(), "a=b".to_string()),
+                ]);
+                assert_eq!(deploy.init_containers.len(), 1);
+                assert_eq!(deploy.init_containers[0].image, "busybox:1.36");
+                assert!(deploy.init_containers[0].command.is_empty());
+                assert_eq!((deploy.fs_group, deploy.run_as_user), (Some(70), None));
+            }
+            other => panic!("unexpected mode: {:?}", other),
+        }
+        
+        let invalid = Args::try_parse_from([
+            "toygres", "create", "mydb", "-p", "password123", "--readiness-probe", r#"{"period_seconds":5}"#,
+        ]).unwrap_err();
+        assert!(invalid.to_string().contains("invalid JSON"), "{}", invalid);
+        assert!(Args::try_parse_from([
+            "toygres", "create", "mydb", "-p", "password123", "--service-annotation", "team",
+        ]).is_err());
+    }
+}
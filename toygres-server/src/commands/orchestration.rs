@@ -1,16 +1,32 @@
 use anyhow::Result;
 use reqwest::StatusCode;
+use std::collections::HashMap;
+use toygres_orchestrations::flows::NodeStatus;
 
 use crate::commands::server::ensure_server_running;
 
-pub async fn list(status: Option<String>, instance: Option<String>, limit: usize) -> Result<()> {
+pub async fn list(status: Option<String>, instance: Option<String>, limit: usize, offset: usize) -> Result<()> {
     // Ensure server is running (auto-start if needed)
     ensure_server_running().await?;
     
     let api_url = std::env::var("TOYGRES_API_URL")
         .unwrap_or_else(|_| "http://localhost:8080".to_string());
     
-    let response = reqwest::get(format!("{}/api/server/orchestrations", api_url))
+    // Filtering and the limit are applied by the server
+    let mut params = vec![("limit", limit.to_string()), ("offset", offset.to_string())];
+    if let Some(status_filter) = &status {
+        params.push(("status", status_filter.clone()));
+    }
+    if let Some(instance_filter) = &instance {
+        // The server matches ids by instance (see toygres_orchestrations::ids), so a
+        // name does not also pick up other instances whose names contain it
+        params.push(("name", instance_filter.clone()));
+    }
+    
+    let response = reqwest::Client::new()
+        .get(format!("{}/api/server/orchestrations", api_url))
+        .query(&params)
+        .send()
         .await
         .map_err(|e| anyhow::anyhow!("Failed to connect to API: {}", e))?;
     
@@ -18,30 +34,7 @@ pub async fn list(status: Option<String>, instance: Option<String>, limit: usize
         anyhow::bail!("API error: {}", response.status());
     }
     
-    let mut orchestrations: Vec<serde_json::Value> = response.json().await?;
-    
-    // Filter by status if provided
-    if let Some(status_filter) = &status {
-        orchestrations.retain(|o| {
-            o["status"].as_str().map(|s| s.contains(status_filter)).unwrap_or(false)
-        });
-    }
-    
-    // Filter by instance name if provided
-    if let Some(instance_filter) = &instance {
-        orchestrations.retain(|o| {
-            // Check if the instance_id contains the instance name
-            // Orchestration IDs follow patterns like: create-<name>-<guid>, delete-<name>-<guid>
-            if let Some(id) = o["instance_id"].as_str() {
-                id.contains(instance_filter)
-            } else {
-                false
-            }
-        });
-    }
-    
-    // Limit results
-    orchestrations.truncate(limit);
+    let orchestrations: Vec<serde_json::Value> = response.json().await?;
     
     // Show filter info if applied
     if let Some(ref inst) = instance {
@@ -156,11 +149,18 @@ pub async fn get(id: &str, history: bool) -> Result<()> {
                 println!("{}", "-".repeat(80));
                 println!();
                 
-                // Pretty-print the history JSON
-                if let Ok(pretty) = serde_json::to_string_pretty(history_arr) {
-                    println!("{}", pretty);
-                } else {
-                    println!("{:?}", history_arr);
+                println!("{:<6} {:<6} {:<30} {:<8} {:<4}",
+                         "EXEC", "EVENT", "KIND", "SOURCE", "NAME");
+                for event in history_arr {
+                    let source = event["source_event_id"].as_u64()
+                        .map(|id| id.to_string())
+                        .unwrap_or_else(|| "-".to_string());
+                    println!("{:<6} {:<6} {:<30} {:<8} {}",
+                             event["execution_id"].as_u64().unwrap_or(0),
+                             event["event_id"].as_u64().unwrap_or(0),
+                             event["kind"].as_str().unwrap_or("Unknown"),
+                             source,
+                             event["name"].as_str().unwrap_or("-"));
                 }
                 println!();
             } else {
@@ -269,3 +269,133 @@ pub async fn cancel(id: &str, force: bool) -> Result<()> {
     Ok(())
 }
 
+
+pub async fn flow(id: &str) -> Result<()> {
+    // Ensure server is running (auto-start if needed)
+    ensure_server_running().await?;
+    
+    let api_url = std::env::var("TOYGRES_API_URL")
+        .unwrap_or_else(|_| "http://localhost:8080".to_string());
+    
+    let response = reqwest::get(format!("{}/api/server/orchestrations/{}/flow", api_url, id))
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to connect to API: {}", e))?;
+    
+    if response.status() == StatusCode::NOT_FOUND {
+        let error: serde_json::Value = response.json().await.unwrap_or_default();
+        anyhow::bail!("{}", error["error"].as_str().unwrap_or("Orchestration not found"));
+    }
+    
+    if !response.status().is_success() {
+        anyhow::bail!("API error: {}", response.status());
+    }
+    
+    let flow: serde_json::Value = response.json().await?;
+    let nodes: HashMap<String, NodeStatus> = flow["nodes"].as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|node| {
+            let status = serde_json::from_value(node["status"].clone()).ok()?;
+            Some((node["node_id"].as_str()?.to_string(), status))
+        })
+        .collect();
+    
+    println!("Orchestration: {} ({})", id, flow["status"].as_str().unwrap_or("-"));
+    println!("Flow:          {}", flow["orchestration_name"].as_str().unwrap_or("-"));
+    println!("{}", "=".repeat(80));
+    println!();
+    println!("{}", render_flow_progress(flow["mermaid"].as_str().unwrap_or(""), &nodes));
+    println!();
+    println!("✓ done   ▶ running   ✗ failed   · pending");
+    
+    Ok(())
+}
+
+/// Terminal marker for a node status
+fn status_marker(status: NodeStatus) -> char {
+    match status {
+        NodeStatus::Done => '✓',
+        NodeStatus::Running => '▶',
+        NodeStatus::Failed => '✗',
+        NodeStatus::Pending => '·',
+    }
+}
+
+/// The mermaid source with a gutter marking each tracked node's definition line
+fn render_flow_progress(mermaid: &str, nodes: &HashMap<String, NodeStatus>) -> String {
+    mermaid.lines()
+        .map(|line| {
+            let marker = node_definition_id(line)
+                .and_then(|id| nodes.get(id))
+                .map(|status| status_marker(*status))
+                .unwrap_or(' ');
+            format!("{} {}", marker, line)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub async fn raise_event(id: &str, name: &str, data: &str) -> Result<()> {
+    // Ensure server is running (auto-start if needed)
+    ensure_server_running().await?;
+    
+    let api_url = std::env::var("TOYGRES_API_URL")
+        .unwrap_or_else(|_| "http://localhost:8080".to_string());
+    
+    let response = reqwest::Client::new()
+        .post(format!("{}/api/server/orchestrations/{}/raise-event", api_url, id))
+        .json(&serde_json::json!({
+            "event_name": name,
+            "event_data": data,
+        }))
+        .send()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to connect to API: {}", e))?;
+    
+    if !response.status().is_success() {
+        let error_msg = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+        anyhow::bail!("Failed to raise event: {}", error_msg);
+    }
+    
+    println!("✓ Raised '{}' on {}", name, id);
+    println!("  Data: {}", data);
+    
+    Ok(())
+}
+
+/// The node ID if `line` defines a node (`id[...]`, `id{...}`, `id(...)`)
+fn node_definition_id(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    let end = line.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))?;
+    let id = &line[..end];
+    (!id.is_empty() && line[end..].starts_with(['[', '{', '('])).then_some(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_flow_progress_rendering_marks_tracked_nodes() {
+        let mermaid = "flowchart TD\n    cms_record[\"Create CMS Record\"]\n    deploy_k8s[\"Deploy\"]\n    \
+                       wait_ready{\"Pod Ready?\"}\n    get_conn[\"Get Connection\"]\n    start([\"Start\"])\n    \
+                       cms_record --> deploy_k8s";
+        let nodes: HashMap<String, NodeStatus> = [
+            ("cms_record", NodeStatus::Done),
+            ("deploy_k8s", NodeStatus::Failed),
+            ("wait_ready", NodeStatus::Running),
+            ("get_conn", NodeStatus::Pending),
+        ].into_iter().map(|(id, status)| (id.to_string(), status)).collect();
+        
+        let rendered = render_flow_progress(mermaid, &nodes);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines[0], "  flowchart TD");
+        assert_eq!(lines[1], "✓     cms_record[\"Create CMS Record\"]");
+        assert_eq!(lines[2], "✗     deploy_k8s[\"Deploy\"]");
+        assert_eq!(lines[3], "▶     wait_ready{\"Pod Ready?\"}");
+        assert_eq!(lines[4], "·     get_conn[\"Get Connection\"]");
+        // Untracked nodes and edges get a blank gutter
+        assert_eq!(lines[5], "      start([\"Start\"])");
+        assert_eq!(lines[6], "      cms_record --> deploy_k8s");
+    }
+}
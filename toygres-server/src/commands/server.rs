@@ -25,8 +25,8 @@ pub async fn handle_command(command: ServerCommand) -> Result<()> {
         ServerCommand::Logs { follow, tail, orchestration } => {
             logs(&log_file, follow, tail, orchestration).await
         }
-        ServerCommand::Orchestrations { status, instance, limit } => {
-            crate::commands::orchestration::list(status, instance, limit).await
+        ServerCommand::Orchestrations { status, instance, limit, offset } => {
+            crate::commands::orchestration::list(status, instance, limit, offset).await
         }
         ServerCommand::Orchestration { id, history } => {
             crate::commands::orchestration::get(&id, history).await
@@ -34,6 +34,9 @@ pub async fn handle_command(command: ServerCommand) -> Result<()> {
         ServerCommand::Cancel { id, force } => {
             crate::commands::orchestration::cancel(&id, force).await
         }
+        ServerCommand::RaiseEvent { id, name, data } => {
+            crate::commands::orchestration::raise_event(&id, &name, &data).await
+        }
         ServerCommand::Stats { watch } => {
             crate::commands::system::stats(watch).await
         }
@@ -55,6 +58,8 @@ pub async fn run_standalone_mode(port: u16, _workers: usize) -> Result<()> {
     
     // Initialize Duroxide
     let (runtime, store) = crate::duroxide::initialize().await?;
+    // Stable across restarts, so a restarted server refreshes its own row
+    let heartbeat = crate::worker::Heartbeat::start(&format!("standalone-{}", crate::worker::hostname())).await?;
     
     // Create API state
     let client = std::sync::Arc::new(duroxide::Client::new(store.clone()));
@@ -82,6 +87,9 @@ pub async fn run_standalone_mode(port: u16, _workers: usize) -> Result<()> {
     
     tracing::info!("Shutting down...");
     api_handle.abort();
+    if let Some(heartbeat) = heartbeat {
+        heartbeat.stop().await;
+    }
     
     tracing::info!("Shutting down Duroxide runtime");
     runtime.shutdown(None).await;
@@ -241,12 +249,16 @@ async fn status(pid_file: &Path) -> Result<()> {
 }
 
 async fn logs(log_file: &Path, follow: bool, tail: usize, orchestration: Option<String>) -> Result<()> {
+    use crate::logs::{parse_line, read_entries, LogFilter};
+
     if !log_file.exists() {
         println!("✗ No log file found at: {}", log_file.display());
         println!("  Server may not have been started yet");
         return Ok(());
     }
     
+    let filter = LogFilter { text: orchestration.clone(), ..Default::default() };
+    
     if follow {
         // Follow logs (like tail -f)
         if let Some(ref orch_id) = orchestration {
@@ -257,68 +269,57 @@ async fn logs(log_file: &Path, follow: bool, tail: usize, orchestration: Option<
         println!("Press Ctrl+C to stop");
         println!();
         
-        // Use tail command with grep on Unix
-        #[cfg(unix)]
-        {
-            if let Some(orch_id) = orchestration {
-                // Use tail -f piped through grep for filtering
-                let mut child = std::process::Command::new("sh")
-                    .args([
-                        "-c",
-                        &format!(
-                            "tail -f -n {} {} | grep --line-buffered '{}'",
-                            tail,
-                            log_file.to_str().unwrap(),
-                            orch_id
-                        )
-                    ])
-                    .spawn()?;
-                
-                let status = child.wait()?;
-                if !status.success() {
-                    anyhow::bail!("Failed to tail and filter logs");
+        for entry in read_entries(log_file, &filter, tail)? {
+            println!("{}", entry.render());
+        }
+        
+        // Poll for appended lines; a shorter file means it was truncated or replaced
+        use std::io::{Read, Seek, SeekFrom};
+        let mut offset = std::fs::metadata(log_file)?.len();
+        let mut pending = String::new();
+        loop {
+            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+            
+            let len = match std::fs::metadata(log_file) {
+                Ok(metadata) => metadata.len(),
+                Err(_) => continue,
+            };
+            if len < offset {
+                offset = 0;
+                pending.clear();
+            }
+            if len == offset {
+                continue;
+            }
+            
+            let mut file = std::fs::File::open(log_file)?;
+            file.seek(SeekFrom::Start(offset))?;
+            let mut chunk = Vec::new();
+            file.take(len - offset).read_to_end(&mut chunk)?;
+            offset = len;
+            pending.push_str(&String::from_utf8_lossy(&chunk));
+            
+            // Keep a trailing partial line until the rest of it is written
+            while let Some(newline) = pending.find('\n') {
+                let line: String = pending.drain(..=newline).collect();
+                let line = line.trim_end();
+                if line.is_empty() {
+                    continue;
                 }
-            } else {
-                let status = std::process::Command::new("tail")
-                    .args(["-f", "-n", &tail.to_string(), log_file.to_str().unwrap()])
-                    .status()?;
-                
-                if !status.success() {
-                    anyhow::bail!("Failed to tail logs");
+                let entry = parse_line(line);
+                if entry.matches(&filter) {
+                    println!("{}", entry.render());
                 }
             }
         }
-        
-        #[cfg(not(unix))]
-        {
-            anyhow::bail!("Follow mode not supported on this platform");
-        }
     } else {
-        // Show last N lines
-        use std::io::{BufRead, BufReader};
-        
-        let file = std::fs::File::open(log_file)?;
-        let reader = BufReader::new(file);
-        
-        // Filter lines if orchestration ID is provided
-        let all_lines: Vec<String> = reader.lines().filter_map(|l| l.ok()).collect();
+        // Show last N entries
+        let entries = read_entries(log_file, &filter, usize::MAX)?;
         
-        let filtered_lines: Vec<&String> = if let Some(ref orch_id) = orchestration {
-            all_lines.iter()
-                .filter(|line| line.contains(orch_id))
-                .collect()
-        } else {
-            all_lines.iter().collect()
-        };
-        
-        let start = if filtered_lines.len() > tail { 
-            filtered_lines.len() - tail 
-        } else { 
-            0 
-        };
+        let start = entries.len().saturating_sub(tail);
         
         if let Some(ref orch_id) = orchestration {
-            if filtered_lines.is_empty() {
+            if entries.is_empty() {
                 println!("No log entries found for orchestration: {}", orch_id);
                 println!();
                 println!("Tips:");
@@ -327,20 +328,20 @@ async fn logs(log_file: &Path, follow: bool, tail: usize, orchestration: Option<
                 return Ok(());
             }
             
-            println!("Showing {} log entries for orchestration: {}", filtered_lines.len(), orch_id);
+            println!("Showing {} log entries for orchestration: {}", entries.len(), orch_id);
             println!("{}", "-".repeat(80));
             println!();
         }
         
-        for line in &filtered_lines[start..] {
-            println!("{}", line);
+        for entry in &entries[start..] {
+            println!("{}", entry.render());
         }
         
-        if let Some(_) = orchestration {
+        if orchestration.is_some() {
             println!();
             println!("Showing last {} matching entries (total: {} matches)", 
-                     filtered_lines.len() - start, 
-                     filtered_lines.len());
+                     entries.len() - start, 
+                     entries.len());
         }
     }
     
@@ -384,11 +385,9 @@ pub async fn ensure_server_running() -> Result<()> {
         .unwrap_or_else(|_| "http://localhost:8080".to_string());
     
     // First, try to connect to the API
-    if let Ok(response) = reqwest::get(format!("{}/health", api_url)).await {
-        if response.status().is_success() {
-            // Server is running
-            return Ok(());
-        }
+    if is_healthy(&api_url).await {
+        // Server is running
+        return Ok(());
     }
     
     // If using a non-local API, can't auto-start
@@ -403,19 +402,105 @@ pub async fn ensure_server_running() -> Result<()> {
     
     // Check if server process exists
     if is_running(&pid_file)? {
-        // PID file exists but API not responding - give it more time
-        println!("Server is starting up, waiting...");
-        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+        // PID file exists but API not responding - it may still be running migrations
+        let timeout = startup_wait();
+        println!("Server is starting up, waiting up to {}s...", timeout.as_secs());
         
-        if let Ok(response) = reqwest::get(format!("{}/health", api_url)).await {
-            if response.status().is_success() {
-                return Ok(());
-            }
+        if wait_for_health(&api_url, timeout, STARTUP_POLL_INTERVAL).await {
+            return Ok(());
         }
         
-        anyhow::bail!("Server is running but not responding. Check logs: ./toygres server logs");
+        anyhow::bail!(
+            "Server is running but not responding after {}s (set {} to wait longer). Check logs: ./toygres server logs",
+            timeout.as_secs(),
+            STARTUP_WAIT_ENV
+        );
     }
     
     anyhow::bail!("Server is required for this command. Start it with: ./toygres server start");
 }
 
+/// Environment variable for how long to wait for a starting server (seconds)
+pub const STARTUP_WAIT_ENV: &str = "TOYGRES_STARTUP_WAIT_SECS";
+
+/// Default wait for a starting server; long enough for startup migrations
+const DEFAULT_STARTUP_WAIT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// How often `/health` is polled while waiting
+const STARTUP_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Resolve the startup wait from the environment
+fn startup_wait() -> std::time::Duration {
+    std::env::var(STARTUP_WAIT_ENV)
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .map(std::time::Duration::from_secs)
+        .unwrap_or(DEFAULT_STARTUP_WAIT)
+}
+
+async fn is_healthy(api_url: &str) -> bool {
+    matches!(reqwest::get(format!("{}/health", api_url)).await, Ok(response) if response.status().is_success())
+}
+
+/// Poll `/health` every `interval` until it succeeds or `timeout` has passed
+async fn wait_for_health(api_url: &str, timeout: std::time::Duration, interval: std::time::Duration) -> bool {
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        if is_healthy(api_url).await {
+            return true;
+        }
+        if tokio::time::Instant::now() + interval > deadline {
+            return false;
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, routing::get, Router};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// Serve `/health` that fails until it has been polled `healthy_after` times
+    async fn mock_server(healthy_after: usize) -> (String, Arc<AtomicUsize>) {
+        let polls = Arc::new(AtomicUsize::new(0));
+        let counter = polls.clone();
+        let app = Router::new().route("/health", get(move || {
+            let counter = counter.clone();
+            async move {
+                if counter.fetch_add(1, Ordering::SeqCst) + 1 >= healthy_after {
+                    StatusCode::OK
+                } else {
+                    StatusCode::SERVICE_UNAVAILABLE
+                }
+            }
+        }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (url, polls)
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_health_retries_until_healthy() {
+        let (url, polls) = mock_server(4).await;
+
+        assert!(wait_for_health(&url, Duration::from_secs(5), Duration::from_millis(10)).await);
+        assert_eq!(polls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_health_gives_up_after_timeout() {
+        let (url, polls) = mock_server(usize::MAX).await;
+        let started = std::time::Instant::now();
+
+        assert!(!wait_for_health(&url, Duration::from_millis(200), Duration::from_millis(20)).await);
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert!(polls.load(Ordering::SeqCst) > 1, "polled {} times", polls.load(Ordering::SeqCst));
+    }
+}
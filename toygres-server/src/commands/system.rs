@@ -1,5 +1,5 @@
 use anyhow::Result;
-use std::collections::HashMap;
+use toygres_models::SystemStats;
 
 use crate::commands::server::ensure_server_running;
 
@@ -31,26 +31,20 @@ pub async fn stats(watch: bool) -> Result<()> {
 }
 
 async fn display_stats(api_url: &str) -> Result<()> {
-    // Fetch orchestrations
-    let orchestrations_response = reqwest::get(format!("{}/api/server/orchestrations", api_url))
+    let response = reqwest::get(format!("{}/api/server/summary", api_url))
         .await
-        .map_err(|e| anyhow::anyhow!("Failed to fetch orchestrations: {}", e))?;
+        .map_err(|e| anyhow::anyhow!("Failed to fetch summary: {}", e))?;
     
-    let orchestrations: Vec<serde_json::Value> = if orchestrations_response.status().is_success() {
-        orchestrations_response.json().await.unwrap_or_default()
-    } else {
-        Vec::new()
-    };
+    if !response.status().is_success() {
+        anyhow::bail!("API error: {}", response.status());
+    }
     
-    // Fetch instances
-    let instances_response = reqwest::get(format!("{}/api/instances", api_url))
-        .await
-        .map_err(|e| anyhow::anyhow!("Failed to fetch instances: {}", e))?;
+    let summary: SystemStats = response.json().await?;
+    let instances = &summary.instances;
+    let orchestrations = &summary.orchestrations;
     
-    let instances: Vec<serde_json::Value> = if instances_response.status().is_success() {
-        instances_response.json().await.unwrap_or_default()
-    } else {
-        Vec::new()
+    let count = |map: &std::collections::BTreeMap<String, i64>, key: &str| -> usize {
+        map.get(key).copied().unwrap_or(0).max(0) as usize
     };
     
     println!("Toygres System Statistics");
@@ -58,11 +52,11 @@ async fn display_stats(api_url: &str) -> Result<()> {
     println!();
     
     // Instance statistics
-    let total_instances = instances.len();
-    let running = instances.iter().filter(|i| i["state"].as_str() == Some("running")).count();
-    let creating = instances.iter().filter(|i| i["state"].as_str() == Some("creating")).count();
-    let deleting = instances.iter().filter(|i| i["state"].as_str() == Some("deleting")).count();
-    let failed = instances.iter().filter(|i| i["state"].as_str() == Some("failed")).count();
+    let total_instances = instances.total.max(0) as usize;
+    let running = count(&instances.by_state, "running");
+    let creating = count(&instances.by_state, "creating");
+    let deleting = count(&instances.by_state, "deleting");
+    let failed = count(&instances.by_state, "failed");
     
     println!("Instances:");
     println!("  Total:             {}", total_instances);
@@ -73,73 +67,124 @@ async fn display_stats(api_url: &str) -> Result<()> {
     println!();
     
     // Health status
-    let healthy = instances.iter().filter(|i| i["health_status"].as_str() == Some("healthy")).count();
-    let unhealthy = instances.iter().filter(|i| i["health_status"].as_str() == Some("unhealthy")).count();
-    let unknown = instances.iter().filter(|i| {
-        let health = i["health_status"].as_str().unwrap_or("unknown");
-        health != "healthy" && health != "unhealthy"
-    }).count();
+    let healthy = count(&instances.by_health, "healthy");
+    let degraded = count(&instances.by_health, "degraded");
+    let unhealthy = count(&instances.by_health, "unhealthy");
+    let provisioning = count(&instances.by_health, "provisioning");
+    let unmonitored = count(&instances.by_health, "unmonitored");
+    let unknown = total_instances.saturating_sub(healthy + degraded + unhealthy + provisioning + unmonitored);
     
     println!("Health Status:");
     println!("  Healthy:           {}  {}", healthy, format_percentage(healthy, total_instances));
+    println!("  Degraded:          {}  {}", degraded, format_percentage(degraded, total_instances));
     println!("  Unhealthy:         {}  {}", unhealthy, format_percentage(unhealthy, total_instances));
+    println!("  Provisioning:      {}  {}", provisioning, format_percentage(provisioning, total_instances));
+    println!("  Unmonitored:       {}  {}", unmonitored, format_percentage(unmonitored, total_instances));
     println!("  Unknown:           {}  {}", unknown, format_percentage(unknown, total_instances));
     println!();
     
     // Orchestration statistics
-    let total_orches = orchestrations.len();
-    let running_orches = orchestrations.iter().filter(|o| o["status"].as_str() == Some("Running")).count();
-    let completed_orches = orchestrations.iter().filter(|o| o["status"].as_str() == Some("Completed")).count();
-    let failed_orches = orchestrations.iter().filter(|o| o["status"].as_str() == Some("Failed")).count();
+    let total_orches = orchestrations.total as usize;
+    let running_orches = orchestrations.running as usize;
+    let completed_orches = orchestrations.completed as usize;
+    let failed_orches = orchestrations.failed as usize;
     
     println!("Orchestrations (All Time):");
     println!("  Total:             {}", total_orches);
-    println!("  Running:           {}  {}", running_orches, format_percentage(running_orches, total_orches));
-    println!("  Completed:         {}  {}", completed_orches, format_percentage(completed_orches, total_orches));
-    println!("  Failed:            {}  {}", failed_orches, format_percentage(failed_orches, total_orches));
+    if let Some(note) = &orchestrations.note {
+        println!("  Note: {}", note);
+    } else {
+        println!("  Running:           {}  {}", running_orches, format_percentage(running_orches, total_orches));
+        println!("  Completed:         {}  {}", completed_orches, format_percentage(completed_orches, total_orches));
+        println!("  Failed:            {}  {}", failed_orches, format_percentage(failed_orches, total_orches));
+    }
     println!();
     
     // By type
-    let mut type_counts: HashMap<String, (usize, usize, usize)> = HashMap::new();
-    for orch in &orchestrations {
-        if let Some(name) = orch["orchestration_name"].as_str() {
-            let short_name = name.split("::").last().unwrap_or(name).to_string();
-            let status = orch["status"].as_str().unwrap_or("unknown");
-            
-            let entry = type_counts.entry(short_name).or_insert((0, 0, 0));
-            entry.0 += 1; // total
-            if status == "Completed" {
-                entry.1 += 1; // completed
-            } else if status == "Running" {
-                entry.2 += 1; // running
-            }
-        }
-    }
-    
-    if !type_counts.is_empty() {
+    if !orchestrations.by_type.is_empty() {
         println!("By Type:");
-        for (name, (total, completed, running)) in type_counts.iter() {
-            println!("  {:<25} {} total, {} completed, {} running", 
-                     name, total, completed, running);
+        for type_stats in &orchestrations.by_type {
+            let name = &type_stats.orchestration_name;
+            let short_name = name.split("::").last().unwrap_or(name);
+            if orchestrations.note.is_some() {
+                println!("  {:<25} {} tracked", short_name, type_stats.total);
+            } else {
+                println!("  {:<25} {} total, {} completed, {} running", 
+                         short_name,
+                         type_stats.total,
+                         count(&type_stats.by_status, "Completed"),
+                         count(&type_stats.by_status, "Running"));
+            }
         }
         println!();
     }
     
     // Resource usage
-    let total_storage: i64 = instances.iter()
-        .filter_map(|i| i["storage_size_gb"].as_i64())
-        .sum();
+    let total_storage = instances.total_storage_gb;
     
     if total_instances > 0 {
         println!("Resource Usage:");
         println!("  Storage (provisioned):  {} GB across {} instances", total_storage, total_instances);
-        println!("  Average per instance:   {} GB", if total_instances > 0 { total_storage / total_instances as i64 } else { 0 });
+        println!("  Average per instance:   {} GB", total_storage / total_instances as i64);
         println!();
     }
     
     // Timestamp
-    let now = chrono::Utc::now();
-    println!("Last Updated: {} (just now)", now.to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
+    println!("Last Updated: {}", summary.generated_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
+    
+    Ok(())
+}
+
+/// The worker processes that have reported in, live ones first
+fn display_worker_heartbeats(heartbeats: &[toygres_models::WorkerHeartbeat]) {
+    if heartbeats.is_empty() {
+        println!("No worker heartbeats recorded");
+        println!();
+        return;
+    }
+    
+    println!("{:<40} {:<25} {:<8} {:<22} {:<20}", "WORKER", "HOST", "STATUS", "LAST HEARTBEAT", "STARTED");
+    println!("{}", "-".repeat(120));
+    for worker in heartbeats.iter().filter(|w| !w.stale).chain(heartbeats.iter().filter(|w| w.stale)) {
+        println!(
+            "{:<40} {:<25} {:<8} {:<22} {}",
+            worker.worker_id,
+            worker.hostname,
+            if worker.stale { "stale" } else { "live" },
+            worker.last_heartbeat.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            worker.started_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        );
+    }
+    let live = heartbeats.iter().filter(|w| !w.stale).count();
+    println!();
+    println!("{} live, {} stale (no heartbeat for {}s)", live, heartbeats.len() - live, toygres_models::WORKER_STALE_AFTER_SECS);
+    println!();
+}
+
+/// `workers` output when the store has no management API: the ids the CMS tracks,
+/// without status
+fn display_tracked_orchestrations(orchestrations: &[serde_json::Value]) -> Result<()> {
+    println!("Note: detailed orchestration info is unavailable (the Duroxide store has no");
+    println!("management API). Showing orchestration ids tracked in the CMS; status is unknown.");
+    println!();
+    
+    if orchestrations.is_empty() {
+        println!("No orchestrations tracked in the CMS");
+        return Ok(());
+    }
+    
+    println!("{:<45} {:<20} {:<20}", "ID", "TYPE", "INSTANCE CREATED");
+    println!("{}", "-".repeat(80));
+    for orch in orchestrations {
+        let id = orch["instance_id"].as_str().unwrap_or("-");
+        let name = orch["orchestration_name"].as_str()
+            .and_then(|s| s.split("::").last())
+            .unwrap_or("-");
+        let created = orch["created_at"].as_str().unwrap_or("-");
+        println!("{:<45} {:<20} {}", id, name, created);
+    }
+    println!();
+    println!("{} orchestration(s) tracked", orchestrations.len());
     
     Ok(())
 }
@@ -189,7 +234,7 @@ pub async fn config() -> Result<()> {
     // Kubernetes
     let cluster = std::env::var("AKS_CLUSTER_NAME").unwrap_or_else(|_| "not set".to_string());
     let resource_group = std::env::var("AKS_RESOURCE_GROUP").unwrap_or_else(|_| "not set".to_string());
-    let namespace = std::env::var("AKS_NAMESPACE").unwrap_or_else(|_| "toygres".to_string());
+    let namespace = toygres_models::default_namespace();
     let kubeconfig = std::env::var("HOME")
         .map(|h| format!("{}/.kube/config", h))
         .unwrap_or_else(|_| "~/.kube/config".to_string());
@@ -303,6 +348,15 @@ pub async fn workers(_watch: bool) -> Result<()> {
     let api_url = std::env::var("TOYGRES_API_URL")
         .unwrap_or_else(|_| "http://localhost:8080".to_string());
     
+    // Worker processes, from their heartbeats
+    let response = reqwest::get(format!("{}/api/server/workers", api_url))
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to fetch workers: {}", e))?;
+    if !response.status().is_success() {
+        anyhow::bail!("API error: {}", response.status());
+    }
+    let heartbeats: Vec<toygres_models::WorkerHeartbeat> = response.json().await?;
+    
     // Fetch orchestrations to see what's running
     let response = reqwest::get(format!("{}/api/server/orchestrations", api_url))
         .await
@@ -312,11 +366,19 @@ pub async fn workers(_watch: bool) -> Result<()> {
         anyhow::bail!("API error: {}", response.status());
     }
     
+    let cms_only = response.headers()
+        .get(crate::api::ORCHESTRATION_SOURCE_HEADER)
+        .is_some_and(|source| source == "cms");
     let orchestrations: Vec<serde_json::Value> = response.json().await?;
     
     println!("Duroxide Workers");
     println!("{}", "=".repeat(80));
     println!();
+    display_worker_heartbeats(&heartbeats);
+    
+    if cms_only {
+        return display_tracked_orchestrations(&orchestrations);
+    }
     
     // Filter running orchestrations
     let running: Vec<&serde_json::Value> = orchestrations.iter()
@@ -29,8 +29,7 @@ impl Config {
                 .context("AKS_CLUSTER_NAME must be set")?,
             aks_resource_group: std::env::var("AKS_RESOURCE_GROUP")
                 .context("AKS_RESOURCE_GROUP must be set")?,
-            aks_namespace: std::env::var("AKS_NAMESPACE")
-                .unwrap_or_else(|_| "toygres".to_string()),
+            aks_namespace: toygres_models::default_namespace(),
         })
     }
 }
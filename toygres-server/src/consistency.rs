@@ -0,0 +1,168 @@
+//! CMS/Kubernetes consistency gauge
+//!
+//! Every [`SWEEP_INTERVAL`] the API process compares the CMS with the cluster and
+//! publishes what disagrees as the [`INCONSISTENCIES`] gauge on `/metrics`:
+//! - `running_not_ready`: instances `running` in the CMS whose StatefulSet has no
+//!   ready replica (or no StatefulSet at all)
+//! - `unrecorded_resources`: Toygres-labeled resources whose instance has no CMS row
+//!   other than a deleted one
+//!
+//! The sweep runs on its own schedule rather than per scrape so scrapes never wait
+//! on the cluster. Until the first sweep succeeds the gauge is not exported.
+
+use std::collections::{BTreeSet, HashSet};
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+use toygres_orchestrations::activities::list_toygres_resources;
+use toygres_orchestrations::activity_types::ToygresResource;
+
+/// Gauge of CMS/cluster disagreements found by the last sweep, labeled by `kind`
+pub const INCONSISTENCIES: &str = "toygres_cms_k8s_inconsistencies";
+
+/// How often the sweep runs
+pub const SWEEP_INTERVAL: Duration = Duration::from_secs(300);
+
+/// What one sweep found
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Inconsistencies {
+    pub running_not_ready: usize,
+    pub unrecorded_resources: usize,
+}
+
+static LAST_SWEEP: Mutex<Option<Inconsistencies>> = Mutex::new(None);
+
+/// Count the disagreements in one namespace.
+///
+/// `instances` are the namespace's `(k8s_name, state)` CMS rows other than deleted
+/// ones, `resources` its labeled resources and `ready` the instances whose
+/// StatefulSet has a ready replica.
+pub fn count_inconsistencies(
+    instances: &[(String, String)],
+    resources: &[ToygresResource],
+    ready: &HashSet<String>,
+) -> Inconsistencies {
+    let recorded: HashSet<&str> = instances.iter().map(|(name, _)| name.as_str()).collect();
+
+    Inconsistencies {
+        running_not_ready: instances.iter()
+            .filter(|(name, state)| state == "running" && !ready.contains(name))
+            .count(),
+        unrecorded_resources: resources.iter()
+            .filter(|resource| !recorded.contains(resource.instance.as_str()))
+            .count(),
+    }
+}
+
+/// Compare the CMS with every namespace it mentions (plus the default one)
+async fn sweep(pool: &sqlx::PgPool) -> anyhow::Result<Inconsistencies> {
+    let rows = crate::db::recorded_instances(pool).await?;
+    let client = toygres_orchestrations::k8s_client::get_k8s_client().await?;
+
+    let namespaces: BTreeSet<String> = rows.iter()
+        .map(|(_, namespace, _)| namespace.clone())
+        .chain([toygres_models::default_namespace()])
+        .collect();
+
+    let mut total = Inconsistencies::default();
+    for namespace in namespaces {
+        let instances: Vec<(String, String)> = rows.iter()
+            .filter(|(_, ns, _)| *ns == namespace)
+            .map(|(name, _, state)| (name.clone(), state.clone()))
+            .collect();
+        let resources = list_toygres_resources::list_resources(&client, &namespace).await
+            .map_err(anyhow::Error::msg)?;
+        let ready: HashSet<String> = list_toygres_resources::ready_instances(&client, &namespace).await
+            .map_err(anyhow::Error::msg)?
+            .into_iter()
+            .collect();
+
+        let found = count_inconsistencies(&instances, &resources, &ready);
+        total.running_not_ready += found.running_not_ready;
+        total.unrecorded_resources += found.unrecorded_resources;
+    }
+    Ok(total)
+}
+
+/// Sweep every [`SWEEP_INTERVAL`] in the background, keeping the last result for
+/// [`render_prometheus`]. A failed sweep keeps the previous result.
+pub fn spawn(pool: sqlx::PgPool) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            match sweep(&pool).await {
+                Ok(found) => {
+                    if found != Inconsistencies::default() {
+                        tracing::warn!(
+                            running_not_ready = found.running_not_ready,
+                            unrecorded_resources = found.unrecorded_resources,
+                            "CMS and cluster disagree"
+                        );
+                    }
+                    *LAST_SWEEP.lock().unwrap_or_else(|e| e.into_inner()) = Some(found);
+                }
+                Err(e) => tracing::warn!("Consistency sweep failed: {:#}", e),
+            }
+        }
+    });
+}
+
+/// The gauge in the Prometheus text format; empty before the first successful sweep
+pub fn render_prometheus() -> String {
+    let Some(found) = *LAST_SWEEP.lock().unwrap_or_else(|e| e.into_inner()) else {
+        return String::new();
+    };
+
+    let mut out = String::new();
+    let _ = writeln!(out, "# HELP {} CMS/Kubernetes disagreements found by the last consistency sweep", INCONSISTENCIES);
+    let _ = writeln!(out, "# TYPE {} gauge", INCONSISTENCIES);
+    let _ = writeln!(out, "{}{{kind=\"running_not_ready\"}} {}", INCONSISTENCIES, found.running_not_ready);
+    let _ = writeln!(out, "{}{{kind=\"unrecorded_resources\"}} {}", INCONSISTENCIES, found.unrecorded_resources);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use list_toygres_resources::{PERSISTENT_VOLUME_CLAIM, SERVICE, STATEFUL_SET};
+
+    fn resource(kind: &str, instance: &str) -> ToygresResource {
+        ToygresResource {
+            kind: kind.to_string(),
+            name: list_toygres_resources::expected_name(kind, instance).unwrap(),
+            instance: instance.to_string(),
+            uid: None,
+            created_at_ms: None,
+        }
+    }
+
+    #[test]
+    fn test_inconsistencies_counted_from_cms_and_cluster() {
+        let instances: Vec<(String, String)> = [
+            ("healthy-1", "running"),
+            ("crashing-1", "running"),
+            ("vanished-1", "running"),
+            ("creating-1", "creating"),
+            ("failed-1", "failed"),
+        ].iter().map(|(name, state)| (name.to_string(), state.to_string())).collect();
+        let resources = vec![
+            resource(STATEFUL_SET, "healthy-1"),
+            resource(SERVICE, "healthy-1"),
+            resource(STATEFUL_SET, "crashing-1"),
+            // A create still deploying, and a failed one whose cleanup failed: both recorded
+            resource(PERSISTENT_VOLUME_CLAIM, "creating-1"),
+            resource(STATEFUL_SET, "failed-1"),
+            // Leftovers of an instance the CMS no longer knows
+            resource(SERVICE, "ghost-1"),
+            resource(PERSISTENT_VOLUME_CLAIM, "ghost-1"),
+        ];
+        let ready = HashSet::from(["healthy-1".to_string()]);
+
+        assert_eq!(
+            count_inconsistencies(&instances, &resources, &ready),
+            Inconsistencies { running_not_ready: 2, unrecorded_resources: 2 }
+        );
+        assert_eq!(count_inconsistencies(&[], &[], &HashSet::new()), Inconsistencies::default());
+    }
+}
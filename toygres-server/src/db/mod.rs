@@ -1,4 +1,7 @@
 use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use toygres_orchestrations::activity_types::InstanceDeployOptions;
+use toygres_models::{InstanceManifest, InstanceStats, MaintenanceMode, OrchestrationTypeStats, WorkerHeartbeat, MANIFEST_VERSION};
 
 /// Initialize the CMS schema in the database
 pub async fn initialize_cms_schema(db_url: &str) -> Result<()> {
@@ -59,8 +62,12 @@ pub async fn verify_cms_tables(db_url: &str) -> Result<()> {
     }
 }
 
-/// Look up Kubernetes name by user-provided DNS name
-pub async fn lookup_k8s_name_by_user_name(db_url: &str, dns_name: &str) -> Result<String> {
+/// Look up the Kubernetes name and namespace by user-provided DNS name
+pub async fn lookup_k8s_name_by_user_name(
+    db_url: &str,
+    dns_name: &str,
+    namespace: Option<&str>,
+) -> Result<(String, String)> {
     use sqlx::postgres::PgPoolOptions;
     
     // Connect to database
@@ -70,22 +77,14 @@ pub async fn lookup_k8s_name_by_user_name(db_url: &str, dns_name: &str) -> Resul
         .await
         .context("Failed to connect to database for instance lookup")?;
     
-    // Look up the k8s_name by dns_name
-    let result: Option<(String,)> = sqlx::query_as(
-        "SELECT k8s_name FROM toygres_cms.instances 
-         WHERE dns_name = $1 
-         AND state != 'deleted'
-         ORDER BY created_at DESC 
-         LIMIT 1"
-    )
-    .bind(dns_name)
-    .fetch_optional(&pool)
-    .await
-    .context("Failed to look up instance by DNS name")?;
-    
-    match result {
-        Some((k8s_name,)) => Ok(k8s_name),
-        None => anyhow::bail!(
+    match find_instance(&pool, dns_name, namespace).await? {
+        InstanceLookup::Found { k8s_name, namespace } => Ok((k8s_name, namespace)),
+        InstanceLookup::Ambiguous(namespaces) => anyhow::bail!(
+            "Instance '{}' exists in namespaces {}; pass --namespace to choose one",
+            dns_name,
+            namespaces.join(", ")
+        ),
+        InstanceLookup::NotFound => anyhow::bail!(
             "Instance with DNS name '{}' not found in database. \n\
              Note: Use the DNS name you provided during creation (e.g., 'adardb5'), not the K8s name with GUID suffix.",
             dns_name
@@ -93,3 +92,1306 @@ pub async fn lookup_k8s_name_by_user_name(db_url: &str, dns_name: &str) -> Resul
     }
 }
 
+/// Result of resolving a user-facing DNS name to a live CMS record
+#[derive(Debug, Clone, PartialEq)]
+pub enum InstanceLookup {
+    NotFound,
+    Found { k8s_name: String, namespace: String },
+    /// The name is in use in several namespaces and no namespace was given
+    Ambiguous(Vec<String>),
+}
+
+/// Live instances with a DNS name, optionally limited to one namespace (newest first per namespace)
+const FIND_INSTANCE_SQL: &str =
+    "SELECT k8s_name, namespace FROM toygres_cms.instances
+     WHERE dns_name = $1 AND state != 'deleted' AND ($2::text IS NULL OR namespace = $2)
+     ORDER BY namespace, created_at DESC";
+
+/// Resolve a DNS name to its instance. DNS names are only unique within a namespace,
+/// so a name used in several namespaces needs `namespace` to be disambiguated.
+pub async fn find_instance<'e, E>(executor: E, dns_name: &str, namespace: Option<&str>) -> Result<InstanceLookup>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let rows: Vec<(String, String)> = sqlx::query_as(FIND_INSTANCE_SQL)
+        .bind(dns_name)
+        .bind(namespace)
+        .fetch_all(executor)
+        .await
+        .context("Failed to look up instance by DNS name")?;
+    
+    Ok(pick_instance(rows))
+}
+
+fn pick_instance(rows: Vec<(String, String)>) -> InstanceLookup {
+    let mut namespaces: Vec<String> = rows.iter().map(|(_, ns)| ns.clone()).collect();
+    namespaces.dedup();
+    
+    match namespaces.len() {
+        0 => InstanceLookup::NotFound,
+        1 => {
+            let (k8s_name, namespace) = rows.into_iter().next().unwrap();
+            InstanceLookup::Found { k8s_name, namespace }
+        }
+        _ => InstanceLookup::Ambiguous(namespaces),
+    }
+}
+
+/// Suffixes tried by [`unique_k8s_name`] before giving up
+const UNIQUE_NAME_ATTEMPTS: usize = 5;
+
+/// Build `<base>-<suffix>` with a suffix from [`toygres_models::generate_instance_suffix`],
+/// retrying with a fresh suffix if the name is already taken in the CMS
+pub async fn unique_k8s_name(pool: &sqlx::PgPool, base: &str) -> Result<String> {
+    for _ in 0..UNIQUE_NAME_ATTEMPTS {
+        let candidate = format!("{}-{}", base, toygres_models::generate_instance_suffix());
+        
+        let (taken,): (bool,) = sqlx::query_as(
+            "SELECT EXISTS(SELECT 1 FROM toygres_cms.instances WHERE k8s_name = $1)"
+        )
+        .bind(&candidate)
+        .fetch_one(pool)
+        .await
+        .context("Failed to check instance name availability")?;
+        
+        if !taken {
+            return Ok(candidate);
+        }
+        tracing::warn!("Generated instance name {} already exists, retrying", candidate);
+    }
+    
+    anyhow::bail!(
+        "Could not generate a unique name for '{}' after {} attempts",
+        base,
+        UNIQUE_NAME_ATTEMPTS
+    )
+}
+
+/// Aggregate of live (non-deleted) instances grouped by state and health
+const INSTANCE_STATS_SQL: &str =
+    "SELECT state::text, health_status::text, COUNT(*)::BIGINT, COALESCE(SUM(storage_size_gb), 0)::BIGINT
+     FROM toygres_cms.instances
+     WHERE state != 'deleted'
+     GROUP BY state, health_status";
+
+/// Compute instance counts by state and health with a single aggregate query
+pub async fn instance_stats<'e, E>(executor: E) -> Result<InstanceStats>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let rows: Vec<(String, String, i64, i64)> = sqlx::query_as(INSTANCE_STATS_SQL)
+        .fetch_all(executor)
+        .await
+        .context("Failed to aggregate instance counts")?;
+    
+    Ok(fold_instance_stats(rows))
+}
+
+fn fold_instance_stats(rows: Vec<(String, String, i64, i64)>) -> InstanceStats {
+    let mut stats = InstanceStats::default();
+    for (state, health, count, storage_gb) in rows {
+        stats.total += count;
+        stats.total_storage_gb += storage_gb;
+        *stats.by_state.entry(state).or_insert(0) += count;
+        *stats.by_health.entry(health).or_insert(0) += count;
+    }
+    stats
+}
+
+/// `(k8s_name, namespace, state)` of every instance not yet deleted
+pub async fn recorded_instances<'e, E>(executor: E) -> Result<Vec<(String, String, String)>>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query_as(
+        "SELECT k8s_name, namespace, state::text FROM toygres_cms.instances WHERE state != 'deleted'"
+    )
+    .fetch_all(executor)
+    .await
+    .context("Failed to list recorded instances")
+}
+
+/// Turn deletion protection on or off for a live instance. Returns `false` when no
+/// live instance has this name.
+pub async fn set_deletion_protected<'e, E>(executor: E, k8s_name: &str, protected: bool) -> Result<bool>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let result = sqlx::query(
+        "UPDATE toygres_cms.instances
+         SET deletion_protected = $2, updated_at = NOW()
+         WHERE k8s_name = $1 AND state != 'deleted'"
+    )
+    .bind(k8s_name)
+    .bind(protected)
+    .execute(executor)
+    .await
+    .context("Failed to update deletion protection")?;
+    
+    Ok(result.rows_affected() > 0)
+}
+
+/// Record the custom health query of a live instance's actor. Returns `false` when
+/// no live instance has this name.
+pub async fn set_health_sql<'e, E>(executor: E, k8s_name: &str, health_sql: &str) -> Result<bool>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let result = sqlx::query(
+        "UPDATE toygres_cms.instances
+         SET health_sql = $2, updated_at = NOW()
+         WHERE k8s_name = $1 AND state != 'deleted'"
+    )
+    .bind(k8s_name)
+    .bind(health_sql)
+    .execute(executor)
+    .await
+    .context("Failed to update health query")?;
+    
+    Ok(result.rows_affected() > 0)
+}
+
+/// The actor orchestration recorded for a live instance and whether an operator stopped
+/// it, locking the row until the transaction ends so a restart cannot swap the actor
+/// meanwhile. Returns `None` when no live instance has this name.
+pub async fn lock_instance_actor<'e, E>(executor: E, k8s_name: &str) -> Result<Option<(Option<String>, bool)>>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query_as(
+        "SELECT instance_actor_orchestration_id, actor_stopped
+         FROM toygres_cms.instances
+         WHERE k8s_name = $1 AND state != 'deleted'
+         FOR UPDATE"
+    )
+    .bind(k8s_name)
+    .fetch_optional(executor)
+    .await
+    .context("Failed to load instance actor")
+}
+
+/// Claim an instance for resuming its failed create orchestration `create_orchestration_id`.
+///
+/// Succeeds only if the instance has no CMS record (a failed create cleans up after
+/// itself) or its record is still the `failed` one that create left behind; either way
+/// the record is left in `creating`, so a second resume, or one after the instance was
+/// resumed successfully, finds it taken. The resumed create refreshes the placeholder
+/// columns when it reserves the record.
+pub async fn claim_resume<'e, E>(
+    executor: E,
+    k8s_name: &str,
+    user_name: &str,
+    namespace: &str,
+    create_orchestration_id: &str,
+) -> Result<bool>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let claimed: Option<(uuid::Uuid,)> = sqlx::query_as(
+        "INSERT INTO toygres_cms.instances
+         (user_name, k8s_name, namespace, postgres_version, storage_size_gb, state, create_orchestration_id)
+         VALUES ($2, $1, $3, $4, 0, 'creating', $5)
+         ON CONFLICT (k8s_name) DO UPDATE
+         SET state = 'creating', updated_at = NOW()
+         WHERE toygres_cms.instances.state = 'failed'
+           AND toygres_cms.instances.create_orchestration_id = EXCLUDED.create_orchestration_id
+         RETURNING id"
+    )
+    .bind(k8s_name)
+    .bind(user_name)
+    .bind(namespace)
+    .bind(toygres_models::DEFAULT_PG_VERSION)
+    .bind(create_orchestration_id)
+    .fetch_optional(executor)
+    .await
+    .context("Failed to claim instance for resume")?;
+    
+    Ok(claimed.is_some())
+}
+
+/// Hand back a [`claim_resume`] whose orchestration could not be started, leaving the
+/// instance `failed` so it can be resumed again
+pub async fn release_resume<'e, E>(executor: E, k8s_name: &str, create_orchestration_id: &str) -> Result<()>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query(
+        "UPDATE toygres_cms.instances
+         SET state = 'failed', updated_at = NOW()
+         WHERE k8s_name = $1 AND state = 'creating' AND create_orchestration_id = $2"
+    )
+    .bind(k8s_name)
+    .bind(create_orchestration_id)
+    .execute(executor)
+    .await
+    .context("Failed to release resume claim")?;
+    
+    Ok(())
+}
+
+/// Replace the CMS tags of a live instance. Returns `false` when no live instance has
+/// this name.
+pub async fn set_tags<'e, E>(
+    executor: E,
+    k8s_name: &str,
+    tags: &BTreeMap<String, serde_json::Value>,
+) -> Result<bool>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let result = sqlx::query(
+        "UPDATE toygres_cms.instances
+         SET tags = $2, updated_at = NOW()
+         WHERE k8s_name = $1 AND state != 'deleted'"
+    )
+    .bind(k8s_name)
+    .bind(sqlx::types::Json(tags))
+    .execute(executor)
+    .await
+    .context("Failed to update instance tags")?;
+    
+    Ok(result.rows_affected() > 0)
+}
+
+/// The deletion-protected instances among `k8s_names`
+pub async fn protected_instances<'e, E>(executor: E, k8s_names: &[String]) -> Result<Vec<String>>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query_scalar(
+        "SELECT k8s_name FROM toygres_cms.instances
+         WHERE k8s_name = ANY($1) AND deletion_protected AND state != 'deleted'
+         ORDER BY k8s_name"
+    )
+    .bind(k8s_names)
+    .fetch_all(executor)
+    .await
+    .context("Failed to check deletion protection")
+}
+
+/// The stored config an instance was deployed with, as far as the CMS records it
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstanceDeployConfig {
+    pub namespace: String,
+    pub postgres_version: String,
+    pub storage_size_gb: i32,
+    pub use_load_balancer: bool,
+    /// DNS label passed to the deploy; `None` when the instance was created without one
+    pub dns_name: Option<String>,
+    pub password_secret_ref: Option<String>,
+    /// The remaining create options; `None` for instances created before they were stored
+    pub deploy_options: Option<InstanceDeployOptions>,
+}
+
+/// `instances` columns that make up an [`InstanceDeployConfig`]
+type DeployConfigRow = (
+    String, String, i32, bool, Option<String>, Option<String>, Option<sqlx::types::Json<InstanceDeployOptions>>,
+);
+
+/// The deploy config of a live instance
+pub async fn instance_deploy_config<'e, E>(executor: E, k8s_name: &str) -> Result<Option<InstanceDeployConfig>>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let row: Option<DeployConfigRow> = sqlx::query_as(
+        "SELECT namespace, postgres_version, storage_size_gb, use_load_balancer, dns_name, password_secret_ref,
+                deploy_options
+         FROM toygres_cms.instances
+         WHERE k8s_name = $1 AND state != 'deleted'"
+    )
+    .bind(k8s_name)
+    .fetch_optional(executor)
+    .await
+    .context("Failed to load instance deploy config")?;
+    
+    Ok(row.map(|(namespace, postgres_version, storage_size_gb, use_load_balancer, dns_name, password_secret_ref, deploy_options)| {
+        InstanceDeployConfig {
+            namespace,
+            postgres_version,
+            storage_size_gb,
+            use_load_balancer,
+            dns_name,
+            password_secret_ref,
+            deploy_options: deploy_options.map(|json| json.0),
+        }
+    }))
+}
+
+/// `instances` columns that make up an [`InstanceManifest`]
+type ManifestRow = (String, Option<String>, String, i32, bool, String, sqlx::types::Json<BTreeMap<String, serde_json::Value>>);
+
+/// Build the portable manifest for a live instance (see [`find_instance`] to resolve a DNS name)
+pub async fn instance_manifest<'e, E>(executor: E, k8s_name: &str) -> Result<Option<InstanceManifest>>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let row: Option<ManifestRow> = sqlx::query_as(
+        "SELECT user_name, dns_name, postgres_version, storage_size_gb, use_load_balancer, namespace, tags
+         FROM toygres_cms.instances
+         WHERE k8s_name = $1 AND state != 'deleted'"
+    )
+    .bind(k8s_name)
+    .fetch_optional(executor)
+    .await
+    .context("Failed to load instance for manifest")?;
+    
+    Ok(row.map(|(name, dns_name, postgres_version, storage_size_gb, use_load_balancer, namespace, tags)| {
+        InstanceManifest {
+            manifest_version: MANIFEST_VERSION,
+            dns_label: dns_name.filter(|label| *label != name),
+            name,
+            postgres_version,
+            storage_size_gb,
+            use_load_balancer,
+            namespace,
+            tags: tags.0,
+        }
+    }))
+}
+
+/// A row of `instance_events`; emitted on state changes and by the actor supervisor
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct InstanceEvent {
+    pub event_type: String,
+    pub old_state: Option<String>,
+    pub new_state: Option<String>,
+    pub message: Option<String>,
+    /// Structured details, e.g. `{"retry": {"attempts": 5, ...}}` on a failed create
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+    pub created_at: String,
+}
+
+/// `instance_events` columns that make up an [`InstanceEvent`]
+type EventRow = (String, Option<String>, Option<String>, Option<String>, Option<serde_json::Value>, String);
+
+/// The newest `limit` events of an instance, newest first
+pub async fn recent_instance_events<'e, E>(executor: E, k8s_name: &str, limit: i64) -> Result<Vec<InstanceEvent>>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let rows: Vec<EventRow> = sqlx::query_as(
+        "SELECT e.event_type, e.old_state, e.new_state, e.message, e.metadata, e.created_at::text
+         FROM toygres_cms.instance_events e
+         JOIN toygres_cms.instances i ON i.id = e.instance_id
+         WHERE i.k8s_name = $1
+         ORDER BY e.created_at DESC, e.id DESC
+         LIMIT $2"
+    )
+    .bind(k8s_name)
+    .bind(limit)
+    .fetch_all(executor)
+    .await
+    .context("Failed to load instance events")?;
+    
+    Ok(rows
+        .into_iter()
+        .map(|(event_type, old_state, new_state, message, metadata, created_at)| InstanceEvent {
+            event_type,
+            old_state,
+            new_state,
+            message,
+            metadata,
+            created_at,
+        })
+        .collect())
+}
+
+/// An `instance_events` row with the instance it belongs to, for the fleet-wide audit feed
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AuditEvent {
+    pub id: i64,
+    /// User-facing instance name
+    pub instance_name: String,
+    pub k8s_name: String,
+    pub namespace: String,
+    pub event_type: String,
+    pub old_state: Option<String>,
+    pub new_state: Option<String>,
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
+    pub created_at: String,
+}
+
+type AuditRow = (
+    i64, String, String, String, String,
+    Option<String>, Option<String>, Option<String>, Option<serde_json::Value>, String,
+);
+
+/// Events of every instance, oldest first, optionally from `$1` on and of type `$2`;
+/// `$3`/`$4` page through the result
+const AUDIT_EVENTS_SQL: &str =
+    "SELECT e.id, i.user_name, i.k8s_name, i.namespace, e.event_type,
+            e.old_state, e.new_state, e.message, e.metadata, e.created_at::text
+     FROM toygres_cms.instance_events e
+     JOIN toygres_cms.instances i ON i.id = e.instance_id
+     WHERE ($1::timestamptz IS NULL OR e.created_at >= $1)
+       AND ($2::text IS NULL OR e.event_type = $2)
+     ORDER BY e.created_at, e.id
+     LIMIT $3 OFFSET $4";
+
+/// One page of the fleet-wide audit feed (see [`AUDIT_EVENTS_SQL`])
+pub async fn audit_events<'e, E>(
+    executor: E,
+    since: Option<chrono::DateTime<chrono::Utc>>,
+    event_type: Option<&str>,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<AuditEvent>>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let rows: Vec<AuditRow> = sqlx::query_as(AUDIT_EVENTS_SQL)
+        .bind(since)
+        .bind(event_type)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(executor)
+        .await
+        .context("Failed to load audit events")?;
+    
+    Ok(rows
+        .into_iter()
+        .map(|(id, instance_name, k8s_name, namespace, event_type, old_state, new_state, message, metadata, created_at)| AuditEvent {
+            id,
+            instance_name,
+            k8s_name,
+            namespace,
+            event_type,
+            old_state,
+            new_state,
+            message,
+            metadata,
+            created_at,
+        })
+        .collect())
+}
+
+/// A row of `instance_health_checks`, as recorded by the instance actor
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct HealthCheck {
+    pub status: String,
+    pub postgres_version: Option<String>,
+    pub response_time_ms: Option<i32>,
+    pub error_message: Option<String>,
+    /// Why a failed check failed, e.g. "auth_failed"
+    pub health_reason: Option<String>,
+    /// Replay lag reported by a replica; absent for primaries
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replication_lag_ms: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub database_size_bytes: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connection_count: Option<i32>,
+    /// Share of the provisioned volume in use (0-100)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage_pct: Option<f64>,
+    pub checked_at: String,
+}
+
+/// `instance_health_checks` columns that make up a [`HealthCheck`]
+type HealthCheckRow = (
+    String, Option<String>, Option<i32>, Option<String>, Option<String>,
+    Option<i64>, Option<i64>, Option<i32>, Option<f64>, String,
+);
+
+/// The newest `limit` health checks of an instance, newest first
+pub async fn recent_health_checks<'e, E>(executor: E, k8s_name: &str, limit: i64) -> Result<Vec<HealthCheck>>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let rows: Vec<HealthCheckRow> = sqlx::query_as(
+        "SELECT h.status, h.postgres_version, h.response_time_ms, h.error_message, h.health_reason,
+                h.replication_lag_ms, h.database_size_bytes, h.connection_count, h.storage_pct,
+                h.checked_at::text
+         FROM toygres_cms.instance_health_checks h
+         JOIN toygres_cms.instances i ON i.id = h.instance_id
+         WHERE i.k8s_name = $1
+         ORDER BY h.checked_at DESC
+         LIMIT $2"
+    )
+    .bind(k8s_name)
+    .bind(limit)
+    .fetch_all(executor)
+    .await
+    .context("Failed to load health checks")?;
+    
+    Ok(rows.into_iter().map(health_check).collect())
+}
+
+fn health_check(row: HealthCheckRow) -> HealthCheck {
+    let (status, postgres_version, response_time_ms, error_message, health_reason,
+         replication_lag_ms, database_size_bytes, connection_count, storage_pct, checked_at) = row;
+    HealthCheck {
+        status,
+        postgres_version,
+        response_time_ms,
+        error_message,
+        health_reason,
+        replication_lag_ms,
+        database_size_bytes,
+        connection_count,
+        storage_pct,
+        checked_at,
+    }
+}
+
+/// The k8s name followed by the [`HealthCheckRow`] columns
+type FreshHealthCheckRow = (
+    String, String, Option<String>, Option<i32>, Option<String>, Option<String>,
+    Option<i64>, Option<i64>, Option<i32>, Option<f64>, String,
+);
+
+/// The newest health check of each of `k8s_names` recorded at or after `since`, by
+/// k8s name; instances without such a check are absent
+pub async fn health_checks_since<'e, E>(
+    executor: E,
+    k8s_names: &[String],
+    since: chrono::DateTime<chrono::Utc>,
+) -> Result<BTreeMap<String, HealthCheck>>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let rows: Vec<FreshHealthCheckRow> = sqlx::query_as(
+        "SELECT DISTINCT ON (i.k8s_name)
+                i.k8s_name, h.status, h.postgres_version, h.response_time_ms, h.error_message,
+                h.health_reason, h.replication_lag_ms, h.database_size_bytes, h.connection_count,
+                h.storage_pct, h.checked_at::text
+         FROM toygres_cms.instance_health_checks h
+         JOIN toygres_cms.instances i ON i.id = h.instance_id
+         WHERE i.k8s_name = ANY($1) AND h.checked_at >= $2
+         ORDER BY i.k8s_name, h.checked_at DESC"
+    )
+    .bind(k8s_names)
+    .bind(since)
+    .fetch_all(executor)
+    .await
+    .context("Failed to load fresh health checks")?;
+    
+    Ok(rows
+        .into_iter()
+        .map(|(k8s_name, status, postgres_version, response_time_ms, error_message, health_reason,
+               replication_lag_ms, database_size_bytes, connection_count, storage_pct, checked_at)| {
+            let check = health_check((status, postgres_version, response_time_ms, error_message, health_reason,
+                                      replication_lag_ms, database_size_bytes, connection_count, storage_pct, checked_at));
+            (k8s_name, check)
+        })
+        .collect())
+}
+
+/// An instance selected for a health refresh
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefreshTarget {
+    pub user_name: String,
+    pub k8s_name: String,
+    pub actor_id: Option<String>,
+}
+
+/// Live instances matching every filter given: user names, state, and a `tags` key
+/// with a (text) value. With no filter at all, every running instance.
+pub async fn health_refresh_targets<'e, E>(
+    executor: E,
+    names: Option<&[String]>,
+    state: Option<&str>,
+    tag: Option<(&str, &str)>,
+) -> Result<Vec<RefreshTarget>>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let state = match (names, state, tag) {
+        (None, None, None) => Some("running"),
+        _ => state,
+    };
+    let rows: Vec<(String, String, Option<String>)> = sqlx::query_as(
+        "SELECT user_name, k8s_name, instance_actor_orchestration_id
+         FROM toygres_cms.instances
+         WHERE state != 'deleted'
+           AND ($1::text[] IS NULL OR user_name = ANY($1))
+           AND ($2::text IS NULL OR state::text = $2)
+           AND ($3::text IS NULL OR tags ->> $3 = $4)
+         ORDER BY user_name"
+    )
+    .bind(names)
+    .bind(state)
+    .bind(tag.map(|(key, _)| key))
+    .bind(tag.map(|(_, value)| value))
+    .fetch_all(executor)
+    .await
+    .context("Failed to select instances to refresh")?;
+    
+    Ok(rows
+        .into_iter()
+        .map(|(user_name, k8s_name, actor_id)| RefreshTarget { user_name, k8s_name, actor_id })
+        .collect())
+}
+
+/// Orchestration ids recorded for an instance, as `(role, id)`: `create`, then
+/// `delete` and `actor` when set
+pub async fn instance_orchestration_ids<'e, E>(executor: E, k8s_name: &str) -> Result<Vec<(&'static str, String)>>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let row: Option<(String, Option<String>, Option<String>)> = sqlx::query_as(
+        "SELECT create_orchestration_id, delete_orchestration_id, instance_actor_orchestration_id
+         FROM toygres_cms.instances
+         WHERE k8s_name = $1"
+    )
+    .bind(k8s_name)
+    .fetch_optional(executor)
+    .await
+    .context("Failed to load instance orchestration ids")?;
+    
+    Ok(row
+        .map(|(create, delete, actor)| {
+            [("create", Some(create)), ("delete", delete), ("actor", actor)]
+                .into_iter()
+                .filter_map(|(role, id)| id.map(|id| (role, id)))
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+/// An orchestration id recorded on a CMS instance row
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackedOrchestration {
+    /// `create`, `delete` or `actor`
+    pub role: &'static str,
+    pub instance_id: String,
+    /// When the owning CMS instance was created
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+type TrackedRow = (String, Option<String>, Option<String>, chrono::DateTime<chrono::Utc>);
+
+/// Every orchestration id the CMS tracks, oldest instance first. Used when the
+/// Duroxide store cannot list its own instances.
+pub async fn tracked_orchestrations<'e, E>(executor: E) -> Result<Vec<TrackedOrchestration>>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let rows: Vec<TrackedRow> = sqlx::query_as(
+        "SELECT create_orchestration_id, delete_orchestration_id, instance_actor_orchestration_id, created_at
+         FROM toygres_cms.instances
+         ORDER BY created_at, id"
+    )
+    .fetch_all(executor)
+    .await
+    .context("Failed to load tracked orchestration ids")?;
+    
+    Ok(rows
+        .into_iter()
+        .flat_map(|(create, delete, actor, created_at)| {
+            [("create", Some(create)), ("delete", delete), ("actor", actor)]
+                .into_iter()
+                .filter_map(move |(role, id)| id.map(|instance_id| TrackedOrchestration { role, instance_id, created_at }))
+        })
+        .collect())
+}
+
+/// Insert or refresh a worker's row in `toygres_cms.workers`. `last_heartbeat` is the
+/// database clock, so staleness does not depend on the worker hosts' clocks.
+pub async fn record_worker_heartbeat<'e, E>(
+    executor: E,
+    worker_id: &str,
+    hostname: &str,
+    started_at: chrono::DateTime<chrono::Utc>,
+) -> Result<()>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query(
+        "INSERT INTO toygres_cms.workers (worker_id, hostname, started_at, last_heartbeat)
+         VALUES ($1, $2, $3, NOW())
+         ON CONFLICT (worker_id) DO UPDATE
+         SET hostname = EXCLUDED.hostname,
+             started_at = EXCLUDED.started_at,
+             last_heartbeat = NOW()"
+    )
+    .bind(worker_id)
+    .bind(hostname)
+    .bind(started_at)
+    .execute(executor)
+    .await
+    .context("Failed to record worker heartbeat")?;
+    
+    Ok(())
+}
+
+/// Drop a worker's row when it shuts down cleanly
+pub async fn remove_worker<'e, E>(executor: E, worker_id: &str) -> Result<()>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query("DELETE FROM toygres_cms.workers WHERE worker_id = $1")
+        .bind(worker_id)
+        .execute(executor)
+        .await
+        .context("Failed to remove worker")?;
+    
+    Ok(())
+}
+
+/// The maintenance-mode switch
+pub async fn maintenance_mode<'e, E>(executor: E) -> Result<MaintenanceMode>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let (enabled, updated_at): (bool, chrono::DateTime<chrono::Utc>) = sqlx::query_as(
+        "SELECT enabled, updated_at FROM toygres_cms.maintenance_mode"
+    )
+    .fetch_one(executor)
+    .await
+    .context("Failed to load maintenance mode")?;
+    
+    Ok(MaintenanceMode { enabled, updated_at })
+}
+
+/// Turn maintenance mode on or off; actors pick the change up on their next iteration
+pub async fn set_maintenance_mode<'e, E>(executor: E, enabled: bool) -> Result<MaintenanceMode>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let (enabled, updated_at): (bool, chrono::DateTime<chrono::Utc>) = sqlx::query_as(
+        "INSERT INTO toygres_cms.maintenance_mode (id, enabled, updated_at)
+         VALUES (TRUE, $1, NOW())
+         ON CONFLICT (id) DO UPDATE SET enabled = EXCLUDED.enabled, updated_at = NOW()
+         RETURNING enabled, updated_at"
+    )
+    .bind(enabled)
+    .fetch_one(executor)
+    .await
+    .context("Failed to set maintenance mode")?;
+    
+    Ok(MaintenanceMode { enabled, updated_at })
+}
+
+type WorkerRow = (String, String, chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>);
+
+/// Every worker's latest heartbeat, most recent first, marked stale against the
+/// database clock
+pub async fn worker_heartbeats<'e, E>(executor: E) -> Result<Vec<WorkerHeartbeat>>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let rows: Vec<WorkerRow> = sqlx::query_as(
+        "SELECT worker_id, hostname, started_at, last_heartbeat, NOW()
+         FROM toygres_cms.workers
+         ORDER BY last_heartbeat DESC, worker_id"
+    )
+    .fetch_all(executor)
+    .await
+    .context("Failed to load worker heartbeats")?;
+    
+    Ok(rows
+        .into_iter()
+        .map(|(worker_id, hostname, started_at, last_heartbeat, now)| WorkerHeartbeat {
+            worker_id,
+            hostname,
+            started_at,
+            last_heartbeat,
+            stale: WorkerHeartbeat::is_stale(last_heartbeat, now),
+        })
+        .collect())
+}
+
+/// Count orchestrations per type and current-execution status in the Duroxide store
+pub async fn orchestration_type_stats(
+    pool: &sqlx::PgPool,
+    schema: &str,
+) -> Result<Vec<OrchestrationTypeStats>> {
+    let rows: Vec<(String, String, i64)> = sqlx::query_as(&format!(
+        "SELECT i.orchestration_name, e.status, COUNT(*)::BIGINT
+         FROM {schema}.instances i
+         JOIN {schema}.executions e
+           ON e.instance_id = i.instance_id AND e.execution_id = i.current_execution_id
+         GROUP BY i.orchestration_name, e.status
+         ORDER BY i.orchestration_name"
+    ))
+    .fetch_all(pool)
+    .await
+    .context("Failed to aggregate orchestration counts")?;
+    
+    let mut by_type: Vec<OrchestrationTypeStats> = Vec::new();
+    for (name, status, count) in rows {
+        match by_type.last_mut() {
+            Some(last) if last.orchestration_name == name => {
+                last.total += count;
+                last.by_status.insert(status, count);
+            }
+            _ => by_type.push(OrchestrationTypeStats {
+                orchestration_name: name,
+                total: count,
+                by_status: [(status, count)].into_iter().collect(),
+            }),
+        }
+    }
+    
+    Ok(by_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fold_instance_stats() {
+        let stats = fold_instance_stats(vec![
+            ("running".to_string(), "healthy".to_string(), 3, 30),
+            ("running".to_string(), "unhealthy".to_string(), 1, 20),
+            ("creating".to_string(), "unknown".to_string(), 2, 20),
+        ]);
+        
+        assert_eq!(stats.total, 6);
+        assert_eq!(stats.total_storage_gb, 70);
+        assert_eq!(stats.by_state["running"], 4);
+        assert_eq!(stats.by_state["creating"], 2);
+        assert_eq!(stats.by_health["healthy"], 3);
+        assert_eq!(stats.by_health["unknown"], 2);
+    }
+
+    #[test]
+    fn test_pick_instance_by_namespace() {
+        let row = |k8s: &str, ns: &str| (k8s.to_string(), ns.to_string());
+        
+        assert_eq!(pick_instance(vec![]), InstanceLookup::NotFound);
+        // Several records in one namespace resolve to the newest
+        assert_eq!(
+            pick_instance(vec![row("mydb-new", "team-a"), row("mydb-old", "team-a")]),
+            InstanceLookup::Found { k8s_name: "mydb-new".to_string(), namespace: "team-a".to_string() }
+        );
+        assert_eq!(
+            pick_instance(vec![row("mydb-a", "team-a"), row("mydb-b", "team-b")]),
+            InstanceLookup::Ambiguous(vec!["team-a".to_string(), "team-b".to_string()])
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL with the toygres_cms schema"]
+    async fn test_same_name_in_two_namespaces_is_disambiguated() {
+        let db_url = std::env::var("DATABASE_URL").unwrap();
+        let pool = sqlx::PgPool::connect(&db_url).await.unwrap();
+        let mut tx = pool.begin().await.unwrap();
+        
+        let suffix = &uuid::Uuid::new_v4().to_string()[..8];
+        let dns_name = format!("scoped-{}", suffix);
+        for namespace in ["team-a", "team-b"] {
+            sqlx::query(
+                "INSERT INTO toygres_cms.instances
+                 (user_name, k8s_name, namespace, postgres_version, storage_size_gb,
+                  use_load_balancer, dns_name, state, create_orchestration_id)
+                 VALUES ($1, $2, $3, '18', 5, false, $1, 'running', $4)"
+            )
+            .bind(&dns_name)
+            .bind(format!("{}-{}", dns_name, namespace))
+            .bind(namespace)
+            .bind(format!("create-{}-{}", dns_name, namespace))
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        }
+        
+        let unscoped = find_instance(&mut *tx, &dns_name, None).await.unwrap();
+        let team_b = find_instance(&mut *tx, &dns_name, Some("team-b")).await.unwrap();
+        let team_c = find_instance(&mut *tx, &dns_name, Some("team-c")).await.unwrap();
+        tx.rollback().await.unwrap();
+        
+        assert_eq!(unscoped, InstanceLookup::Ambiguous(vec!["team-a".to_string(), "team-b".to_string()]));
+        assert_eq!(team_b, InstanceLookup::Found {
+            k8s_name: format!("{}-team-b", dns_name),
+            namespace: "team-b".to_string(),
+        });
+        assert_eq!(team_c, InstanceLookup::NotFound);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL with the toygres_cms schema"]
+    async fn test_unique_k8s_name_is_unused_in_cms() {
+        let db_url = std::env::var("DATABASE_URL").unwrap();
+        let pool = sqlx::PgPool::connect(&db_url).await.unwrap();
+        
+        let name = unique_k8s_name(&pool, "suffix-test").await.unwrap();
+        let suffix = name.strip_prefix("suffix-test-").unwrap();
+        assert_eq!(suffix.len(), toygres_models::suffix_length());
+        
+        let (exists,): (bool,) = sqlx::query_as(
+            "SELECT EXISTS(SELECT 1 FROM toygres_cms.instances WHERE k8s_name = $1)"
+        )
+        .bind(&name)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert!(!exists);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL with the toygres_cms schema"]
+    async fn test_instance_stats_sql_counts_per_state() {
+        let db_url = std::env::var("DATABASE_URL").unwrap();
+        let pool = sqlx::PgPool::connect(&db_url).await.unwrap();
+        let mut tx = pool.begin().await.unwrap();
+        
+        let before = instance_stats(&mut *tx).await.unwrap();
+        
+        let suffix = &uuid::Uuid::new_v4().to_string()[..8];
+        for (i, state) in ["running", "running", "failed", "deleted"].iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO toygres_cms.instances
+                 (user_name, k8s_name, namespace, postgres_version, storage_size_gb,
+                  use_load_balancer, state, create_orchestration_id)
+                 VALUES ($1, $2, 'toygres', '18', 5, false, $3::instance_state, $4)"
+            )
+            .bind(format!("stats-{}", suffix))
+            .bind(format!("stats-{}-{}", suffix, i))
+            .bind(state)
+            .bind(format!("create-stats-{}-{}", suffix, i))
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        }
+        
+        let after = instance_stats(&mut *tx).await.unwrap();
+        tx.rollback().await.unwrap();
+        
+        let delta = |map: &std::collections::BTreeMap<String, i64>, key: &str, base: &std::collections::BTreeMap<String, i64>| {
+            map.get(key).copied().unwrap_or(0) - base.get(key).copied().unwrap_or(0)
+        };
+        
+        // Deleted instances are excluded from every count
+        assert_eq!(after.total - before.total, 3);
+        assert_eq!(delta(&after.by_state, "running", &before.by_state), 2);
+        assert_eq!(delta(&after.by_state, "failed", &before.by_state), 1);
+        assert_eq!(delta(&after.by_state, "deleted", &before.by_state), 0);
+        assert_eq!(delta(&after.by_health, "unknown", &before.by_health), 3);
+        assert_eq!(after.total_storage_gb - before.total_storage_gb, 15);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL with the toygres_cms schema"]
+    async fn test_describe_queries_return_newest_rows_first() {
+        let db_url = std::env::var("DATABASE_URL").unwrap();
+        let pool = sqlx::PgPool::connect(&db_url).await.unwrap();
+        let mut tx = pool.begin().await.unwrap();
+        
+        let k8s_name = format!("describe-{}", &uuid::Uuid::new_v4().to_string()[..8]);
+        let (id,): (uuid::Uuid,) = sqlx::query_as(
+            "INSERT INTO toygres_cms.instances
+             (user_name, k8s_name, namespace, postgres_version, storage_size_gb,
+              use_load_balancer, state, create_orchestration_id, instance_actor_orchestration_id)
+             VALUES ('describe', $1, 'toygres', '18', 5, false, 'running', $2, $3)
+             RETURNING id"
+        )
+        .bind(&k8s_name)
+        .bind(format!("create-{}", k8s_name))
+        .bind(format!("actor-{}", k8s_name))
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+        for (minutes_ago, event_type) in [(3, "created"), (2, "state_change"), (1, "health_change")] {
+            sqlx::query(
+                "INSERT INTO toygres_cms.instance_events (instance_id, event_type, created_at)
+                 VALUES ($1, $2, NOW() - make_interval(mins => $3))"
+            )
+            .bind(id)
+            .bind(event_type)
+            .bind(minutes_ago)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        }
+        sqlx::query(
+            "INSERT INTO toygres_cms.instance_health_checks (instance_id, status, response_time_ms)
+             VALUES ($1, 'healthy', 12)"
+        )
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+        
+        let events = recent_instance_events(&mut *tx, &k8s_name, 2).await.unwrap();
+        let checks = recent_health_checks(&mut *tx, &k8s_name, 10).await.unwrap();
+        let ids = instance_orchestration_ids(&mut *tx, &k8s_name).await.unwrap();
+        tx.rollback().await.unwrap();
+        
+        let types: Vec<&str> = events.iter().map(|e| e.event_type.as_str()).collect();
+        assert_eq!(types, vec!["health_change", "state_change"]);
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].response_time_ms, Some(12));
+        assert_eq!(ids, vec![
+            ("create", format!("create-{}", k8s_name)),
+            ("actor", format!("actor-{}", k8s_name)),
+        ]);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL with the toygres_cms schema"]
+    async fn test_audit_events_filtered_and_chronological() {
+        let db_url = std::env::var("DATABASE_URL").unwrap();
+        let pool = sqlx::PgPool::connect(&db_url).await.unwrap();
+        let mut tx = pool.begin().await.unwrap();
+        
+        // Events far in the future so rows already in the table sort before them
+        let base: chrono::DateTime<chrono::Utc> = "2999-01-01T00:00:00Z".parse().unwrap();
+        let mut ids = Vec::new();
+        for name in ["audit-a", "audit-b"] {
+            let k8s_name = format!("{}-{}", name, &uuid::Uuid::new_v4().to_string()[..8]);
+            let (id,): (uuid::Uuid,) = sqlx::query_as(
+                "INSERT INTO toygres_cms.instances
+                 (user_name, k8s_name, namespace, postgres_version, storage_size_gb,
+                  use_load_balancer, state, create_orchestration_id)
+                 VALUES ($1, $2, 'toygres', '18', 5, false, 'running', $3)
+                 RETURNING id"
+            )
+            .bind(name)
+            .bind(&k8s_name)
+            .bind(format!("create-{}", k8s_name))
+            .fetch_one(&mut *tx)
+            .await
+            .unwrap();
+            ids.push(id);
+        }
+        // Inserted out of order: (instance, type, minutes after base)
+        for (instance, event_type, minutes) in [(1, "state_change", 3), (0, "created", 0), (0, "state_change", 2), (1, "created", 1)] {
+            sqlx::query(
+                "INSERT INTO toygres_cms.instance_events (instance_id, event_type, created_at)
+                 VALUES ($1, $2, $3::timestamptz + make_interval(mins => $4))"
+            )
+            .bind(ids[instance])
+            .bind(event_type)
+            .bind(base)
+            .bind(minutes)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        }
+        
+        let all = audit_events(&mut *tx, Some(base), None, 10, 0).await.unwrap();
+        let changes = audit_events(&mut *tx, Some(base), Some("state_change"), 10, 0).await.unwrap();
+        let later = audit_events(&mut *tx, Some(base + chrono::Duration::minutes(2)), None, 10, 0).await.unwrap();
+        let second_page = audit_events(&mut *tx, Some(base), None, 2, 2).await.unwrap();
+        tx.rollback().await.unwrap();
+        
+        let feed = |events: &[AuditEvent]| -> Vec<(String, String)> {
+            events.iter().map(|e| (e.instance_name.clone(), e.event_type.clone())).collect()
+        };
+        let pair = |name: &str, event_type: &str| (name.to_string(), event_type.to_string());
+        assert_eq!(feed(&all), vec![
+            pair("audit-a", "created"),
+            pair("audit-b", "created"),
+            pair("audit-a", "state_change"),
+            pair("audit-b", "state_change"),
+        ]);
+        assert_eq!(feed(&changes), vec![pair("audit-a", "state_change"), pair("audit-b", "state_change")]);
+        assert_eq!(feed(&later), feed(&changes));
+        assert_eq!(feed(&second_page), feed(&all[2..]));
+    }
+    
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL with the toygres_cms schema"]
+    async fn test_deletion_protection_toggles() {
+        let db_url = std::env::var("DATABASE_URL").unwrap();
+        let pool = sqlx::PgPool::connect(&db_url).await.unwrap();
+        let mut tx = pool.begin().await.unwrap();
+        
+        let suffix = &uuid::Uuid::new_v4().to_string()[..8];
+        let names: Vec<String> = ["a", "b"].iter().map(|n| format!("protect-{}-{}", suffix, n)).collect();
+        for name in &names {
+            sqlx::query(
+                "INSERT INTO toygres_cms.instances
+                 (user_name, k8s_name, namespace, postgres_version, storage_size_gb,
+                  use_load_balancer, state, create_orchestration_id)
+                 VALUES ($1, $1, 'toygres', '18', 5, false, 'running', $2)"
+            )
+            .bind(name)
+            .bind(format!("create-{}", name))
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        }
+        
+        let initially = protected_instances(&mut *tx, &names).await.unwrap();
+        assert!(set_deletion_protected(&mut *tx, &names[1], true).await.unwrap());
+        let protected = protected_instances(&mut *tx, &names).await.unwrap();
+        assert!(set_deletion_protected(&mut *tx, &names[1], false).await.unwrap());
+        let unprotected = protected_instances(&mut *tx, &names).await.unwrap();
+        let missing = set_deletion_protected(&mut *tx, "protect-missing", true).await.unwrap();
+        tx.rollback().await.unwrap();
+        
+        assert!(initially.is_empty());
+        assert_eq!(protected, vec![names[1].clone()]);
+        assert!(unprotected.is_empty());
+        assert!(!missing);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL with the toygres_cms schema"]
+    async fn test_instance_actor_is_read_with_its_stop_flag() {
+        let db_url = std::env::var("DATABASE_URL").unwrap();
+        let pool = sqlx::PgPool::connect(&db_url).await.unwrap();
+        let mut tx = pool.begin().await.unwrap();
+        
+        let name = format!("actor-lock-{}", &uuid::Uuid::new_v4().to_string()[..8]);
+        sqlx::query(
+            "INSERT INTO toygres_cms.instances
+             (user_name, k8s_name, namespace, postgres_version, storage_size_gb,
+              use_load_balancer, state, create_orchestration_id)
+             VALUES ($1, $1, 'toygres', '18', 5, false, 'running', $2)"
+        )
+        .bind(&name)
+        .bind(format!("create-{}", name))
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+        
+        let unrecorded = lock_instance_actor(&mut *tx, &name).await.unwrap();
+        let actor_id = format!("actor-{}.1a2b3c4d", name);
+        sqlx::query(
+            "UPDATE toygres_cms.instances SET instance_actor_orchestration_id = $2, actor_stopped = TRUE
+             WHERE k8s_name = $1"
+        )
+        .bind(&name)
+        .bind(&actor_id)
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+        let stopped = lock_instance_actor(&mut *tx, &name).await.unwrap();
+        let missing = lock_instance_actor(&mut *tx, "actor-lock-missing").await.unwrap();
+        tx.rollback().await.unwrap();
+        
+        assert_eq!(unrecorded, Some((None, false)));
+        assert_eq!(stopped, Some((Some(actor_id), true)));
+        assert_eq!(missing, None);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL with the toygres_cms schema"]
+    async fn test_resume_is_claimed_once() {
+        let db_url = std::env::var("DATABASE_URL").unwrap();
+        let pool = sqlx::PgPool::connect(&db_url).await.unwrap();
+        let mut tx = pool.begin().await.unwrap();
+        
+        let name = format!("resume-{}", &uuid::Uuid::new_v4().to_string()[..8]);
+        let create_id = format!("create-{}", name);
+        const STATE_SQL: &str = "SELECT state::text FROM toygres_cms.instances WHERE k8s_name = $1";
+        
+        // Cleaned-up failed create: no record, so the first resume claims it
+        let first = claim_resume(&mut *tx, &name, "resume", "toygres", &create_id).await.unwrap();
+        let (after_first,): (String,) = sqlx::query_as(STATE_SQL).bind(&name).fetch_one(&mut *tx).await.unwrap();
+        // Double resume
+        let second = claim_resume(&mut *tx, &name, "resume", "toygres", &create_id).await.unwrap();
+        
+        // The resumed create succeeded
+        sqlx::query("UPDATE toygres_cms.instances SET state = 'running' WHERE k8s_name = $1")
+            .bind(&name)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        let after_success = claim_resume(&mut *tx, &name, "resume", "toygres", &create_id).await.unwrap();
+        
+        // Failed again, without cleaning up: resumable once more, but only for its own create
+        sqlx::query("UPDATE toygres_cms.instances SET state = 'failed' WHERE k8s_name = $1")
+            .bind(&name)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        let other_create = claim_resume(&mut *tx, &name, "resume", "toygres", "create-other").await.unwrap();
+        let after_failure = claim_resume(&mut *tx, &name, "resume", "toygres", &create_id).await.unwrap();
+        
+        // A claim whose orchestration never started is handed back
+        release_resume(&mut *tx, &name, &create_id).await.unwrap();
+        let (after_release,): (String,) = sqlx::query_as(STATE_SQL).bind(&name).fetch_one(&mut *tx).await.unwrap();
+        tx.rollback().await.unwrap();
+        
+        assert!(first);
+        assert_eq!(after_first, "creating");
+        assert!(!second);
+        assert!(!after_success);
+        assert!(!other_create);
+        assert!(after_failure);
+        assert_eq!(after_release, "failed");
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL with the toygres_cms schema"]
+    async fn test_set_tags_replaces_tags() {
+        let db_url = std::env::var("DATABASE_URL").unwrap();
+        let pool = sqlx::PgPool::connect(&db_url).await.unwrap();
+        let mut tx = pool.begin().await.unwrap();
+        
+        let k8s_name = format!("tags-{}", &uuid::Uuid::new_v4().to_string()[..8]);
+        sqlx::query(
+            "INSERT INTO toygres_cms.instances
+             (user_name, k8s_name, namespace, postgres_version, storage_size_gb,
+              use_load_balancer, state, create_orchestration_id, tags)
+             VALUES ($1, $1, 'toygres', '18', 5, false, 'running', $2, '{\"team\": \"data\"}')"
+        )
+        .bind(&k8s_name)
+        .bind(format!("create-{}", k8s_name))
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+        
+        let tags: BTreeMap<String, serde_json::Value> = [("env".to_string(), serde_json::json!("prod"))].into_iter().collect();
+        assert!(set_tags(&mut *tx, &k8s_name, &tags).await.unwrap());
+        let (stored,): (sqlx::types::Json<BTreeMap<String, serde_json::Value>>,) =
+            sqlx::query_as("SELECT tags FROM toygres_cms.instances WHERE k8s_name = $1")
+                .bind(&k8s_name)
+                .fetch_one(&mut *tx)
+                .await
+                .unwrap();
+        let missing = set_tags(&mut *tx, "tags-missing", &tags).await.unwrap();
+        tx.rollback().await.unwrap();
+        
+        assert_eq!(stored.0, tags);
+        assert!(!missing);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL with the toygres_cms schema"]
+    async fn test_worker_heartbeat_upsert_and_staleness() {
+        let db_url = std::env::var("DATABASE_URL").unwrap();
+        let pool = sqlx::PgPool::connect(&db_url).await.unwrap();
+        let mut tx = pool.begin().await.unwrap();
+        
+        let worker_id = format!("worker-{}", uuid::Uuid::new_v4());
+        let started_at = chrono::Utc::now();
+        record_worker_heartbeat(&mut *tx, &worker_id, "node-a", started_at).await.unwrap();
+        record_worker_heartbeat(&mut *tx, &worker_id, "node-b", started_at).await.unwrap();
+        let fresh = worker_heartbeats(&mut *tx).await.unwrap();
+        
+        sqlx::query("UPDATE toygres_cms.workers SET last_heartbeat = NOW() - INTERVAL '5 minutes' WHERE worker_id = $1")
+            .bind(&worker_id)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        let dead = worker_heartbeats(&mut *tx).await.unwrap();
+        
+        remove_worker(&mut *tx, &worker_id).await.unwrap();
+        let removed = worker_heartbeats(&mut *tx).await.unwrap();
+        tx.rollback().await.unwrap();
+        
+        let find = |workers: &[WorkerHeartbeat]| workers.iter().find(|w| w.worker_id == worker_id).cloned();
+        let fresh = find(&fresh).unwrap();
+        assert_eq!(fresh.hostname, "node-b", "the second heartbeat updates the same row");
+        assert!(!fresh.stale);
+        assert!(find(&dead).unwrap().stale);
+        assert!(find(&removed).is_none());
+    }
+    
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL with the toygres_cms schema"]
+    async fn test_maintenance_mode_toggle() {
+        let db_url = std::env::var("DATABASE_URL").unwrap();
+        let pool = sqlx::PgPool::connect(&db_url).await.unwrap();
+        let mut tx = pool.begin().await.unwrap();
+        
+        let on = set_maintenance_mode(&mut *tx, true).await.unwrap();
+        let read = maintenance_mode(&mut *tx).await.unwrap();
+        let off = set_maintenance_mode(&mut *tx, false).await.unwrap();
+        tx.rollback().await.unwrap();
+        
+        assert!(on.enabled);
+        assert_eq!(read, on);
+        assert!(!off.enabled);
+    }
+}
@@ -31,15 +31,24 @@ pub async fn initialize() -> Result<(Arc<Runtime>, Arc<PostgresProvider>)> {
         db::verify_cms_tables(&db_url).await?;
     }
     
+    // Catch a broken template override now rather than on the first create
+    if let Some(dir) = toygres_orchestrations::activities::deploy_postgres::validate_template_override()
+        .map_err(|e| anyhow::anyhow!("Invalid deployment templates: {:#}", e))?
+    {
+        tracing::info!("Using deployment templates from {} (embedded for any missing)", dir.display());
+    }
+    
     // Create activity and orchestration registries
     let activities = Arc::new(create_activity_registry());
     let orchestrations = create_orchestration_registry();
     
     // Configure runtime options
-    let mut runtime_options = RuntimeOptions::default();
-    runtime_options.orchestration_concurrency = 10;  // 10 orchestration workers (default: 2)
-    runtime_options.worker_concurrency = 10;         // 10 activity workers (default: 2)
-    runtime_options.worker_lock_timeout = std::time::Duration::from_secs(300); // 5 minutes
+    let mut runtime_options = RuntimeOptions {
+        orchestration_concurrency: 10,  // 10 orchestration workers (default: 2)
+        worker_concurrency: 10,         // 10 activity workers (default: 2)
+        worker_lock_timeout: std::time::Duration::from_secs(300), // 5 minutes
+        ..Default::default()
+    };
     
     // Configure observability (metrics and structured logging)
     let observability_enabled = std::env::var("DUROXIDE_OBSERVABILITY_ENABLED")
@@ -98,6 +107,17 @@ pub async fn initialize() -> Result<(Arc<Runtime>, Arc<PostgresProvider>)> {
     // Initialize the duroxide client for activities that need it (e.g., raise_event)
     let client = Arc::new(duroxide::Client::new(store.clone()));
     toygres_orchestrations::init_duroxide_client(client);
+    toygres_orchestrations::set_duroxide_reconnect(Arc::new(move || {
+        let db_url = db_url.clone();
+        Box::pin(async move {
+            tracing::warn!("Reconnecting the Duroxide client used by activities");
+            let store = PostgresProvider::new_with_schema(&db_url, Some(schema_name))
+                .await
+                .map_err(|e| format!("Failed to reconnect to Duroxide store: {}", e))?;
+            let client: Arc<duroxide::Client> = Arc::new(duroxide::Client::new(Arc::new(store)));
+            Ok(client)
+        })
+    }));
     
     tracing::info!("✓ Duroxide runtime ready");
     
@@ -0,0 +1,280 @@
+//! Parsing for `~/.toygres/server.log`
+//!
+//! The file layer writes one JSON object per line (tracing-subscriber's `json` format).
+//! Logs written before the switch are flat text; those lines parse to a raw entry
+//! carrying the whole line as the message so older files stay readable.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::path::PathBuf;
+
+/// Location of the server log when not running in Kubernetes
+pub fn log_file_path() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".toygres").join("server.log")
+}
+
+/// One line of the server log
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogEntry {
+    pub timestamp: Option<String>,
+    /// Upper-case level (`INFO`, `WARN`, ...)
+    pub level: Option<String>,
+    pub target: Option<String>,
+    pub message: String,
+    /// Event fields other than `message`
+    #[serde(skip_serializing_if = "Map::is_empty")]
+    pub fields: Map<String, Value>,
+    /// Enclosing spans, outermost first, as `name{key=value ...}`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub spans: Vec<String>,
+    /// False for lines that were not JSON; only `message` is set on those
+    pub structured: bool,
+}
+
+impl LogEntry {
+    fn raw(line: &str) -> Self {
+        Self {
+            timestamp: None,
+            level: None,
+            target: None,
+            message: strip_ansi(line),
+            fields: Map::new(),
+            spans: Vec::new(),
+            structured: false,
+        }
+    }
+
+    /// Render as a single text line, like the old flat format without colors
+    pub fn render(&self) -> String {
+        if !self.structured {
+            return self.message.clone();
+        }
+
+        let mut out = String::new();
+        if let Some(ts) = &self.timestamp {
+            out.push_str(ts);
+            out.push(' ');
+        }
+        if let Some(level) = &self.level {
+            out.push_str(&format!("{:>5} ", level));
+        }
+        if !self.spans.is_empty() {
+            out.push_str(&self.spans.join(":"));
+            out.push_str(": ");
+        }
+        if let Some(target) = &self.target {
+            out.push_str(target);
+            out.push_str(": ");
+        }
+        out.push_str(&self.message);
+        for (key, value) in &self.fields {
+            out.push_str(&format!(" {}={}", key, display_value(value)));
+        }
+        out
+    }
+
+    /// Whether `text` appears in the message, a field or a span
+    pub fn contains(&self, text: &str) -> bool {
+        self.message.contains(text)
+            || self.spans.iter().any(|s| s.contains(text))
+            || self.fields.iter().any(|(k, v)| k.contains(text) || display_value(v).contains(text))
+    }
+
+    /// Whether the entry passes the level, target and text filters. Raw lines have
+    /// no level or target, so only the text filter applies to them.
+    pub fn matches(&self, filter: &LogFilter) -> bool {
+        if let Some(text) = &filter.text {
+            if !self.contains(text) {
+                return false;
+            }
+        }
+        if !self.structured {
+            return true;
+        }
+        if let Some(min) = filter.level.as_deref().and_then(level_rank) {
+            if self.level.as_deref().and_then(level_rank).is_none_or(|rank| rank < min) {
+                return false;
+            }
+        }
+        if let Some(prefix) = &filter.target {
+            if !self.target.as_deref().is_some_and(|t| t.starts_with(prefix.as_str())) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Filters applied when reading the log
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    /// Minimum level; `warn` keeps WARN and ERROR
+    pub level: Option<String>,
+    /// Target prefix, e.g. `toygres_orchestrations`
+    pub target: Option<String>,
+    /// Substring of the message, a field or a span
+    pub text: Option<String>,
+}
+
+/// Parse one line of the log, falling back to a raw entry for non-JSON lines
+pub fn parse_line(line: &str) -> LogEntry {
+    let object = match serde_json::from_str::<Value>(line) {
+        Ok(Value::Object(object)) if object.contains_key("level") => object,
+        _ => return LogEntry::raw(line),
+    };
+
+    let string = |key: &str| object.get(key).and_then(Value::as_str).map(str::to_string);
+
+    let mut fields = match object.get("fields") {
+        Some(Value::Object(fields)) => fields.clone(),
+        _ => Map::new(),
+    };
+    let message = match fields.remove("message") {
+        Some(Value::String(message)) => message,
+        Some(other) => other.to_string(),
+        None => String::new(),
+    };
+
+    let spans = match object.get("spans") {
+        Some(Value::Array(spans)) => spans.iter().filter_map(Value::as_object).map(render_span).collect(),
+        _ => Vec::new(),
+    };
+
+    LogEntry {
+        timestamp: string("timestamp"),
+        level: string("level").map(|l| l.to_ascii_uppercase()),
+        target: string("target"),
+        message,
+        fields,
+        spans,
+        structured: true,
+    }
+}
+
+/// Read the log, apply `filter` and keep the last `limit` matching entries
+pub fn read_entries(path: &std::path::Path, filter: &LogFilter, limit: usize) -> std::io::Result<Vec<LogEntry>> {
+    use std::io::{BufRead, BufReader};
+
+    let file = std::fs::File::open(path)?;
+    let mut entries: Vec<LogEntry> = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| parse_line(&line))
+        .filter(|entry| entry.matches(filter))
+        .collect();
+
+    let start = entries.len().saturating_sub(limit);
+    Ok(entries.split_off(start))
+}
+
+fn render_span(span: &Map<String, Value>) -> String {
+    let name = span.get("name").and_then(Value::as_str).unwrap_or("span");
+    let fields: Vec<String> = span.iter()
+        .filter(|(key, _)| key.as_str() != "name")
+        .map(|(key, value)| format!("{}={}", key, display_value(value)))
+        .collect();
+    if fields.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}{{{}}}", name, fields.join(" "))
+    }
+}
+
+/// Strings without their JSON quotes, everything else as JSON
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn level_rank(level: &str) -> Option<u8> {
+    match level.to_ascii_uppercase().as_str() {
+        "TRACE" => Some(0),
+        "DEBUG" => Some(1),
+        "INFO" => Some(2),
+        "WARN" => Some(3),
+        "ERROR" => Some(4),
+        _ => None,
+    }
+}
+
+/// Remove ANSI escape sequences left by the old colored text format
+fn strip_ansi(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' && chars.peek() == Some(&'[') {
+            chars.next();
+            // Skip parameters up to and including the final byte (a letter)
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JSON_LINE: &str = r#"{"timestamp":"2026-10-15T09:12:01.123456Z","level":"INFO","fields":{"message":"Deploying instance","instance":"mydb-1a2b","storage_gb":10},"target":"toygres_orchestrations::create","spans":[{"name":"orchestration","instance_id":"create-mydb-1a2b"}]}"#;
+
+    #[test]
+    fn test_json_line_parsed_into_fields() {
+        let entry = parse_line(JSON_LINE);
+
+        assert!(entry.structured);
+        assert_eq!(entry.timestamp.as_deref(), Some("2026-10-15T09:12:01.123456Z"));
+        assert_eq!(entry.level.as_deref(), Some("INFO"));
+        assert_eq!(entry.target.as_deref(), Some("toygres_orchestrations::create"));
+        assert_eq!(entry.message, "Deploying instance");
+        assert_eq!(entry.fields.get("instance"), Some(&Value::from("mydb-1a2b")));
+        assert_eq!(entry.fields.get("storage_gb"), Some(&Value::from(10)));
+        assert!(!entry.fields.contains_key("message"));
+        assert_eq!(entry.spans, vec!["orchestration{instance_id=create-mydb-1a2b}"]);
+        assert_eq!(
+            entry.render(),
+            "2026-10-15T09:12:01.123456Z  INFO orchestration{instance_id=create-mydb-1a2b}: \
+             toygres_orchestrations::create: Deploying instance instance=mydb-1a2b storage_gb=10"
+        );
+    }
+
+    #[test]
+    fn test_text_line_falls_back_to_raw() {
+        let entry = parse_line("\u{1b}[2m2026-10-15T09:12:01Z\u{1b}[0m \u{1b}[32m INFO\u{1b}[0m toygres_server: started");
+
+        assert!(!entry.structured);
+        assert_eq!(entry.level, None);
+        assert_eq!(entry.message, "2026-10-15T09:12:01Z  INFO toygres_server: started");
+        assert_eq!(entry.render(), entry.message);
+    }
+
+    #[test]
+    fn test_filters_by_level_target_and_text() {
+        let entry = parse_line(JSON_LINE);
+        let filter = |level: Option<&str>, target: Option<&str>, text: Option<&str>| LogFilter {
+            level: level.map(str::to_string),
+            target: target.map(str::to_string),
+            text: text.map(str::to_string),
+        };
+
+        assert!(entry.matches(&LogFilter::default()));
+        assert!(entry.matches(&filter(Some("debug"), Some("toygres_orchestrations"), Some("create-mydb-1a2b"))));
+        assert!(!entry.matches(&filter(Some("warn"), None, None)));
+        assert!(!entry.matches(&filter(None, Some("toygres_server"), None)));
+        assert!(!entry.matches(&filter(None, None, Some("other-instance"))));
+
+        // Raw lines ignore level and target but still honour the text filter
+        let raw = parse_line("plain text line");
+        assert!(raw.matches(&filter(Some("error"), Some("toygres_server"), Some("plain"))));
+        assert!(!raw.matches(&filter(None, None, Some("json"))));
+    }
+}
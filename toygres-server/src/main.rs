@@ -8,8 +8,12 @@ mod auth;
 mod cli;
 mod commands;
 mod config;
+mod consistency;
 mod db;
 mod duroxide;
+mod history;
+mod logs;
+mod supervisor;
 mod worker;
 
 use cli::{Args, Mode};
@@ -65,10 +69,13 @@ fn initialize_tracing() -> Result<()> {
         // CRITICAL: Keep guard alive for the lifetime of the program
         std::mem::forget(guard);
         
-        // File layer
+        // File layer: one JSON object per line, parsed back by `logs::parse_line`
         let file_layer = fmt::layer()
+            .json()
+            .with_current_span(false)
+            .with_span_list(true)
             .with_writer(file_writer)
-            .with_ansi(true);
+            .with_ansi(false);
         
         tracing_subscriber::registry()
             .with(env_filter)
@@ -76,7 +83,7 @@ fn initialize_tracing() -> Result<()> {
             .init();
         
         eprintln!("✓ Tracing initialized");
-        eprintln!("  - File: ~/.toygres/server.log (flat text with colors)");
+        eprintln!("  - File: ~/.toygres/server.log (JSON lines)");
     }
     
     Ok(())
@@ -104,8 +111,19 @@ async fn main() -> Result<()> {
         Mode::Worker { worker_id } => {
             run_worker_mode(worker_id).await
         }
-        Mode::Create { name, password, version, storage, internal, namespace } => {
-            commands::instance::run_create(name, password, version, storage, internal, namespace).await
+        Mode::Create { name, dns_label, password, password_stdin, password_file, version, storage, internal, namespace, from_manifest, wait, wait_timeout, deploy } => {
+            let password = commands::instance::PasswordSource::from_args(password, password_stdin, password_file)?;
+            let wait = wait.then(|| std::time::Duration::from_secs(wait_timeout));
+            commands::instance::run_create(name, dns_label, password, version, storage, internal, namespace, from_manifest, *deploy, wait).await
+        }
+        Mode::Export { name, output } => {
+            commands::instance::run_export(name, output).await
+        }
+        Mode::Diff { name, namespace, output } => {
+            commands::instance::run_diff(name, namespace, output).await
+        }
+        Mode::Render { name, namespace, output } => {
+            commands::instance::run_render(name, namespace, output).await
         }
         Mode::Delete { name, namespace } => {
             commands::instance::run_delete(name, namespace).await
@@ -116,6 +134,12 @@ async fn main() -> Result<()> {
         Mode::Get { name, output } => {
             commands::instance::run_get(name, output).await
         }
+        Mode::Logs { name, tail, follow } => {
+            commands::instance::run_logs(name, tail, follow).await
+        }
+        Mode::Flow { id } => {
+            commands::orchestration::flow(&id).await
+        }
         Mode::Server { command } => {
             commands::server::handle_command(command).await
         }
@@ -140,9 +164,21 @@ async fn run_worker_mode(worker_id: Option<String>) -> Result<()> {
     tracing::info!("Starting Toygres in worker-only mode");
     tracing::info!("Worker ID: {}", id);
     
-    // TODO: Implement worker mode
-    // - Start Duroxide runtime with workers
-    // - No API server
+    // Runtime with workers, no API server. `initialize` also sets up the Duroxide
+    // client that raise-event activities use.
+    let (runtime, _store) = duroxide::initialize().await?;
+    let heartbeat = worker::Heartbeat::start(&id).await?;
     
-    anyhow::bail!("Worker mode not yet implemented")
+    tracing::info!("✓ Worker {} ready", id);
+    tracing::info!("  Press Ctrl+C to stop");
+    
+    tokio::signal::ctrl_c().await?;
+    
+    if let Some(heartbeat) = heartbeat {
+        heartbeat.stop().await;
+    }
+    tracing::info!("Shutting down Duroxide runtime");
+    runtime.shutdown(None).await;
+    
+    Ok(())
 }
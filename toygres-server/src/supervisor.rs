@@ -0,0 +1,607 @@
+//! Instance actor supervision
+//!
+//! An instance actor only stops on purpose when its instance is deleted. If it ends
+//! any other way (a failure, or a worker crash before continue-as-new committed),
+//! health monitoring for that instance silently stops. [`supervise_actors`] sweeps
+//! `running` instances and starts a fresh actor for any whose actor is terminal or
+//! missing.
+//!
+//! A create whose actor started but could not be recorded leaves the row without an
+//! actor id (and an `actor_not_recorded` event). Sweeps adopt that create-time actor
+//! when it is still running rather than start a second one.
+//!
+//! Sweeps are idempotent: the CMS row is switched to the new actor id with a
+//! compare-and-set on the old id, so a second (or concurrent) sweep sees a running
+//! actor, or loses the race, and starts nothing.
+//!
+//! Operators can also stop an actor on purpose ([`stop_actor`]), e.g. during long manual
+//! maintenance. The instance is then marked `actor_stopped` and sweeps leave it alone
+//! until [`start_actor`] starts a fresh one.
+
+use anyhow::{Context, Result};
+use duroxide::{Client, OrchestrationStatus};
+use serde::Serialize;
+use toygres_orchestrations::actor_events::ActorEvent;
+use toygres_orchestrations::ids::{self, IdKind};
+use toygres_orchestrations::names::orchestrations;
+use toygres_orchestrations::trace::TraceLevel;
+use toygres_orchestrations::types::InstanceActorInput;
+
+/// State of an instance's actor orchestration, as far as supervision cares
+#[derive(Debug, Clone, PartialEq)]
+pub enum ActorStatus {
+    Running,
+    /// Completed or failed, with the status name
+    Terminal(&'static str),
+    /// No actor id in the CMS, or no such orchestration
+    Missing,
+}
+
+impl From<&OrchestrationStatus> for ActorStatus {
+    fn from(status: &OrchestrationStatus) -> Self {
+        match status {
+            OrchestrationStatus::Running => ActorStatus::Running,
+            OrchestrationStatus::Completed { .. } => ActorStatus::Terminal("Completed"),
+            OrchestrationStatus::Failed { .. } => ActorStatus::Terminal("Failed"),
+            OrchestrationStatus::NotFound => ActorStatus::Missing,
+        }
+    }
+}
+
+impl ActorStatus {
+    fn name(&self) -> &'static str {
+        match self {
+            ActorStatus::Running => "Running",
+            ActorStatus::Terminal(name) => name,
+            ActorStatus::Missing => "Missing",
+        }
+    }
+}
+
+/// Whether an instance in `instance_state` needs a new actor.
+///
+/// Only `running` instances are monitored; creating ones get their actor at the end
+/// of the create orchestration and deleting ones are meant to lose it. Actors an
+/// operator stopped stay stopped.
+pub fn should_restart(instance_state: &str, actor_stopped: bool, actor: &ActorStatus) -> bool {
+    instance_state == "running" && !actor_stopped && *actor != ActorStatus::Running
+}
+
+/// An actor started by a sweep
+#[derive(Debug, Clone, Serialize)]
+pub struct RestartedActor {
+    pub k8s_name: String,
+    pub previous_actor_id: Option<String>,
+    pub previous_status: String,
+    pub new_actor_id: String,
+}
+
+/// Outcome of one supervision sweep
+#[derive(Debug, Default, Serialize)]
+pub struct SupervisionReport {
+    /// Running instances inspected
+    pub checked: usize,
+    pub restarted: Vec<RestartedActor>,
+    /// Running create-time actors that the CMS did not know about, now recorded
+    pub adopted: Vec<String>,
+    /// Instances that could not be checked or restarted, with the reason
+    pub errors: Vec<serde_json::Value>,
+}
+
+type RunningInstanceRow = (uuid::Uuid, String, String, Option<String>);
+
+/// Check every running instance's actor and restart the ones that are not running,
+/// except those an operator stopped
+pub async fn supervise_actors(client: &Client, pool: &sqlx::PgPool) -> Result<SupervisionReport> {
+    let instances: Vec<RunningInstanceRow> = sqlx::query_as(
+        "SELECT id, k8s_name, namespace, instance_actor_orchestration_id
+         FROM toygres_cms.instances
+         WHERE state = 'running' AND NOT actor_stopped
+         ORDER BY created_at"
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to list running instances")?;
+
+    let mut report = SupervisionReport {
+        checked: instances.len(),
+        ..Default::default()
+    };
+
+    for (instance_id, k8s_name, namespace, actor_id) in instances {
+        let status = match &actor_id {
+            Some(id) => match client.get_orchestration_status(id).await {
+                Ok(status) => ActorStatus::from(&status),
+                Err(e) => {
+                    report.errors.push(serde_json::json!({
+                        "k8s_name": k8s_name,
+                        "error": format!("Failed to get actor status: {}", e),
+                    }));
+                    continue;
+                }
+            },
+            None => match adoptable_actor(client, &k8s_name).await {
+                Some(create_actor_id) => {
+                    match adopt_actor(pool, instance_id, &create_actor_id).await {
+                        Ok(true) => report.adopted.push(create_actor_id),
+                        Ok(false) => tracing::info!("Actor for {} was recorded meanwhile, skipping", k8s_name),
+                        Err(e) => report.errors.push(serde_json::json!({
+                            "k8s_name": k8s_name,
+                            "error": format!("{:#}", e),
+                        })),
+                    }
+                    continue;
+                }
+                None => ActorStatus::Missing,
+            },
+        };
+
+        if !should_restart("running", false, &status) {
+            continue;
+        }
+
+        match restart_actor(client, pool, instance_id, &k8s_name, &namespace, actor_id.as_deref(), false, &status, "actor_restarted").await {
+            Ok(Some(restarted)) => report.restarted.push(restarted),
+            Ok(None) => tracing::info!("Actor for {} was already replaced, skipping", k8s_name),
+            Err(e) => report.errors.push(serde_json::json!({
+                "k8s_name": k8s_name,
+                "error": format!("{:#}", e),
+            })),
+        }
+    }
+
+    Ok(report)
+}
+
+/// The actor the create orchestration starts (`actor-<k8s_name>`), if it is running
+async fn adoptable_actor(client: &Client, k8s_name: &str) -> Option<String> {
+    let actor_id = ids::actor(k8s_name);
+    match client.get_orchestration_status(&actor_id).await {
+        Ok(OrchestrationStatus::Running) => Some(actor_id),
+        _ => None,
+    }
+}
+
+/// Record a running actor for a row that has none. Returns `false` if the row got an
+/// actor id meanwhile.
+async fn adopt_actor(pool: &sqlx::PgPool, instance_id: uuid::Uuid, actor_id: &str) -> Result<bool> {
+    let mut tx = pool.begin().await.context("Failed to start transaction")?;
+
+    let claimed = sqlx::query(
+        "UPDATE toygres_cms.instances
+         SET instance_actor_orchestration_id = $2, updated_at = NOW()
+         WHERE id = $1 AND instance_actor_orchestration_id IS NULL"
+    )
+    .bind(instance_id)
+    .bind(actor_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to record adopted actor id")?;
+
+    if claimed.rows_affected() == 0 {
+        return Ok(false);
+    }
+
+    sqlx::query(
+        "INSERT INTO toygres_cms.instance_events
+         (instance_id, event_type, message, metadata)
+         VALUES ($1, 'actor_adopted', $2, $3)"
+    )
+    .bind(instance_id)
+    .bind(format!("Instance actor {} was running but not recorded; adopted it", actor_id))
+    .bind(serde_json::json!({ "actor_id": actor_id }))
+    .execute(&mut *tx)
+    .await
+    .context("Failed to record actor adoption event")?;
+
+    tx.commit().await.context("Failed to commit actor adoption")?;
+
+    tracing::warn!("Adopted unrecorded instance actor {}", actor_id);
+    Ok(true)
+}
+
+/// Point the CMS row at a new actor (clearing the stopped mark) and start it,
+/// recording `event_type`. The row is claimed with a compare-and-set on the
+/// `previous_actor_id` and `stopped` the caller saw, so of two callers that saw the
+/// same row only one starts an actor. Returns `None` for the other, or when the row
+/// changed otherwise (stopped or started meanwhile). The new actor runs the health
+/// query recorded for the instance.
+#[allow(clippy::too_many_arguments)]
+async fn restart_actor(
+    client: &Client,
+    pool: &sqlx::PgPool,
+    instance_id: uuid::Uuid,
+    k8s_name: &str,
+    namespace: &str,
+    previous_actor_id: Option<&str>,
+    stopped: bool,
+    status: &ActorStatus,
+    event_type: &str,
+) -> Result<Option<RestartedActor>> {
+    let previous_status = status.name();
+    let new_actor_id = ids::run(IdKind::Actor, k8s_name);
+
+    let mut tx = pool.begin().await.context("Failed to start transaction")?;
+
+    let claimed: Option<(Option<String>,)> = sqlx::query_as(
+        "UPDATE toygres_cms.instances
+         SET instance_actor_orchestration_id = $2, actor_stopped = FALSE, updated_at = NOW()
+         WHERE id = $1 AND state = 'running' AND actor_stopped = $4
+           AND instance_actor_orchestration_id IS NOT DISTINCT FROM $3
+         RETURNING health_sql"
+    )
+    .bind(instance_id)
+    .bind(&new_actor_id)
+    .bind(previous_actor_id)
+    .bind(stopped)
+    .fetch_optional(&mut *tx)
+    .await
+    .context("Failed to record new actor id")?;
+
+    let Some((health_sql,)) = claimed else {
+        return Ok(None);
+    };
+
+    sqlx::query(
+        "INSERT INTO toygres_cms.instance_events
+         (instance_id, event_type, message, metadata)
+         VALUES ($1, $2, $3, $4)"
+    )
+    .bind(instance_id)
+    .bind(event_type)
+    .bind(format!("Instance actor was {}, started {}", previous_status, new_actor_id))
+    .bind(serde_json::json!({
+        "previous_actor_id": previous_actor_id,
+        "previous_status": previous_status,
+        "new_actor_id": new_actor_id,
+    }))
+    .execute(&mut *tx)
+    .await
+    .context("Failed to record actor restart event")?;
+
+    tx.commit().await.context("Failed to commit actor restart")?;
+
+    // Started after the commit: if this fails the row points at a missing actor,
+    // which the next sweep restarts
+    let input = InstanceActorInput {
+        k8s_name: k8s_name.to_string(),
+        namespace: namespace.to_string(),
+        orchestration_id: new_actor_id.clone(),
+        trace_level: Some(TraceLevel::from_env()),
+        slow_threshold_ms: None,
+        max_replication_lag_ms: None,
+        health_sql,
+        last_storage_pct: None,
+    };
+    client
+        .start_orchestration(&new_actor_id, orchestrations::INSTANCE_ACTOR, serde_json::to_string(&input)?)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to start actor {}: {}", new_actor_id, e))?;
+
+    tracing::warn!("Restarted instance actor for {} ({} was {})", k8s_name,
+                   previous_actor_id.unwrap_or("none"), previous_status);
+
+    Ok(Some(RestartedActor {
+        k8s_name: k8s_name.to_string(),
+        previous_actor_id: previous_actor_id.map(str::to_string),
+        previous_status: previous_status.to_string(),
+        new_actor_id,
+    }))
+}
+
+/// An instance's actor as seen by the CMS and Duroxide
+#[derive(Debug, Clone, Serialize)]
+pub struct ActorState {
+    pub k8s_name: String,
+    pub actor_id: Option<String>,
+    /// "Running", "Completed", "Failed" or "Missing"
+    pub status: String,
+    /// Stopped by an operator; sweeps do not restart it
+    pub stopped: bool,
+}
+
+type ActorRow = (uuid::Uuid, String, String, Option<String>, bool);
+
+async fn actor_row(pool: &sqlx::PgPool, k8s_name: &str) -> Result<Option<ActorRow>> {
+    sqlx::query_as(
+        "SELECT id, namespace, state::text, instance_actor_orchestration_id, actor_stopped
+         FROM toygres_cms.instances
+         WHERE k8s_name = $1 AND state != 'deleted'"
+    )
+    .bind(k8s_name)
+    .fetch_optional(pool)
+    .await
+    .context("Failed to load instance actor")
+}
+
+async fn actor_status(client: &Client, actor_id: Option<&str>) -> Result<ActorStatus> {
+    match actor_id {
+        Some(id) => client
+            .get_orchestration_status(id)
+            .await
+            .map(|status| ActorStatus::from(&status))
+            .map_err(|e| anyhow::anyhow!("Failed to get actor status: {}", e)),
+        None => Ok(ActorStatus::Missing),
+    }
+}
+
+/// The actor of a live instance, or `None` when there is no such instance
+pub async fn actor_state(client: &Client, pool: &sqlx::PgPool, k8s_name: &str) -> Result<Option<ActorState>> {
+    let Some((_, _, _, actor_id, stopped)) = actor_row(pool, k8s_name).await? else {
+        return Ok(None);
+    };
+    let status = actor_status(client, actor_id.as_deref()).await?;
+
+    Ok(Some(ActorState {
+        k8s_name: k8s_name.to_string(),
+        actor_id,
+        status: status.name().to_string(),
+        stopped,
+    }))
+}
+
+/// Mark the actor stopped, so sweeps leave it alone, and send it `Cancel` if it is
+/// running. Returns the state before the actor has processed the signal, or `None`
+/// when there is no such instance.
+pub async fn stop_actor(client: &Client, pool: &sqlx::PgPool, k8s_name: &str) -> Result<Option<ActorState>> {
+    let Some((instance_id, _, _, actor_id, _)) = actor_row(pool, k8s_name).await? else {
+        return Ok(None);
+    };
+    let status = actor_status(client, actor_id.as_deref()).await?;
+
+    let mut tx = pool.begin().await.context("Failed to start transaction")?;
+    sqlx::query(
+        "UPDATE toygres_cms.instances SET actor_stopped = TRUE, updated_at = NOW() WHERE id = $1"
+    )
+    .bind(instance_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to mark actor stopped")?;
+    sqlx::query(
+        "INSERT INTO toygres_cms.instance_events
+         (instance_id, event_type, message, metadata)
+         VALUES ($1, 'actor_stopped', $2, $3)"
+    )
+    .bind(instance_id)
+    .bind(format!("Instance actor {} stopped by request", actor_id.as_deref().unwrap_or("none")))
+    .bind(serde_json::json!({ "actor_id": actor_id, "status": status.name() }))
+    .execute(&mut *tx)
+    .await
+    .context("Failed to record actor stop event")?;
+    tx.commit().await.context("Failed to commit actor stop")?;
+
+    // Sent after the commit: a sweep racing with this sees the flag and starts nothing
+    if let (Some(id), ActorStatus::Running) = (&actor_id, &status) {
+        let cancel = ActorEvent::Cancel;
+        client
+            .raise_event(id, cancel.name(), cancel.data())
+            .await
+            .map_err(|e| anyhow::anyhow!("Marked stopped, but failed to signal actor {}: {}", id, e))?;
+    }
+
+    Ok(Some(ActorState {
+        k8s_name: k8s_name.to_string(),
+        actor_id,
+        status: status.name().to_string(),
+        stopped: true,
+    }))
+}
+
+/// Outcome of [`start_actor`]
+#[derive(Debug)]
+pub enum StartActor {
+    Started(RestartedActor),
+    /// The instance's actor is running and was not stopped
+    AlreadyRunning(String),
+    /// Only `running` instances are monitored; the instance is in this state
+    NotRunning(String),
+    NotFound,
+}
+
+/// Clear the stopped mark and start a fresh actor for a running instance
+pub async fn start_actor(client: &Client, pool: &sqlx::PgPool, k8s_name: &str) -> Result<StartActor> {
+    let Some((instance_id, namespace, state, actor_id, stopped)) = actor_row(pool, k8s_name).await? else {
+        return Ok(StartActor::NotFound);
+    };
+    if state != "running" {
+        return Ok(StartActor::NotRunning(state));
+    }
+    let status = actor_status(client, actor_id.as_deref()).await?;
+    if !stopped && status == ActorStatus::Running {
+        return Ok(StartActor::AlreadyRunning(actor_id.unwrap_or_default()));
+    }
+
+    // Clearing the stopped mark and recording the new actor is one claim, so a
+    // concurrent start (or sweep) that saw the same row starts nothing. A stopped actor
+    // that has not processed `Cancel` yet exits on its own; the CMS row points at the
+    // new one either way.
+    match restart_actor(client, pool, instance_id, k8s_name, &namespace, actor_id.as_deref(), stopped, &status, "actor_started").await? {
+        Some(started) => Ok(StartActor::Started(started)),
+        None => Ok(StartActor::AlreadyRunning(actor_id.unwrap_or_default())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_restart_decision() {
+        let failed = ActorStatus::from(&OrchestrationStatus::Failed {
+            details: duroxide::ErrorDetails::Application {
+                kind: duroxide::AppErrorKind::OrchestrationFailed,
+                message: "boom".to_string(),
+                retryable: false,
+            },
+        });
+        assert_eq!(failed, ActorStatus::Terminal("Failed"));
+
+        let completed = ActorStatus::from(&OrchestrationStatus::Completed { output: "{}".to_string() });
+        let missing = ActorStatus::from(&OrchestrationStatus::NotFound);
+        let running = ActorStatus::from(&OrchestrationStatus::Running);
+
+        // A running instance needs an actor unless its actor is running
+        assert!(should_restart("running", false, &failed));
+        assert!(should_restart("running", false, &completed));
+        assert!(should_restart("running", false, &missing));
+        assert!(!should_restart("running", false, &running));
+
+        // Other states are never supervised
+        for state in ["creating", "deleting", "deleted", "failed"] {
+            assert!(!should_restart(state, false, &missing), "state {}", state);
+            assert!(!should_restart(state, false, &failed), "state {}", state);
+        }
+    }
+
+    #[test]
+    fn test_stopped_actors_are_not_restarted() {
+        let completed = ActorStatus::from(&OrchestrationStatus::Completed { output: "{}".to_string() });
+
+        // An actor that exited after a stop request stays stopped
+        assert!(!should_restart("running", true, &completed));
+        assert!(!should_restart("running", true, &ActorStatus::Missing));
+        // Once started again it is supervised as before
+        assert!(should_restart("running", false, &completed));
+        assert_eq!(completed.name(), "Completed");
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL with the toygres_cms schema"]
+    async fn test_stop_and_start_track_actor_state() {
+        use duroxide::providers::sqlite::SqliteProvider;
+        use std::sync::Arc;
+
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap()).await.unwrap();
+        let client = Client::new(Arc::new(SqliteProvider::new_in_memory().await.unwrap()));
+        let k8s_name = format!("actor-test-{}", &uuid::Uuid::new_v4().to_string()[..8]);
+        sqlx::query(
+            "INSERT INTO toygres_cms.instances
+             (user_name, k8s_name, namespace, postgres_version, storage_size_gb,
+              use_load_balancer, state, create_orchestration_id)
+             VALUES ('actor-test', $1, 'toygres', '18', 5, false, 'running', $2)"
+        )
+        .bind(&k8s_name)
+        .bind(format!("create-{}", k8s_name))
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let stopped = stop_actor(&client, &pool, &k8s_name).await;
+        let after_stop = actor_state(&client, &pool, &k8s_name).await;
+        let report = supervise_actors(&client, &pool).await;
+        let started = start_actor(&client, &pool, &k8s_name).await;
+        let after_start = actor_state(&client, &pool, &k8s_name).await;
+
+        sqlx::query("DELETE FROM toygres_cms.instances WHERE k8s_name = $1")
+            .bind(&k8s_name)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(stopped.unwrap().unwrap().stopped);
+        let after_stop = after_stop.unwrap().unwrap();
+        assert!(after_stop.stopped);
+        assert_eq!(after_stop.status, "Missing");
+        assert!(report.unwrap().restarted.iter().all(|r| r.k8s_name != k8s_name));
+
+        let StartActor::Started(started) = started.unwrap() else { panic!("expected a new actor") };
+        let after_start = after_start.unwrap().unwrap();
+        assert!(!after_start.stopped);
+        assert_eq!(after_start.actor_id, Some(started.new_actor_id));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL with the toygres_cms schema"]
+    async fn test_concurrent_starts_start_one_actor() {
+        use duroxide::providers::sqlite::SqliteProvider;
+        use std::sync::Arc;
+
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap()).await.unwrap();
+        let client = Client::new(Arc::new(SqliteProvider::new_in_memory().await.unwrap()));
+        let k8s_name = format!("actor-test-{}", &uuid::Uuid::new_v4().to_string()[..8]);
+        let (instance_id,): (uuid::Uuid,) = sqlx::query_as(
+            "INSERT INTO toygres_cms.instances
+             (user_name, k8s_name, namespace, postgres_version, storage_size_gb,
+              use_load_balancer, state, create_orchestration_id, actor_stopped)
+             VALUES ('actor-test', $1, 'toygres', '18', 5, false, 'running', $2, TRUE)
+             RETURNING id"
+        )
+        .bind(&k8s_name)
+        .bind(format!("create-{}", k8s_name))
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        // Two starts that both read the stopped row with no actor
+        let start = || restart_actor(&client, &pool, instance_id, &k8s_name, "toygres", None, true, &ActorStatus::Missing, "actor_started");
+        let first = start().await;
+        let second = start().await;
+        let after = actor_state(&client, &pool, &k8s_name).await;
+
+        sqlx::query("DELETE FROM toygres_cms.instances WHERE k8s_name = $1")
+            .bind(&k8s_name)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let first = first.unwrap().expect("the first start claims the row");
+        assert!(second.unwrap().is_none());
+        let after = after.unwrap().unwrap();
+        assert!(!after.stopped);
+        assert_eq!(after.actor_id, Some(first.new_actor_id));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires DATABASE_URL with the toygres_cms schema"]
+    async fn test_restarted_actor_keeps_the_health_query() {
+        use duroxide::providers::sqlite::SqliteProvider;
+        use duroxide::runtime::{self, registry::ActivityRegistry};
+        use duroxide::{OrchestrationContext, OrchestrationRegistry};
+        use std::sync::Arc;
+
+        let pool = sqlx::PgPool::connect(&std::env::var("DATABASE_URL").unwrap()).await.unwrap();
+        let k8s_name = format!("actor-test-{}", &uuid::Uuid::new_v4().to_string()[..8]);
+        let (instance_id,): (uuid::Uuid,) = sqlx::query_as(
+            "INSERT INTO toygres_cms.instances
+             (user_name, k8s_name, namespace, postgres_version, storage_size_gb,
+              use_load_balancer, state, create_orchestration_id)
+             VALUES ('actor-test', $1, 'toygres', '18', 5, false, 'running', $2)
+             RETURNING id"
+        )
+        .bind(&k8s_name)
+        .bind(format!("create-{}", k8s_name))
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let recorded = crate::db::set_health_sql(&pool, &k8s_name, "SELECT 1 FROM orders LIMIT 1").await;
+
+        // A stand-in actor that completes with the input it was started with
+        let orchestrations = OrchestrationRegistry::builder()
+            .register(orchestrations::INSTANCE_ACTOR, |_ctx: OrchestrationContext, input: String| async move { Ok(input) })
+            .build();
+        let store = Arc::new(SqliteProvider::new_in_memory().await.unwrap());
+        let rt = runtime::Runtime::start_with_store(store.clone(), Arc::new(ActivityRegistry::builder().build()), orchestrations).await;
+        let client = Client::new(store);
+
+        let restarted = restart_actor(&client, &pool, instance_id, &k8s_name, "toygres", None, false, &ActorStatus::Missing, "actor_restarted").await;
+        let status = match &restarted {
+            Ok(Some(restarted)) => client.wait_for_orchestration(&restarted.new_actor_id, std::time::Duration::from_secs(10)).await.ok(),
+            _ => None,
+        };
+        rt.shutdown(None).await;
+
+        sqlx::query("DELETE FROM toygres_cms.instances WHERE k8s_name = $1")
+            .bind(&k8s_name)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(recorded.unwrap());
+        restarted.unwrap().expect("the actor is restarted");
+        let Some(OrchestrationStatus::Completed { output }) = status else {
+            panic!("actor did not complete: {:?}", status);
+        };
+        let input: InstanceActorInput = serde_json::from_str(&output).unwrap();
+        assert_eq!(input.health_sql.as_deref(), Some("SELECT 1 FROM orders LIMIT 1"));
+    }
+}
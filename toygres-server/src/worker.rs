@@ -1,2 +1,73 @@
-// Duroxide worker initialization placeholder
+//! Worker heartbeats
+//!
+//! Each process that runs Duroxide workers (worker mode and standalone mode) upserts
+//! a row in `toygres_cms.workers` every [`HEARTBEAT_INTERVAL`]. `GET /api/server/workers`
+//! lists those rows; one whose heartbeat is older than
+//! [`toygres_models::WORKER_STALE_AFTER_SECS`] belongs to a worker that died without
+//! shutting down. A clean shutdown removes the row.
 
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// How often a worker refreshes its row
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+
+/// A running heartbeat task
+pub struct Heartbeat {
+    worker_id: String,
+    pool: sqlx::PgPool,
+    task: JoinHandle<()>,
+}
+
+impl Heartbeat {
+    /// Start heartbeating as `worker_id`. Returns `None` when there is no CMS database
+    /// (`DATABASE_URL` unset or SQLite), since there is nowhere to report to.
+    pub async fn start(worker_id: &str) -> anyhow::Result<Option<Self>> {
+        let Some(db_url) = std::env::var("DATABASE_URL").ok().filter(|url| !url.starts_with("sqlite")) else {
+            tracing::info!("No CMS database; worker heartbeats disabled");
+            return Ok(None);
+        };
+        let pool = sqlx::PgPool::connect(&db_url).await
+            .map_err(|e| anyhow::anyhow!("Failed to connect to CMS for worker heartbeats: {}", e))?;
+
+        let hostname = hostname();
+        let started_at = chrono::Utc::now();
+        // The first heartbeat is written before returning so a misconfigured table fails startup
+        crate::db::record_worker_heartbeat(&pool, worker_id, &hostname, started_at).await?;
+
+        let task = {
+            let pool = pool.clone();
+            let worker_id = worker_id.to_string();
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(HEARTBEAT_INTERVAL);
+                interval.tick().await;
+                loop {
+                    interval.tick().await;
+                    if let Err(e) = crate::db::record_worker_heartbeat(&pool, &worker_id, &hostname, started_at).await {
+                        tracing::warn!(worker_id = %worker_id, "Worker heartbeat failed: {:#}", e);
+                    }
+                }
+            })
+        };
+
+        tracing::info!("Worker heartbeat started for {} (every {}s)", worker_id, HEARTBEAT_INTERVAL.as_secs());
+        Ok(Some(Self { worker_id: worker_id.to_string(), pool, task }))
+    }
+
+    /// Stop heartbeating and remove the worker's row
+    pub async fn stop(self) {
+        self.task.abort();
+        if let Err(e) = crate::db::remove_worker(&self.pool, &self.worker_id).await {
+            tracing::warn!(worker_id = %self.worker_id, "Failed to remove worker row: {:#}", e);
+        }
+    }
+}
+
+/// This machine's hostname (the pod name in Kubernetes)
+pub fn hostname() -> String {
+    std::env::var("HOSTNAME").ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
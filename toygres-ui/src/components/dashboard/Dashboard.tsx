@@ -2,7 +2,7 @@ import { useQuery } from '@tanstack/react-query';
 import { Database, CheckCircle, Zap, Activity } from 'lucide-react';
 import { Card, CardContent, CardDescription, CardHeader, CardTitle } from '@/components/ui/Card';
 import { api } from '@/lib/api';
-import { formatRelativeTime } from '@/lib/utils';
+import { formatRelativeTime, instanceNameFromId } from '@/lib/utils';
 
 function StatCard({ 
   title, 
@@ -91,7 +91,7 @@ export function Dashboard() {
               ) : (
                 recentActivity.map((orch) => {
                   const shortName = orch.orchestration_name.split('::').pop() || orch.orchestration_name;
-                  const instanceName = orch.instance_id.split('-')[0] || orch.instance_id;
+                  const instanceName = instanceNameFromId(orch.instance_id);
                   
                   return (
                     <div key={orch.instance_id} className="flex items-start space-x-3 text-sm">
@@ -8,11 +8,12 @@ export function Logs() {
   const [filter, setFilter] = useState('');
   const [autoRefresh, setAutoRefresh] = useState(true);
   const [limit, setLimit] = useState(500);
+  const [level, setLevel] = useState('');
   const logContainerRef = useRef<HTMLDivElement>(null);
 
   const { data: logs } = useQuery({
-    queryKey: ['server-logs', limit, filter],
-    queryFn: () => api.getLogs(limit, filter || undefined),
+    queryKey: ['server-logs', limit, filter, level],
+    queryFn: () => api.getLogs(limit, filter || undefined, level || undefined),
     refetchInterval: autoRefresh ? 2000 : false,
   });
 
@@ -23,33 +24,10 @@ export function Logs() {
     }
   }, [logs, autoRefresh]);
 
-  const stripAnsi = (str: string) => {
-    // Remove ANSI color codes (both with and without ESC character)
-    // Also handle the case where logs contain duplicate content with ANSI codes
-    // eslint-disable-next-line no-control-regex
-    const withoutEsc = str.replace(/\x1b\[[0-9;]*m/g, ''); // Standard ANSI codes with ESC
-    return withoutEsc
-      .replace(/\[[\d;]*m/g, '')      // ANSI codes without ESC character (using * not + to handle [m too)
-      .replace(/\[\d+m/g, '');        // Additional cleanup for any remaining codes
-  };
-
-  const parseLogLine = (line: string) => {
-    // Strip ANSI codes first
-    const clean = stripAnsi(line);
-    
-    // Try to extract structured log components
-    // Format typically: "2024-11-18T00:36:31.162Z INFO duroxide::runtime [instance_id] message"
-    const timestampMatch = clean.match(/^(\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}\.\d{3}[Z+\-\d:]+)/);
-    const levelMatch = clean.match(/\s+(TRACE|DEBUG|INFO|WARN|ERROR)\s+/);
-    const targetMatch = clean.match(/\s+(duroxide::\w+|toygres[_\w]*::\w+)/);
-    
-    return {
-      timestamp: timestampMatch?.[1],
-      level: levelMatch?.[1],
-      target: targetMatch?.[1],
-      raw: clean,
-    };
-  };
+  const formatFields = (fields?: Record<string, unknown>) =>
+    Object.entries(fields ?? {})
+      .map(([key, value]) => `${key}=${typeof value === 'string' ? value : JSON.stringify(value)}`)
+      .join(' ');
 
   const getLevelColor = (level?: string) => {
     switch (level) {
@@ -87,6 +65,18 @@ export function Logs() {
           <div className="flex items-center justify-between">
             <CardTitle>Filters & Settings</CardTitle>
             <div className="flex items-center space-x-2 text-sm text-muted-foreground">
+              <span>Level:</span>
+              <select
+                className="rounded border border-input bg-background px-2 py-1 text-sm"
+                value={level}
+                onChange={(e) => setLevel(e.target.value)}
+              >
+                <option value="">All</option>
+                <option value="debug">DEBUG+</option>
+                <option value="info">INFO+</option>
+                <option value="warn">WARN+</option>
+                <option value="error">ERROR</option>
+              </select>
               <span>Showing:</span>
               <select
                 className="rounded border border-input bg-background px-2 py-1 text-sm"
@@ -104,7 +94,7 @@ export function Logs() {
         <CardContent>
           <input
             type="text"
-            placeholder="Filter logs (e.g., orchestration ID, instance name, ERROR, etc.)..."
+            placeholder="Filter logs (e.g., orchestration ID, instance name, etc.)..."
             className="w-full rounded-md border border-input bg-background px-3 py-2 text-sm"
             value={filter}
             onChange={(e) => setFilter(e.target.value)}
@@ -116,7 +106,7 @@ export function Logs() {
         <CardHeader>
           <div className="flex items-center justify-between">
             <CardTitle>
-              Log Output {logs && `(${logs.length} lines)`}
+              Log Output {logs && `(${logs.length} entries)`}
             </CardTitle>
             {autoRefresh && (
               <span className="text-xs text-muted-foreground animate-pulse">
@@ -135,26 +125,25 @@ export function Logs() {
                 {filter ? 'No matching log entries found' : 'No logs available yet'}
               </p>
             ) : (
-              logs.map((line, idx) => {
-                const parsed = parseLogLine(line);
-                // Remove timestamp, level, and target from the cleaned message
-                const message = parsed.raw
-                  .replace(/^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}\.\d{3}[Z+\-\d:]+/, '')
-                  .replace(/\s+(TRACE|DEBUG|INFO|WARN|ERROR)\s+/, '')
-                  .replace(/\s+(duroxide::\w+|toygres[_\w]*::\w+)\s*:\s*/, '');
+              logs.map((entry, idx) => {
+                const fields = formatFields(entry.fields);
                 
                 return (
                   <div key={idx} className="whitespace-pre-wrap break-all mb-0.5">
-                    {parsed.timestamp && (
-                      <span className="text-gray-500">{parsed.timestamp} </span>
+                    {entry.timestamp && (
+                      <span className="text-gray-500">{entry.timestamp} </span>
+                    )}
+                    {entry.level && (
+                      <span className={getLevelColor(entry.level)}>{entry.level} </span>
                     )}
-                    {parsed.level && (
-                      <span className={getLevelColor(parsed.level)}>{parsed.level} </span>
+                    {entry.spans && entry.spans.length > 0 && (
+                      <span className="text-cyan-400">{entry.spans.join(':')}: </span>
                     )}
-                    {parsed.target && (
-                      <span className="text-purple-400">{parsed.target}: </span>
+                    {entry.target && (
+                      <span className="text-purple-400">{entry.target}: </span>
                     )}
-                    <span className="text-green-400">{message}</span>
+                    <span className="text-green-400">{entry.message}</span>
+                    {fields && <span className="text-gray-400"> {fields}</span>}
                   </div>
                 );
               })
@@ -169,7 +158,7 @@ export function Logs() {
         </CardHeader>
         <CardContent className="text-sm text-muted-foreground space-y-2">
           <p>• Logs auto-refresh every 2 seconds when not paused</p>
-          <p>• Use the filter to search for specific orchestration IDs, instance names, or log levels</p>
+          <p>• Use the filter to search for specific orchestration IDs or instance names, and the level selector to hide noise</p>
           <p>• Logs automatically scroll to bottom in live mode</p>
           <p>• Click "Pause" to stop auto-scroll and inspect older logs</p>
           <p>• Increase the limit dropdown to see more history</p>
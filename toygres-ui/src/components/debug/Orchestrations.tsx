@@ -6,6 +6,7 @@ import { Button } from '@/components/ui/Button';
 import { useToast } from '@/lib/toast';
 import { api } from '@/lib/api';
 import { formatRelativeTime, getStatusIcon } from '@/lib/utils';
+import type { OrchestrationEvent } from '@/lib/types';
 import mermaid from 'mermaid';
 
 // Initialize mermaid
@@ -222,52 +223,21 @@ export function Orchestrations() {
     setExpandedEvents(newExpanded);
   };
 
-  const parseEvent = (eventStr: string) => {
-    try {
-      // Extract event type - now inside "kind: TypeName { ... }" since Event is a struct
-      // Try new format first: "Event { ..., kind: TypeName { ... } }"
-      let eventType = 'Unknown';
-      const kindMatch = eventStr.match(/kind:\s*(\w+)\s*[{\[]/);
-      if (kindMatch) {
-        eventType = kindMatch[1];
-      } else {
-        // Fallback: old format "TypeName { ... }" at start
-        const typeMatch = eventStr.match(/^(\w+)\s*\{/);
-        if (typeMatch) {
-          eventType = typeMatch[1];
-        }
-      }
-      
-      // Extract key fields
-      const eventIdMatch = eventStr.match(/event_id:\s*(\d+)/);
-      const executionIdMatch = eventStr.match(/execution_id:\s*(\d+)/);
-      // Look for name in the kind block - it may appear multiple times, get the one after "kind:"
-      const kindSection = eventStr.match(/kind:\s*\w+\s*\{([^}]+)\}/);
-      const nameMatch = kindSection 
-        ? kindSection[1].match(/name:\s*"([^"]+)"/)
-        : eventStr.match(/name:\s*"([^"]+)"/);
-      const sourceMatch = eventStr.match(/source_event_id:\s*Some\((\d+)\)|source_event_id:\s*(\d+)/);
-      const fireAtMatch = eventStr.match(/fire_at_ms:\s*(\d+)/);
-      const durationMatch = eventStr.match(/duration_ms:\s*(\d+)/);
-      
-      return {
-        type: eventType,
-        eventId: eventIdMatch ? parseInt(eventIdMatch[1]) : 0,
-        executionId: executionIdMatch ? parseInt(executionIdMatch[1]) : 0,
-        name: nameMatch ? nameMatch[1] : undefined,
-        sourceEventId: sourceMatch ? parseInt(sourceMatch[1] || sourceMatch[2]) : undefined,
-        fireAtMs: fireAtMatch ? parseInt(fireAtMatch[1]) : undefined,
-        durationMs: durationMatch ? parseInt(durationMatch[1]) : undefined,
-        raw: eventStr,
-      };
-    } catch {
-      return {
-        type: 'Unknown',
-        eventId: 0,
-        executionId: 0,
-        raw: eventStr,
-      };
-    }
+  const parseEvent = (event: OrchestrationEvent) => {
+    const details = event.details ?? {};
+    const numberField = (key: string) =>
+      typeof details[key] === 'number' ? (details[key] as number) : undefined;
+
+    return {
+      type: event.kind || 'Unknown',
+      eventId: event.event_id ?? 0,
+      executionId: event.execution_id ?? 0,
+      name: event.name ?? undefined,
+      sourceEventId: event.source_event_id ?? undefined,
+      fireAtMs: numberField('fire_at_ms'),
+      durationMs: numberField('duration_ms'),
+      raw: event.raw ?? JSON.stringify(details, null, 2),
+    };
   };
 
   const getEventColor = (eventType: string) => {
@@ -293,8 +263,8 @@ export function Orchestrations() {
   ];
 
   // Build a map of source events for correlation
-  const buildEventCorrelation = (history: { event: string }[]) => {
-    const parsed = history.map(h => parseEvent(h.event));
+  const buildEventCorrelation = (history: OrchestrationEvent[]) => {
+    const parsed = history.map(parseEvent);
     const sourceMap = new Map<number, { name?: string; type: string; colorIndex: number }>();
     let colorIndex = 0;
 
@@ -314,8 +284,8 @@ export function Orchestrations() {
   };
 
   // Generate Mermaid flowchart from execution history
-  const generateMermaidDiagram = (history: { event: string }[]) => {
-    const parsed = history.map(h => parseEvent(h.event));
+  const generateMermaidDiagram = (history: OrchestrationEvent[]) => {
+    const parsed = history.map(parseEvent);
     const lines: string[] = ['flowchart TD'];
     const nodeStyles: string[] = [];
     
@@ -462,14 +432,14 @@ export function Orchestrations() {
   const applyExecutionStateToFlow = (
     staticMermaid: string,
     nodeMappings: Array<{ node_id: string; activity_pattern: string }>,
-    history: { event: string }[]
+    history: OrchestrationEvent[]
   ): string => {
     // Parse history to determine completed activities
     const completedActivities = new Set<string>();
     const failedActivities = new Set<string>();
     const inProgressActivities = new Set<string>();
     
-    const parsed = history.map(h => parseEvent(h.event));
+    const parsed = history.map(parseEvent);
     const pendingActivities = new Map<number, string>();
     
     parsed.forEach(event => {
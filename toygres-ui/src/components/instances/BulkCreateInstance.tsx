@@ -31,7 +31,9 @@ export function BulkCreateInstance() {
     }) => api.bulkCreateInstances(data),
     onSuccess: (data) => {
       queryClient.invalidateQueries({ queryKey: ['instances'] });
-      showToast('success', `Created ${data.count} instances successfully`);
+      const started = data.items.filter((item) => item.status === 'Started').length;
+      const failed = data.items.length - started;
+      showToast('success', `Created ${started} instances${failed > 0 ? ` (${failed} failed)` : ''}`);
       navigate('/instances');
     },
     onError: (error: Error) => {
@@ -25,7 +25,9 @@ export function InstanceList() {
     mutationFn: (names: string[]) => api.bulkDeleteInstances(names),
     onSuccess: (data) => {
       queryClient.invalidateQueries({ queryKey: ['instances'] });
-      showToast('success', `Deleted ${data.deleted} instances${data.errors > 0 ? ` (${data.errors} errors)` : ''}`);
+      const started = data.items.filter((item) => item.status === 'Started').length;
+      const other = data.items.length - started;
+      showToast('success', `Deleted ${started} instances${other > 0 ? ` (${other} skipped or failed)` : ''}`);
       setSelectedInstances(new Set());
       setShowBulkDeleteModal(false);
     },
@@ -166,10 +168,15 @@ export function InstanceList() {
                       >
                         <span className={getHealthColor(instance.health_status)}>
                           {instance.health_status === 'healthy' && '✓'}
+                          {instance.health_status === 'degraded' && '◐'}
                           {instance.health_status === 'unhealthy' && '✗'}
                           {instance.health_status === 'unknown' && '○'}
+                          {instance.health_status === 'provisioning' && '…'}
+                          {instance.health_status === 'unmonitored' && '!'}
                           {' '}
-                          {instance.health_status}
+                          {instance.health_status === 'provisioning'
+                            ? 'waiting for connection info'
+                            : instance.health_status}
                         </span>
                       </td>
                       <td
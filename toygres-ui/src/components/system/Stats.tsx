@@ -21,6 +21,7 @@ export function Stats() {
     creating: instances?.filter(i => i.state === 'creating').length || 0,
     failed: instances?.filter(i => i.state === 'failed').length || 0,
     healthy: instances?.filter(i => i.health_status === 'healthy').length || 0,
+    degraded: instances?.filter(i => i.health_status === 'degraded').length || 0,
     unhealthy: instances?.filter(i => i.health_status === 'unhealthy').length || 0,
   };
 
@@ -65,6 +66,10 @@ export function Stats() {
               <div className="text-2xl font-bold text-green-600">{instanceStats.healthy}</div>
               <p className="text-xs text-muted-foreground">Healthy</p>
             </div>
+            <div>
+              <div className="text-2xl font-bold text-yellow-600">{instanceStats.degraded}</div>
+              <p className="text-xs text-muted-foreground">Degraded</p>
+            </div>
             <div>
               <div className="text-2xl font-bold text-red-600">{instanceStats.unhealthy}</div>
               <p className="text-xs text-muted-foreground">Unhealthy</p>
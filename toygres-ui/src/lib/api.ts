@@ -1,4 +1,4 @@
-import type { Instance, InstanceDetail, Orchestration, HealthResponse, ServerStatus } from './types';
+import type { Instance, InstanceDetail, Orchestration, HealthResponse, ServerStatus, LogEntry, BulkResult, StartedOrchestration } from './types';
 
 const API_BASE = ''; // Proxy configured in vite.config.ts
 
@@ -84,23 +84,14 @@ export const api = {
     storage_size_gb?: number;
     internal?: boolean;
     namespace?: string;
-  }): Promise<{
-    instance_name: string;
-    k8s_name: string;
-    orchestration_id: string;
-    dns_name: string;
-  }> {
+  }): Promise<StartedOrchestration & { dns_name: string }> {
     return fetchJson(`${API_BASE}/api/instances`, {
       method: 'POST',
       body: JSON.stringify(data),
     });
   },
 
-  async deleteInstance(name: string): Promise<{
-    instance_name: string;
-    k8s_name: string;
-    orchestration_id: string;
-  }> {
+  async deleteInstance(name: string): Promise<StartedOrchestration> {
     return fetchJson(`${API_BASE}/api/instances/${name}`, {
       method: 'DELETE',
     });
@@ -114,34 +105,14 @@ export const api = {
     storage_size_gb?: number;
     internal?: boolean;
     namespace?: string;
-  }): Promise<{
-    count: number;
-    instances: Array<{
-      instance_name: string;
-      k8s_name: string;
-      orchestration_id: string;
-      dns_name: string;
-    }>;
-  }> {
+  }): Promise<BulkResult> {
     return fetchJson(`${API_BASE}/api/instances/bulk`, {
       method: 'POST',
       body: JSON.stringify(data),
     });
   },
 
-  async bulkDeleteInstances(instance_names: string[]): Promise<{
-    deleted: number;
-    errors: number;
-    instances: Array<{
-      instance_name: string;
-      k8s_name: string;
-      orchestration_id: string;
-    }>;
-    failures: Array<{
-      instance_name: string;
-      error: string;
-    }>;
-  }> {
+  async bulkDeleteInstances(instance_names: string[]): Promise<BulkResult> {
     return fetchJson(`${API_BASE}/api/instances/bulk/delete`, {
       method: 'POST',
       body: JSON.stringify({ instance_names }),
@@ -208,12 +179,13 @@ export const api = {
   },
 
   // Logs
-  async getLogs(limit?: number, filter?: string): Promise<string[]> {
+  async getLogs(limit?: number, filter?: string, level?: string): Promise<LogEntry[]> {
     const params = new URLSearchParams();
     if (limit) params.append('limit', limit.toString());
     if (filter) params.append('filter', filter);
+    if (level) params.append('level', level);
     const query = params.toString() ? `?${params.toString()}` : '';
-    return fetchJson<string[]>(`${API_BASE}/api/server/logs${query}`);
+    return fetchJson<LogEntry[]>(`${API_BASE}/api/server/logs${query}`);
   },
 };
 
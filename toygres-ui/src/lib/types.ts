@@ -1,9 +1,10 @@
 export interface Instance {
   user_name: string;
   k8s_name: string;
+  namespace: string;
   dns_name: string | null;
   state: 'creating' | 'running' | 'deleting' | 'deleted' | 'failed';
-  health_status: 'unknown' | 'healthy' | 'unhealthy';
+  health_status: 'unknown' | 'healthy' | 'degraded' | 'unhealthy' | 'provisioning' | 'unmonitored';
   postgres_version: string;
   storage_size_gb: number;
   created_at: string;
@@ -16,9 +17,9 @@ export interface Instance {
 
 export interface InstanceDetail extends Instance {
   id: string;
-  namespace: string;
   use_load_balancer: boolean;
   create_orchestration_id: string | null;
+  orchestration_version: string | null;
   delete_orchestration_id: string | null;
   instance_actor_orchestration_id: string | null;
 }
@@ -36,8 +37,14 @@ export interface Orchestration {
 }
 
 export interface OrchestrationEvent {
-  event: string;
   execution_id: number;
+  event_id: number;
+  source_event_id: number | null;
+  kind: string;
+  name: string | null;
+  timestamp: string;
+  details: Record<string, unknown>;
+  raw?: string;
 }
 
 export interface HealthResponse {
@@ -59,6 +66,7 @@ export interface Stats {
     creating: number;
     failed: number;
     healthy: number;
+    degraded: number;
     unhealthy: number;
   };
   orchestrations: {
@@ -68,3 +76,36 @@ export interface Stats {
   };
 }
 
+
+export interface LogEntry {
+  timestamp: string | null;
+  level: string | null;
+  target: string | null;
+  message: string;
+  fields?: Record<string, unknown>;
+  spans?: string[];
+  // False for lines written before the log switched to JSON; only `message` is set
+  structured: boolean;
+}
+
+// Returned by every endpoint that starts an instance orchestration
+export interface StartedOrchestration {
+  instance_name: string;
+  k8s_name: string;
+  orchestration_id: string;
+  kind: 'create' | 'delete';
+}
+
+export type BulkItemStatus = 'Started' | 'Skipped' | 'Failed';
+
+// Started items also carry the StartedOrchestration fields
+export interface BulkItem extends Partial<StartedOrchestration> {
+  name: string;
+  status: BulkItemStatus;
+  detail?: string;
+}
+
+// Response from the bulk create and bulk delete endpoints
+export interface BulkResult {
+  items: BulkItem[];
+}
@@ -19,6 +19,16 @@ export function formatRelativeTime(dateString: string): string {
   return `${diffDays}d ago`;
 }
 
+/**
+ * Instance name in an orchestration id: ids are `<kind>-<k8s_name>`, optionally with a
+ * `.<run>` suffix, and k8s names never contain a '.'
+ */
+export function instanceNameFromId(instanceId: string): string {
+  const [kindAndName] = instanceId.split('.');
+  const separator = kindAndName.indexOf('-');
+  return separator >= 0 ? kindAndName.slice(separator + 1) : instanceId;
+}
+
 export function formatDateTime(dateString: string): string {
   const date = new Date(dateString);
   return date.toLocaleString();
@@ -45,8 +55,14 @@ export function getHealthColor(health: string): string {
   switch (health) {
     case 'healthy':
       return 'text-green-600 dark:text-green-400';
+    case 'degraded':
+      return 'text-yellow-600 dark:text-yellow-400';
     case 'unhealthy':
       return 'text-red-600 dark:text-red-400';
+    case 'provisioning':
+      return 'text-blue-600 dark:text-blue-400';
+    case 'unmonitored':
+      return 'text-orange-600 dark:text-orange-400';
     case 'unknown':
       return 'text-gray-600 dark:text-gray-400';
     default: